@@ -0,0 +1,141 @@
+// 音频回调耗时基准测试
+// 对比旧版回调（多把 Mutex + 回调内转单声道/降采样/VAD/AGC 并分配内存）
+// 与新版回调（只写入 SPSC 环形缓冲）的单次回调耗时
+//
+// 运行：cargo bench --bench audio_callback
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use crossbeam_channel::bounded;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[path = "../src/audio_capture.rs"]
+#[allow(dead_code)]
+mod audio_capture;
+
+const DEVICE_SAMPLE_RATE: u32 = 48000;
+const CHANNELS: u16 = 2;
+const TARGET_SAMPLE_RATE: u32 = 16000;
+const CHUNK_SAMPLES: usize = 3200;
+// 10ms 的设备缓冲（48kHz 双声道）
+const FRAMES_PER_CALLBACK: usize = 480;
+
+fn test_buffer() -> Vec<f32> {
+    (0..FRAMES_PER_CALLBACK * CHANNELS as usize)
+        .map(|i| (i as f32 * 0.05).sin() * 0.3)
+        .collect()
+}
+
+fn to_mono(input: &[f32], channels: u16) -> Vec<f32> {
+    let channels = channels as usize;
+    input.chunks_exact(channels).map(|f| f.iter().sum::<f32>() / channels as f32).collect()
+}
+
+fn resample(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    let ratio = from_rate as f64 / to_rate as f64;
+    let output_len = (input.len() as f64 / ratio) as usize;
+    (0..output_len)
+        .map(|i| {
+            let src_idx = i as f64 * ratio;
+            let idx_floor = src_idx.floor() as usize;
+            let idx_ceil = (idx_floor + 1).min(input.len() - 1);
+            let frac = src_idx - idx_floor as f64;
+            (input[idx_floor] as f64 * (1.0 - frac) + input[idx_ceil] as f64 * frac) as f32
+        })
+        .collect()
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32).sqrt()
+}
+
+/// 复刻旧版 StreamingRecorder 的 F32 回调（仅用于对比）
+fn bench_legacy_callback(c: &mut Criterion) {
+    let is_recording = Arc::new(Mutex::new(true));
+    let full_audio_data: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
+    let pending_samples: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
+    let last_emit_time = Arc::new(Mutex::new(Instant::now()));
+    let vad_hangover = Arc::new(Mutex::new(0usize));
+    let agc_gain = Arc::new(Mutex::new(1.0f32));
+    let (chunk_tx, chunk_rx) = bounded::<Vec<i16>>(50);
+    let data = test_buffer();
+
+    let mut callback = move |data: &[f32]| {
+        if !*is_recording.lock().unwrap() {
+            return;
+        }
+        full_audio_data.lock().unwrap().extend_from_slice(data);
+
+        let mono = to_mono(data, CHANNELS);
+        let resampled = resample(&mono, DEVICE_SAMPLE_RATE, TARGET_SAMPLE_RATE);
+
+        {
+            let mut last_emit = last_emit_time.lock().unwrap();
+            if last_emit.elapsed().as_millis() >= 33 {
+                black_box(rms(&resampled));
+                *last_emit = Instant::now();
+            }
+        }
+
+        let mut pending = pending_samples.lock().unwrap();
+        pending.extend(resampled);
+        while pending.len() >= CHUNK_SAMPLES {
+            let mut chunk: Vec<f32> = pending.drain(..CHUNK_SAMPLES).collect();
+            let is_active = rms(&chunk) > 0.003;
+            let mut hangover = vad_hangover.lock().unwrap();
+            if is_active {
+                *hangover = 3;
+            } else if *hangover > 0 {
+                *hangover -= 1;
+            }
+            drop(hangover);
+
+            let gain = *agc_gain.lock().unwrap();
+            chunk.iter_mut().for_each(|s| *s *= gain);
+            let chunk_i16: Vec<i16> = chunk.iter().map(|&s| (s * i16::MAX as f32) as i16).collect();
+            let _ = chunk_tx.try_send(chunk_i16);
+        }
+    };
+
+    c.bench_function("callback/legacy_mutex", |b| {
+        b.iter(|| {
+            callback(black_box(&data));
+            // 模拟消费端，避免通道写满后走不同分支
+            while chunk_rx.try_recv().is_ok() {}
+        })
+    });
+}
+
+/// 新版回调：只写入环形缓冲，处理在工作线程中进行
+fn bench_ring_callback(c: &mut Criterion) {
+    let recording = Arc::new(AtomicBool::new(true));
+    let (mut producer, consumer) = audio_capture::capture_ring(DEVICE_SAMPLE_RATE, CHANNELS, recording);
+    let worker = consumer.spawn_worker(|samples| {
+        black_box(samples);
+    });
+    let data = test_buffer();
+
+    // 只计时回调本身；每 100 次回调（约 1 秒音频，小于缓冲容量）在计时外等待工作线程排空，
+    // 保证测到的是正常写入路径而不是缓冲满时的丢弃路径
+    c.bench_function("callback/ring_buffer", |b| {
+        b.iter_custom(|iters| {
+            let mut elapsed = Duration::ZERO;
+            for i in 0..iters {
+                if i % 100 == 0 {
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                let start = Instant::now();
+                producer.push_f32(black_box(&data));
+                elapsed += start.elapsed();
+            }
+            elapsed
+        })
+    });
+
+    drop(producer);
+    worker.finish();
+}
+
+criterion_group!(benches, bench_legacy_callback, bench_ring_callback);
+criterion_main!(benches);
@@ -0,0 +1,148 @@
+// 应用数据目录存储工具
+// 录音归档和 LLM 缓存共用：定位 <数据目录>/PushToTalk/<子目录>、生成时间戳、
+// 按文件修改时间从旧到新执行保留策略
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 应用数据目录下的子目录（不存在时创建）
+pub fn data_subdir(name: &str) -> Result<PathBuf> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| anyhow::anyhow!("无法获取应用数据目录"))?;
+    let dir = data_dir.join("PushToTalk").join(name);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 当前 Unix 毫秒时间戳
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 保留策略，None 表示不限制
+#[derive(Debug, Clone, Default)]
+pub struct Retention {
+    /// 超过该时长的文件直接删除
+    pub max_age: Option<Duration>,
+    /// 最多保留的文件数
+    pub max_files: Option<usize>,
+    /// 最多占用的字节数
+    pub max_bytes: Option<u64>,
+}
+
+/// 执行保留策略：先删除过期的文件，再从最旧的开始删除直到满足数量和容量上限
+///
+/// 只统计指定扩展名的文件；`remove` 负责删除一个文件（及其附属文件），返回是否删除成功
+pub fn prune_oldest_first(
+    dir: &Path,
+    extension: &str,
+    retention: &Retention,
+    remove: impl Fn(&Path) -> bool,
+) -> Result<usize> {
+    // (路径, 修改时间, 大小)
+    let mut entries: Vec<(PathBuf, SystemTime, u64)> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|x| x.to_str()) == Some(extension))
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some((e.path(), meta.modified().unwrap_or(UNIX_EPOCH), meta.len()))
+        })
+        .collect();
+    entries.sort_by_key(|(_, modified, _)| *modified);
+
+    let mut removed = 0;
+
+    if let Some(max_age) = retention.max_age {
+        let now = SystemTime::now();
+        entries.retain(|(path, modified, _)| {
+            let expired = now.duration_since(*modified).map(|age| age > max_age).unwrap_or(false);
+            if expired && remove(path) {
+                removed += 1;
+            }
+            !expired
+        });
+    }
+
+    let mut count = entries.len();
+    let mut total: u64 = entries.iter().map(|(_, _, size)| size).sum();
+    for (path, _, size) in &entries {
+        let over_count = retention.max_files.is_some_and(|max| count > max);
+        let over_bytes = retention.max_bytes.is_some_and(|max| total > max);
+        if !over_count && !over_bytes {
+            break;
+        }
+        if remove(path) {
+            removed += 1;
+        }
+        count -= 1;
+        total = total.saturating_sub(*size);
+    }
+
+    Ok(removed)
+}
+
+/// 测试用的临时目录和文件时间设置
+#[cfg(test)]
+pub mod test_support {
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    pub fn temp_dir(prefix: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}_{}", prefix, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 显式设置修改时间，不依赖文件系统的时间戳精度
+    pub fn set_age(path: &Path, age_secs: u64) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(age_secs))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::{set_age, temp_dir};
+    use super::*;
+
+    fn remove(path: &Path) -> bool {
+        std::fs::remove_file(path).is_ok()
+    }
+
+    #[test]
+    fn test_prune_by_count_and_age() {
+        let dir = temp_dir("ptt_app_data_test");
+        for (age_secs, name) in [(7200, "a"), (30, "b"), (20, "c"), (10, "d")] {
+            let path = dir.join(format!("{}.json", name));
+            std::fs::write(&path, "{}").unwrap();
+            set_age(&path, age_secs);
+        }
+        std::fs::write(dir.join("other.txt"), "").unwrap();
+
+        let retention = Retention { max_age: Some(Duration::from_secs(3600)), max_files: Some(2), max_bytes: None };
+        assert_eq!(prune_oldest_first(&dir, "json", &retention, remove).unwrap(), 2);
+        assert!(!dir.join("a.json").exists());
+        assert!(!dir.join("b.json").exists());
+        assert!(dir.join("c.json").exists());
+        // 其他扩展名的文件不受影响
+        assert!(dir.join("other.txt").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_prune_unlimited_keeps_all() {
+        let dir = temp_dir("ptt_app_data_test");
+        std::fs::write(dir.join("a.json"), "{}").unwrap();
+        assert_eq!(prune_oldest_first(&dir, "json", &Retention::default(), remove).unwrap(), 0);
+        assert!(dir.join("a.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,160 @@
+// 按应用配置模块
+// 热键按下时读取前台窗口的进程名 / 类名 / 标题，选出第一个匹配的 AppProfile，
+// 本次录音的润色预设、ASR 服务商、语言、标点、插入方式和插入后按键按其覆盖
+
+use anyhow::Result;
+
+use crate::config::{AppProfile, PostInsertAction, ProfileMatch, PunctuationPolicy};
+use crate::insertion::{InsertionBackend, TypedKey};
+
+/// 句读标点（去除标点时只处理这些，保留代码中常见的 - _ / 等符号）
+const SENTENCE_PUNCTUATION: &[char] = &[
+    '。', '，', '！', '？', '、', '；', '：', '“', '”', '‘', '’', '…',
+    '.', ',', '!', '?', ';', ':', '"',
+];
+
+/// 前台窗口信息
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowInfo {
+    pub process: Option<String>,
+    pub class: Option<String>,
+    pub title: Option<String>,
+}
+
+impl WindowInfo {
+    /// 读取窗口信息
+    #[cfg(target_os = "windows")]
+    pub fn from_hwnd(hwnd: isize) -> Self {
+        use crate::win32_input;
+        Self {
+            process: win32_input::get_window_process_name(hwnd),
+            class: win32_input::get_window_class(hwnd),
+            title: win32_input::get_window_title(hwnd),
+        }
+    }
+}
+
+/// 选出第一个启用且匹配的配置
+pub fn select<'a>(profiles: &'a [AppProfile], window: &WindowInfo) -> Option<&'a AppProfile> {
+    profiles.iter().find(|p| p.enabled && matches(&p.matcher, window))
+}
+
+/// 已填写的条件全部满足才算匹配；没有任何条件的配置不匹配
+fn matches(matcher: &ProfileMatch, window: &WindowInfo) -> bool {
+    let normalize = |name: &str| {
+        let name = name.trim().to_lowercase();
+        name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+    };
+    let conditions = [
+        matcher.process.as_deref().map(|p| {
+            window.process.as_deref().is_some_and(|actual| normalize(actual) == normalize(p))
+        }),
+        matcher.window_class.as_deref().map(|c| {
+            window.class.as_deref().is_some_and(|actual| actual.eq_ignore_ascii_case(c.trim()))
+        }),
+        matcher.title_contains.as_deref().map(|t| {
+            window.title.as_deref().is_some_and(|actual| actual.to_lowercase().contains(&t.to_lowercase()))
+        }),
+    ];
+    let mut given = conditions.iter().flatten().peekable();
+    given.peek().is_some() && given.all(|&ok| ok)
+}
+
+/// 按标点策略处理文本
+pub fn apply_punctuation(text: &str, policy: PunctuationPolicy) -> String {
+    match policy {
+        PunctuationPolicy::Keep => text.to_string(),
+        PunctuationPolicy::StripTrailing => text.trim_end_matches(SENTENCE_PUNCTUATION).to_string(),
+        PunctuationPolicy::StripAll => text.chars().filter(|c| !SENTENCE_PUNCTUATION.contains(c)).collect(),
+    }
+}
+
+/// 插入完成后依次发送配置的按键
+pub fn run_post_insert_actions(backend: &dyn InsertionBackend, actions: &[PostInsertAction]) -> Result<()> {
+    if actions.is_empty() {
+        return Ok(());
+    }
+    if !backend.can_type_unicode() {
+        anyhow::bail!("插入后端 {} 不支持发送插入后按键", backend.name());
+    }
+    for &action in actions {
+        backend.type_key(post_insert_key(action))?;
+    }
+    Ok(())
+}
+
+fn post_insert_key(action: PostInsertAction) -> TypedKey {
+    match action {
+        PostInsertAction::Enter => TypedKey::Enter { shift: false },
+        PostInsertAction::ShiftEnter => TypedKey::Enter { shift: true },
+        PostInsertAction::Tab => TypedKey::Tab,
+        PostInsertAction::Space => TypedKey::Char(' '),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::insertion::fake::{FakeBackend, FakeKey};
+
+    fn profile(name: &str, matcher: ProfileMatch) -> AppProfile {
+        AppProfile {
+            name: name.to_string(),
+            enabled: true,
+            matcher,
+            llm_preset_id: None,
+            asr_provider: None,
+            language: None,
+            punctuation: None,
+            insertion_method: None,
+            post_insert_actions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_select_profile() {
+        let profiles = vec![
+            profile("空条件", ProfileMatch::default()),
+            profile("VS Code", ProfileMatch { process: Some("Code.exe".to_string()), ..Default::default() }),
+            profile(
+                "Outlook 邮件",
+                ProfileMatch {
+                    process: Some("outlook".to_string()),
+                    title_contains: Some("message".to_string()),
+                    ..Default::default()
+                },
+            ),
+        ];
+        let window = |process: &str, title: &str| WindowInfo {
+            process: Some(process.to_string()),
+            class: None,
+            title: Some(title.to_string()),
+        };
+
+        assert_eq!(select(&profiles, &window("code.exe", "main.rs")).map(|p| p.name.as_str()), Some("VS Code"));
+        assert_eq!(
+            select(&profiles, &window("OUTLOOK.EXE", "Untitled - Message (HTML)")).map(|p| p.name.as_str()),
+            Some("Outlook 邮件")
+        );
+        assert!(select(&profiles, &window("OUTLOOK.EXE", "Inbox")).is_none());
+        assert!(select(&profiles, &WindowInfo::default()).is_none());
+    }
+
+    #[test]
+    fn test_apply_punctuation() {
+        assert_eq!(apply_punctuation("你好，世界。", PunctuationPolicy::Keep), "你好，世界。");
+        assert_eq!(apply_punctuation("你好，世界。", PunctuationPolicy::StripTrailing), "你好，世界");
+        assert_eq!(apply_punctuation("get_user_name, ok.", PunctuationPolicy::StripAll), "get_user_name ok");
+    }
+
+    #[test]
+    fn test_post_insert_actions() {
+        let backend = FakeBackend::new();
+        run_post_insert_actions(&backend, &[PostInsertAction::Space, PostInsertAction::Enter]).unwrap();
+        assert_eq!(
+            backend.events(),
+            vec![FakeKey::Typed(TypedKey::Char(' ')), FakeKey::Typed(TypedKey::Enter { shift: false })]
+        );
+        assert!(run_post_insert_actions(&FakeBackend::clipboard_only(), &[PostInsertAction::Tab]).is_err());
+    }
+}
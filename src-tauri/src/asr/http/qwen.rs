@@ -6,6 +6,7 @@ use crate::asr::utils;
 const QWEN_API_URL: &str = "https://dashscope.aliyuncs.com/api/v1/services/aigc/multimodal-generation/generation";
 const MODEL: &str = "qwen3-asr-flash";
 const MAX_RETRIES: u32 = 2;
+const DEFAULT_LANGUAGE: &str = "zh";
 
 #[derive(Clone)]
 pub struct QwenASRClient {
@@ -13,6 +14,7 @@ pub struct QwenASRClient {
     client: reqwest::Client,
     max_retries: u32,
     dictionary: Vec<String>,
+    language: String,
 }
 
 impl QwenASRClient {
@@ -22,9 +24,16 @@ impl QwenASRClient {
             client: utils::create_http_client(),
             max_retries: MAX_RETRIES,
             dictionary,
+            language: DEFAULT_LANGUAGE.to_string(),
         }
     }
 
+    /// 指定识别语言（按应用配置覆盖）
+    pub fn with_language(mut self, language: String) -> Self {
+        self.language = language;
+        self
+    }
+
     /// 热更新词库
     pub fn update_dictionary(&mut self, dictionary: Vec<String>) {
         self.dictionary = dictionary;
@@ -84,7 +93,7 @@ impl QwenASRClient {
                 "result_format": "message",
                 "enable_itn": false,
                 "disfluency_removal": true,
-                "language": "zh"
+                "language": self.language
             }
         });
 
@@ -3,11 +3,12 @@ use std::time::Duration;
 use anyhow::Result;
 use super::{QwenASRClient, SenseVoiceClient, DoubaoASRClient};
 
+/// 千问 + SenseVoice 主备竞速，返回（文本, 是否为 SenseVoice 备用结果）
 pub async fn transcribe_with_fallback_clients(
     qwen_client: QwenASRClient,
     sensevoice_client: SenseVoiceClient,
     audio_data: Vec<u8>,
-) -> Result<String> {
+) -> Result<(String, bool)> {
     tracing::info!("启动主备并行转录 (内存模式), 音频大小: {} bytes", audio_data.len());
 
     let audio_data_sensevoice = audio_data.clone();
@@ -35,7 +36,7 @@ pub async fn transcribe_with_fallback_clients(
                 match sv_result {
                     Ok(text) => {
                         tracing::info!("✅千问重试前发现 SenseVoice 已成功，立即使用: {}", text);
-                        return Ok(text.clone());
+                        return Ok((text.clone(), true));
                     }
                     Err(e) => {
                         tracing::warn!("⚠️ SenseVoice 也失败了: {}，继续千问重试", e);
@@ -50,7 +51,7 @@ pub async fn transcribe_with_fallback_clients(
         match qwen_client.transcribe_from_memory(&audio_data).await {
             Ok(text) => {
                 tracing::info!("✅千问转录成功: {}", text);
-                return Ok(text);
+                return Ok((text, false));
             }
             Err(e) => {
                 tracing::error!("❌千问第{} 次尝试失败 {}", attempt + 1, e);
@@ -66,7 +67,7 @@ pub async fn transcribe_with_fallback_clients(
         match result {
             Ok(text) => {
                 tracing::info!("✅使用 SenseVoice 备用结果: {}", text);
-                return Ok(text);
+                return Ok((text, true));
             }
             Err(sensevoice_error) => {
                 tracing::error!("❌两个 API 都失败了");
@@ -84,11 +85,12 @@ pub async fn transcribe_with_fallback_clients(
     Err(anyhow::anyhow!("所有API都失败"))
 }
 
+/// 豆包 + SenseVoice 主备竞速，返回（文本, 是否为 SenseVoice 备用结果）
 pub async fn transcribe_doubao_sensevoice_race(
     doubao_client: DoubaoASRClient,
     sensevoice_client: SenseVoiceClient,
     audio_data: Vec<u8>,
-) -> Result<String> {
+) -> Result<(String, bool)> {
     tracing::info!("启动豆包+SenseVoice并行转录, 音频大小: {} bytes", audio_data.len());
 
     let audio_data_sensevoice = audio_data.clone();
@@ -116,7 +118,7 @@ pub async fn transcribe_doubao_sensevoice_race(
                 match sv_result {
                     Ok(text) => {
                         tracing::info!("✅豆包重试前发现 SenseVoice 已成功，立即使用: {}", text);
-                        return Ok(text.clone());
+                        return Ok((text.clone(), true));
                     }
                     Err(e) => {
                         tracing::warn!("⚠️ SenseVoice 也失败了: {}，继续豆包重试", e);
@@ -131,7 +133,7 @@ pub async fn transcribe_doubao_sensevoice_race(
         match doubao_client.transcribe_bytes(&audio_data).await {
             Ok(text) => {
                 tracing::info!("✅豆包转录成功: {}", text);
-                return Ok(text);
+                return Ok((text, false));
             }
             Err(e) => {
                 tracing::error!("❌豆包第{} 次尝试失败: {}", attempt + 1, e);
@@ -147,7 +149,7 @@ pub async fn transcribe_doubao_sensevoice_race(
         match result {
             Ok(text) => {
                 tracing::info!("✅使用 SenseVoice 备用结果: {}", text);
-                return Ok(text);
+                return Ok((text, true));
             }
             Err(sensevoice_error) => {
                 tracing::error!("❌两个 API 都失败了");
@@ -73,6 +73,7 @@ pub struct ConnectionPool {
     api_key: String,
     connection: Arc<Mutex<Option<PooledConnection>>>,
     dictionary: Vec<String>,
+    language: String,
 }
 
 struct PooledConnection {
@@ -85,6 +86,7 @@ impl ConnectionPool {
             api_key,
             connection: Arc::new(Mutex::new(None)),
             dictionary,
+            language: "zh".to_string(),
         }
     }
 
@@ -141,7 +143,7 @@ impl ConnectionPool {
         let corpus_text = self.dictionary.join("、");
 
         let mut input_audio_transcription = serde_json::json!({
-            "language": "zh"
+            "language": self.language
         });
         if !corpus_text.is_empty() {
             tracing::info!("Qwen 流式 ASR 词库: {} 个词, corpus={}", self.dictionary.len(), corpus_text);
@@ -346,6 +348,12 @@ impl QwenRealtimeClient {
         }
     }
 
+    /// 指定识别语言（按应用配置覆盖）
+    pub fn with_language(mut self, language: String) -> Self {
+        self.pool.language = language;
+        self
+    }
+
     /// 创建新的转录会话
     pub async fn start_session(&self) -> Result<RealtimeSession> {
         self.pool.get_session().await
@@ -3,62 +3,108 @@
 // AI 助手处理器
 //
 // 支持双系统提示词：问答模式和文本处理模式
+// 支持多轮对话：最近的问答作为历史消息随请求发送（conversation.rs）
+// 支持工具调用：模型可以执行内置工具（assistant_tools.rs），动作类工具的结果不插入
+// 支持备用端点：主端点失败时依次尝试（llm_fallback.rs）
+// 支持响应缓存：没有对话历史和工具调用时，相同的指令直接返回缓存结果（llm_cache.rs）
 
 use anyhow::Result;
+use std::sync::{Arc, Mutex};
 
-use crate::config::AssistantConfig;
-use crate::openai_client::{ChatOptions, OpenAiClient, OpenAiClientConfig};
+use crate::assistant_tools::{ToolOutcome, ToolRegistry};
+use crate::config::{AssistantConfig, AssistantToolsConfig, LlmCacheConfig};
+use crate::conversation::ConversationSession;
+use crate::llm_cache::{CacheKey, LlmCache};
+use crate::llm_fallback::FallbackClient;
+use crate::openai_client::{ChatOptions, Message};
+
+/// 工具调用最多进行的轮数（搜索历史等结果需要交回模型继续回答）
+const MAX_TOOL_ROUNDS: usize = 3;
+
+/// 提供工具时追加到系统提示词末尾
+const TOOLS_PROMPT: &str = "\n\n你可以调用工具来直接完成用户要求的操作（打开网址、切换润色预设等）。调用工具时不要输出其他文字。";
+
+/// AI 助手的回答
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssistantReply {
+    /// 要插入的文本（模型回答以及插入类工具的结果）
+    pub text: String,
+    /// 已执行的工具动作摘要（不插入）
+    pub actions: Vec<String>,
+    /// 产出回答的端点（工具调用多轮时为最后一轮）
+    pub endpoint: Option<String>,
+}
 
 /// AI 助手处理器
 ///
 /// 根据是否有上下文（选中文本）使用不同的系统提示词
+///
+/// 克隆的实例共享同一个对话会话
 #[derive(Clone)]
 pub struct AssistantProcessor {
-    client: OpenAiClient,
+    client: FallbackClient,
+    /// 主端点模型（缓存键的一部分，备用端点的回答不缓存）
+    model: String,
     /// 问答模式系统提示词（无选中文本时使用）
     qa_system_prompt: String,
     /// 文本处理模式系统提示词（有选中文本时使用）
     text_processing_system_prompt: String,
+    /// 多轮对话会话
+    conversation: Arc<Mutex<ConversationSession>>,
+    /// 工具调用配置
+    tools: AssistantToolsConfig,
+    /// 响应缓存配置
+    cache: LlmCacheConfig,
 }
 
 impl AssistantProcessor {
     /// 创建新的 AI 助手处理器实例
     pub fn new(config: AssistantConfig) -> Self {
-        let client_config = OpenAiClientConfig::new(
-            &config.endpoint,
-            &config.api_key,
-            &config.model,
-        );
-        let client = OpenAiClient::new(client_config);
+        let client = FallbackClient::new(config.primary_endpoint(), &config.fallback);
 
         Self {
             client,
+            model: config.model,
             qa_system_prompt: config.qa_system_prompt,
             text_processing_system_prompt: config.text_processing_system_prompt,
+            conversation: Arc::new(Mutex::new(ConversationSession::new(config.conversation))),
+            tools: config.tools,
+            cache: config.cache,
         }
     }
 
+    /// 工具调用配置（未启用或协议不支持工具调用时为 None）
+    pub fn tools_config(&self) -> Option<&AssistantToolsConfig> {
+        (self.tools.enabled && self.client.supports_tools()).then_some(&self.tools)
+    }
+
+    /// 清空对话历史，下一次指令开始新对话
+    pub fn reset_conversation(&self) {
+        self.conversation.lock().unwrap().reset();
+    }
+
     /// 处理用户指令（无上下文 - 问答模式）
     ///
     /// # Arguments
     /// * `user_input` - 用户的语音转写文本（问题/指令）
+    /// * `tools` - 可调用的工具（为 None 时只返回文本）
+    /// * `on_delta` - 流式生成过程中每个文本片段的回调
     ///
     /// # Returns
     /// * LLM 的回答
-    pub async fn process(&self, user_input: &str) -> Result<String> {
+    pub async fn process(
+        &self,
+        user_input: &str,
+        tools: Option<&ToolRegistry>,
+        on_delta: impl FnMut(&str),
+    ) -> Result<AssistantReply> {
         if user_input.trim().is_empty() {
-            return Ok(String::new());
+            return Ok(AssistantReply::default());
         }
 
         tracing::info!("AssistantProcessor: 问答模式处理指令: {}", user_input);
 
-        self.client
-            .chat_simple(
-                &self.qa_system_prompt,
-                user_input,
-                ChatOptions::for_smart_command(),
-            )
-            .await
+        self.chat_with_history(&self.qa_system_prompt, user_input, tools, on_delta).await
     }
 
     /// 带上下文的指令处理（文本处理模式）
@@ -66,6 +112,8 @@ impl AssistantProcessor {
     /// # Arguments
     /// * `user_instruction` - 用户的语音指令
     /// * `selected_text` - 选中的文本
+    /// * `tools` - 可调用的工具（为 None 时只返回文本）
+    /// * `on_delta` - 流式生成过程中每个文本片段的回调
     ///
     /// # Returns
     /// * LLM 处理后的结果
@@ -73,9 +121,11 @@ impl AssistantProcessor {
         &self,
         user_instruction: &str,
         selected_text: &str,
-    ) -> Result<String> {
+        tools: Option<&ToolRegistry>,
+        on_delta: impl FnMut(&str),
+    ) -> Result<AssistantReply> {
         if user_instruction.trim().is_empty() {
-            return Ok(String::new());
+            return Ok(AssistantReply::default());
         }
 
         tracing::info!(
@@ -90,13 +140,96 @@ impl AssistantProcessor {
             selected_text, user_instruction
         );
 
-        self.client
-            .chat_simple(
-                &self.text_processing_system_prompt,
-                &user_message,
-                ChatOptions::for_smart_command(),
-            )
-            .await
+        self.chat_with_history(&self.text_processing_system_prompt, &user_message, tools, on_delta).await
+    }
+
+    /// 带对话历史的流式请求，完成后记录本轮问答
+    ///
+    /// 模型请求工具调用时逐个执行：插入类结果作为文本片段交给 `on_delta`，动作类结果只记录摘要，
+    /// 有需要交回模型的结果时带上工具结果继续请求。请求中途被取消（future 被丢弃）时不记录
+    async fn chat_with_history(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        tools: Option<&ToolRegistry>,
+        mut on_delta: impl FnMut(&str),
+    ) -> Result<AssistantReply> {
+        let history = self.conversation.lock().unwrap().history();
+        if !history.is_empty() {
+            tracing::info!("AssistantProcessor: 携带 {} 轮对话历史", history.len() / 2);
+        }
+
+        // 只缓存与上下文无关的请求：有历史或工具时结果依赖会话状态
+        let cache_key = if self.cache.enabled && history.is_empty() && tools.is_none() {
+            CacheKey::new(system_prompt, &self.model, user_message, &self.cache)
+        } else {
+            None
+        };
+        if let Some(hit) = cache_key.as_ref().and_then(|key| LlmCache::get(key, &self.cache)) {
+            on_delta(&hit.response);
+            self.conversation.lock().unwrap().record(user_message, &hit.response);
+            return Ok(AssistantReply { text: hit.response, actions: Vec::new(), endpoint: Some(hit.endpoint) });
+        }
+
+        let mut options = ChatOptions::for_smart_command();
+        let system_prompt = match tools {
+            Some(registry) => {
+                options = options.with_tools(registry.definitions());
+                format!("{}{}", system_prompt, TOOLS_PROMPT)
+            }
+            None => system_prompt.to_string(),
+        };
+
+        let mut messages = Vec::with_capacity(history.len() + 2);
+        messages.push(Message::system(system_prompt));
+        messages.extend(history);
+        messages.push(Message::user(user_message));
+
+        let mut reply = AssistantReply::default();
+        let mut truncated = false;
+        let mut from_fallback = false;
+        for round in 1..=MAX_TOOL_ROUNDS {
+            // 最后一轮不再提供工具，要求模型直接回答
+            if round == MAX_TOOL_ROUNDS {
+                options.tools.clear();
+            }
+            let routed = self.client.chat_stream(&messages, options.clone(), &mut on_delta).await?;
+            let completion = routed.value;
+            reply.endpoint = Some(routed.endpoint);
+            from_fallback |= routed.from_fallback;
+            truncated = completion.finish_reason.as_deref() == Some("length");
+            reply.text.push_str(&completion.content);
+
+            let Some(registry) = tools.filter(|_| !completion.tool_calls.is_empty()) else {
+                break;
+            };
+            messages.push(Message::assistant_with_tool_calls(completion.content, completion.tool_calls.clone()));
+            let mut needs_answer = false;
+            for call in &completion.tool_calls {
+                let outcome = registry.execute(call).await;
+                match outcome {
+                    ToolOutcome::Insert(ref text) => {
+                        on_delta(text);
+                        reply.text.push_str(text);
+                    }
+                    ToolOutcome::Action(ref summary) => reply.actions.push(summary.clone()),
+                    ToolOutcome::Context(_) => needs_answer = true,
+                }
+                messages.push(Message::tool(&call.id, outcome.content()));
+            }
+            if !needs_answer {
+                break;
+            }
+        }
+
+        let recorded = if reply.text.is_empty() { reply.actions.join("\n") } else { reply.text.clone() };
+        self.conversation.lock().unwrap().record(user_message, &recorded);
+        if let (Some(key), Some(endpoint)) = (cache_key, &reply.endpoint) {
+            if !reply.text.is_empty() && !truncated && !from_fallback {
+                LlmCache::put(&key, &reply.text, endpoint, &self.cache);
+            }
+        }
+        Ok(reply)
     }
 }
 
@@ -108,11 +241,16 @@ mod tests {
     fn create_test_config() -> AssistantConfig {
         AssistantConfig {
             enabled: true,
+            protocol: Default::default(),
             endpoint: "https://api.example.com/v1/chat/completions".to_string(),
             model: "test-model".to_string(),
             api_key: "test-key".to_string(),
             qa_system_prompt: DEFAULT_ASSISTANT_QA_PROMPT.to_string(),
             text_processing_system_prompt: DEFAULT_ASSISTANT_TEXT_PROCESSING_PROMPT.to_string(),
+            conversation: Default::default(),
+            tools: Default::default(),
+            fallback: Default::default(),
+            cache: Default::default(),
         }
     }
 
@@ -123,4 +261,14 @@ mod tests {
         assert!(!processor.qa_system_prompt.is_empty());
         assert!(!processor.text_processing_system_prompt.is_empty());
     }
+
+    #[test]
+    fn test_clones_share_conversation() {
+        let processor = AssistantProcessor::new(create_test_config());
+        let clone = processor.clone();
+        clone.conversation.lock().unwrap().record("问", "答");
+        assert_eq!(processor.conversation.lock().unwrap().history().len(), 2);
+        processor.reset_conversation();
+        assert!(clone.conversation.lock().unwrap().history().is_empty());
+    }
 }
@@ -0,0 +1,469 @@
+// AI 助手内置工具
+//
+// 启用工具调用后，模型可以直接执行动作而不是返回要插入的文本：
+// 打开网址、搜索录音归档、切换润色预设、开关 LLM 润色、插入当前日期时间，
+// 以及执行用户白名单中的命令（执行前在悬浮窗确认）
+//
+// 工具结果分三类：动作（只显示摘要，不插入）、插入文本、交回模型继续回答（搜索归档）
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audio_archive::{ArchiveMetadata, AudioArchive};
+use crate::config::{AppConfig, AssistantToolsConfig, LlmPreset};
+use crate::llm_post_processor::LlmPostProcessor;
+use crate::openai_client::{ToolCall, ToolDefinition};
+use crate::AppState;
+
+/// 等待用户确认命令的超时，超时视为拒绝
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(30);
+/// 命令执行超时
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+/// 搜索历史最多返回的条数
+const SEARCH_LIMIT: usize = 5;
+/// 命令输出摘要的最大字符数
+const OUTPUT_SUMMARY_CHARS: usize = 200;
+
+/// 插入的时间格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateTimeKind {
+    Date,
+    Time,
+    DateTime,
+}
+
+/// 解析后的工具调用
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolAction {
+    OpenUrl { url: String },
+    SearchHistory { query: String },
+    SwitchLlmPreset { preset: String },
+    /// enabled 为 None 时切换开关
+    SetPostProcessing { enabled: Option<bool> },
+    InsertDateTime { kind: DateTimeKind },
+    RunCommand { command: String },
+}
+
+/// 工具执行结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolOutcome {
+    /// 已执行的动作摘要（显示给用户，不插入）
+    Action(String),
+    /// 要插入到目标窗口的文本
+    Insert(String),
+    /// 交回模型继续生成回答的内容
+    Context(String),
+}
+
+impl ToolOutcome {
+    /// 作为 tool 消息发回模型的内容
+    pub fn content(&self) -> &str {
+        match self {
+            ToolOutcome::Action(text) | ToolOutcome::Insert(text) | ToolOutcome::Context(text) => text,
+        }
+    }
+}
+
+/// 命令确认请求（tool_confirmation_request 事件）
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolConfirmationRequest {
+    pub id: String,
+    pub command: String,
+}
+
+/// 工具定义（提供给模型）
+///
+/// 预设名称和允许的命令以 enum 形式列出，模型只能从中选择；
+/// 搜索工具只检索录音归档，未开启归档时不提供
+pub fn definitions(config: &AssistantToolsConfig, presets: &[LlmPreset], archive_enabled: bool) -> Vec<ToolDefinition> {
+    let tool = |name: &str, description: &str, parameters: Value| ToolDefinition {
+        name: name.to_string(),
+        description: description.to_string(),
+        parameters,
+    };
+    let preset_names: Vec<&str> = presets.iter().map(|p| p.name.as_str()).collect();
+
+    let mut tools = vec![
+        tool(
+            "open_url",
+            "在默认浏览器中打开网址",
+            json!({
+                "type": "object",
+                "properties": { "url": { "type": "string", "description": "完整网址，以 http:// 或 https:// 开头" } },
+                "required": ["url"]
+            }),
+        ),
+        tool(
+            "switch_llm_preset",
+            "切换听写模式使用的 LLM 润色预设",
+            json!({
+                "type": "object",
+                "properties": { "preset": { "type": "string", "enum": preset_names } },
+                "required": ["preset"]
+            }),
+        ),
+        tool(
+            "set_post_processing",
+            "开启或关闭听写模式的 LLM 润色，不指定 enabled 时切换当前状态",
+            json!({
+                "type": "object",
+                "properties": { "enabled": { "type": "boolean" } }
+            }),
+        ),
+        tool(
+            "insert_datetime",
+            "在光标处插入当前日期或时间",
+            json!({
+                "type": "object",
+                "properties": { "kind": { "type": "string", "enum": ["date", "time", "datetime"] } },
+                "required": ["kind"]
+            }),
+        ),
+    ];
+    if archive_enabled {
+        tools.push(tool(
+            "search_history",
+            "按关键词搜索录音归档中的转写记录（只包含开启录音归档后保存的录音），返回最近的匹配记录",
+            json!({
+                "type": "object",
+                "properties": { "query": { "type": "string", "description": "关键词" } },
+                "required": ["query"]
+            }),
+        ));
+    }
+    if !config.allowed_commands.is_empty() {
+        tools.push(tool(
+            "run_command",
+            "执行用户允许的命令（执行前需要用户确认）",
+            json!({
+                "type": "object",
+                "properties": { "command": { "type": "string", "enum": config.allowed_commands } },
+                "required": ["command"]
+            }),
+        ));
+    }
+    tools
+}
+
+impl ToolAction {
+    /// 解析模型给出的工具调用，命令不在白名单中时报错
+    pub fn parse(call: &ToolCall, config: &AssistantToolsConfig) -> Result<Self> {
+        let args: Value = if call.arguments.trim().is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str(&call.arguments)
+                .map_err(|e| anyhow::anyhow!("工具 {} 参数不可解析 ({}): {}", call.name, e, call.arguments))?
+        };
+        let string_arg = |key: &str| -> Result<String> {
+            args[key]
+                .as_str()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| anyhow::anyhow!("工具 {} 缺少参数 {}", call.name, key))
+        };
+
+        Ok(match call.name.as_str() {
+            "open_url" => {
+                let url = string_arg("url")?;
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    anyhow::bail!("只能打开 http/https 网址: {}", url);
+                }
+                ToolAction::OpenUrl { url }
+            }
+            "search_history" => ToolAction::SearchHistory { query: string_arg("query")? },
+            "switch_llm_preset" => ToolAction::SwitchLlmPreset { preset: string_arg("preset")? },
+            "set_post_processing" => ToolAction::SetPostProcessing { enabled: args["enabled"].as_bool() },
+            "insert_datetime" => ToolAction::InsertDateTime {
+                kind: match args["kind"].as_str() {
+                    Some("date") => DateTimeKind::Date,
+                    Some("time") => DateTimeKind::Time,
+                    _ => DateTimeKind::DateTime,
+                },
+            },
+            "run_command" => {
+                let command = string_arg("command")?;
+                if !config.allowed_commands.iter().any(|c| c.trim() == command) {
+                    anyhow::bail!("命令不在白名单中: {}", command);
+                }
+                ToolAction::RunCommand { command }
+            }
+            other => anyhow::bail!("未知工具: {}", other),
+        })
+    }
+}
+
+/// 格式化当前日期时间
+fn format_datetime(kind: DateTimeKind, now: DateTime<Local>) -> String {
+    let format = match kind {
+        DateTimeKind::Date => "%Y-%m-%d",
+        DateTimeKind::Time => "%H:%M",
+        DateTimeKind::DateTime => "%Y-%m-%d %H:%M",
+    };
+    now.format(format).to_string()
+}
+
+/// 按 ID 或名称查找预设（先精确匹配，再忽略大小写包含匹配）
+fn find_preset<'a>(presets: &'a [LlmPreset], query: &str) -> Option<&'a LlmPreset> {
+    let lower = query.to_lowercase();
+    presets
+        .iter()
+        .find(|p| p.id == query || p.name == query)
+        .or_else(|| presets.iter().find(|p| p.name.to_lowercase().contains(&lower)))
+}
+
+/// 搜索录音归档（最近的在前），匹配最终文本或 ASR 原文
+fn search_entries<'a>(entries: &'a [ArchiveMetadata], query: &str) -> Vec<&'a ArchiveMetadata> {
+    let lower = query.to_lowercase();
+    let mut matches: Vec<&ArchiveMetadata> = entries
+        .iter()
+        .filter(|e| {
+            e.text.to_lowercase().contains(&lower)
+                || e.original_text.as_deref().is_some_and(|t| t.to_lowercase().contains(&lower))
+        })
+        .collect();
+    matches.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+    matches.truncate(SEARCH_LIMIT);
+    matches
+}
+
+/// 截断过长的文本
+fn summarize(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(OUTPUT_SUMMARY_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// 工具执行器
+pub struct ToolRegistry {
+    app: AppHandle,
+    config: AssistantToolsConfig,
+}
+
+impl ToolRegistry {
+    pub fn new(app: AppHandle, config: AssistantToolsConfig) -> Self {
+        Self { app, config }
+    }
+
+    /// 当前可用的工具定义
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        let config = AppConfig::load().unwrap_or_else(|_| AppConfig::new());
+        definitions(&self.config, &config.llm_config.presets, config.audio_archive_config.enabled)
+    }
+
+    /// 执行一次工具调用，失败时返回失败摘要（不插入）
+    pub async fn execute(&self, call: &ToolCall) -> ToolOutcome {
+        tracing::info!("AI 助手工具调用: {} {}", call.name, call.arguments);
+        let result = match ToolAction::parse(call, &self.config) {
+            Ok(action) => self.run(action).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(outcome) => {
+                tracing::info!("AI 助手工具 {} 完成: {:?}", call.name, outcome);
+                outcome
+            }
+            Err(e) => {
+                tracing::warn!("AI 助手工具 {} 失败: {}", call.name, e);
+                ToolOutcome::Action(format!("{} 执行失败: {}", call.name, e))
+            }
+        }
+    }
+
+    async fn run(&self, action: ToolAction) -> Result<ToolOutcome> {
+        match action {
+            ToolAction::OpenUrl { url } => {
+                use tauri_plugin_opener::OpenerExt;
+                self.app
+                    .opener()
+                    .open_url(&url, None::<&str>)
+                    .map_err(|e| anyhow::anyhow!("打开网址失败: {}", e))?;
+                Ok(ToolOutcome::Action(format!("已打开 {}", url)))
+            }
+            ToolAction::SearchHistory { query } => {
+                let entries = tauri::async_runtime::spawn_blocking(AudioArchive::list).await??;
+                let matches = search_entries(&entries, &query);
+                if matches.is_empty() {
+                    return Ok(ToolOutcome::Context(format!("没有找到包含“{}”的历史记录", query)));
+                }
+                let lines: Vec<String> = matches
+                    .iter()
+                    .map(|e| {
+                        let time = DateTime::<chrono::Utc>::from_timestamp_millis(e.created_at as i64)
+                            .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+                            .unwrap_or_default();
+                        format!("[{}] {}", time, e.text)
+                    })
+                    .collect();
+                Ok(ToolOutcome::Context(lines.join("\n")))
+            }
+            ToolAction::SwitchLlmPreset { preset } => self.switch_llm_preset(&preset),
+            ToolAction::SetPostProcessing { enabled } => self.set_post_processing(enabled),
+            ToolAction::InsertDateTime { kind } => Ok(ToolOutcome::Insert(format_datetime(kind, Local::now()))),
+            ToolAction::RunCommand { command } => {
+                if !self.confirm(&command).await {
+                    return Ok(ToolOutcome::Action(format!("已取消执行: {}", command)));
+                }
+                run_command(&command).await
+            }
+        }
+    }
+
+    /// 切换润色预设：更新运行中的处理器并保存配置，通知前端同步
+    fn switch_llm_preset(&self, query: &str) -> Result<ToolOutcome> {
+        let mut config = AppConfig::load()?;
+        let preset = find_preset(&config.llm_config.presets, query)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("没有名为“{}”的预设", query))?;
+        config.llm_config.active_preset_id = preset.id.clone();
+        config.save()?;
+
+        let state = self.app.state::<AppState>();
+        let mut processor = state.post_processor.lock().unwrap();
+        *processor = processor.take().map(|p| p.with_preset(&preset.id));
+        drop(processor);
+
+        let _ = self.app.emit("assistant_config_changed", json!({ "active_preset_id": preset.id }));
+        Ok(ToolOutcome::Action(format!("已切换润色预设：{}", preset.name)))
+    }
+
+    /// 开关 LLM 润色：开启时按已保存的 LLM 配置创建处理器
+    fn set_post_processing(&self, enabled: Option<bool>) -> Result<ToolOutcome> {
+        let mut config = AppConfig::load()?;
+        let state = self.app.state::<AppState>();
+        let enabled = enabled.unwrap_or(!*state.enable_post_process.lock().unwrap());
+        if enabled && !config.llm_config.is_valid() {
+            anyhow::bail!("LLM 润色未配置 API");
+        }
+        config.enable_llm_post_process = enabled;
+        config.save()?;
+
+        *state.enable_post_process.lock().unwrap() = enabled;
+        *state.post_processor.lock().unwrap() = enabled.then(|| LlmPostProcessor::new(config.llm_config));
+
+        let _ = self.app.emit("assistant_config_changed", json!({ "enable_post_process": enabled }));
+        Ok(ToolOutcome::Action(if enabled { "已开启 LLM 润色" } else { "已关闭 LLM 润色" }.to_string()))
+    }
+
+    /// 在悬浮窗请求用户确认，拒绝或超时返回 false
+    async fn confirm(&self, command: &str) -> bool {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let confirmations = self.app.state::<AppState>().tool_confirmations.clone();
+        confirmations.lock().unwrap().insert(id.clone(), tx);
+
+        tracing::info!("AI 助手请求确认执行命令: {}", command);
+        let _ = self.app.emit(
+            "tool_confirmation_request",
+            ToolConfirmationRequest { id: id.clone(), command: command.to_string() },
+        );
+        let approved = matches!(tokio::time::timeout(CONFIRMATION_TIMEOUT, rx).await, Ok(Ok(true)));
+        if confirmations.lock().unwrap().remove(&id).is_some() {
+            tracing::info!("AI 助手命令确认超时");
+            let _ = self.app.emit("tool_confirmation_closed", &id);
+        }
+        approved
+    }
+}
+
+/// 通过系统 shell 执行命令，返回退出状态和输出摘要
+async fn run_command(command: &str) -> Result<ToolOutcome> {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        // CREATE_NO_WINDOW：不弹出控制台窗口
+        cmd.creation_flags(0x0800_0000);
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    cmd.kill_on_drop(true);
+
+    tracing::info!("AI 助手执行命令: {}", command);
+    let output = tokio::time::timeout(COMMAND_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| anyhow::anyhow!("命令执行超时"))??;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let detail = summarize(if stdout.trim().is_empty() { &stderr } else { &stdout });
+
+    let status = if output.status.success() { "已执行" } else { "执行失败" };
+    Ok(ToolOutcome::Action(if detail.is_empty() {
+        format!("{}: {}", status, command)
+    } else {
+        format!("{}: {}\n{}", status, command, detail)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn call(name: &str, arguments: &str) -> ToolCall {
+        ToolCall { id: "call_1".to_string(), name: name.to_string(), arguments: arguments.to_string() }
+    }
+
+    fn preset(id: &str, name: &str) -> LlmPreset {
+        LlmPreset { id: id.to_string(), name: name.to_string(), system_prompt: String::new(), bypass_cache: false }
+    }
+
+    #[test]
+    fn test_parse_actions() {
+        let config = AssistantToolsConfig { enabled: true, allowed_commands: vec!["git pull".to_string()] };
+        assert_eq!(
+            ToolAction::parse(&call("open_url", r#"{"url":"https://example.com"}"#), &config).unwrap(),
+            ToolAction::OpenUrl { url: "https://example.com".to_string() }
+        );
+        assert!(ToolAction::parse(&call("open_url", r#"{"url":"file:///etc/passwd"}"#), &config).is_err());
+        assert_eq!(
+            ToolAction::parse(&call("set_post_processing", ""), &config).unwrap(),
+            ToolAction::SetPostProcessing { enabled: None }
+        );
+        assert_eq!(
+            ToolAction::parse(&call("insert_datetime", r#"{"kind":"time"}"#), &config).unwrap(),
+            ToolAction::InsertDateTime { kind: DateTimeKind::Time }
+        );
+        assert!(ToolAction::parse(&call("run_command", r#"{"command":"git pull"}"#), &config).is_ok());
+        assert!(ToolAction::parse(&call("run_command", r#"{"command":"rm -rf /"}"#), &config).is_err());
+        assert!(ToolAction::parse(&call("delete_files", "{}"), &config).is_err());
+    }
+
+    #[test]
+    fn test_definitions_list_presets_and_commands() {
+        let presets = vec![preset("polishing", "文本润色"), preset("email", "邮件模式")];
+        let tools = definitions(&AssistantToolsConfig::default(), &presets, false);
+        assert!(tools.iter().all(|t| t.name != "run_command"));
+        // 未开启录音归档时没有可搜索的记录
+        assert!(tools.iter().all(|t| t.name != "search_history"));
+        let switch = tools.iter().find(|t| t.name == "switch_llm_preset").unwrap();
+        assert_eq!(switch.parameters["properties"]["preset"]["enum"], json!(["文本润色", "邮件模式"]));
+
+        let config = AssistantToolsConfig { enabled: true, allowed_commands: vec!["git pull".to_string()] };
+        let tools = definitions(&config, &presets, true);
+        assert!(tools.iter().any(|t| t.name == "run_command"));
+        assert!(tools.iter().any(|t| t.name == "search_history"));
+    }
+
+    #[test]
+    fn test_find_preset_and_format_datetime() {
+        let presets = vec![preset("polishing", "文本润色"), preset("email", "邮件模式")];
+        assert_eq!(find_preset(&presets, "email").map(|p| p.name.as_str()), Some("邮件模式"));
+        assert_eq!(find_preset(&presets, "邮件").map(|p| p.id.as_str()), Some("email"));
+        assert!(find_preset(&presets, "翻译").is_none());
+
+        let now = Local.with_ymd_and_hms(2026, 10, 19, 9, 5, 0).unwrap();
+        assert_eq!(format_datetime(DateTimeKind::Date, now), "2026-10-19");
+        assert_eq!(format_datetime(DateTimeKind::DateTime, now), "2026-10-19 09:05");
+    }
+}
@@ -0,0 +1,272 @@
+// 录音归档模块
+// 可选地将每次录音（16kHz WAV）与转写 ID、元数据一起保存到应用数据目录，
+// 便于回听误识别的录音、反馈给服务商或重新转写
+//
+// 存储布局：<数据目录>/PushToTalk/recordings/<id>.wav + <id>.json
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::app_data::{self, Retention};
+use crate::config::AudioArchiveConfig;
+
+/// 归档录音的元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveMetadata {
+    /// 转写 ID（与前端历史记录 ID 一致）
+    pub id: String,
+    /// 创建时间（Unix 毫秒）
+    pub created_at: u64,
+    /// 处理模式（normal / assistant）
+    pub mode: String,
+    /// 使用的 ASR 服务商
+    #[serde(default)]
+    pub asr_provider: Option<String>,
+    /// 音频时长（毫秒）
+    pub duration_ms: u64,
+    /// 最终文本
+    pub text: String,
+    /// ASR 原始文本（经过 LLM 处理时存在）
+    #[serde(default)]
+    pub original_text: Option<String>,
+    pub asr_time_ms: u64,
+    #[serde(default)]
+    pub llm_time_ms: Option<u64>,
+}
+
+/// 录音归档
+///
+/// 持有当前配置和一段待归档的录音：录音停止时暂存，转写成功后连同结果写盘
+pub struct AudioArchive {
+    config: AudioArchiveConfig,
+    pending_audio: Option<Vec<u8>>,
+    /// 实际完成本次转写的 ASR 服务商
+    pending_provider: Option<String>,
+}
+
+impl AudioArchive {
+    pub fn new(config: AudioArchiveConfig) -> Self {
+        Self {
+            config,
+            pending_audio: None,
+            pending_provider: None,
+        }
+    }
+
+    /// 更新配置（关闭时同时丢弃暂存的录音）
+    pub fn set_config(&mut self, config: AudioArchiveConfig) {
+        if !config.enabled {
+            self.pending_audio = None;
+        }
+        self.config = config;
+    }
+
+    pub fn config(&self) -> &AudioArchiveConfig {
+        &self.config
+    }
+
+    /// 暂存本次录音（未启用归档时忽略）
+    pub fn stash(&mut self, wav_data: &[u8]) {
+        if self.config.enabled {
+            self.pending_audio = Some(wav_data.to_vec());
+        }
+    }
+
+    /// 记录实际完成本次转写的 ASR 服务商（实时转写失败改用 HTTP 时会被覆盖）
+    pub fn set_asr_provider(&mut self, provider: Option<String>) {
+        self.pending_provider = provider;
+    }
+
+    /// 取出暂存的录音及完成转写的 ASR 服务商
+    pub fn take_pending(&mut self) -> Option<(Vec<u8>, Option<String>)> {
+        let provider = self.pending_provider.take();
+        self.pending_audio.take().map(|audio| (audio, provider))
+    }
+
+    /// 归档目录
+    pub fn archive_dir() -> Result<PathBuf> {
+        app_data::data_subdir("recordings")
+    }
+
+    /// 保存录音并执行保留策略
+    pub fn save(wav_data: &[u8], metadata: &ArchiveMetadata, config: &AudioArchiveConfig) -> Result<()> {
+        let dir = Self::archive_dir()?;
+        save_in(&dir, wav_data, metadata)?;
+        let removed = enforce_retention_in(&dir, config)?;
+        if removed > 0 {
+            tracing::info!("录音归档保留策略已清理 {} 条旧录音", removed);
+        }
+        Ok(())
+    }
+
+    /// 读取归档的录音和元数据
+    pub fn load(id: &str) -> Result<(Vec<u8>, ArchiveMetadata)> {
+        load_in(&Self::archive_dir()?, id)
+    }
+
+    /// 列出所有归档录音（按时间倒序）
+    pub fn list() -> Result<Vec<ArchiveMetadata>> {
+        let dir = Self::archive_dir()?;
+        let mut entries: Vec<ArchiveMetadata> = std::fs::read_dir(&dir)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
+            .filter_map(|p| std::fs::read_to_string(p).ok())
+            .filter_map(|content| serde_json::from_str(&content).ok())
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+        Ok(entries)
+    }
+
+    /// 删除全部归档录音，返回删除的录音条数
+    pub fn purge_all() -> Result<usize> {
+        purge_in(&Self::archive_dir()?)
+    }
+}
+
+/// 校验 ID，防止路径穿越
+fn validate_id(id: &str) -> Result<()> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(anyhow::anyhow!("无效的录音 ID: {}", id));
+    }
+    Ok(())
+}
+
+fn save_in(dir: &Path, wav_data: &[u8], metadata: &ArchiveMetadata) -> Result<()> {
+    validate_id(&metadata.id)?;
+    std::fs::write(dir.join(format!("{}.wav", metadata.id)), wav_data)?;
+    std::fs::write(
+        dir.join(format!("{}.json", metadata.id)),
+        serde_json::to_string_pretty(metadata)?,
+    )?;
+    tracing::info!("录音已归档: {} ({} bytes)", metadata.id, wav_data.len());
+    Ok(())
+}
+
+fn load_in(dir: &Path, id: &str) -> Result<(Vec<u8>, ArchiveMetadata)> {
+    validate_id(id)?;
+    let wav_path = dir.join(format!("{}.wav", id));
+    if !wav_path.exists() {
+        return Err(anyhow::anyhow!("未找到归档录音: {}（可能未启用归档或已被清理）", id));
+    }
+    let wav_data = std::fs::read(&wav_path)?;
+    let metadata = serde_json::from_str(&std::fs::read_to_string(dir.join(format!("{}.json", id)))?)?;
+    Ok((wav_data, metadata))
+}
+
+/// 删除一条录音（WAV + 元数据）
+fn remove_entry(wav_path: &Path) -> bool {
+    let removed = std::fs::remove_file(wav_path).is_ok();
+    let _ = std::fs::remove_file(wav_path.with_extension("json"));
+    removed
+}
+
+/// 执行保留策略：先按保存期限删除，再从最旧的开始删除直到总容量低于上限
+fn enforce_retention_in(dir: &Path, config: &AudioArchiveConfig) -> Result<usize> {
+    let retention = Retention {
+        max_age: (config.max_age_days > 0).then(|| Duration::from_secs(config.max_age_days as u64 * 24 * 3600)),
+        max_files: None,
+        max_bytes: (config.max_total_mb > 0).then(|| config.max_total_mb * 1024 * 1024),
+    };
+    app_data::prune_oldest_first(dir, "wav", &retention, remove_entry)
+}
+
+fn purge_in(dir: &Path) -> Result<usize> {
+    let mut removed = 0;
+    for entry in std::fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("wav") => {
+                std::fs::remove_file(&path)?;
+                removed += 1;
+            }
+            Some("json") => std::fs::remove_file(&path)?,
+            _ => {}
+        }
+    }
+    tracing::info!("已清空录音归档: {} 条", removed);
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_data::now_millis;
+    use crate::app_data::test_support::{set_age, temp_dir};
+
+    fn metadata(id: &str) -> ArchiveMetadata {
+        ArchiveMetadata {
+            id: id.to_string(),
+            created_at: now_millis(),
+            mode: "normal".to_string(),
+            asr_provider: Some("qwen".to_string()),
+            duration_ms: 1000,
+            text: "你好".to_string(),
+            original_text: None,
+            asr_time_ms: 300,
+            llm_time_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_save_and_purge() {
+        let dir = temp_dir("ptt_archive_test");
+        save_in(&dir, &[1, 2, 3], &metadata("abc")).unwrap();
+
+        assert_eq!(std::fs::read(dir.join("abc.wav")).unwrap(), vec![1, 2, 3]);
+        let meta: ArchiveMetadata =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("abc.json")).unwrap()).unwrap();
+        assert_eq!(meta.text, "你好");
+
+        let (wav, meta) = load_in(&dir, "abc").unwrap();
+        assert_eq!(wav, vec![1, 2, 3]);
+        assert_eq!(meta.id, "abc");
+
+        assert_eq!(purge_in(&dir).unwrap(), 1);
+        assert!(load_in(&dir, "abc").is_err());
+        assert!(!dir.join("abc.wav").exists());
+        assert!(!dir.join("abc.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_retention_by_size() {
+        let dir = temp_dir("ptt_archive_test");
+        let one_mb = vec![0u8; 1024 * 1024];
+        for (age_secs, id) in [(30, "a"), (20, "b"), (10, "c")] {
+            save_in(&dir, &one_mb, &metadata(id)).unwrap();
+            set_age(&dir.join(format!("{}.wav", id)), age_secs);
+        }
+
+        let config = AudioArchiveConfig { enabled: true, max_total_mb: 2, max_age_days: 0 };
+        assert_eq!(enforce_retention_in(&dir, &config).unwrap(), 1);
+        // 最旧的录音连同元数据被删除
+        assert!(!dir.join("a.wav").exists());
+        assert!(!dir.join("a.json").exists());
+        assert!(dir.join("c.wav").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_validate_id_rejects_paths() {
+        assert!(validate_id("../config").is_err());
+        assert!(validate_id("").is_err());
+        assert!(validate_id("550e8400-e29b-41d4-a716-446655440000").is_ok());
+    }
+
+    #[test]
+    fn test_pending_provider() {
+        let mut archive = AudioArchive::new(AudioArchiveConfig { enabled: true, ..Default::default() });
+        archive.stash(&[1, 2]);
+        archive.set_asr_provider(Some("qwen".to_string()));
+        // 实时转写失败后由 HTTP 备用完成
+        archive.set_asr_provider(Some("siliconflow".to_string()));
+        assert_eq!(archive.take_pending(), Some((vec![1, 2], Some("siliconflow".to_string()))));
+        assert_eq!(archive.take_pending(), None);
+
+        archive.stash(&[3]);
+        assert_eq!(archive.take_pending(), Some((vec![3], None)));
+    }
+}
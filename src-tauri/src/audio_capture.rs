@@ -0,0 +1,192 @@
+// 实时音频采集模块
+// cpal 回调线程只把样本写入预分配的 SPSC 环形缓冲（无锁、无内存分配），
+// 转单声道、降采样、VAD、AGC、电平发送等处理全部在独立的工作线程中完成，
+// 避免在音频线程上等锁导致优先级反转和丢帧
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// 环形缓冲容量（秒），工作线程短暂卡顿时用于吸收音频
+const RING_SECONDS: usize = 2;
+/// 工作线程无数据时的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// 回调侧写入端（在 cpal 回调中使用）
+pub struct CaptureProducer {
+    producer: rtrb::Producer<f32>,
+    recording: Arc<AtomicBool>,
+    dropped: Arc<AtomicUsize>,
+    channels: usize,
+}
+
+impl CaptureProducer {
+    #[inline]
+    pub fn push_f32(&mut self, data: &[f32]) {
+        self.push_iter(data.len(), data.iter().copied());
+    }
+
+    #[inline]
+    pub fn push_i16(&mut self, data: &[i16]) {
+        self.push_iter(data.len(), data.iter().map(|&s| s as f32 / i16::MAX as f32));
+    }
+
+    #[inline]
+    pub fn push_u16(&mut self, data: &[u16]) {
+        self.push_iter(data.len(), data.iter().map(|&s| (s as f32 - 32768.0) / 32768.0));
+    }
+
+    /// 写入样本：缓冲区满时丢弃放不下的部分（按整帧截断）并计数，绝不阻塞
+    #[inline]
+    fn push_iter(&mut self, len: usize, samples: impl Iterator<Item = f32>) {
+        if !self.recording.load(Ordering::Acquire) {
+            return;
+        }
+        let free = self.producer.slots();
+        let n = if len <= free { len } else { free - free % self.channels };
+        if n < len {
+            self.dropped.fetch_add(len - n, Ordering::Relaxed);
+        }
+        if n == 0 {
+            return;
+        }
+        if let Ok(chunk) = self.producer.write_chunk_uninit(n) {
+            chunk.fill_from_iter(samples);
+        }
+    }
+}
+
+/// 工作线程侧读取端（启动工作线程前，回调写入的样本暂存在环形缓冲中）
+pub struct CaptureConsumer {
+    consumer: rtrb::Consumer<f32>,
+    dropped: Arc<AtomicUsize>,
+    channels: usize,
+}
+
+/// 音频处理工作线程
+///
+/// 停止时先让回调停止写入（drop stream），再调用 finish 排空缓冲并等待线程退出
+pub struct CaptureWorker {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// 创建预分配的环形缓冲
+///
+/// recording 为 false 时回调侧直接丢弃样本
+pub fn capture_ring(sample_rate: u32, channels: u16, recording: Arc<AtomicBool>) -> (CaptureProducer, CaptureConsumer) {
+    let channels = channels.max(1) as usize;
+    let capacity = sample_rate as usize * channels * RING_SECONDS;
+    let (producer, consumer) = rtrb::RingBuffer::<f32>::new(capacity);
+    let dropped = Arc::new(AtomicUsize::new(0));
+    (
+        CaptureProducer { producer, recording, dropped: Arc::clone(&dropped), channels },
+        CaptureConsumer { consumer, dropped, channels },
+    )
+}
+
+impl CaptureConsumer {
+    /// 启动工作线程
+    ///
+    /// process 在工作线程中收到设备原始格式（交错多声道 f32）的样本，长度总是声道数的整数倍
+    pub fn spawn_worker<F>(self, mut process: F) -> CaptureWorker
+    where
+        F: FnMut(&[f32]) + Send + 'static,
+    {
+        let Self { mut consumer, dropped, channels } = self;
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_worker = Arc::clone(&stop);
+
+        let handle = std::thread::Builder::new()
+            .name("audio-capture".to_string())
+            .spawn(move || {
+                let mut buffer: Vec<f32> = Vec::with_capacity(consumer.buffer().capacity());
+                loop {
+                    // 先读停止标志再检查数据，保证停止前写入的样本都会被处理
+                    let stopping = stop_worker.load(Ordering::Acquire);
+                    let available = consumer.slots();
+                    let available = available - available % channels;
+                    if available > 0 {
+                        if let Ok(chunk) = consumer.read_chunk(available) {
+                            let (first, second) = chunk.as_slices();
+                            buffer.clear();
+                            buffer.extend_from_slice(first);
+                            buffer.extend_from_slice(second);
+                            chunk.commit_all();
+                            process(&buffer);
+                        }
+                    } else if stopping {
+                        break;
+                    } else {
+                        std::thread::sleep(POLL_INTERVAL);
+                    }
+                }
+
+                let dropped = dropped.load(Ordering::Relaxed);
+                if dropped > 0 {
+                    tracing::warn!("音频处理线程处理过慢，环形缓冲溢出丢弃 {} 个样本", dropped);
+                }
+            })
+            .expect("创建音频处理线程失败");
+
+        CaptureWorker { stop, handle: Some(handle) }
+    }
+}
+
+impl CaptureWorker {
+    /// 处理完缓冲中剩余的样本后结束工作线程
+    pub fn finish(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for CaptureWorker {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_worker_receives_all_samples() {
+        let recording = Arc::new(AtomicBool::new(true));
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_worker = Arc::clone(&received);
+        let (mut producer, consumer) = capture_ring(100, 2, Arc::clone(&recording));
+
+        // 工作线程启动前写入的样本暂存在环形缓冲中
+        producer.push_f32(&[0.5, -0.5, 0.25, -0.25]);
+        let worker = consumer.spawn_worker(move |samples| {
+            received_worker.lock().unwrap().extend_from_slice(samples);
+        });
+        producer.push_i16(&[i16::MAX, 0]);
+        recording.store(false, Ordering::Release);
+        producer.push_f32(&[1.0, 1.0]);
+        worker.finish();
+
+        assert_eq!(*received.lock().unwrap(), vec![0.5, -0.5, 0.25, -0.25, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_overflow_drops_whole_frames() {
+        // 容量 = 1Hz * 2 声道 * 2 秒 = 4 样本
+        let (mut producer, consumer) = capture_ring(1, 2, Arc::new(AtomicBool::new(true)));
+        producer.push_f32(&[0.0; 2]);
+        // 只剩 2 个空位：写入一帧，丢弃一帧
+        producer.push_f32(&[1.0; 4]);
+        assert_eq!(consumer.dropped.load(Ordering::Relaxed), 2);
+        assert_eq!(consumer.consumer.slots(), 4);
+    }
+}
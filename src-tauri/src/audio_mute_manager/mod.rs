@@ -0,0 +1,910 @@
+// 录音时自动静音 / 压低其他应用程序的声音
+//
+// 平台相关的会话枚举、静音和音量调节由 AudioSessionBackend 实现：
+// - Windows: WASAPI 会话（wasapi.rs）
+// - Linux: PulseAudio / PipeWire sink-input（pulse.rs）
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::{MuteMode, MuteOtherAppsConfig};
+
+#[cfg(target_os = "windows")]
+mod wasapi;
+#[cfg(target_os = "linux")]
+mod pulse;
+
+/// 一个应用的播放会话
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioSession {
+    /// 会话标识（Windows 为进程 ID，PulseAudio 为 sink-input 序号）
+    pub id: u32,
+    /// 所属进程 ID（0 表示未知或系统声音）
+    pub pid: u32,
+    /// 进程名（可能为空）
+    pub name: String,
+    /// 当前是否已静音
+    pub muted: bool,
+    /// 当前音量（0.0-1.0）
+    pub volume: f32,
+}
+
+/// 平台音频会话后端
+pub trait AudioSessionBackend: Send + Sync {
+    /// 枚举当前默认输出设备上的所有播放会话
+    fn list_sessions(&self) -> Result<Vec<AudioSession>, String>;
+    /// 设置会话的静音状态
+    fn set_mute(&self, id: u32, muted: bool) -> Result<(), String>;
+    /// 设置会话音量（0.0-1.0）
+    fn set_volume(&self, id: u32, volume: f32) -> Result<(), String>;
+}
+
+/// 不支持的平台：没有可静音的会话
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+struct NullBackend;
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+impl AudioSessionBackend for NullBackend {
+    fn list_sessions(&self) -> Result<Vec<AudioSession>, String> {
+        Ok(Vec::new())
+    }
+
+    fn set_mute(&self, _id: u32, _muted: bool) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn set_volume(&self, _id: u32, _volume: f32) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// 当前平台的会话后端
+#[cfg(target_os = "windows")]
+fn platform_backend() -> Arc<dyn AudioSessionBackend> {
+    Arc::new(wasapi::WasapiBackend)
+}
+
+#[cfg(target_os = "linux")]
+fn platform_backend() -> Arc<dyn AudioSessionBackend> {
+    Arc::new(pulse::PulseBackend)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn platform_backend() -> Arc<dyn AudioSessionBackend> {
+    Arc::new(NullBackend)
+}
+
+/// 看门狗检查间隔（毫秒）
+const WATCHDOG_INTERVAL_MS: u64 = 1000;
+
+/// 录音会话超时时间（秒）
+/// 如果录音持续超过这个时间，看门狗会强制重置状态并恢复音量
+/// 这是防止"全静音卡死"的核弹级兜底机制
+const SESSION_TIMEOUT_SECS: u64 = 180; // 3 分钟
+
+/// 音量渐变的步进间隔（毫秒）
+const FADE_STEP_MS: u64 = 20;
+
+/// 我们对某个会话做过的调整（用于恢复）
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Adjustment {
+    /// 被静音
+    Muted,
+    /// 被压低音量，记录原始音量
+    Ducked { original: f32 },
+}
+
+/// 音量渐变目标：(会话标识, 起始音量, 目标音量)
+type Fade = (u32, f32, f32);
+
+/// 渐变的单步函数：每次调用前进一步，返回 false 表示渐变已结束（完成或被中止）
+type FadeStep = Box<dyn FnMut() -> bool + Send>;
+
+/// 渐变调度：决定何时执行下一步
+trait FadeScheduler: Send + Sync {
+    fn schedule(&self, step: FadeStep);
+}
+
+/// 在后台线程中每隔 FADE_STEP_MS 执行一步
+struct ThreadScheduler;
+
+impl FadeScheduler for ThreadScheduler {
+    fn schedule(&self, mut step: FadeStep) {
+        thread::spawn(move || {
+            while step() {
+                thread::sleep(Duration::from_millis(FADE_STEP_MS));
+            }
+        });
+    }
+}
+
+/// 会话调整逻辑（管理器和看门狗线程共享）
+struct SessionController {
+    backend: Arc<dyn AudioSessionBackend>,
+    /// 当前进程 ID（避免静音自己）
+    own_process_id: u32,
+    /// 被我们调整过的会话及其调整前的状态
+    adjusted: Mutex<HashMap<u32, Adjustment>>,
+    config: Mutex<MuteOtherAppsConfig>,
+    /// 渐变代数：每次开始新的渐变递增，旧的渐变据此中止
+    fade_generation: AtomicU64,
+    fade_scheduler: Arc<dyn FadeScheduler>,
+}
+
+impl SessionController {
+    fn is_excluded(config: &MuteOtherAppsConfig, session: &AudioSession) -> bool {
+        if session.name.is_empty() {
+            return false;
+        }
+        let name = session.name.to_lowercase();
+        let name = name.strip_suffix(".exe").unwrap_or(&name);
+        config.excluded_apps.iter().any(|app| {
+            let app = app.trim().to_lowercase();
+            app.strip_suffix(".exe").unwrap_or(&app) == name
+        })
+    }
+
+    /// 静音或压低其他应用，返回新处理的会话数量
+    fn adjust(self: &Arc<Self>) -> Result<usize, String> {
+        let config = self.config.lock().unwrap().clone();
+        let ratio = config.duck_percent.min(100) as f32 / 100.0;
+
+        let sessions = self.backend.list_sessions()?;
+        tracing::debug!("Found {} audio sessions", sessions.len());
+
+        let mut count = 0;
+        let mut fades: Vec<Fade> = Vec::new();
+        let mut adjusted = self.adjusted.lock().unwrap();
+
+        for session in sessions {
+            // 1. 跳过自己
+            if session.pid == self.own_process_id {
+                tracing::debug!("Skipping own process (pid: {})", session.pid);
+                continue;
+            }
+
+            // 2. 跳过系统声音 (PID 0)
+            if session.pid == 0 {
+                tracing::debug!("Skipping system sounds (session: {})", session.id);
+                continue;
+            }
+
+            // 3. 跳过用户排除的应用
+            if Self::is_excluded(&config, &session) {
+                tracing::debug!("Skipping excluded app (session: {}, name: {})", session.id, session.name);
+                continue;
+            }
+
+            match adjusted.get(&session.id) {
+                // 4. 恢复渐变被新的录音打断的会话：从当前音量重新压低
+                Some(Adjustment::Ducked { original }) => {
+                    if config.mode == MuteMode::Duck {
+                        fades.push((session.id, session.volume, original * ratio));
+                    }
+                    continue;
+                }
+                // 5. 跳过已经在我们列表中的（避免重复操作）
+                Some(Adjustment::Muted) => {
+                    tracing::debug!("Already in muted list, skipping (session: {})", session.id);
+                    continue;
+                }
+                None => {}
+            }
+
+            // 6. 只处理当前未静音的应用
+            if session.muted {
+                tracing::debug!(
+                    "Session already muted externally, skipping (session: {}, pid: {})",
+                    session.id,
+                    session.pid
+                );
+                continue;
+            }
+
+            match config.mode {
+                MuteMode::Mute => match self.backend.set_mute(session.id, true) {
+                    Ok(()) => {
+                        adjusted.insert(session.id, Adjustment::Muted);
+                        count += 1;
+                        tracing::debug!("Muted session (session: {}, pid: {})", session.id, session.pid);
+                    }
+                    Err(e) => tracing::debug!("Failed to mute session {}: {}", session.id, e),
+                },
+                MuteMode::Duck => {
+                    adjusted.insert(session.id, Adjustment::Ducked { original: session.volume });
+                    fades.push((session.id, session.volume, session.volume * ratio));
+                    count += 1;
+                    tracing::debug!(
+                        "Ducking session (session: {}, pid: {}, volume: {:.2})",
+                        session.id,
+                        session.pid,
+                        session.volume
+                    );
+                }
+            }
+        }
+
+        tracing::info!("Adjusted {} audio applications (total tracked: {})", count, adjusted.len());
+        drop(adjusted);
+
+        if !fades.is_empty() {
+            self.fade(fades, config.fade_ms, false);
+        }
+        Ok(count)
+    }
+
+    /// 恢复被调整的会话，返回恢复的会话数量
+    ///
+    /// 安全机制：
+    /// 1. 恢复成功一个，从列表删除一个（避免竞态条件）
+    /// 2. 中断检测：如果恢复过程中用户又开始录音，立即停止恢复
+    /// 3. 僵尸会话清理：自动清理已关闭应用的会话，防止看门狗空转
+    ///
+    /// 压低的会话在渐变完成后才从列表删除；渐变被新的录音打断时保留原始音量，
+    /// 以便下次恢复到正确的音量
+    fn restore(self: &Arc<Self>, active_sessions: &AtomicU32, with_fade: bool) -> Result<usize, String> {
+        // 获取快照，放入 pending 用于跟踪僵尸会话
+        let mut pending: HashMap<u32, Adjustment> = self.adjusted.lock().unwrap().clone();
+
+        if pending.is_empty() {
+            tracing::debug!("No adjusted applications to restore");
+            return Ok(0);
+        }
+
+        tracing::debug!("Restoring {} adjusted applications", pending.len());
+
+        let sessions = self.backend.list_sessions()?;
+        let mut restored_count = 0;
+        let mut fades: Vec<Fade> = Vec::new();
+
+        for session in sessions {
+            // === 中断检测 ===
+            // 如果在恢复过程中用户又按下了录音键，立即停止恢复
+            // 这样残留的会话会在 adjust 中被跳过，保持静音（正确行为）
+            if active_sessions.load(Ordering::Relaxed) > 0 {
+                tracing::info!("New session started during restore, aborting restore operation");
+                return Ok(restored_count);
+            }
+
+            // 如果这个会话在我们的待处理列表里
+            // 无论恢复成功与否，都说明这个会话还活着（剩下的就是僵尸会话）
+            match pending.remove(&session.id) {
+                Some(Adjustment::Muted) if self.backend.set_mute(session.id, false).is_ok() => {
+                    // 恢复成功后，立即从全局列表中删除
+                    self.adjusted.lock().unwrap().remove(&session.id);
+                    restored_count += 1;
+                    tracing::debug!("Restored audio for session (session: {}, pid: {})", session.id, session.pid);
+                }
+                Some(Adjustment::Ducked { original }) => {
+                    fades.push((session.id, session.volume, original));
+                    restored_count += 1;
+                }
+                Some(Adjustment::Muted) | None => {}
+            }
+        }
+
+        // === 僵尸会话清理 ===
+        // 循环结束后，pending 里剩下的就是"在 adjusted 里，但没在系统活跃会话里找到"的会话
+        // 说明对应的应用已经关闭了。必须从全局列表里删掉它们，否则看门狗会死循环空转。
+        if !pending.is_empty() {
+            let mut adjusted = self.adjusted.lock().unwrap();
+            for zombie in pending.keys() {
+                adjusted.remove(zombie);
+                tracing::debug!("Removed zombie session ({}) from adjusted list", zombie);
+            }
+            tracing::info!("Cleaned up {} zombie sessions", pending.len());
+        }
+
+        if !fades.is_empty() {
+            let fade_ms = if with_fade { self.config.lock().unwrap().fade_ms } else { 0 };
+            self.fade(fades, fade_ms, true);
+        }
+
+        tracing::info!("Restored {} audio applications", restored_count);
+        Ok(restored_count)
+    }
+
+    /// 渐变音量（中止之前未完成的渐变）
+    ///
+    /// fade_ms 为 0 时立即设置，否则交给渐变调度逐步执行；release 为 true 时渐变完成后从列表删除
+    fn fade(self: &Arc<Self>, fades: Vec<Fade>, fade_ms: u32, release: bool) {
+        let generation = self.fade_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let steps = (fade_ms as u64 / FADE_STEP_MS).max(1);
+        // 弱引用：管理器销毁后未完成的渐变自动结束
+        let controller = Arc::downgrade(self);
+        let mut step = 0;
+        let mut next: FadeStep = Box::new(move || {
+            let Some(controller) = controller.upgrade() else {
+                return false;
+            };
+            step += 1;
+            controller.fade_step(&fades, step, steps, generation, release)
+        });
+
+        if fade_ms == 0 {
+            next();
+        } else {
+            self.fade_scheduler.schedule(next);
+        }
+    }
+
+    /// 执行渐变的第 step 步（共 steps 步），返回是否还有后续步骤
+    fn fade_step(&self, fades: &[Fade], step: u64, steps: u64, generation: u64, release: bool) -> bool {
+        if self.fade_generation.load(Ordering::SeqCst) != generation {
+            tracing::debug!("Volume fade superseded, stopping");
+            return false;
+        }
+        let t = step as f32 / steps as f32;
+        for &(id, from, to) in fades {
+            if let Err(e) = self.backend.set_volume(id, from + (to - from) * t) {
+                tracing::debug!("Failed to set volume for session {}: {}", id, e);
+            }
+        }
+        if step < steps {
+            return true;
+        }
+
+        if release {
+            // 渐变完成前可能又开始了新的压低（代数已变），此时保留原始音量
+            let mut adjusted = self.adjusted.lock().unwrap();
+            if self.fade_generation.load(Ordering::SeqCst) == generation {
+                for &(id, _, _) in fades {
+                    adjusted.remove(&id);
+                }
+            }
+        }
+        false
+    }
+}
+
+/// 看门狗：兜底恢复被调整的会话
+struct Watchdog {
+    controller: Arc<SessionController>,
+    enabled: Arc<AtomicBool>,
+    active_sessions: Arc<AtomicU32>,
+    last_session_start: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Watchdog {
+    /// 执行一次检查：
+    /// 1. 如果没有活跃录音会话但有应用被静音，则自动恢复
+    /// 2. 如果录音会话超时（>3分钟），强制重置状态并恢复音量（核弹级兜底）
+    fn check(&self) {
+        // 如果功能未启用，跳过检查
+        if !self.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let current_sessions = self.active_sessions.load(Ordering::Relaxed);
+
+        // === 安全保险：超时强制重置 ===
+        // 如果 sessions > 0 但持续时间超过 SESSION_TIMEOUT_SECS（防止程序逻辑卡死导致永远静音）
+        let is_timeout = {
+            let start_opt = self.last_session_start.lock().unwrap();
+            if let Some(start) = *start_opt {
+                start.elapsed().as_secs() > SESSION_TIMEOUT_SECS
+            } else {
+                false
+            }
+        };
+
+        if is_timeout && current_sessions > 0 {
+            tracing::error!(
+                "⚠️ CRITICAL: Recording session timed out (>{}s). Forcing volume restore!",
+                SESSION_TIMEOUT_SECS
+            );
+            // 强制归零计数器
+            self.active_sessions.store(0, Ordering::Relaxed);
+            // 清除计时器
+            *self.last_session_start.lock().unwrap() = None;
+            // 继续执行下面的恢复逻辑
+        }
+
+        // === 正常的恢复检查 ===
+        // 重新读取 active_sessions（因为上面可能刚刚重置了）
+        if self.active_sessions.load(Ordering::Relaxed) == 0 {
+            let has_adjusted = !self.controller.adjusted.lock().unwrap().is_empty();
+
+            if has_adjusted {
+                tracing::warn!(
+                    "Watchdog detected muted apps without active session, restoring..."
+                );
+                if let Err(e) = self.controller.restore(&self.active_sessions, true) {
+                    tracing::error!("Watchdog failed to restore volumes: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// 音频静音管理器
+/// 负责在录音时静音或压低其他应用，录音结束后恢复
+/// 使用看门狗机制确保即使出现异常也能恢复静音状态
+///
+/// 安全机制：
+/// 1. 引用计数：跟踪活跃会话数，只有归零时才触发恢复
+/// 2. 超时强制重置：录音超过 3 分钟自动强制恢复（防止计数器锁死）
+/// 3. 僵尸进程清理：自动清理已关闭应用的会话
+/// 4. 中断检测：恢复过程中检测新会话，及时中止
+pub struct AudioMuteManager {
+    controller: Arc<SessionController>,
+    /// 是否启用静音功能
+    enabled: Arc<AtomicBool>,
+    /// 当前活跃的录音会话计数（用于看门狗判断）
+    active_sessions: Arc<AtomicU32>,
+    /// 记录最后一次开始录音的时间，用于超时强制重置
+    last_session_start: Arc<Mutex<Option<Instant>>>,
+    /// 看门狗线程是否应该停止
+    watchdog_stop: Arc<AtomicBool>,
+    /// 看门狗线程句柄
+    watchdog_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl AudioMuteManager {
+    /// 创建新的音频静音管理器（使用当前平台的会话后端）
+    pub fn new(enabled: bool) -> Self {
+        Self::with_backend(platform_backend(), enabled)
+    }
+
+    /// 使用指定的会话后端创建管理器
+    pub fn with_backend(backend: Arc<dyn AudioSessionBackend>, enabled: bool) -> Self {
+        Self::with_scheduler(backend, Arc::new(ThreadScheduler), enabled)
+    }
+
+    fn with_scheduler(backend: Arc<dyn AudioSessionBackend>, fade_scheduler: Arc<dyn FadeScheduler>, enabled: bool) -> Self {
+        let own_process_id = std::process::id();
+        tracing::info!(
+            "AudioMuteManager created, own_pid: {}, enabled: {}",
+            own_process_id,
+            enabled
+        );
+
+        let controller = Arc::new(SessionController {
+            backend,
+            own_process_id,
+            adjusted: Mutex::new(HashMap::new()),
+            config: Mutex::new(MuteOtherAppsConfig::default()),
+            fade_generation: AtomicU64::new(0),
+            fade_scheduler,
+        });
+        let mut manager = Self {
+            controller,
+            enabled: Arc::new(AtomicBool::new(enabled)),
+            active_sessions: Arc::new(AtomicU32::new(0)),
+            last_session_start: Arc::new(Mutex::new(None)),
+            watchdog_stop: Arc::new(AtomicBool::new(false)),
+            watchdog_handle: None,
+        };
+
+        // 启动看门狗线程
+        manager.watchdog_handle = Some(Self::start_watchdog(manager.watchdog(), Arc::clone(&manager.watchdog_stop)));
+        manager
+    }
+
+    /// 看门狗检查所需的共享状态
+    fn watchdog(&self) -> Watchdog {
+        Watchdog {
+            controller: Arc::clone(&self.controller),
+            enabled: Arc::clone(&self.enabled),
+            active_sessions: Arc::clone(&self.active_sessions),
+            last_session_start: Arc::clone(&self.last_session_start),
+        }
+    }
+
+    /// 启动看门狗线程，每 WATCHDOG_INTERVAL_MS 执行一次检查
+    fn start_watchdog(watchdog: Watchdog, stop_flag: Arc<AtomicBool>) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            tracing::info!("AudioMuteManager watchdog started");
+
+            while !stop_flag.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(WATCHDOG_INTERVAL_MS));
+
+                if stop_flag.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                watchdog.check();
+            }
+
+            tracing::info!("AudioMuteManager watchdog stopped");
+        })
+    }
+
+    /// 设置是否启用静音功能
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        tracing::info!("AudioMuteManager enabled: {}", enabled);
+
+        // 如果禁用，立即恢复所有静音的应用
+        if !enabled {
+            if let Err(e) = self.restore_volumes() {
+                tracing::warn!("Failed to restore volumes when disabling: {}", e);
+            }
+        }
+    }
+
+    /// 更新静音 / 闪避方式（下次录音时生效）
+    pub fn set_config(&self, config: MuteOtherAppsConfig) {
+        tracing::info!(
+            "AudioMuteManager config: mode={:?}, duck={}%, fade={}ms, excluded={:?}",
+            config.mode,
+            config.duck_percent,
+            config.fade_ms,
+            config.excluded_apps
+        );
+        *self.controller.config.lock().unwrap() = config;
+    }
+
+    /// 检查是否启用
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// 开始录音会话（增加活跃计数）
+    /// 只有从 0 变 1 时才重置计时器，代表一轮新的录音开始
+    pub fn begin_session(&self) {
+        let prev = self.active_sessions.fetch_add(1, Ordering::Relaxed);
+        if prev == 0 {
+            // 从 0 变 1，开始新的一轮录音，记录开始时间
+            *self.last_session_start.lock().unwrap() = Some(Instant::now());
+        }
+        tracing::debug!("AudioMuteManager session started, active: {}", prev + 1);
+    }
+
+    /// 结束录音会话（减少活跃计数）
+    /// 使用 fetch_update (CAS) 防止下溢，确保计数器不会变成 u32::MAX
+    /// 计数器归零时清除开始时间
+    pub fn end_session(&self) {
+        let result = self.active_sessions.fetch_update(
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            |x| if x > 0 { Some(x - 1) } else { None },
+        );
+
+        match result {
+            Ok(prev) => {
+                if prev == 1 {
+                    // 从 1 变 0，本轮录音结束，清除计时器
+                    *self.last_session_start.lock().unwrap() = None;
+                }
+                tracing::debug!("AudioMuteManager session ended, active: {}", prev - 1);
+            }
+            Err(_) => {
+                // 计数器已经是 0，不执行减法，记录警告
+                tracing::warn!("AudioMuteManager end_session called but active_sessions already 0, ignoring to prevent underflow");
+            }
+        }
+    }
+
+    /// 静音（或按配置压低）所有其他音频应用
+    /// 返回新处理的应用数量
+    /// 注意：不再清空之前的记录，新处理的应用会累加到列表中
+    pub fn mute_other_apps(&self) -> Result<usize, String> {
+        if !self.is_enabled() {
+            tracing::debug!("AudioMuteManager is disabled, skipping mute");
+            return Ok(0);
+        }
+        self.controller.adjust()
+    }
+
+    /// 恢复之前被静音或压低的应用（压低的应用渐变回原音量）
+    /// 返回恢复的应用数量
+    pub fn restore_volumes(&self) -> Result<usize, String> {
+        self.controller.restore(&self.active_sessions, true)
+    }
+}
+
+/// 确保在 AudioMuteManager 销毁时恢复所有被静音的应用并停止看门狗
+impl Drop for AudioMuteManager {
+    fn drop(&mut self) {
+        tracing::debug!("AudioMuteManager dropping...");
+
+        // 停止看门狗线程
+        self.watchdog_stop.store(true, Ordering::Relaxed);
+
+        // 恢复所有静音的应用（不渐变，立即恢复）
+        if let Err(e) = self.controller.restore(&self.active_sessions, false) {
+            tracing::warn!("Failed to restore volumes on drop: {}", e);
+        }
+
+        // 等待看门狗线程结束（看门狗每 WATCHDOG_INTERVAL_MS 检查一次停止标志，join 不设超时）
+        if let Some(handle) = self.watchdog_handle.take() {
+            let _ = handle.join();
+        }
+
+        tracing::debug!("AudioMuteManager dropped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 模拟声音服务器：记录每个会话的静音状态和音量
+    struct FakeBackend {
+        sessions: Mutex<Vec<AudioSession>>,
+    }
+
+    impl FakeBackend {
+        fn new(sessions: Vec<AudioSession>) -> Arc<Self> {
+            Arc::new(Self { sessions: Mutex::new(sessions) })
+        }
+
+        fn get(&self, id: u32) -> AudioSession {
+            self.sessions.lock().unwrap().iter().find(|s| s.id == id).unwrap().clone()
+        }
+
+        fn is_muted(&self, id: u32) -> bool {
+            self.get(id).muted
+        }
+
+        fn volume(&self, id: u32) -> f32 {
+            self.get(id).volume
+        }
+
+        fn close(&self, id: u32) {
+            self.sessions.lock().unwrap().retain(|s| s.id != id);
+        }
+    }
+
+    impl AudioSessionBackend for FakeBackend {
+        fn list_sessions(&self) -> Result<Vec<AudioSession>, String> {
+            Ok(self.sessions.lock().unwrap().clone())
+        }
+
+        fn set_mute(&self, id: u32, muted: bool) -> Result<(), String> {
+            let mut sessions = self.sessions.lock().unwrap();
+            let session = sessions.iter_mut().find(|s| s.id == id).ok_or("not found")?;
+            session.muted = muted;
+            Ok(())
+        }
+
+        fn set_volume(&self, id: u32, volume: f32) -> Result<(), String> {
+            let mut sessions = self.sessions.lock().unwrap();
+            let session = sessions.iter_mut().find(|s| s.id == id).ok_or("not found")?;
+            session.volume = volume;
+            Ok(())
+        }
+    }
+
+    /// 手动步进的渐变调度
+    #[derive(Default)]
+    struct ManualScheduler {
+        steps: Mutex<Vec<FadeStep>>,
+    }
+
+    impl FadeScheduler for ManualScheduler {
+        fn schedule(&self, step: FadeStep) {
+            self.steps.lock().unwrap().push(step);
+        }
+    }
+
+    impl ManualScheduler {
+        /// 所有进行中的渐变前进一步
+        fn tick(&self) {
+            let mut running = std::mem::take(&mut *self.steps.lock().unwrap());
+            running.retain_mut(|step| step());
+            let mut steps = self.steps.lock().unwrap();
+            running.append(&mut steps);
+            *steps = running;
+        }
+
+        fn ticks(&self, n: usize) {
+            for _ in 0..n {
+                self.tick();
+            }
+        }
+
+        /// 执行到所有渐变结束
+        fn finish(&self) {
+            while !self.steps.lock().unwrap().is_empty() {
+                self.tick();
+            }
+        }
+    }
+
+    fn manual_manager(backend: Arc<FakeBackend>, fade_ms: u32) -> (AudioMuteManager, Arc<ManualScheduler>) {
+        let scheduler = Arc::new(ManualScheduler::default());
+        let manager = AudioMuteManager::with_scheduler(backend, scheduler.clone(), true);
+        manager.set_config(duck_config(fade_ms));
+        (manager, scheduler)
+    }
+
+    fn session(id: u32, pid: u32, muted: bool) -> AudioSession {
+        AudioSession { id, pid, name: format!("app{}", pid), muted, volume: 1.0 }
+    }
+
+    fn duck_config(fade_ms: u32) -> MuteOtherAppsConfig {
+        MuteOtherAppsConfig {
+            mode: MuteMode::Duck,
+            duck_percent: 25,
+            fade_ms,
+            excluded_apps: vec!["App1003.exe".to_string()],
+        }
+    }
+
+    fn assert_volume(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "volume {} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_mute_and_restore_skips_own_and_external() {
+        let own = std::process::id();
+        let backend = FakeBackend::new(vec![
+            session(1, own, false),
+            session(2, 0, false),
+            session(3, 1000, false),
+            session(4, 1001, true),
+            session(5, 1002, false),
+        ]);
+        let manager = AudioMuteManager::with_backend(backend.clone(), true);
+
+        manager.begin_session();
+        assert_eq!(manager.mute_other_apps().unwrap(), 2);
+        assert!(!backend.is_muted(1));
+        assert!(!backend.is_muted(2));
+        assert!(backend.is_muted(3));
+        assert!(backend.is_muted(5));
+        // 重复调用不会重复静音
+        assert_eq!(manager.mute_other_apps().unwrap(), 0);
+
+        manager.end_session();
+        assert_eq!(manager.restore_volumes().unwrap(), 2);
+        assert!(!backend.is_muted(3));
+        assert!(!backend.is_muted(5));
+        // 用户自己静音的会话保持静音
+        assert!(backend.is_muted(4));
+    }
+
+    #[test]
+    fn test_disabled_manager_does_nothing() {
+        let backend = FakeBackend::new(vec![session(3, 1000, false)]);
+        let manager = AudioMuteManager::with_backend(backend.clone(), false);
+        assert_eq!(manager.mute_other_apps().unwrap(), 0);
+        assert!(!backend.is_muted(3));
+    }
+
+    #[test]
+    fn test_restore_cleans_up_closed_sessions() {
+        let backend = FakeBackend::new(vec![session(3, 1000, false), session(5, 1002, false)]);
+        let manager = AudioMuteManager::with_backend(backend.clone(), true);
+        manager.begin_session();
+        manager.mute_other_apps().unwrap();
+        backend.close(5);
+
+        manager.end_session();
+        assert_eq!(manager.restore_volumes().unwrap(), 1);
+        assert!(manager.controller.adjusted.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_aborts_when_new_session_starts() {
+        let backend = FakeBackend::new(vec![session(3, 1000, false)]);
+        let manager = AudioMuteManager::with_backend(backend.clone(), true);
+        manager.begin_session();
+        manager.mute_other_apps().unwrap();
+
+        // 仍在录音：恢复被中断，保持静音
+        assert_eq!(manager.restore_volumes().unwrap(), 0);
+        assert!(backend.is_muted(3));
+        manager.end_session();
+    }
+
+    #[test]
+    fn test_watchdog_restores_without_active_session() {
+        let backend = FakeBackend::new(vec![session(3, 1000, false)]);
+        let scheduler = Arc::new(ManualScheduler::default());
+        let manager = AudioMuteManager::with_scheduler(backend.clone(), scheduler.clone(), true);
+        manager.begin_session();
+        manager.mute_other_apps().unwrap();
+        // 模拟调用方漏掉 restore_volumes
+        manager.end_session();
+
+        manager.watchdog().check();
+        scheduler.finish();
+        assert!(!backend.is_muted(3));
+        assert!(manager.controller.adjusted.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_watchdog_resets_timed_out_session() {
+        let backend = FakeBackend::new(vec![session(3, 1000, false)]);
+        let scheduler = Arc::new(ManualScheduler::default());
+        let manager = AudioMuteManager::with_scheduler(backend.clone(), scheduler.clone(), true);
+        manager.begin_session();
+        manager.mute_other_apps().unwrap();
+
+        // 会话未超时：保持静音
+        manager.watchdog().check();
+        assert!(backend.is_muted(3));
+
+        // 模拟录音开始于超时之前
+        *manager.last_session_start.lock().unwrap() =
+            Some(Instant::now() - Duration::from_secs(SESSION_TIMEOUT_SECS + 1));
+        manager.watchdog().check();
+        scheduler.finish();
+        assert!(!backend.is_muted(3));
+        assert_eq!(manager.active_sessions.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_duck_remembers_original_volume() {
+        let mut quiet = session(5, 1002, false);
+        quiet.volume = 0.4;
+        let backend = FakeBackend::new(vec![session(3, 1000, false), quiet, session(7, 1003, false)]);
+        let manager = AudioMuteManager::with_backend(backend.clone(), true);
+        manager.set_config(duck_config(0));
+
+        manager.begin_session();
+        assert_eq!(manager.mute_other_apps().unwrap(), 2);
+        assert!(!backend.is_muted(3));
+        assert_volume(backend.volume(3), 0.25);
+        assert_volume(backend.volume(5), 0.1);
+        // 排除的应用不受影响
+        assert_volume(backend.volume(7), 1.0);
+
+        manager.end_session();
+        assert_eq!(manager.restore_volumes().unwrap(), 2);
+        assert_volume(backend.volume(3), 1.0);
+        assert_volume(backend.volume(5), 0.4);
+        assert!(manager.controller.adjusted.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_duck_fades_in_steps() {
+        let backend = FakeBackend::new(vec![session(3, 1000, false)]);
+        // 100ms / 20ms = 5 步
+        let (manager, scheduler) = manual_manager(backend.clone(), 100);
+
+        manager.begin_session();
+        manager.mute_other_apps().unwrap();
+        assert_volume(backend.volume(3), 1.0);
+        scheduler.tick();
+        assert_volume(backend.volume(3), 0.85);
+        scheduler.finish();
+        assert_volume(backend.volume(3), 0.25);
+
+        manager.end_session();
+        manager.restore_volumes().unwrap();
+        scheduler.ticks(4);
+        assert_volume(backend.volume(3), 0.85);
+        // 渐变完成前仍保留原始音量记录
+        assert!(!manager.controller.adjusted.lock().unwrap().is_empty());
+        scheduler.tick();
+        assert_volume(backend.volume(3), 1.0);
+        assert!(manager.controller.adjusted.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_duck_interrupted_restore_keeps_original() {
+        let backend = FakeBackend::new(vec![session(3, 1000, false)]);
+        // 200ms / 20ms = 10 步
+        let (manager, scheduler) = manual_manager(backend.clone(), 200);
+
+        manager.begin_session();
+        manager.mute_other_apps().unwrap();
+        scheduler.finish();
+
+        // 恢复渐变途中再次开始录音
+        manager.end_session();
+        manager.restore_volumes().unwrap();
+        scheduler.ticks(4);
+        assert_volume(backend.volume(3), 0.55);
+        manager.begin_session();
+        assert_eq!(manager.mute_other_apps().unwrap(), 0);
+        // 被打断的恢复渐变不再调整音量，新的压低从当前音量开始
+        scheduler.tick();
+        assert_volume(backend.volume(3), 0.52);
+        scheduler.finish();
+        assert_volume(backend.volume(3), 0.25);
+        assert!(!manager.controller.adjusted.lock().unwrap().is_empty());
+
+        manager.end_session();
+        manager.restore_volumes().unwrap();
+        scheduler.finish();
+        assert_volume(backend.volume(3), 1.0);
+    }
+}
@@ -0,0 +1,131 @@
+// PulseAudio / PipeWire 后端
+// 通过 pactl 枚举 sink-input（每个播放流一个），PipeWire 经 pipewire-pulse 提供相同接口
+
+use std::process::Command;
+
+use super::{AudioSession, AudioSessionBackend};
+
+/// PulseAudio / PipeWire 会话后端
+///
+/// 会话以 sink-input 序号作为标识，进程 ID 取自 application.process.id 属性
+pub struct PulseBackend;
+
+impl PulseBackend {
+    fn pactl(args: &[&str]) -> Result<String, String> {
+        let output = Command::new("pactl")
+            .args(args)
+            // 固定英文输出，便于解析
+            .env("LC_ALL", "C")
+            .output()
+            .map_err(|e| format!("无法执行 pactl（需要 PulseAudio 或 pipewire-pulse）: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "pactl {} 失败: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl AudioSessionBackend for PulseBackend {
+    fn list_sessions(&self) -> Result<Vec<AudioSession>, String> {
+        Ok(parse_sink_inputs(&Self::pactl(&["list", "sink-inputs"])?))
+    }
+
+    fn set_mute(&self, id: u32, muted: bool) -> Result<(), String> {
+        Self::pactl(&["set-sink-input-mute", &id.to_string(), if muted { "1" } else { "0" }]).map(|_| ())
+    }
+
+    fn set_volume(&self, id: u32, volume: f32) -> Result<(), String> {
+        let percent = format!("{}%", (volume.clamp(0.0, 1.0) * 100.0).round() as u32);
+        Self::pactl(&["set-sink-input-volume", &id.to_string(), &percent]).map(|_| ())
+    }
+}
+
+/// 解析 `pactl list sink-inputs` 的输出
+///
+/// 没有进程 ID 属性的流（如系统事件音）记为 pid 0，音量取第一个声道
+fn parse_sink_inputs(output: &str) -> Vec<AudioSession> {
+    let mut sessions = Vec::new();
+    let mut current: Option<AudioSession> = None;
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(index) = line.strip_prefix("Sink Input #") {
+            sessions.extend(current.take());
+            current = index.trim().parse().ok().map(|id| AudioSession {
+                id,
+                pid: 0,
+                name: String::new(),
+                muted: false,
+                volume: 1.0,
+            });
+            continue;
+        }
+        let Some(session) = current.as_mut() else {
+            continue;
+        };
+        if let Some(mute) = line.strip_prefix("Mute:") {
+            session.muted = mute.trim() == "yes";
+        } else if let Some(volume) = line.strip_prefix("Volume:") {
+            // front-left: 65536 /  100% / 0.00 dB,   front-right: ...
+            if let Some(percent) = volume.split('/').nth(1).and_then(|p| p.trim().strip_suffix('%')) {
+                session.volume = percent.parse::<f32>().map(|p| p / 100.0).unwrap_or(1.0);
+            }
+        } else if let Some(name) = line.strip_prefix("application.process.binary = ") {
+            session.name = name.trim_matches('"').to_string();
+        } else if let Some(pid) = line.strip_prefix("application.process.id = ") {
+            session.pid = pid.trim_matches('"').parse().unwrap_or(0);
+        }
+    }
+    sessions.extend(current);
+    sessions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sink_inputs() {
+        let output = r#"Sink Input #42
+	Driver: protocol-native.c
+	Owner Module: 10
+	Client: 77
+	Sink: 1
+	Mute: no
+	Volume: front-left: 32768 /  50% / -18.06 dB,   front-right: 32768 /  50% / -18.06 dB
+	Properties:
+		application.name = "Firefox"
+		application.process.id = "4321"
+		application.process.binary = "firefox"
+
+Sink Input #57
+	Driver: PipeWire
+	Mute: yes
+	Properties:
+		media.name = "event"
+
+Sink Input #60
+	Mute: no
+	Properties:
+		application.process.id = "99"
+"#;
+        let sessions = parse_sink_inputs(output);
+        assert_eq!(
+            sessions,
+            vec![
+                AudioSession { id: 42, pid: 4321, name: "firefox".to_string(), muted: false, volume: 0.5 },
+                AudioSession { id: 57, pid: 0, name: String::new(), muted: true, volume: 1.0 },
+                AudioSession { id: 60, pid: 99, name: String::new(), muted: false, volume: 1.0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_output() {
+        assert!(parse_sink_inputs("").is_empty());
+    }
+}
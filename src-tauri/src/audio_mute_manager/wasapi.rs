@@ -0,0 +1,138 @@
+// Windows Audio Session API (WASAPI) 后端
+// 通过默认输出设备的会话管理器枚举各应用的音频会话
+
+use windows::core::Interface;
+use windows::Win32::Media::Audio::{
+    eMultimedia, eRender, IAudioSessionControl, IAudioSessionControl2, IAudioSessionEnumerator,
+    IAudioSessionManager2, IMMDeviceEnumerator, ISimpleAudioVolume, MMDeviceEnumerator,
+};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_MULTITHREADED,
+};
+
+use super::{AudioSession, AudioSessionBackend};
+use crate::win32_input::get_process_name;
+
+/// RAII Guard for COM initialization
+/// 确保 CoUninitialize 在作用域结束时被调用
+struct ComGuard;
+
+impl Drop for ComGuard {
+    fn drop(&mut self) {
+        unsafe {
+            CoUninitialize();
+        }
+    }
+}
+
+/// WASAPI 会话后端
+///
+/// 会话以进程 ID 作为标识，同一进程的多个会话一起静音 / 恢复
+pub struct WasapiBackend;
+
+impl WasapiBackend {
+    /// 遍历默认输出设备上的所有会话：(会话控制, 进程 ID)
+    fn for_each_session(
+        mut f: impl FnMut(&IAudioSessionControl, u32),
+    ) -> Result<(), String> {
+        unsafe {
+            // 初始化 COM，使用 Multithreaded 模式以适应 Tauri 线程池
+            // 注意：CoInitializeEx 是幂等的，重复调用不会出错
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+            // 使用 RAII 确保 CoUninitialize 被调用
+            let _com_guard = ComGuard;
+
+            let session_enumerator = Self::session_enumerator()?;
+            let count = session_enumerator
+                .GetCount()
+                .map_err(|e| format!("Failed to get session count: {}", e))?;
+
+            for i in 0..count {
+                if let Ok(control) = session_enumerator.GetSession(i) {
+                    // 获取 IAudioSessionControl2 以访问进程信息
+                    let control2: IAudioSessionControl2 = match control.cast() {
+                        Ok(c) => c,
+                        Err(_) => continue,
+                    };
+                    let pid = control2.GetProcessId().unwrap_or(0);
+                    f(&control, pid);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 对进程的所有会话执行音量操作
+    fn apply(
+        id: u32,
+        op: impl Fn(&ISimpleAudioVolume) -> windows::core::Result<()>,
+    ) -> Result<(), String> {
+        let mut found = false;
+        let mut result = Ok(());
+        Self::for_each_session(|control, pid| {
+            if pid != id {
+                return;
+            }
+            found = true;
+            if let Err(e) = control.cast::<ISimpleAudioVolume>().and_then(|volume| op(&volume)) {
+                result = Err(format!("Failed to update audio session (pid: {}): {}", pid, e));
+            }
+        })?;
+        if !found {
+            return Err(format!("Audio session not found (pid: {})", id));
+        }
+        result
+    }
+
+    unsafe fn session_enumerator() -> Result<IAudioSessionEnumerator, String> {
+        // 获取设备枚举器
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                .map_err(|e| format!("Failed to create device enumerator: {}", e))?;
+
+        // 获取默认音频输出设备
+        let device = enumerator
+            .GetDefaultAudioEndpoint(eRender, eMultimedia)
+            .map_err(|e| format!("Failed to get default endpoint: {}", e))?;
+
+        // 获取音频会话管理器
+        let session_manager: IAudioSessionManager2 = device
+            .Activate(CLSCTX_ALL, None)
+            .map_err(|e| format!("Failed to activate session manager: {}", e))?;
+
+        // 获取会话枚举器
+        session_manager
+            .GetSessionEnumerator()
+            .map_err(|e| format!("Failed to get session enumerator: {}", e))
+    }
+}
+
+impl AudioSessionBackend for WasapiBackend {
+    fn list_sessions(&self) -> Result<Vec<AudioSession>, String> {
+        let mut sessions: Vec<AudioSession> = Vec::new();
+        Self::for_each_session(|control, pid| {
+            // 同一进程只记录第一个会话
+            if sessions.iter().any(|s| s.id == pid) {
+                return;
+            }
+            let state = unsafe {
+                control.cast::<ISimpleAudioVolume>().and_then(|volume| {
+                    Ok((volume.GetMute()?.as_bool(), volume.GetMasterVolume()?))
+                })
+            };
+            if let Ok((muted, volume)) = state {
+                sessions.push(AudioSession { id: pid, pid, name: get_process_name(pid).unwrap_or_default(), muted, volume });
+            }
+        })?;
+        Ok(sessions)
+    }
+
+    fn set_mute(&self, id: u32, muted: bool) -> Result<(), String> {
+        Self::apply(id, |volume| unsafe { volume.SetMute(muted, std::ptr::null()) })
+    }
+
+    fn set_volume(&self, id: u32, level: f32) -> Result<(), String> {
+        Self::apply(id, |volume| unsafe { volume.SetMasterVolume(level.clamp(0.0, 1.0), std::ptr::null()) })
+    }
+}
@@ -1,13 +1,16 @@
 // 音频录制模块
 use hound::{WavSpec, WavWriter};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::io::Cursor;
 use anyhow::Result;
 use cpal::Stream;
 use tauri::AppHandle;
 
-use crate::audio_utils::{calculate_audio_level, emit_audio_level, apply_agc, validate_audio};
+use crate::audio_capture::{capture_ring, CaptureWorker};
+use crate::audio_utils::{calculate_audio_level, check_clipping, emit_audio_level, apply_agc, validate_audio, ClippingMonitor, VoiceActivityTracker};
+use crate::pre_roll_buffer::PreRollBuffer;
 
 // API 要求的目标采样率
 const TARGET_SAMPLE_RATE: u32 = 16000;
@@ -16,8 +19,10 @@ pub struct AudioRecorder {
     device_sample_rate: u32,  // 设备实际采样率
     channels: u16,
     audio_data: Arc<Mutex<Vec<f32>>>,
-    is_recording: Arc<Mutex<bool>>,
+    is_recording: Arc<AtomicBool>,
     stream: Option<Stream>,  // 保存 stream 引用
+    capture_worker: Option<CaptureWorker>,  // 音频处理工作线程
+    voice_activity: VoiceActivityTracker,
 }
 
 impl AudioRecorder {
@@ -26,8 +31,10 @@ impl AudioRecorder {
             device_sample_rate: 48000,  // 默认值，会在 start_recording 时更新
             channels: 1,
             audio_data: Arc::new(Mutex::new(Vec::new())),
-            is_recording: Arc::new(Mutex::new(false)),
+            is_recording: Arc::new(AtomicBool::new(false)),
             stream: None,
+            capture_worker: None,
+            voice_activity: VoiceActivityTracker::default(),
         })
     }
 
@@ -76,14 +83,17 @@ impl AudioRecorder {
         output
     }
 
-    pub fn start_recording(&mut self, app_handle: Option<AppHandle>) -> Result<()> {
+    /// 开始录音
+    /// pre_roll 存在时，会把预录缓冲中的音频拼接到录音开头
+    pub fn start_recording(&mut self, app_handle: Option<AppHandle>, pre_roll: Option<&PreRollBuffer>) -> Result<()> {
         use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 
         tracing::info!("开始录音...");
 
         // 清空之前的音频数据
         self.audio_data.lock().unwrap().clear();
-        *self.is_recording.lock().unwrap() = true;
+        self.is_recording.store(true, Ordering::Release);
+        self.voice_activity.reset();
 
         let host = cpal::default_host();
         let device = host
@@ -107,108 +117,71 @@ impl AudioRecorder {
         tracing::info!("设备配置: 采样率={}Hz, 声道={}, 目标采样率={}Hz",
             self.device_sample_rate, self.channels, TARGET_SAMPLE_RATE);
 
-        let audio_data = Arc::clone(&self.audio_data);
-        let is_recording = Arc::clone(&self.is_recording);
         let err_fn = |err| tracing::error!("录音流错误: {}", err);
 
-        // 基于时间的音频级别发送控制（目标 30-40Hz）
-        use std::time::Instant;
-        let last_emit_time: Arc<Mutex<Instant>> = Arc::new(Mutex::new(Instant::now()));
+        // 回调只写入环形缓冲（无锁、无分配），处理在工作线程中进行
+        let (mut producer, consumer) = capture_ring(self.device_sample_rate, self.channels, Arc::clone(&self.is_recording));
 
         // 根据采样格式创建不同的 stream
         let stream = match supported_config.sample_format() {
-            cpal::SampleFormat::F32 => {
-                let app_handle_f32 = app_handle.clone();
-                let last_emit_time_f32 = Arc::clone(&last_emit_time);
-                device.build_input_stream(
-                    &config,
-                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                        if *is_recording.lock().unwrap() {
-                            let mut buffer = audio_data.lock().unwrap();
-                            buffer.extend_from_slice(data);
-
-                            // 基于时间的音频级别发送（目标 ~30Hz，每 33ms 发送一次）
-                            if let Some(ref app) = app_handle_f32 {
-                                let mut last_emit = last_emit_time_f32.lock().unwrap();
-                                if last_emit.elapsed().as_millis() >= 33 {
-                                    let level = calculate_audio_level(data);
-                                    emit_audio_level(app, level);
-                                    *last_emit = Instant::now();
-                                }
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )?
-            },
-            cpal::SampleFormat::I16 => {
-                let audio_data_i16 = Arc::clone(&audio_data);
-                let is_recording_i16 = Arc::clone(&is_recording);
-                let app_handle_i16 = app_handle.clone();
-                let last_emit_time_i16 = Arc::clone(&last_emit_time);
-                device.build_input_stream(
-                    &config,
-                    move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                        if *is_recording_i16.lock().unwrap() {
-                            let mut buffer = audio_data_i16.lock().unwrap();
-                            // 转换 i16 到 f32
-                            let f32_data: Vec<f32> = data.iter()
-                                .map(|&s| s as f32 / i16::MAX as f32)
-                                .collect();
-                            buffer.extend(&f32_data);
-
-                            // 基于时间的音频级别发送（目标 ~30Hz）
-                            if let Some(ref app) = app_handle_i16 {
-                                let mut last_emit = last_emit_time_i16.lock().unwrap();
-                                if last_emit.elapsed().as_millis() >= 33 {
-                                    let level = calculate_audio_level(&f32_data);
-                                    emit_audio_level(app, level);
-                                    *last_emit = Instant::now();
-                                }
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )?
-            }
-            cpal::SampleFormat::U16 => {
-                let audio_data_u16 = Arc::clone(&audio_data);
-                let is_recording_u16 = Arc::clone(&is_recording);
-                let app_handle_u16 = app_handle;
-                let last_emit_time_u16 = Arc::clone(&last_emit_time);
-                device.build_input_stream(
-                    &config,
-                    move |data: &[u16], _: &cpal::InputCallbackInfo| {
-                        if *is_recording_u16.lock().unwrap() {
-                            let mut buffer = audio_data_u16.lock().unwrap();
-                            // 转换 u16 到 f32
-                            let f32_data: Vec<f32> = data.iter()
-                                .map(|&s| (s as f32 - 32768.0) / 32768.0)
-                                .collect();
-                            buffer.extend(&f32_data);
-
-                            // 基于时间的音频级别发送（目标 ~30Hz）
-                            if let Some(ref app) = app_handle_u16 {
-                                let mut last_emit = last_emit_time_u16.lock().unwrap();
-                                if last_emit.elapsed().as_millis() >= 33 {
-                                    let level = calculate_audio_level(&f32_data);
-                                    emit_audio_level(app, level);
-                                    *last_emit = Instant::now();
-                                }
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )?
-            }
+            cpal::SampleFormat::F32 => device.build_input_stream(
+                &config,
+                move |data: &[f32], _: &cpal::InputCallbackInfo| producer.push_f32(data),
+                err_fn,
+                None,
+            )?,
+            cpal::SampleFormat::I16 => device.build_input_stream(
+                &config,
+                move |data: &[i16], _: &cpal::InputCallbackInfo| producer.push_i16(data),
+                err_fn,
+                None,
+            )?,
+            cpal::SampleFormat::U16 => device.build_input_stream(
+                &config,
+                move |data: &[u16], _: &cpal::InputCallbackInfo| producer.push_u16(data),
+                err_fn,
+                None,
+            )?,
             _ => return Err(anyhow::anyhow!("不支持的采样格式")),
         };
 
+        // 主录音流启动前取预录快照：之后采集的音频只进入主录音流，拼接时不会重复
+        // （工作线程尚未启动，新采集的音频暂存在环形缓冲中，拼接在预录之后）
+        let snapshot = pre_roll.map(PreRollBuffer::snapshot);
+
         stream.play()?;
 
+        if let Some(snapshot) = snapshot {
+            if snapshot.matches(self.device_sample_rate, self.channels) {
+                let pre_roll_len = snapshot.samples.len();
+                self.audio_data.lock().unwrap().extend(snapshot.samples);
+                tracing::info!("已拼接预录音频: {} 样本", pre_roll_len);
+            } else {
+                tracing::warn!("预录音频格式与录音设备不一致，跳过拼接");
+            }
+        }
+
+        // 启动工作线程：保存音频、语音活动检测、削波检测、音频级别发送
+        let audio_data = Arc::clone(&self.audio_data);
+        let voice_activity = self.voice_activity.clone();
+        let mut clipping_monitor = ClippingMonitor::new(self.device_sample_rate, self.channels);
+        use std::time::Instant;
+        let mut last_emit_time = Instant::now();
+        self.capture_worker = Some(consumer.spawn_worker(move |samples| {
+            audio_data.lock().unwrap().extend_from_slice(samples);
+            voice_activity.update(samples);
+            check_clipping(&app_handle, &mut clipping_monitor, samples);
+
+            // 基于时间的音频级别发送（目标 ~30Hz，每 33ms 发送一次）
+            if let Some(ref app) = app_handle {
+                if last_emit_time.elapsed().as_millis() >= 33 {
+                    let level = calculate_audio_level(samples);
+                    emit_audio_level(app, level);
+                    last_emit_time = Instant::now();
+                }
+            }
+        }));
+
         // 保存 stream 引用，保持录音流活跃
         self.stream = Some(stream);
 
@@ -220,13 +193,15 @@ impl AudioRecorder {
         tracing::info!("停止录音...");
 
         // 停止录音
-        *self.is_recording.lock().unwrap() = false;
+        self.is_recording.store(false, Ordering::Release);
 
         // Drop stream，停止音频流
         self.stream = None;
 
-        // 等待一小段时间确保所有数据都已写入
-        std::thread::sleep(std::time::Duration::from_millis(100));
+        // 等待工作线程处理完环形缓冲中剩余的数据
+        if let Some(worker) = self.capture_worker.take() {
+            worker.finish();
+        }
 
         let raw_audio = self.audio_data.lock().unwrap().clone();
         let original_len = raw_audio.len();
@@ -279,13 +254,15 @@ impl AudioRecorder {
         tracing::info!("停止录音...");
 
         // 停止录音
-        *self.is_recording.lock().unwrap() = false;
+        self.is_recording.store(false, Ordering::Release);
 
         // Drop stream，停止音频流
         self.stream = None;
 
-        // 等待一小段时间确保所有数据都已写入
-        std::thread::sleep(std::time::Duration::from_millis(100));
+        // 等待工作线程处理完环形缓冲中剩余的数据
+        if let Some(worker) = self.capture_worker.take() {
+            worker.finish();
+        }
 
         let raw_audio = self.audio_data.lock().unwrap().clone();
 
@@ -328,9 +305,14 @@ impl AudioRecorder {
         Ok(file_path)
     }
 
+    /// 获取语音活动跟踪器（用于静音自动停止）
+    pub fn voice_activity(&self) -> VoiceActivityTracker {
+        self.voice_activity.clone()
+    }
+
     /// 检查是否正在录音
     pub fn is_recording(&self) -> bool {
-        *self.is_recording.lock().unwrap()
+        self.is_recording.load(Ordering::Acquire)
     }
 }
 
@@ -2,6 +2,9 @@
 // 提供音频级别计算、事件发送等共享功能
 
 use anyhow::Result;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 
 /// 音频级别事件 payload
@@ -44,21 +47,27 @@ pub fn calculate_rms(samples: &[f32]) -> f32 {
     (sum / samples.len() as f64).sqrt() as f32
 }
 
+/// AGC 目标 RMS，平衡小声音放大
+pub const AGC_TARGET_RMS: f32 = 0.10;
+/// AGC 最大增益，平衡微弱声音和抗噪能力
+pub const AGC_MAX_GAIN: f32 = 5.0;
+/// VAD 阈值（与 AGC 底噪阈值对齐），平衡灵敏度和抗噪能力
+pub const VAD_THRESHOLD: f32 = 0.003;
+/// 削波阈值：绝对值达到此值的样本视为削波
+pub const CLIP_THRESHOLD: f32 = 0.99;
+
 /// AGC：自动增益控制（带平滑处理）
 /// current_gain: 当前增益状态，用于平滑过渡
 pub fn apply_agc(samples: &mut [f32], current_gain: &mut f32) {
-    const TARGET_RMS: f32 = 0.10;   // 目标 RMS，平衡小声音放大
-    const MAX_GAIN: f32 = 5.0;      // 最大增益，平衡微弱声音和抗噪能力
     const MIN_GAIN: f32 = 0.1;      // 允许大幅衰减，压住大嗓门
-    const NOISE_FLOOR: f32 = 0.003; // 底噪阈值，平衡灵敏度和抗噪能力
 
     let rms = calculate_rms(samples);
 
     // 计算目标增益，底噪时保持 1.0
-    let target_gain = if rms < NOISE_FLOOR {
+    let target_gain = if rms < VAD_THRESHOLD {
         1.0
     } else {
-        (TARGET_RMS / rms).clamp(MIN_GAIN, MAX_GAIN)
+        (AGC_TARGET_RMS / rms).clamp(MIN_GAIN, AGC_MAX_GAIN)
     };
 
     // 增益平滑：Attack 快（防爆音），Release 慢（防呼吸效应）
@@ -72,8 +81,122 @@ pub fn apply_agc(samples: &mut [f32], current_gain: &mut f32) {
 
 /// VAD：基于 RMS 阈值判断是否有语音
 pub fn is_voice_active(samples: &[f32]) -> bool {
-    const THRESHOLD: f32 = 0.003; // 与 NOISE_FLOOR 对齐，平衡灵敏度和抗噪能力
-    calculate_rms(samples) > THRESHOLD
+    calculate_rms(samples) > VAD_THRESHOLD
+}
+
+// ============================================================================
+// 实时削波检测
+// ============================================================================
+
+/// 削波提醒事件 payload（mic_clipping）
+#[derive(Clone, serde::Serialize)]
+pub struct ClippingPayload {
+    /// 最近一秒内削波样本比例
+    pub ratio: f32,
+}
+
+/// 实时削波检测器（在音频处理线程中使用）
+///
+/// 按约 1 秒的窗口统计削波样本比例，超过阈值时提醒，两次提醒至少间隔 3 秒
+pub struct ClippingMonitor {
+    window_samples: usize,
+    counted: usize,
+    clipped: usize,
+    last_warning: Option<Instant>,
+}
+
+impl ClippingMonitor {
+    /// 窗口内削波比例超过此值时提醒（麦克风诊断使用同一阈值）
+    pub const RATIO_LIMIT: f32 = 0.001;
+    const WARNING_INTERVAL: Duration = Duration::from_secs(3);
+
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            window_samples: (sample_rate as usize * channels.max(1) as usize).max(1),
+            counted: 0,
+            clipped: 0,
+            last_warning: None,
+        }
+    }
+
+    /// 统计样本，窗口结束且需要提醒时返回削波比例
+    pub fn update(&mut self, samples: &[f32]) -> Option<f32> {
+        self.counted += samples.len();
+        self.clipped += samples.iter().filter(|s| s.abs() >= CLIP_THRESHOLD).count();
+        if self.counted < self.window_samples {
+            return None;
+        }
+
+        let ratio = self.clipped as f32 / self.counted as f32;
+        self.counted = 0;
+        self.clipped = 0;
+        if ratio <= Self::RATIO_LIMIT {
+            return None;
+        }
+        if self.last_warning.is_some_and(|t| t.elapsed() < Self::WARNING_INTERVAL) {
+            return None;
+        }
+        self.last_warning = Some(Instant::now());
+        Some(ratio)
+    }
+}
+
+/// 统计削波并在需要时发送提醒事件到前端
+pub fn check_clipping(app: &Option<AppHandle>, monitor: &mut ClippingMonitor, samples: &[f32]) {
+    if let Some(ratio) = monitor.update(samples) {
+        tracing::warn!("检测到麦克风削波: {:.2}% 样本", ratio * 100.0);
+        if let Some(app) = app {
+            let _ = app.emit("mic_clipping", ClippingPayload { ratio });
+        }
+    }
+}
+
+// ============================================================================
+// 语音活动跟踪
+// ============================================================================
+
+/// 语音活动跟踪器
+///
+/// 录音回调中按 VAD 结果更新最近一次检测到语音的时间，
+/// 供松手模式的静音自动停止使用。内部只用原子变量，可在回调中安全调用
+#[derive(Clone)]
+pub struct VoiceActivityTracker {
+    origin: Instant,
+    /// 最近一次语音距 origin 的毫秒数
+    last_voice_ms: Arc<AtomicU64>,
+}
+
+impl Default for VoiceActivityTracker {
+    fn default() -> Self {
+        Self {
+            origin: Instant::now(),
+            last_voice_ms: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+impl VoiceActivityTracker {
+    fn now_ms(&self) -> u64 {
+        self.origin.elapsed().as_millis() as u64
+    }
+
+    /// 重新开始计时（录音开始时调用，视为刚刚有过语音）
+    pub fn reset(&self) {
+        self.last_voice_ms.store(self.now_ms(), Ordering::Relaxed);
+    }
+
+    /// 根据一段样本更新语音活动状态
+    pub fn update(&self, samples: &[f32]) {
+        if is_voice_active(samples) {
+            self.last_voice_ms.store(self.now_ms(), Ordering::Relaxed);
+        }
+    }
+
+    /// 距最近一次语音的静音时长
+    pub fn silence_duration(&self) -> Duration {
+        let last = self.last_voice_ms.load(Ordering::Relaxed);
+        Duration::from_millis(self.now_ms().saturating_sub(last))
+    }
 }
 
 // ============================================================================
@@ -143,3 +266,36 @@ pub fn validate_audio(audio_data: &[u8]) -> Result<()> {
     );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_voice_activity_tracker() {
+        let tracker = VoiceActivityTracker::default();
+        tracker.reset();
+        std::thread::sleep(Duration::from_millis(30));
+
+        // 静音不刷新时间
+        tracker.update(&[0.0; 160]);
+        assert!(tracker.silence_duration() >= Duration::from_millis(30));
+
+        // 检测到语音后静音时长归零（克隆共享同一状态）
+        tracker.clone().update(&[0.5; 160]);
+        assert!(tracker.silence_duration() < Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_clipping_monitor() {
+        let mut monitor = ClippingMonitor::new(100, 1);
+        // 窗口未满不提醒
+        assert_eq!(monitor.update(&[1.0; 50]), None);
+        assert!(monitor.update(&[0.1; 50]).is_some());
+        // 间隔内不重复提醒
+        assert_eq!(monitor.update(&[1.0; 100]), None);
+        // 无削波不提醒
+        let mut monitor = ClippingMonitor::new(100, 1);
+        assert_eq!(monitor.update(&[0.5; 100]), None);
+    }
+}
@@ -1,44 +1,190 @@
+use rodio::source::{SineWave, Zero};
 use rodio::{Decoder, OutputStream, Sink, Source};
-use std::io::Cursor;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::time::Duration;
+
+use crate::config::{AudioCueConfig, CueSetting, TriggerMode};
 
 // 在编译时嵌入音效文件
 const NOTIFICATION_SOUND: &[u8] = include_bytes!("../resources/notification.ogg");
 
-// 音量系数 (0.0 - 1.0)，调小这个值可以降低音量
-const VOLUME: f32 = 0.2;
+/// 内置音效中 AI 助手模式的变调比例（约升高大三度，同时略微加快）
+const ASSISTANT_PITCH: f32 = 1.26;
+
+/// 合成音的淡入时长，避免爆音
+const TONE_FADE: Duration = Duration::from_millis(5);
+
+/// 提示音事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioCue {
+    Start,
+    Stop,
+    Lock,
+    Cancel,
+    Error,
+    AnswerReady,
+}
+
+/// 内置音效
+#[derive(Debug, PartialEq)]
+enum BuiltinSound {
+    /// 嵌入的提示音，按给定速度播放（速度越低音调越低）
+    Notification { speed: f32 },
+    /// 合成音序列：(频率 Hz, 时长 ms)，频率为 0 表示停顿
+    Tones(&'static [(f32, u64)]),
+}
+
+fn builtin_sound(cue: AudioCue) -> BuiltinSound {
+    match cue {
+        AudioCue::Start => BuiltinSound::Notification { speed: 1.0 },
+        AudioCue::Stop => BuiltinSound::Notification { speed: 0.8 },
+        // 两声短促同音
+        AudioCue::Lock => BuiltinSound::Tones(&[(880.0, 60), (0.0, 50), (880.0, 60)]),
+        // 下行两音
+        AudioCue::Cancel => BuiltinSound::Tones(&[(660.0, 90), (440.0, 140)]),
+        // 三声低音
+        AudioCue::Error => BuiltinSound::Tones(&[(220.0, 120), (0.0, 60), (220.0, 120), (0.0, 60), (220.0, 120)]),
+        // 上行琶音
+        AudioCue::AnswerReady => BuiltinSound::Tones(&[(523.0, 80), (659.0, 80), (784.0, 160)]),
+    }
+}
+
+/// 查找某个模式下某个事件的提示音设置
+pub fn cue_setting(config: &AudioCueConfig, mode: TriggerMode, cue: AudioCue) -> &CueSetting {
+    let set = match mode {
+        TriggerMode::Dictation => &config.dictation,
+        TriggerMode::AiAssistant => &config.assistant,
+    };
+    match cue {
+        AudioCue::Start => &set.start,
+        AudioCue::Stop => &set.stop,
+        AudioCue::Lock => &set.lock,
+        AudioCue::Cancel => &set.cancel,
+        AudioCue::Error => &set.error,
+        AudioCue::AnswerReady => &set.answer_ready,
+    }
+}
+
+/// 按配置播放提示音（非阻塞，全局静音时不播放）
+pub fn play_cue(config: &AudioCueConfig, mode: TriggerMode, cue: AudioCue) {
+    if config.muted {
+        return;
+    }
+    play_setting(cue_setting(config, mode, cue).clone(), mode, cue);
+}
 
-/// 播放提示音（非阻塞）
-pub fn play_notification() {
+/// 播放指定的提示音设置（非阻塞，用于设置界面试听，忽略全局静音）
+pub fn play_setting(setting: CueSetting, mode: TriggerMode, cue: AudioCue) {
     // 在新线程中播放，避免阻塞主线程
-    std::thread::spawn(|| {
-        if let Err(e) = play_notification_blocking() {
-            tracing::error!("播放提示音失败: {}", e);
+    std::thread::spawn(move || {
+        if let Err(e) = play_blocking(&setting, mode, cue) {
+            tracing::error!("播放提示音失败 ({:?}/{:?}): {}", mode, cue, e);
         }
     });
 }
 
 /// 阻塞式播放提示音
-fn play_notification_blocking() -> Result<(), Box<dyn std::error::Error>> {
+fn play_blocking(setting: &CueSetting, mode: TriggerMode, cue: AudioCue) -> Result<(), Box<dyn std::error::Error>> {
     // 获取音频输出流
     let (_stream, stream_handle) = OutputStream::try_default()?;
     let sink = Sink::try_new(&stream_handle)?;
+    let volume = setting.volume.clamp(0.0, 1.0);
 
-    // 从嵌入的字节数据创建解码器
-    let cursor = Cursor::new(NOTIFICATION_SOUND);
-    let source = Decoder::new(cursor)?.amplify(VOLUME); // 降低音量
+    // 优先使用自定义音效文件，读取失败时回退到内置音效
+    let custom = setting
+        .file
+        .as_deref()
+        .filter(|path| !path.trim().is_empty())
+        .and_then(|path| match load_custom(path) {
+            Ok(decoder) => Some(decoder),
+            Err(e) => {
+                tracing::warn!("读取自定义提示音失败，使用内置音效: {} ({})", path, e);
+                None
+            }
+        });
+
+    match custom {
+        Some(decoder) => sink.append(decoder.amplify(volume)),
+        None => append_builtin(&sink, mode, cue, volume)?,
+    }
 
-    sink.append(source);
     sink.sleep_until_end(); // 等待播放完成
 
     Ok(())
 }
 
-/// 播放"开始录音"提示音
-pub fn play_start_beep() {
-    play_notification();
+fn load_custom(path: &str) -> Result<Decoder<BufReader<File>>, Box<dyn std::error::Error>> {
+    Ok(Decoder::new(BufReader::new(File::open(path)?))?)
+}
+
+fn append_builtin(sink: &Sink, mode: TriggerMode, cue: AudioCue, volume: f32) -> Result<(), Box<dyn std::error::Error>> {
+    let pitch = match mode {
+        TriggerMode::Dictation => 1.0,
+        TriggerMode::AiAssistant => ASSISTANT_PITCH,
+    };
+
+    match builtin_sound(cue) {
+        BuiltinSound::Notification { speed } => {
+            // 从嵌入的字节数据创建解码器
+            let source = Decoder::new(Cursor::new(NOTIFICATION_SOUND))?;
+            sink.append(source.speed(speed * pitch).amplify(volume));
+        }
+        BuiltinSound::Tones(tones) => {
+            for &(freq, ms) in tones {
+                let duration = Duration::from_millis(ms);
+                if freq > 0.0 {
+                    sink.append(
+                        SineWave::new(freq * pitch)
+                            .take_duration(duration)
+                            .fade_in(TONE_FADE)
+                            .amplify(volume),
+                    );
+                } else {
+                    sink.append(Zero::<f32>::new(1, 48000).take_duration(duration));
+                }
+            }
+        }
+    }
+    Ok(())
 }
 
-/// 播放"停止录音"提示音
-pub fn play_stop_beep() {
-    play_notification();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_CUES: [AudioCue; 6] = [
+        AudioCue::Start,
+        AudioCue::Stop,
+        AudioCue::Lock,
+        AudioCue::Cancel,
+        AudioCue::Error,
+        AudioCue::AnswerReady,
+    ];
+
+    #[test]
+    fn test_builtin_sounds_are_distinct() {
+        for (i, a) in ALL_CUES.iter().enumerate() {
+            for b in &ALL_CUES[i + 1..] {
+                assert_ne!(builtin_sound(*a), builtin_sound(*b), "{:?} 与 {:?} 内置音效相同", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cue_setting_per_mode() {
+        let mut config = AudioCueConfig::default();
+        config.dictation.lock.volume = 0.5;
+        config.assistant.answer_ready.file = Some("answer.wav".to_string());
+
+        assert_eq!(cue_setting(&config, TriggerMode::Dictation, AudioCue::Lock).volume, 0.5);
+        assert_eq!(cue_setting(&config, TriggerMode::AiAssistant, AudioCue::Lock).volume, 0.2);
+        assert_eq!(
+            cue_setting(&config, TriggerMode::AiAssistant, AudioCue::AnswerReady).file.as_deref(),
+            Some("answer.wav")
+        );
+        assert!(cue_setting(&config, TriggerMode::Dictation, AudioCue::AnswerReady).file.is_none());
+    }
 }
@@ -0,0 +1,656 @@
+// src-tauri/src/chat_backend.rs
+//
+// 聊天协议适配
+//
+// OpenAiClient 负责 HTTP 传输和流式读取，各协议的请求格式、鉴权头和响应解析由 ChatBackend 实现：
+// - OpenAI 兼容格式：/chat/completions，Bearer 鉴权，SSE 以 [DONE] 结束
+// - Anthropic Messages API：system 为独立字段，x-api-key + anthropic-version 鉴权
+// - Google Gemini：contents / parts 结构，system 放在 systemInstruction，assistant 角色为 model
+// - Ollama 原生 /api/chat：流式响应为逐行 JSON（NDJSON）
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::config::ChatProtocol;
+use crate::openai_client::{ChatCompletion, ChatOptions, Message, OpenAiClientConfig, Role, ToolCall, Usage};
+
+/// Anthropic Messages API 版本
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// 流式响应的分帧方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// text/event-stream，每个事件的 data 字段为一个分块
+    Sse,
+    /// 每行一个 JSON 分块
+    Ndjson,
+}
+
+/// 构建好的 HTTP 请求
+#[derive(Debug)]
+pub struct ChatRequest {
+    pub url: String,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Value,
+}
+
+/// 单个流式分块中的内容
+#[derive(Debug, Default, PartialEq)]
+pub struct StreamChunk {
+    pub delta: String,
+    /// 结束原因，统一为 OpenAI 的取值（stop / length / content_filter）
+    pub finish_reason: Option<String>,
+    pub usage: Option<Usage>,
+    /// 工具调用片段
+    pub tool_calls: Vec<ToolCallDelta>,
+    /// 协议层面的结束标记（OpenAI 的 [DONE]、Anthropic 的 message_stop、Ollama 的 done）
+    pub done: bool,
+}
+
+/// 流式响应中的工具调用片段（同一 index 的片段依次拼接）
+#[derive(Debug, Default, PartialEq)]
+pub struct ToolCallDelta {
+    pub index: usize,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub arguments: String,
+}
+
+/// 聊天协议
+pub trait ChatBackend: Send + Sync {
+    /// 协议名称（用于日志）
+    fn name(&self) -> &'static str;
+
+    /// 构建请求（URL、鉴权头、请求体）
+    fn request(&self, config: &OpenAiClientConfig, messages: &[Message], options: &ChatOptions, stream: bool) -> ChatRequest;
+
+    /// 流式响应的分帧方式
+    fn framing(&self) -> Framing {
+        Framing::Sse
+    }
+
+    /// 是否支持工具调用（不支持时请求中不带 tools）
+    fn supports_tools(&self) -> bool {
+        false
+    }
+
+    /// 解析完整响应
+    fn parse_response(&self, payload: &Value) -> Result<ChatCompletion>;
+
+    /// 解析一个流式分块
+    fn parse_stream_chunk(&self, data: &str) -> Result<StreamChunk>;
+}
+
+/// 根据协议创建对应的实现
+pub fn backend_for(protocol: ChatProtocol) -> Arc<dyn ChatBackend> {
+    match protocol {
+        ChatProtocol::OpenAi => Arc::new(OpenAiBackend),
+        ChatProtocol::Anthropic => Arc::new(AnthropicBackend),
+        ChatProtocol::Gemini => Arc::new(GeminiBackend),
+        ChatProtocol::Ollama => Arc::new(OllamaBackend),
+    }
+}
+
+/// 解析分块 JSON，服务端在流中返回错误时直接报错
+fn parse_payload(name: &str, data: &str) -> Result<Value> {
+    let payload: Value = serde_json::from_str(data)
+        .map_err(|e| anyhow::anyhow!("{} 流式分块不可解析 ({}): {}", name, e, data))?;
+    if !payload["error"].is_null() {
+        anyhow::bail!("{} API 流式响应返回错误: {}", name, payload["error"]);
+    }
+    Ok(payload)
+}
+
+fn tokens(value: &Value) -> u32 {
+    value.as_u64().unwrap_or(0) as u32
+}
+
+/// 拆出 system 消息（Anthropic、Gemini 的 system 不在消息列表中），多条时用空行连接
+fn split_system(messages: &[Message]) -> (Option<String>, Vec<&Message>) {
+    let (system, rest): (Vec<&Message>, Vec<&Message>) =
+        messages.iter().partition(|m| matches!(m.role, Role::System));
+    let system = (!system.is_empty()).then(|| {
+        system
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    });
+    (system, rest)
+}
+
+// ============================================================================
+// OpenAI 兼容格式
+// ============================================================================
+
+struct OpenAiBackend;
+
+impl OpenAiBackend {
+    /// assistant 消息带上 tool_calls，tool 消息带上 tool_call_id
+    fn message(message: &Message) -> Value {
+        let mut json = json!({ "role": message.role.as_str(), "content": message.content });
+        if !message.tool_calls.is_empty() {
+            json["tool_calls"] = message
+                .tool_calls
+                .iter()
+                .map(|c| json!({ "id": c.id, "type": "function", "function": { "name": c.name, "arguments": c.arguments } }))
+                .collect();
+        }
+        if let Some(ref id) = message.tool_call_id {
+            json["tool_call_id"] = Value::String(id.clone());
+        }
+        json
+    }
+}
+
+impl ChatBackend for OpenAiBackend {
+    fn name(&self) -> &'static str {
+        "OpenAI"
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn request(&self, config: &OpenAiClientConfig, messages: &[Message], options: &ChatOptions, stream: bool) -> ChatRequest {
+        let mut body = json!({
+            "model": config.model,
+            "messages": messages.iter().map(Self::message).collect::<Vec<_>>(),
+            "max_tokens": options.max_tokens,
+            "temperature": options.temperature
+        });
+        if !options.tools.is_empty() {
+            body["tools"] = options
+                .tools
+                .iter()
+                .map(|t| {
+                    json!({
+                        "type": "function",
+                        "function": { "name": t.name, "description": t.description, "parameters": t.parameters }
+                    })
+                })
+                .collect();
+        }
+        if stream {
+            body["stream"] = Value::Bool(true);
+            // 让服务端在流结束前返回 token 用量
+            body["stream_options"] = json!({ "include_usage": true });
+        }
+        ChatRequest {
+            url: config.endpoint.clone(),
+            headers: vec![("Authorization", format!("Bearer {}", config.api_key))],
+            body,
+        }
+    }
+
+    fn parse_response(&self, payload: &Value) -> Result<ChatCompletion> {
+        let choice = &payload["choices"][0];
+        let message = &choice["message"];
+        let tool_calls: Vec<ToolCall> = message["tool_calls"]
+            .as_array()
+            .map(|calls| {
+                calls
+                    .iter()
+                    .map(|c| ToolCall {
+                        id: c["id"].as_str().unwrap_or_default().to_string(),
+                        name: c["function"]["name"].as_str().unwrap_or_default().to_string(),
+                        arguments: c["function"]["arguments"].as_str().unwrap_or_default().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        // 只有工具调用时 content 为 null
+        let content = match message["content"].as_str() {
+            Some(content) => content,
+            None if !tool_calls.is_empty() => "",
+            None => anyhow::bail!("OpenAI API 返回格式不可解析: {:?}", payload),
+        };
+        Ok(ChatCompletion {
+            content: content.to_string(),
+            finish_reason: choice["finish_reason"].as_str().map(str::to_string),
+            usage: serde_json::from_value(payload["usage"].clone()).ok(),
+            tool_calls,
+        })
+    }
+
+    /// `{"choices":[{"delta":{"content":"..."},"finish_reason":null}],"usage":null}`
+    fn parse_stream_chunk(&self, data: &str) -> Result<StreamChunk> {
+        if data.trim() == "[DONE]" {
+            return Ok(StreamChunk { done: true, ..Default::default() });
+        }
+        let payload = parse_payload(self.name(), data)?;
+        let choice = payload["choices"].as_array().and_then(|arr| arr.first());
+        Ok(StreamChunk {
+            delta: choice
+                .and_then(|c| c["delta"]["content"].as_str())
+                .unwrap_or_default()
+                .to_string(),
+            finish_reason: choice
+                .and_then(|c| c["finish_reason"].as_str())
+                .map(str::to_string),
+            usage: serde_json::from_value(payload["usage"].clone()).ok(),
+            tool_calls: choice
+                .and_then(|c| c["delta"]["tool_calls"].as_array())
+                .map(|calls| {
+                    calls
+                        .iter()
+                        .map(|c| ToolCallDelta {
+                            index: c["index"].as_u64().unwrap_or(0) as usize,
+                            id: c["id"].as_str().map(str::to_string),
+                            name: c["function"]["name"].as_str().map(str::to_string),
+                            arguments: c["function"]["arguments"].as_str().unwrap_or_default().to_string(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            done: false,
+        })
+    }
+}
+
+// ============================================================================
+// Anthropic Messages API
+// ============================================================================
+
+struct AnthropicBackend;
+
+impl AnthropicBackend {
+    fn finish_reason(stop_reason: &Value) -> Option<String> {
+        stop_reason.as_str().map(|reason| {
+            match reason {
+                "end_turn" | "stop_sequence" => "stop",
+                "max_tokens" => "length",
+                "refusal" => "content_filter",
+                other => other,
+            }
+            .to_string()
+        })
+    }
+}
+
+impl ChatBackend for AnthropicBackend {
+    fn name(&self) -> &'static str {
+        "Anthropic"
+    }
+
+    fn request(&self, config: &OpenAiClientConfig, messages: &[Message], options: &ChatOptions, stream: bool) -> ChatRequest {
+        let (system, rest) = split_system(messages);
+        let messages_json: Vec<Value> = rest
+            .iter()
+            .map(|m| json!({ "role": m.role.as_str(), "content": m.content }))
+            .collect();
+
+        let mut body = json!({
+            "model": config.model,
+            "messages": messages_json,
+            "max_tokens": options.max_tokens,
+            "temperature": options.temperature
+        });
+        if let Some(system) = system {
+            body["system"] = Value::String(system);
+        }
+        if stream {
+            body["stream"] = Value::Bool(true);
+        }
+        ChatRequest {
+            url: config.endpoint.clone(),
+            headers: vec![
+                ("x-api-key", config.api_key.clone()),
+                ("anthropic-version", ANTHROPIC_VERSION.to_string()),
+            ],
+            body,
+        }
+    }
+
+    /// `{"content":[{"type":"text","text":"..."}],"stop_reason":"end_turn","usage":{...}}`
+    fn parse_response(&self, payload: &Value) -> Result<ChatCompletion> {
+        let blocks = payload["content"]
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Anthropic API 返回格式不可解析: {:?}", payload))?;
+        let content: String = blocks
+            .iter()
+            .filter(|b| b["type"] == "text")
+            .filter_map(|b| b["text"].as_str())
+            .collect();
+        let usage = &payload["usage"];
+        Ok(ChatCompletion {
+            content,
+            finish_reason: Self::finish_reason(&payload["stop_reason"]),
+            usage: Some(Usage::new(tokens(&usage["input_tokens"]), tokens(&usage["output_tokens"]))),
+            tool_calls: Vec::new(),
+        })
+    }
+
+    /// 按 type 区分事件：message_start（输入 token）、content_block_delta（文本片段）、
+    /// message_delta（结束原因、输出 token）、message_stop（结束），ping 等其余事件忽略
+    fn parse_stream_chunk(&self, data: &str) -> Result<StreamChunk> {
+        let payload = parse_payload(self.name(), data)?;
+        let mut chunk = StreamChunk::default();
+        match payload["type"].as_str() {
+            Some("message_start") => {
+                let usage = &payload["message"]["usage"];
+                chunk.usage = Some(Usage::new(tokens(&usage["input_tokens"]), tokens(&usage["output_tokens"])));
+            }
+            Some("content_block_delta") => {
+                chunk.delta = payload["delta"]["text"].as_str().unwrap_or_default().to_string();
+            }
+            Some("message_delta") => {
+                chunk.finish_reason = Self::finish_reason(&payload["delta"]["stop_reason"]);
+                let usage = &payload["usage"];
+                chunk.usage = Some(Usage::new(tokens(&usage["input_tokens"]), tokens(&usage["output_tokens"])));
+            }
+            Some("message_stop") => chunk.done = true,
+            _ => {}
+        }
+        Ok(chunk)
+    }
+}
+
+// ============================================================================
+// Google Gemini
+// ============================================================================
+
+struct GeminiBackend;
+
+impl GeminiBackend {
+    fn finish_reason(reason: &Value) -> Option<String> {
+        reason.as_str().map(|reason| match reason {
+            "STOP" => "stop".to_string(),
+            "MAX_TOKENS" => "length".to_string(),
+            "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" => "content_filter".to_string(),
+            other => other.to_lowercase(),
+        })
+    }
+
+    /// 完整响应和流式分块的结构相同
+    fn parse_candidate(payload: &Value) -> StreamChunk {
+        let candidate = &payload["candidates"][0];
+        let delta = candidate["content"]["parts"]
+            .as_array()
+            .map(|parts| parts.iter().filter_map(|p| p["text"].as_str()).collect())
+            .unwrap_or_default();
+        let metadata = &payload["usageMetadata"];
+        StreamChunk {
+            delta,
+            finish_reason: Self::finish_reason(&candidate["finishReason"]),
+            usage: metadata.is_object().then(|| {
+                Usage::new(tokens(&metadata["promptTokenCount"]), tokens(&metadata["candidatesTokenCount"]))
+            }),
+            tool_calls: Vec::new(),
+            done: false,
+        }
+    }
+}
+
+impl ChatBackend for GeminiBackend {
+    fn name(&self) -> &'static str {
+        "Gemini"
+    }
+
+    /// endpoint 为 API 根地址，按模型拼接 `/models/{model}:generateContent`
+    fn request(&self, config: &OpenAiClientConfig, messages: &[Message], options: &ChatOptions, stream: bool) -> ChatRequest {
+        let (system, rest) = split_system(messages);
+        let contents: Vec<Value> = rest
+            .iter()
+            .map(|m| {
+                let role = match m.role {
+                    Role::Assistant => "model",
+                    _ => "user",
+                };
+                json!({ "role": role, "parts": [{ "text": m.content }] })
+            })
+            .collect();
+
+        let mut body = json!({
+            "contents": contents,
+            "generationConfig": {
+                "maxOutputTokens": options.max_tokens,
+                "temperature": options.temperature
+            }
+        });
+        if let Some(system) = system {
+            body["systemInstruction"] = json!({ "parts": [{ "text": system }] });
+        }
+
+        let method = if stream { "streamGenerateContent?alt=sse" } else { "generateContent" };
+        ChatRequest {
+            url: format!("{}/models/{}:{}", config.endpoint.trim_end_matches('/'), config.model, method),
+            headers: vec![("x-goog-api-key", config.api_key.clone())],
+            body,
+        }
+    }
+
+    fn parse_response(&self, payload: &Value) -> Result<ChatCompletion> {
+        if payload["candidates"][0].is_null() {
+            anyhow::bail!("Gemini API 返回格式不可解析: {:?}", payload);
+        }
+        let chunk = Self::parse_candidate(payload);
+        Ok(ChatCompletion {
+            content: chunk.delta,
+            finish_reason: chunk.finish_reason,
+            usage: chunk.usage,
+            tool_calls: Vec::new(),
+        })
+    }
+
+    /// `{"candidates":[{"content":{"parts":[{"text":"..."}]},"finishReason":"STOP"}],"usageMetadata":{...}}`，
+    /// 没有结束标记，连接关闭即结束
+    fn parse_stream_chunk(&self, data: &str) -> Result<StreamChunk> {
+        let payload = parse_payload(self.name(), data)?;
+        Ok(Self::parse_candidate(&payload))
+    }
+}
+
+// ============================================================================
+// Ollama 原生接口
+// ============================================================================
+
+struct OllamaBackend;
+
+impl OllamaBackend {
+    /// 完整响应和流式分块的结构相同，最后一块 done 为 true 并带有 token 统计
+    fn parse_message(payload: &Value) -> StreamChunk {
+        let done = payload["done"].as_bool().unwrap_or(false);
+        StreamChunk {
+            delta: payload["message"]["content"].as_str().unwrap_or_default().to_string(),
+            finish_reason: payload["done_reason"].as_str().map(str::to_string),
+            usage: done.then(|| Usage::new(tokens(&payload["prompt_eval_count"]), tokens(&payload["eval_count"]))),
+            tool_calls: Vec::new(),
+            done,
+        }
+    }
+}
+
+impl ChatBackend for OllamaBackend {
+    fn name(&self) -> &'static str {
+        "Ollama"
+    }
+
+    fn request(&self, config: &OpenAiClientConfig, messages: &[Message], options: &ChatOptions, stream: bool) -> ChatRequest {
+        let body = json!({
+            "model": config.model,
+            "messages": messages
+                .iter()
+                .map(|m| json!({ "role": m.role.as_str(), "content": m.content }))
+                .collect::<Vec<_>>(),
+            "stream": stream,
+            "options": {
+                "num_predict": options.max_tokens,
+                "temperature": options.temperature
+            }
+        });
+        // 本地服务不需要鉴权，填写了 API Key 时（如经过反向代理）按 Bearer 发送
+        let headers = if config.api_key.is_empty() {
+            Vec::new()
+        } else {
+            vec![("Authorization", format!("Bearer {}", config.api_key))]
+        };
+        ChatRequest { url: config.endpoint.clone(), headers, body }
+    }
+
+    fn framing(&self) -> Framing {
+        Framing::Ndjson
+    }
+
+    fn parse_response(&self, payload: &Value) -> Result<ChatCompletion> {
+        if payload["message"].is_null() {
+            anyhow::bail!("Ollama API 返回格式不可解析: {:?}", payload);
+        }
+        let chunk = Self::parse_message(payload);
+        Ok(ChatCompletion {
+            content: chunk.delta,
+            finish_reason: chunk.finish_reason,
+            usage: chunk.usage,
+            tool_calls: Vec::new(),
+        })
+    }
+
+    fn parse_stream_chunk(&self, data: &str) -> Result<StreamChunk> {
+        let payload = parse_payload(self.name(), data)?;
+        Ok(Self::parse_message(&payload))
+    }
+}
+
+// ============================================================================
+// 测试
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<Message> {
+        vec![
+            Message::system("简洁回答"),
+            Message::user("你好"),
+            Message::assistant("你好！"),
+            Message::user("再见"),
+        ]
+    }
+
+    fn config(endpoint: &str) -> OpenAiClientConfig {
+        OpenAiClientConfig::new(endpoint, "key", "model-x")
+    }
+
+    #[test]
+    fn test_openai_stream_chunk() {
+        let backend = OpenAiBackend;
+        let chunk = backend
+            .parse_stream_chunk(r#"{"choices":[{"delta":{"content":"Hi"},"finish_reason":null}]}"#)
+            .unwrap();
+        assert_eq!(chunk.delta, "Hi");
+        assert_eq!(chunk.finish_reason, None);
+
+        let chunk = backend
+            .parse_stream_chunk(r#"{"choices":[],"usage":{"prompt_tokens":5,"completion_tokens":2,"total_tokens":7}}"#)
+            .unwrap();
+        assert_eq!(chunk.delta, "");
+        assert_eq!(chunk.usage, Some(Usage { prompt_tokens: 5, completion_tokens: 2, total_tokens: 7 }));
+
+        assert!(backend.parse_stream_chunk("[DONE]").unwrap().done);
+        assert!(backend.parse_stream_chunk(r#"{"error":{"message":"rate limited"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_openai_tool_calls() {
+        let backend = OpenAiBackend;
+        let tool = crate::openai_client::ToolDefinition {
+            name: "open_url".to_string(),
+            description: "打开网址".to_string(),
+            parameters: json!({ "type": "object" }),
+        };
+        let call = ToolCall { id: "call_1".to_string(), name: "open_url".to_string(), arguments: "{}".to_string() };
+        let history = vec![
+            Message::user("打开百度"),
+            Message::assistant_with_tool_calls("", vec![call.clone()]),
+            Message::tool("call_1", "已打开"),
+        ];
+        let request = backend.request(&config("https://api.example.com"), &history, &ChatOptions::default().with_tools(vec![tool]), false);
+        assert_eq!(request.body["tools"][0]["function"]["name"], "open_url");
+        assert_eq!(request.body["messages"][1]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(request.body["messages"][2]["tool_call_id"], "call_1");
+
+        let chunk = backend
+            .parse_stream_chunk(r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","function":{"name":"open_url","arguments":"{\"u"}}]}}]}"#)
+            .unwrap();
+        assert_eq!(chunk.tool_calls[0].name.as_deref(), Some("open_url"));
+        assert_eq!(chunk.tool_calls[0].arguments, "{\"u");
+
+        let payload = json!({
+            "choices": [{ "message": { "content": null, "tool_calls": [{ "id": "call_1", "type": "function", "function": { "name": "open_url", "arguments": "{}" } }] }, "finish_reason": "tool_calls" }]
+        });
+        assert_eq!(backend.parse_response(&payload).unwrap().tool_calls, vec![call]);
+    }
+
+    #[test]
+    fn test_anthropic_request_and_events() {
+        let backend = AnthropicBackend;
+        let request = backend.request(&config("https://api.anthropic.com/v1/messages"), &messages(), &ChatOptions::default(), true);
+        assert_eq!(request.body["system"], "简洁回答");
+        assert_eq!(request.body["messages"].as_array().unwrap().len(), 3);
+        assert_eq!(request.body["messages"][1]["role"], "assistant");
+        assert!(request.headers.contains(&("x-api-key", "key".to_string())));
+        assert!(request.headers.iter().any(|(name, _)| *name == "anthropic-version"));
+
+        let chunk = backend
+            .parse_stream_chunk(r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"好"}}"#)
+            .unwrap();
+        assert_eq!(chunk.delta, "好");
+        let chunk = backend
+            .parse_stream_chunk(r#"{"type":"message_delta","delta":{"stop_reason":"max_tokens"},"usage":{"output_tokens":9}}"#)
+            .unwrap();
+        assert_eq!(chunk.finish_reason.as_deref(), Some("length"));
+        assert!(backend.parse_stream_chunk(r#"{"type":"message_stop"}"#).unwrap().done);
+        assert!(backend
+            .parse_stream_chunk(r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#)
+            .is_err());
+    }
+
+    #[test]
+    fn test_gemini_request_and_response() {
+        let backend = GeminiBackend;
+        let request = backend.request(
+            &config("https://generativelanguage.googleapis.com/v1beta/"),
+            &messages(),
+            &ChatOptions::default(),
+            true,
+        );
+        assert_eq!(
+            request.url,
+            "https://generativelanguage.googleapis.com/v1beta/models/model-x:streamGenerateContent?alt=sse"
+        );
+        assert_eq!(request.body["systemInstruction"]["parts"][0]["text"], "简洁回答");
+        assert_eq!(request.body["contents"][1]["role"], "model");
+        assert_eq!(request.body["generationConfig"]["maxOutputTokens"], 1024);
+
+        let payload = json!({
+            "candidates": [{ "content": { "parts": [{ "text": "你" }, { "text": "好" }] }, "finishReason": "STOP" }],
+            "usageMetadata": { "promptTokenCount": 4, "candidatesTokenCount": 2, "totalTokenCount": 6 }
+        });
+        let completion = backend.parse_response(&payload).unwrap();
+        assert_eq!(completion.content, "你好");
+        assert_eq!(completion.finish_reason.as_deref(), Some("stop"));
+        assert_eq!(completion.usage, Some(Usage { prompt_tokens: 4, completion_tokens: 2, total_tokens: 6 }));
+    }
+
+    #[test]
+    fn test_ollama_request_and_chunks() {
+        let backend = OllamaBackend;
+        let request = backend.request(&config("http://localhost:11434/api/chat"), &messages(), &ChatOptions::default(), false);
+        assert_eq!(request.body["stream"], false);
+        assert_eq!(request.body["messages"][0]["role"], "system");
+        assert_eq!(request.body["options"]["num_predict"], 1024);
+
+        let chunk = backend
+            .parse_stream_chunk(r#"{"model":"m","message":{"role":"assistant","content":"Hi"},"done":false}"#)
+            .unwrap();
+        assert_eq!(chunk.delta, "Hi");
+        assert!(!chunk.done);
+        let chunk = backend
+            .parse_stream_chunk(r#"{"message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","prompt_eval_count":10,"eval_count":3}"#)
+            .unwrap();
+        assert!(chunk.done);
+        assert_eq!(chunk.usage.map(|u| u.total_tokens), Some(13));
+    }
+}
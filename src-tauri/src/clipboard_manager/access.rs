@@ -0,0 +1,123 @@
+// 剪贴板读写抽象
+//
+// 插入流程通过 InsertionBackend::clipboard() 获取剪贴板：实际后端使用系统剪贴板（arboard），
+// 测试后端使用内存剪贴板，测试不会读写用户的真实剪贴板
+
+use anyhow::Result;
+use arboard::Clipboard;
+
+use super::ClipboardSnapshot;
+
+/// 剪贴板读写
+pub trait ClipboardAccess: Send {
+    /// 读取剪贴板中的文本
+    fn get_text(&mut self) -> Result<String>;
+
+    /// 将文本写入剪贴板（替换原有内容）
+    fn set_text(&mut self, text: &str) -> Result<()>;
+
+    /// 保存当前剪贴板的全部可读格式
+    fn capture(&mut self) -> ClipboardSnapshot;
+
+    /// 将快照写回剪贴板（空快照不做任何操作）
+    fn restore(&mut self, snapshot: &ClipboardSnapshot) -> Result<()>;
+
+    /// 读取 PRIMARY 选区中的文本（仅 Linux X11 / XWayland）
+    ///
+    /// 不支持 PRIMARY 的环境返回错误，调用方应回退到 Ctrl+C
+    fn read_primary(&mut self) -> Result<Option<String>> {
+        anyhow::bail!("当前平台不支持 PRIMARY 选区")
+    }
+}
+
+/// 系统剪贴板
+pub struct SystemClipboard(Clipboard);
+
+impl SystemClipboard {
+    pub fn new() -> Result<Self> {
+        Ok(Self(Clipboard::new()?))
+    }
+}
+
+impl ClipboardAccess for SystemClipboard {
+    fn get_text(&mut self) -> Result<String> {
+        Ok(self.0.get_text()?)
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        Ok(self.0.set_text(text)?)
+    }
+
+    fn capture(&mut self) -> ClipboardSnapshot {
+        ClipboardSnapshot::capture(&mut self.0)
+    }
+
+    fn restore(&mut self, snapshot: &ClipboardSnapshot) -> Result<()> {
+        snapshot.restore(&mut self.0)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read_primary(&mut self) -> Result<Option<String>> {
+        super::selection::read_primary(&mut self.0)
+    }
+}
+
+/// 测试用内存剪贴板：克隆出的句柄共享同一份内容
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct MemoryClipboard(std::sync::Arc<std::sync::Mutex<MemoryContents>>);
+
+#[cfg(test)]
+#[derive(Default)]
+struct MemoryContents {
+    text: Option<String>,
+    /// PRIMARY 选区，None 表示不支持 PRIMARY
+    primary: Option<String>,
+}
+
+#[cfg(test)]
+impl MemoryClipboard {
+    /// 剪贴板中的文本
+    pub fn text(&self) -> Option<String> {
+        self.0.lock().unwrap().text.clone()
+    }
+
+    pub fn set(&self, text: &str) {
+        self.0.lock().unwrap().text = Some(text.to_string());
+    }
+
+    /// 设置 PRIMARY 选区（None 模拟不支持 PRIMARY 的环境）
+    pub fn set_primary(&self, text: Option<&str>) {
+        self.0.lock().unwrap().primary = text.map(str::to_string);
+    }
+}
+
+#[cfg(test)]
+impl ClipboardAccess for MemoryClipboard {
+    fn get_text(&mut self) -> Result<String> {
+        self.text().ok_or_else(|| anyhow::anyhow!("剪贴板中没有文本"))
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        self.set(text);
+        Ok(())
+    }
+
+    fn capture(&mut self) -> ClipboardSnapshot {
+        ClipboardSnapshot::from_text(self.text())
+    }
+
+    fn restore(&mut self, snapshot: &ClipboardSnapshot) -> Result<()> {
+        if let Some(text) = snapshot.text() {
+            self.set(text);
+        }
+        Ok(())
+    }
+
+    fn read_primary(&mut self) -> Result<Option<String>> {
+        match self.0.lock().unwrap().primary.clone() {
+            Some(text) => Ok(Some(text).filter(|t| !t.trim().is_empty())),
+            None => anyhow::bail!("不支持 PRIMARY 选区"),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+// Linux 选中文本读取：AT-SPI 可访问性接口（纯 Wayland 应用）
+// 纯 Wayland 应用不写入 X11 PRIMARY 选区，改为通过 AT-SPI 读取当前文本控件的选区。
+// 后台线程监听辅助功能总线上的焦点和选区变化事件，记住最近一个文本对象；
+// 捕获选中文本时向该对象查询 org.a11y.atspi.Text 的选区，同样不需要模拟 Ctrl+C。
+//
+// 需要桌面已启用辅助功能（GTK4 默认启用；Qt 等需要在系统设置中开启），
+// 否则读取失败，调用方回退到 Ctrl+C
+
+use anyhow::Result;
+use std::sync::{Mutex, OnceLock};
+use zbus::blocking::{connection, Connection, MessageIterator};
+use zbus::zvariant::OwnedObjectPath;
+
+const REGISTRY_DEST: &str = "org.a11y.atspi.Registry";
+const REGISTRY_PATH: &str = "/org/a11y/atspi/registry";
+const TEXT_IFACE: &str = "org.a11y.atspi.Text";
+
+/// 需要应用上报的事件
+const WATCHED_EVENTS: [&str; 2] = ["object:text-selection-changed", "object:state-changed:focused"];
+
+/// 辅助功能总线连接及最近获得焦点 / 选区变化的文本对象（总线名, 对象路径）
+struct Watcher {
+    connection: Connection,
+    last_object: Mutex<Option<(String, OwnedObjectPath)>>,
+}
+
+static WATCHER: OnceLock<Option<Watcher>> = OnceLock::new();
+
+/// 启动 AT-SPI 事件监听（仅 Wayland 会话，重复调用无副作用）
+pub fn start_watcher() {
+    if std::env::var("WAYLAND_DISPLAY").map_or(true, |v| v.is_empty()) {
+        return;
+    }
+    let watcher = WATCHER.get_or_init(|| match connect() {
+        Ok(connection) => {
+            tracing::info!("AT-SPI: 已连接辅助功能总线，纯 Wayland 应用将通过 AT-SPI 读取选中文本");
+            Some(Watcher { connection, last_object: Mutex::new(None) })
+        }
+        Err(e) => {
+            tracing::warn!("AT-SPI: 无法连接辅助功能总线，纯 Wayland 应用将使用 Ctrl+C 读取选中文本: {}", e);
+            None
+        }
+    });
+    if let Some(watcher) = watcher {
+        std::thread::spawn(move || {
+            if let Err(e) = watch(watcher) {
+                tracing::warn!("AT-SPI: 事件监听已停止: {}", e);
+            }
+        });
+    }
+}
+
+/// 读取最近的文本对象中的选中文本
+///
+/// # 返回值
+/// * `Ok(Some(text))` - 有选中文本
+/// * `Ok(None)` - 没有选中内容
+/// * `Err(e)` - AT-SPI 不可用或尚未记录到文本对象，调用方应回退到 Ctrl+C
+pub fn read_selection() -> Result<Option<String>> {
+    let Some(Some(watcher)) = WATCHER.get() else {
+        anyhow::bail!("AT-SPI 未启用");
+    };
+    let Some((sender, path)) = watcher.last_object.lock().unwrap().clone() else {
+        anyhow::bail!("尚未记录到获得焦点的文本对象");
+    };
+
+    let connection = &watcher.connection;
+    let destination = Some(sender.as_str());
+
+    let n_selections: i32 = connection
+        .call_method(destination, &path, Some(TEXT_IFACE), "GetNSelections", &())?
+        .body()
+        .deserialize()?;
+    if n_selections <= 0 {
+        return Ok(None);
+    }
+    let (start, end): (i32, i32) = connection
+        .call_method(destination, &path, Some(TEXT_IFACE), "GetSelection", &(0i32,))?
+        .body()
+        .deserialize()?;
+    if start == end {
+        return Ok(None);
+    }
+    let text: String = connection
+        .call_method(destination, &path, Some(TEXT_IFACE), "GetText", &(start.min(end), start.max(end)))?
+        .body()
+        .deserialize()?;
+    Ok(Some(text).filter(|t| !t.trim().is_empty()))
+}
+
+/// 连接辅助功能总线（地址由会话总线上的 org.a11y.Bus 提供）
+fn connect() -> Result<Connection> {
+    let address: String = match std::env::var("AT_SPI_BUS_ADDRESS") {
+        Ok(address) if !address.is_empty() => address,
+        _ => Connection::session()?
+            .call_method(Some("org.a11y.Bus"), "/org/a11y/bus", Some("org.a11y.Bus"), "GetAddress", &())?
+            .body()
+            .deserialize()?,
+    };
+    let connection = connection::Builder::address(address.as_str())?.build()?;
+
+    // 应用只在有监听者注册时才上报事件
+    for event in WATCHED_EVENTS {
+        connection.call_method(Some(REGISTRY_DEST), REGISTRY_PATH, Some(REGISTRY_DEST), "RegisterEvent", &(event,))?;
+    }
+    Ok(connection)
+}
+
+/// 记录焦点和选区变化的来源对象
+fn watch(watcher: &Watcher) -> Result<()> {
+    let rule = zbus::MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .interface("org.a11y.atspi.Event.Object")?
+        .build();
+    for message in MessageIterator::for_match_rule(rule, &watcher.connection, None)? {
+        let message = message?;
+        let header = message.header();
+        let (Some(member), Some(sender), Some(path)) = (header.member(), header.sender(), header.path()) else {
+            continue;
+        };
+        let relevant = match member.as_str() {
+            "TextSelectionChanged" => true,
+            // 事件参数：(类型, detail1, detail2, ...)，detail1 为 1 表示获得焦点
+            "StateChanged" => message
+                .body()
+                .deserialize_unchecked::<(String, i32, i32)>()
+                .is_ok_and(|(kind, gained, _)| kind == "focused" && gained == 1),
+            _ => false,
+        };
+        if relevant {
+            *watcher.last_object.lock().unwrap() = Some((sender.to_string(), path.to_owned().into()));
+        }
+    }
+    Ok(())
+}
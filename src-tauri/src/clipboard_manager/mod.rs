@@ -0,0 +1,419 @@
+// src-tauri/src/clipboard_manager.rs
+//
+// 剪贴板管理模块 - 用于 AI 助手模式
+//
+// 提供选中文本捕获和剪贴板恢复功能
+// 复制 / 粘贴快捷键由 InsertionBackend 模拟
+// Linux 下优先读取 PRIMARY 选区获取选中文本，不触碰剪贴板（selection.rs）；
+// 纯 Wayland 应用没有 PRIMARY 选区，启用 `atspi` feature 后改用 AT-SPI 可访问性接口读取（atspi.rs），
+// 辅助功能不可用时回退到 Ctrl+C
+// 剪贴板恢复保存全部格式（HTML、RTF、图片、文件列表等），见 snapshot.rs
+
+use std::thread;
+use std::time::{Duration, Instant};
+use anyhow::Result;
+
+use crate::insertion::InsertionBackend;
+
+mod access;
+#[cfg(all(target_os = "linux", feature = "atspi"))]
+mod atspi;
+#[cfg(target_os = "linux")]
+mod selection;
+mod snapshot;
+#[cfg(target_os = "windows")]
+mod win32;
+
+#[cfg(test)]
+pub use access::MemoryClipboard;
+pub use access::{ClipboardAccess, SystemClipboard};
+#[cfg(all(target_os = "linux", feature = "atspi"))]
+pub use atspi::start_watcher as start_atspi_watcher;
+pub use snapshot::ClipboardSnapshot;
+
+/// RAII守卫：自动恢复剪贴板内容
+///
+/// 当守卫被销毁时，自动将原始剪贴板内容（全部格式）恢复
+pub struct ClipboardGuard {
+    original_content: Option<ClipboardSnapshot>,
+    /// 尚未保存原内容：写入剪贴板前由 `save` 保存，未保存时不恢复
+    deferred: bool,
+    clipboard: Box<dyn ClipboardAccess>,
+}
+
+impl ClipboardGuard {
+    /// 创建守卫并保存当前剪贴板内容（剪贴板由插入后端提供）
+    pub fn new(backend: &dyn InsertionBackend) -> Result<Self> {
+        let mut clipboard = backend.clipboard()?;
+        let original_content = Some(clipboard.capture());
+
+        tracing::debug!("ClipboardGuard: 已保存原始剪贴板内容");
+
+        Ok(Self {
+            original_content,
+            deferred: false,
+            clipboard,
+        })
+    }
+
+    /// 创建尚未保存剪贴板内容的守卫（读取 PRIMARY 选区时剪贴板未被改动）
+    fn deferred(clipboard: Box<dyn ClipboardAccess>) -> Self {
+        Self {
+            original_content: None,
+            deferred: true,
+            clipboard,
+        }
+    }
+
+    /// 写入剪贴板前调用：延迟保存的守卫在此时保存原内容，已保存时不做任何操作
+    pub fn save(&mut self) {
+        if self.deferred {
+            self.deferred = false;
+            self.original_content = Some(self.clipboard.capture());
+            tracing::debug!("ClipboardGuard: 已保存原始剪贴板内容（延迟）");
+        }
+    }
+
+    /// 手动恢复剪贴板（消费守卫）
+    pub fn restore(mut self) -> Result<()> {
+        if let Some(content) = self.original_content.take() {
+            self.clipboard.restore(&content)?;
+            tracing::debug!("ClipboardGuard: 已手动恢复剪贴板");
+        }
+        Ok(())
+    }
+
+    /// 放弃恢复（消费守卫），剪贴板保留当前内容
+    pub fn disarm(mut self) {
+        self.deferred = false;
+        self.original_content = None;
+    }
+}
+
+impl Drop for ClipboardGuard {
+    fn drop(&mut self) {
+        if let Some(content) = self.original_content.take() {
+            // 最大努力恢复，忽略错误
+            let _ = self.clipboard.restore(&content);
+            tracing::debug!("ClipboardGuard: 已自动恢复剪贴板（Drop）");
+        }
+    }
+}
+
+/// 获取当前选中的文本
+///
+/// Linux 下先读取 PRIMARY 选区（不改动剪贴板），读取失败时与其他平台一样模拟 Ctrl+C。
+/// 部分应用取消选中后不会清空 PRIMARY，因此 PRIMARY 与上次捕获时相同时视为可能过期，
+/// 改用 Ctrl+C 确认当前是否仍有选中内容
+///
+/// # 参数
+/// * `backend` - 插入后端（无法模拟按键时直接返回无选中文本）
+/// * `last_primary` - 上次捕获时的 PRIMARY 选区（Linux），调用后更新为本次读取的内容
+///
+/// # 返回值
+/// * `Ok((guard, Some(text)))` - 成功捕获选中文本
+/// * `Ok((guard, None))` - 没有选中文本或选中内容为空
+/// * `Err(e)` - 操作失败
+///
+/// # 说明
+/// 返回的 guard 应该保持存活，直到不再需要恢复剪贴板为止；
+/// 从 PRIMARY 读取时剪贴板未被改动，guard 在之后写入剪贴板时（`save`）才保存原内容
+///
+/// # 重要
+/// 调用此函数前，请确保用户已松开所有热键（如 Alt+Space）。
+/// 建议在 on_stop 回调中等待 100ms 后再调用，以避免物理按键与模拟按键冲突。
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+pub fn get_selected_text(
+    backend: &dyn InsertionBackend,
+    last_primary: &mut Option<String>,
+) -> Result<(ClipboardGuard, Option<String>)> {
+    let mut clipboard = backend.clipboard()?;
+
+    // 1. Linux：先读取 PRIMARY 选区（在保存剪贴板之前，不改动剪贴板）
+    #[cfg(target_os = "linux")]
+    match clipboard.read_primary() {
+        Ok(None) => {
+            tracing::debug!("clipboard_manager: PRIMARY 选区为空");
+            *last_primary = None;
+            return Ok((ClipboardGuard::deferred(clipboard), None));
+        }
+        Ok(Some(text)) if last_primary.as_deref() != Some(text.as_str()) => {
+            tracing::info!("clipboard_manager: 从 PRIMARY 选区读取到选中文本 (长度: {} 字符)", text.len());
+            *last_primary = Some(text.clone());
+            return Ok((ClipboardGuard::deferred(clipboard), Some(text)));
+        }
+        Ok(Some(_)) => {
+            tracing::debug!("clipboard_manager: PRIMARY 选区与上次相同（可能已取消选中），改用 Ctrl+C 确认");
+        }
+        Err(e) => {
+            #[cfg(feature = "atspi")]
+            match atspi::read_selection() {
+                Ok(text) => {
+                    tracing::info!("clipboard_manager: 读取 PRIMARY 选区失败，已通过 AT-SPI 读取选区: {}", e);
+                    return Ok((ClipboardGuard::deferred(clipboard), text));
+                }
+                Err(atspi_err) => {
+                    tracing::debug!("clipboard_manager: AT-SPI 读取选区失败: {}", atspi_err);
+                }
+            }
+            tracing::warn!("clipboard_manager: 读取 PRIMARY 选区失败，改用 Ctrl+C: {}", e);
+        }
+    }
+
+    // 2. 保存当前剪贴板
+    let guard = ClipboardGuard::new(backend)?;
+
+    if !backend.can_send_keys() {
+        tracing::debug!("clipboard_manager: 后端 {} 无法模拟按键，跳过选中文本捕获", backend.name());
+        return Ok((guard, None));
+    }
+
+    // 3. 清空剪贴板（用于检测是否有选中内容）
+    clipboard.set_text("")?;
+
+    // 4. 等待剪贴板同步（比 enigo 版本更短）
+    thread::sleep(Duration::from_millis(50));
+
+    // 5. 防御性释放修饰键
+    backend.release_modifiers()?;
+    thread::sleep(Duration::from_millis(5));
+
+    // 6. 模拟 Ctrl+C
+    backend.copy()?;
+
+    // 7. 等待剪贴板更新（带重试机制）
+    let selected_text = wait_for_clipboard_update(clipboard.as_mut(), 3, 80)?;
+
+    if let Some(ref text) = selected_text {
+        tracing::info!("clipboard_manager: 捕获到选中文本 (长度: {} 字符)", text.len());
+    } else {
+        tracing::debug!("clipboard_manager: 未检测到选中文本");
+    }
+
+    Ok((guard, selected_text))
+}
+
+/// 等待剪贴板更新的辅助函数（动态轮询检测）
+///
+/// # 参数
+/// * `clipboard` - 剪贴板实例
+/// * `max_retries` - 最大重试次数（用于兼容旧接口）
+/// * `initial_delay_ms` - 初始轮询间隔（毫秒）
+///
+/// # 返回值
+/// * `Ok(Some(text))` - 成功获取到非空文本
+/// * `Ok(None)` - 剪贴板为空或未更新
+///
+/// # 优化说明
+/// 使用动态轮询替代固定等待，响应快的应用几乎无延迟
+fn wait_for_clipboard_update(
+    clipboard: &mut dyn ClipboardAccess,
+    max_retries: u32,
+    initial_delay_ms: u64,
+) -> Result<Option<String>> {
+    let start = Instant::now();
+    // 最大等待时间：初始延迟 × (1 + 1.5 + 2.25 + ...) ≈ 初始延迟 × 4
+    let max_wait_ms = initial_delay_ms * 4;
+    let poll_interval_ms = 15; // 15ms 轮询间隔
+
+    let mut attempt = 0u32;
+
+    while start.elapsed().as_millis() < max_wait_ms as u128 {
+        match clipboard.get_text() {
+            Ok(text) if !text.is_empty() => {
+                let elapsed = start.elapsed().as_millis();
+                if elapsed > initial_delay_ms as u128 {
+                    tracing::debug!(
+                        "clipboard_manager: {}ms 后成功获取剪贴板内容",
+                        elapsed
+                    );
+                }
+                return Ok(Some(text));
+            }
+            Ok(_) => {
+                // 剪贴板为空，继续轮询
+            }
+            Err(e) => {
+                if attempt == 0 {
+                    tracing::warn!("clipboard_manager: 读取剪贴板失败: {}", e);
+                }
+            }
+        }
+
+        thread::sleep(Duration::from_millis(poll_interval_ms));
+        attempt += 1;
+
+        // 兼容旧的重试次数限制
+        if attempt > max_retries * 10 {
+            break;
+        }
+    }
+
+    // 超时，返回 None（表示没有选中内容）
+    tracing::debug!(
+        "clipboard_manager: 等待 {}ms 后仍未获取到剪贴板内容",
+        start.elapsed().as_millis()
+    );
+    Ok(None)
+}
+
+/// 插入文本（支持上下文感知）
+///
+/// # 参数
+/// * `backend` - 插入后端
+/// * `text` - 要插入的文本
+/// * `has_selection` - 是否有选中文本（如果为 true，粘贴会替换选中内容）
+/// * `clipboard_guard` - 可选的剪贴板守卫（写入前保存原内容，操作完成后恢复）
+///
+/// # 行为
+/// * 有选中文本时：Ctrl+V 会替换选中内容
+/// * 无选中文本时：Ctrl+V 会在光标处插入
+/// * 后端无法模拟按键时：文本留在剪贴板中（不恢复原内容），返回错误
+pub fn insert_text_with_context(
+    backend: &dyn InsertionBackend,
+    text: &str,
+    has_selection: bool,
+    mut clipboard_guard: Option<ClipboardGuard>,
+) -> Result<()> {
+    let mut clipboard = backend.clipboard()?;
+    if let Some(guard) = clipboard_guard.as_mut() {
+        guard.save();
+    }
+
+    // 1. 将文本写入剪贴板
+    clipboard.set_text(text)?;
+
+    if !backend.can_send_keys() {
+        if let Some(guard) = clipboard_guard {
+            guard.disarm();
+        }
+        anyhow::bail!("当前环境无法模拟按键，文本已复制到剪贴板，请手动粘贴");
+    }
+
+    thread::sleep(Duration::from_millis(50));
+
+    tracing::info!(
+        "clipboard_manager: 准备插入文本 (长度: {} 字符, 有选中: {})",
+        text.len(),
+        has_selection
+    );
+
+    // 2. 模拟 Ctrl+V 粘贴
+    backend.paste()?;
+
+    // 3. 等待粘贴完成
+    thread::sleep(Duration::from_millis(150));
+
+    // 4. 恢复原始剪贴板
+    if let Some(guard) = clipboard_guard {
+        guard.restore()?;
+        tracing::debug!("clipboard_manager: 已恢复原始剪贴板");
+    }
+
+    tracing::info!("clipboard_manager: 文本插入成功");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::insertion::fake::{FakeBackend, FakeKey};
+
+    #[test]
+    fn test_clipboard_guard_creation() {
+        let guard = ClipboardGuard::new(&FakeBackend::new());
+        assert!(guard.is_ok());
+    }
+
+    #[test]
+    fn test_get_selected_text() {
+        // 仅检查函数签名是否正确
+        let result = get_selected_text(&FakeBackend::new(), &mut None);
+        match result {
+            Ok(_) | Err(_) => {}
+        }
+    }
+
+    #[test]
+    fn test_clipboard_guard_restores_on_drop() {
+        let backend = FakeBackend::new();
+        backend.memory_clipboard().set("原内容");
+        let guard = ClipboardGuard::new(&backend).unwrap();
+        backend.memory_clipboard().set("临时");
+        drop(guard);
+        assert_eq!(backend.memory_clipboard().text().as_deref(), Some("原内容"));
+    }
+
+    #[test]
+    fn test_get_selected_text_sends_copy() {
+        let backend = FakeBackend::new();
+        backend.memory_clipboard().set("原内容");
+        let (guard, text) = get_selected_text(&backend, &mut None).unwrap();
+        // 内存剪贴板不会因 Ctrl+C 更新，视为没有选中内容
+        assert_eq!(text, None);
+        assert_eq!(backend.events(), vec![FakeKey::ReleaseModifiers, FakeKey::Copy]);
+        guard.restore().unwrap();
+        assert_eq!(backend.memory_clipboard().text().as_deref(), Some("原内容"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_get_selected_text_reads_primary_without_clipboard() {
+        let backend = FakeBackend::new();
+        backend.memory_clipboard().set("剪贴板内容");
+        backend.memory_clipboard().set_primary(Some("选中的文本"));
+        let mut last_primary = None;
+        let (guard, text) = get_selected_text(&backend, &mut last_primary).unwrap();
+        assert_eq!(text.as_deref(), Some("选中的文本"));
+        assert_eq!(last_primary.as_deref(), Some("选中的文本"));
+        assert!(backend.events().is_empty());
+
+        // 未写入剪贴板时守卫不恢复，不会覆盖用户之后复制的内容
+        backend.memory_clipboard().set("之后复制的内容");
+        drop(guard);
+        assert_eq!(backend.memory_clipboard().text().as_deref(), Some("之后复制的内容"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_primary_guard_saves_before_paste() {
+        let backend = FakeBackend::new();
+        backend.memory_clipboard().set("剪贴板内容");
+        backend.memory_clipboard().set_primary(Some("选中的文本"));
+        let (guard, _) = get_selected_text(&backend, &mut None).unwrap();
+        insert_text_with_context(&backend, "回答", true, Some(guard)).unwrap();
+        assert_eq!(backend.events(), vec![FakeKey::Paste(Some("回答".to_string()))]);
+        assert_eq!(backend.memory_clipboard().text().as_deref(), Some("剪贴板内容"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_unchanged_primary_falls_back_to_copy() {
+        let backend = FakeBackend::new();
+        backend.memory_clipboard().set_primary(Some("上次选中的文本"));
+        let mut last_primary = Some("上次选中的文本".to_string());
+        let (_guard, text) = get_selected_text(&backend, &mut last_primary).unwrap();
+        // PRIMARY 可能已过期，Ctrl+C 未复制到内容说明当前没有选中
+        assert_eq!(text, None);
+        assert_eq!(backend.events(), vec![FakeKey::ReleaseModifiers, FakeKey::Copy]);
+    }
+
+    #[test]
+    fn test_insert_with_context_restores_clipboard() {
+        let backend = FakeBackend::new();
+        backend.memory_clipboard().set("原内容");
+        let guard = ClipboardGuard::new(&backend).unwrap();
+        insert_text_with_context(&backend, "回答", true, Some(guard)).unwrap();
+        assert_eq!(backend.events(), vec![FakeKey::Paste(Some("回答".to_string()))]);
+        assert_eq!(backend.memory_clipboard().text().as_deref(), Some("原内容"));
+    }
+
+    #[test]
+    fn test_insert_with_context_clipboard_only_keeps_text() {
+        let backend = FakeBackend::clipboard_only();
+        let guard = ClipboardGuard::new(&backend).unwrap();
+        assert!(insert_text_with_context(&backend, "回答", true, Some(guard)).is_err());
+        assert!(backend.events().is_empty());
+        assert_eq!(backend.memory_clipboard().text().as_deref(), Some("回答"));
+    }
+}
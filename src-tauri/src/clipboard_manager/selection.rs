@@ -0,0 +1,23 @@
+// Linux 选中文本读取：X11 / XWayland 的 PRIMARY 选区
+// 选中文本时 PRIMARY 即被更新，读取它不需要模拟 Ctrl+C，也不会改动剪贴板
+
+use anyhow::Result;
+use arboard::{Clipboard, GetExtLinux, LinuxClipboardKind};
+
+/// 读取 PRIMARY 选区中的文本
+///
+/// # 返回值
+/// * `Ok(Some(text))` - 有选中文本
+/// * `Ok(None)` - PRIMARY 为空（没有选中内容）
+/// * `Err(e)` - 无法读取 PRIMARY（如纯 Wayland 应用），调用方应回退到 Ctrl+C
+///
+/// # 说明
+/// 部分应用在取消选中后不会清空 PRIMARY，此时读到的是上一次选中的文本，
+/// 由调用方与上次读取的内容比较判断是否过期（见 `get_selected_text`）
+pub fn read_primary(clipboard: &mut Clipboard) -> Result<Option<String>> {
+    match clipboard.get().clipboard(LinuxClipboardKind::Primary).text() {
+        Ok(text) if !text.trim().is_empty() => Ok(Some(text)),
+        Ok(_) | Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,255 @@
+// 剪贴板快照：保存并恢复剪贴板中的全部格式
+//
+// - Windows: 通过 Win32 剪贴板 API 逐个复制原始格式数据（HTML、RTF、图片、文件列表等），原样恢复（win32.rs）
+// - 其他平台: 通过 arboard 读取文件列表 / HTML / 图片 / 文本，恢复其中最完整的一种
+//
+// 读取失败的格式会被跳过，不影响其余格式的恢复
+
+use anyhow::Result;
+use arboard::{Clipboard, ImageData};
+use std::path::PathBuf;
+
+#[cfg(target_os = "windows")]
+use super::win32;
+
+/// 通过 arboard 读取到的一种剪贴板内容
+#[derive(Debug, Clone)]
+pub enum ClipboardContent {
+    Files(Vec<PathBuf>),
+    Html(String),
+    Image(ImageData<'static>),
+    Text(String),
+}
+
+impl ClipboardContent {
+    fn kind(&self) -> &'static str {
+        match self {
+            ClipboardContent::Files(_) => "files",
+            ClipboardContent::Html(_) => "html",
+            ClipboardContent::Image(_) => "image",
+            ClipboardContent::Text(_) => "text",
+        }
+    }
+}
+
+enum Snapshot {
+    /// Windows 原始格式数据
+    #[cfg(target_os = "windows")]
+    Raw(win32::RawClipboard),
+    /// 按 arboard 支持的格式保存
+    Portable(Vec<ClipboardContent>),
+}
+
+/// 剪贴板快照
+pub struct ClipboardSnapshot(Snapshot);
+
+impl ClipboardSnapshot {
+    /// 保存当前剪贴板的全部可读格式
+    pub fn capture(clipboard: &mut Clipboard) -> Self {
+        #[cfg(target_os = "windows")]
+        match win32::capture() {
+            Ok(raw) => {
+                tracing::debug!("ClipboardSnapshot: 已保存 {} 种原始格式", raw.len());
+                return Self(Snapshot::Raw(raw));
+            }
+            Err(e) => {
+                tracing::warn!("ClipboardSnapshot: 读取原始格式失败，改用通用格式: {}", e);
+            }
+        }
+
+        let contents = capture_portable(clipboard);
+        tracing::debug!(
+            "ClipboardSnapshot: 已保存 {:?}",
+            contents.iter().map(ClipboardContent::kind).collect::<Vec<_>>()
+        );
+        Self(Snapshot::Portable(contents))
+    }
+
+    /// 只包含文本的快照（测试用内存剪贴板）
+    #[cfg(test)]
+    pub(crate) fn from_text(text: Option<String>) -> Self {
+        Self(Snapshot::Portable(text.map(ClipboardContent::Text).into_iter().collect()))
+    }
+
+    /// 快照中的纯文本
+    #[cfg(test)]
+    pub(crate) fn text(&self) -> Option<&str> {
+        match &self.0 {
+            #[cfg(target_os = "windows")]
+            Snapshot::Raw(_) => None,
+            Snapshot::Portable(contents) => contents.iter().find_map(|c| match c {
+                ClipboardContent::Text(text) => Some(text.as_str()),
+                _ => None,
+            }),
+        }
+    }
+
+    /// 快照中是否没有任何内容（剪贴板原本为空或所有格式都无法读取）
+    pub fn is_empty(&self) -> bool {
+        match &self.0 {
+            #[cfg(target_os = "windows")]
+            Snapshot::Raw(raw) => raw.is_empty(),
+            Snapshot::Portable(contents) => contents.is_empty(),
+        }
+    }
+
+    /// 将快照写回剪贴板
+    ///
+    /// 空快照不做任何操作（保留剪贴板当前内容）
+    pub fn restore(&self, clipboard: &mut Clipboard) -> Result<()> {
+        match &self.0 {
+            #[cfg(target_os = "windows")]
+            Snapshot::Raw(raw) => {
+                if raw.is_empty() {
+                    return Ok(());
+                }
+                win32::restore(raw)
+            }
+            Snapshot::Portable(contents) => restore_portable(clipboard, contents),
+        }
+    }
+}
+
+/// 逐个读取 arboard 支持的格式，读不到的跳过
+fn capture_portable(clipboard: &mut Clipboard) -> Vec<ClipboardContent> {
+    let mut contents = Vec::new();
+    if let Ok(files) = clipboard.get().file_list() {
+        if !files.is_empty() {
+            contents.push(ClipboardContent::Files(files));
+        }
+    }
+    if let Ok(html) = clipboard.get().html() {
+        if !html.is_empty() {
+            contents.push(ClipboardContent::Html(html));
+        }
+    }
+    if let Ok(image) = clipboard.get_image() {
+        contents.push(ClipboardContent::Image(image));
+    }
+    if let Ok(text) = clipboard.get_text() {
+        if !text.is_empty() {
+            contents.push(ClipboardContent::Text(text));
+        }
+    }
+    contents
+}
+
+/// 写入一种剪贴板内容（替换原有内容）
+trait ContentWriter {
+    /// alt_text：写入 HTML 时附带的纯文本
+    fn write(&mut self, content: &ClipboardContent, alt_text: Option<&str>) -> Result<(), arboard::Error>;
+}
+
+impl ContentWriter for Clipboard {
+    fn write(&mut self, content: &ClipboardContent, alt_text: Option<&str>) -> Result<(), arboard::Error> {
+        match content {
+            ClipboardContent::Files(files) => self.set().file_list(files),
+            ClipboardContent::Html(html) => self.set_html(html.as_str(), alt_text),
+            ClipboardContent::Image(image) => self.set_image(image.clone()),
+            ClipboardContent::Text(text) => self.set_text(text.as_str()),
+        }
+    }
+}
+
+/// 选出最完整的一种内容写回
+///
+/// arboard 每次写入都会替换剪贴板，无法同时写入多种格式；
+/// 优先级：文件列表 > HTML（附带纯文本）> 图片 > 纯文本
+fn restore_portable(clipboard: &mut impl ContentWriter, contents: &[ClipboardContent]) -> Result<()> {
+    let text = contents.iter().find_map(|c| match c {
+        ClipboardContent::Text(text) => Some(text.as_str()),
+        _ => None,
+    });
+
+    for content in preferred_order(contents) {
+        match clipboard.write(content, text) {
+            Ok(()) => return Ok(()),
+            Err(e) => tracing::warn!("ClipboardSnapshot: 恢复 {} 失败，尝试下一种格式: {}", content.kind(), e),
+        }
+    }
+
+    if contents.is_empty() {
+        Ok(())
+    } else {
+        anyhow::bail!("剪贴板内容恢复失败")
+    }
+}
+
+fn preferred_order(contents: &[ClipboardContent]) -> Vec<&ClipboardContent> {
+    let rank = |c: &ClipboardContent| match c {
+        ClipboardContent::Files(_) => 0,
+        ClipboardContent::Html(_) => 1,
+        ClipboardContent::Image(_) => 2,
+        ClipboardContent::Text(_) => 3,
+    };
+    let mut ordered: Vec<_> = contents.iter().collect();
+    ordered.sort_by_key(|c| rank(c));
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_preferred_order() {
+        let contents = vec![
+            ClipboardContent::Text("表格".to_string()),
+            ClipboardContent::Image(ImageData { width: 1, height: 1, bytes: Cow::Owned(vec![0; 4]) }),
+            ClipboardContent::Html("<table></table>".to_string()),
+        ];
+        let kinds: Vec<_> = preferred_order(&contents).into_iter().map(ClipboardContent::kind).collect();
+        assert_eq!(kinds, vec!["html", "image", "text"]);
+    }
+
+    /// 内存剪贴板：记录成功写入的内容，rejected 中的格式写入失败
+    #[derive(Default)]
+    struct RecordingWriter {
+        rejected: Vec<&'static str>,
+        written: Vec<(&'static str, Option<String>)>,
+    }
+
+    impl ContentWriter for RecordingWriter {
+        fn write(&mut self, content: &ClipboardContent, alt_text: Option<&str>) -> Result<(), arboard::Error> {
+            if self.rejected.contains(&content.kind()) {
+                return Err(arboard::Error::ConversionFailure);
+            }
+            self.written.push((content.kind(), alt_text.map(str::to_string)));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_restore_portable_writes_richest_format() {
+        let contents = vec![
+            ClipboardContent::Text("表格".to_string()),
+            ClipboardContent::Html("<table></table>".to_string()),
+        ];
+        let mut writer = RecordingWriter::default();
+        restore_portable(&mut writer, &contents).unwrap();
+        assert_eq!(writer.written, vec![("html", Some("表格".to_string()))]);
+    }
+
+    #[test]
+    fn test_restore_portable_falls_back_on_failure() {
+        let contents = vec![
+            ClipboardContent::Files(vec![PathBuf::from("/tmp/a.txt")]),
+            ClipboardContent::Text("a.txt".to_string()),
+        ];
+        let mut writer = RecordingWriter { rejected: vec!["files"], ..Default::default() };
+        restore_portable(&mut writer, &contents).unwrap();
+        assert_eq!(writer.written, vec![("text", Some("a.txt".to_string()))]);
+
+        let mut writer = RecordingWriter { rejected: vec!["files", "text"], ..Default::default() };
+        assert!(restore_portable(&mut writer, &contents).is_err());
+        assert!(writer.written.is_empty());
+    }
+
+    #[test]
+    fn test_restore_portable_empty_is_noop() {
+        let mut writer = RecordingWriter::default();
+        restore_portable(&mut writer, &[]).unwrap();
+        assert!(writer.written.is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+// Windows 剪贴板原始格式读写
+// 枚举剪贴板中的所有格式，复制 HGLOBAL 数据；恢复时按原顺序全部写回
+
+use anyhow::{anyhow, Result};
+use std::thread;
+use std::time::Duration;
+use windows::Win32::Foundation::{GlobalFree, HANDLE, HGLOBAL, HWND};
+use windows::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, EnumClipboardFormats, GetClipboardData, OpenClipboard,
+    SetClipboardData,
+};
+use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE};
+
+/// 单个格式的数据上限，超过的格式跳过（避免超大图片占用过多内存）
+const MAX_FORMAT_BYTES: usize = 64 * 1024 * 1024;
+
+/// 打开剪贴板的重试次数（其他程序可能短暂占用剪贴板）
+const OPEN_RETRIES: u32 = 5;
+
+/// 不是 HGLOBAL 的句柄类格式（GDI 位图、图元文件、调色板等），无法按字节复制；
+/// 其中位图会由系统从 CF_DIB 自动合成
+const HANDLE_FORMATS: [u32; 8] = [
+    2,    // CF_BITMAP
+    3,    // CF_METAFILEPICT
+    9,    // CF_PALETTE
+    14,   // CF_ENHMETAFILE
+    0x80, // CF_OWNERDISPLAY
+    0x82, // CF_DSPBITMAP
+    0x83, // CF_DSPMETAFILEPICT
+    0x8E, // CF_DSPENHMETAFILE
+];
+
+/// 剪贴板中所有可复制格式的原始数据
+pub struct RawClipboard {
+    formats: Vec<(u32, Vec<u8>)>,
+}
+
+impl RawClipboard {
+    pub fn len(&self) -> usize {
+        self.formats.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.formats.is_empty()
+    }
+}
+
+/// 打开剪贴板，离开作用域时自动关闭
+struct OpenGuard;
+
+impl OpenGuard {
+    fn open() -> Result<Self> {
+        for attempt in 0..OPEN_RETRIES {
+            if unsafe { OpenClipboard(HWND::default()) }.is_ok() {
+                return Ok(Self);
+            }
+            thread::sleep(Duration::from_millis(10 * (attempt as u64 + 1)));
+        }
+        Err(anyhow!("剪贴板被其他程序占用"))
+    }
+}
+
+impl Drop for OpenGuard {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseClipboard();
+        }
+    }
+}
+
+/// 读取剪贴板中的全部格式
+pub fn capture() -> Result<RawClipboard> {
+    let _open = OpenGuard::open()?;
+    let mut formats = Vec::new();
+    let mut format = 0u32;
+    loop {
+        format = unsafe { EnumClipboardFormats(format) };
+        if format == 0 {
+            break;
+        }
+        if HANDLE_FORMATS.contains(&format) {
+            continue;
+        }
+        match read_format(format) {
+            Some(data) => formats.push((format, data)),
+            None => tracing::debug!("ClipboardSnapshot: 跳过无法读取的格式 {}", format),
+        }
+    }
+    Ok(RawClipboard { formats })
+}
+
+fn read_format(format: u32) -> Option<Vec<u8>> {
+    unsafe {
+        let handle = GetClipboardData(format).ok()?;
+        let global = HGLOBAL(handle.0);
+        let size = GlobalSize(global);
+        if size == 0 || size > MAX_FORMAT_BYTES {
+            return None;
+        }
+        let ptr = GlobalLock(global) as *const u8;
+        if ptr.is_null() {
+            return None;
+        }
+        let data = std::slice::from_raw_parts(ptr, size).to_vec();
+        let _ = GlobalUnlock(global);
+        Some(data)
+    }
+}
+
+/// 清空剪贴板并写回全部格式
+pub fn restore(raw: &RawClipboard) -> Result<()> {
+    let _open = OpenGuard::open()?;
+    unsafe { EmptyClipboard()? };
+
+    let mut restored = 0;
+    for (format, data) in &raw.formats {
+        match write_format(*format, data) {
+            Ok(()) => restored += 1,
+            Err(e) => tracing::debug!("ClipboardSnapshot: 写回格式 {} 失败: {}", format, e),
+        }
+    }
+
+    if restored == 0 {
+        return Err(anyhow!("没有任何剪贴板格式写回成功"));
+    }
+    tracing::debug!("ClipboardSnapshot: 已写回 {}/{} 种格式", restored, raw.formats.len());
+    Ok(())
+}
+
+fn write_format(format: u32, data: &[u8]) -> Result<()> {
+    unsafe {
+        let global = GlobalAlloc(GMEM_MOVEABLE, data.len())?;
+        let ptr = GlobalLock(global) as *mut u8;
+        if ptr.is_null() {
+            let _ = GlobalFree(global);
+            return Err(anyhow!("GlobalLock 失败"));
+        }
+        std::ptr::copy_nonoverlapping(data.as_ptr(), ptr, data.len());
+        let _ = GlobalUnlock(global);
+
+        // 写入成功后内存归系统所有，失败时需自行释放
+        if let Err(e) = SetClipboardData(format, HANDLE(global.0)) {
+            let _ = GlobalFree(global);
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
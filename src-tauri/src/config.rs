@@ -206,6 +206,12 @@ pub struct DualHotkeyConfig {
     /// AI助手模式快捷键（默认 Alt+Space）
     #[serde(default = "default_assistant_hotkey")]
     pub assistant: HotkeyConfig,
+    /// 重置 AI 助手多轮对话的快捷键（可选，未录音时按下生效）
+    #[serde(default)]
+    pub conversation_reset: Option<Vec<HotkeyKey>>,
+    /// 撤销上次插入的快捷键（可选，未录音时按下生效）
+    #[serde(default)]
+    pub undo_insertion: Option<Vec<HotkeyKey>>,
 }
 
 fn default_dictation_hotkey() -> HotkeyConfig {
@@ -231,6 +237,8 @@ impl Default for DualHotkeyConfig {
         Self {
             dictation: default_dictation_hotkey(),
             assistant: default_assistant_hotkey(),
+            conversation_reset: None,
+            undo_insertion: None,
         }
     }
 }
@@ -268,6 +276,34 @@ impl DualHotkeyConfig {
             );
         }
 
+        // 重置对话快捷键同样不能与录音快捷键冲突
+        if let Some(ref keys) = self.conversation_reset {
+            if keys.is_empty() {
+                anyhow::bail!("重置对话快捷键不能为空");
+            }
+            let reset_set: HashSet<_> = keys.iter().collect();
+            for recording_set in [&dictation_set, &assistant_set] {
+                if reset_set.is_subset(recording_set) || recording_set.is_subset(&reset_set) {
+                    anyhow::bail!("重置对话快捷键不能与听写或AI助手快捷键相同或互相包含");
+                }
+            }
+        }
+
+        if let Some(ref keys) = self.undo_insertion {
+            if keys.is_empty() {
+                anyhow::bail!("撤销插入快捷键不能为空");
+            }
+            let undo_set: HashSet<_> = keys.iter().collect();
+            for recording_set in [&dictation_set, &assistant_set] {
+                if undo_set.is_subset(recording_set) || recording_set.is_subset(&undo_set) {
+                    anyhow::bail!("撤销插入快捷键不能与听写或AI助手快捷键相同或互相包含");
+                }
+            }
+            if self.conversation_reset.as_ref().is_some_and(|reset| reset.iter().collect::<HashSet<_>>() == undo_set) {
+                anyhow::bail!("撤销插入快捷键不能与重置对话快捷键相同");
+            }
+        }
+
         Ok(())
     }
 }
@@ -404,7 +440,529 @@ impl Default for AsrConfig {
     }
 }
 
+// ============================================================================
+// 预录缓冲配置
+// ============================================================================
+
+/// 预录缓冲配置
+///
+/// 启用后麦克风常驻采集，仅在内存中保留最近一小段音频，
+/// 按下热键时拼接到录音开头，避免吞掉第一个字。音频永不落盘。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreRollConfig {
+    /// 是否启用预录（隐私开关，默认关闭）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 预录时长（毫秒）
+    #[serde(default = "default_pre_roll_duration_ms")]
+    pub duration_ms: u32,
+}
+
+fn default_pre_roll_duration_ms() -> u32 {
+    300
+}
+
+impl Default for PreRollConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration_ms: default_pre_roll_duration_ms(),
+        }
+    }
+}
+
+// ============================================================================
+// 录音归档配置
+// ============================================================================
+
+/// 录音归档配置
+///
+/// 启用后每次转写成功的录音会连同转写 ID 和元数据保存到应用数据目录，
+/// 超出容量或保存期限的旧录音会被自动清理
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioArchiveConfig {
+    /// 是否启用录音归档（默认关闭）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 归档总容量上限（MB），0 表示不限制
+    #[serde(default = "default_archive_max_total_mb")]
+    pub max_total_mb: u64,
+    /// 最长保存天数，0 表示不限制
+    #[serde(default = "default_archive_max_age_days")]
+    pub max_age_days: u32,
+}
+
+fn default_archive_max_total_mb() -> u64 {
+    500
+}
+
+fn default_archive_max_age_days() -> u32 {
+    30
+}
+
+impl Default for AudioArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_total_mb: default_archive_max_total_mb(),
+            max_age_days: default_archive_max_age_days(),
+        }
+    }
+}
+
+// ============================================================================
+// 录音限制配置
+// ============================================================================
+
+/// 流式录音限制配置
+///
+/// 限制单次录音的最大时长，并在备用音频超过阈值后溢写到临时文件，
+/// 避免长时间录音占用过多内存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingLimitsConfig {
+    /// 最大录音时长（秒），0 表示不限制（默认不限制，需要时手动设置）
+    #[serde(default = "default_max_duration_secs")]
+    pub max_duration_secs: u32,
+    /// 距上限多少秒时提醒（秒），0 表示不提醒
+    #[serde(default = "default_warn_before_secs")]
+    pub warn_before_secs: u32,
+    /// 备用音频在内存中最多保留的时长（秒），超过后溢写到临时文件，0 表示不溢写
+    #[serde(default = "default_spill_threshold_secs")]
+    pub spill_threshold_secs: u32,
+}
+
+fn default_max_duration_secs() -> u32 {
+    0
+}
+
+fn default_warn_before_secs() -> u32 {
+    30
+}
+
+fn default_spill_threshold_secs() -> u32 {
+    120
+}
+
+impl Default for RecordingLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_duration_secs: default_max_duration_secs(),
+            warn_before_secs: default_warn_before_secs(),
+            spill_threshold_secs: default_spill_threshold_secs(),
+        }
+    }
+}
+
+// ============================================================================
+// 静音自动停止配置
+// ============================================================================
+
+/// 静音自动停止配置（仅松手模式）
+///
+/// 锁定录音期间持续静音达到设定时长后自动完成录音并转写，
+/// 结束前的最后几秒在悬浮窗显示倒计时，期间重新说话会取消倒计时
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoStopConfig {
+    /// 是否启用静音自动停止（默认关闭）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 触发自动停止的静音时长（秒）
+    #[serde(default = "default_auto_stop_silence_secs")]
+    pub silence_secs: u32,
+    /// 停止前的倒计时时长（秒），包含在静音时长内
+    #[serde(default = "default_auto_stop_countdown_secs")]
+    pub countdown_secs: u32,
+}
+
+fn default_auto_stop_silence_secs() -> u32 {
+    5
+}
+
+fn default_auto_stop_countdown_secs() -> u32 {
+    3
+}
+
+impl Default for AutoStopConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            silence_secs: default_auto_stop_silence_secs(),
+            countdown_secs: default_auto_stop_countdown_secs(),
+        }
+    }
+}
+
+// ============================================================================
+// 录音时其他应用音频配置
+// ============================================================================
+
+/// 录音时如何处理其他应用的声音
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MuteMode {
+    /// 完全静音
+    #[default]
+    Mute,
+    /// 压低到指定音量（闪避）
+    Duck,
+}
+
+/// 录音时静音 / 压低其他应用的配置（总开关为 enable_mute_other_apps）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MuteOtherAppsConfig {
+    #[serde(default)]
+    pub mode: MuteMode,
+    /// 闪避模式下压低到原音量的百分比
+    #[serde(default = "default_duck_percent")]
+    pub duck_percent: u32,
+    /// 闪避模式下音量渐变时长（毫秒）
+    #[serde(default = "default_duck_fade_ms")]
+    pub fade_ms: u32,
+    /// 不处理的应用（进程名，不区分大小写，可省略 .exe）
+    #[serde(default)]
+    pub excluded_apps: Vec<String>,
+}
+
+fn default_duck_percent() -> u32 {
+    20
+}
+
+fn default_duck_fade_ms() -> u32 {
+    200
+}
+
+impl Default for MuteOtherAppsConfig {
+    fn default() -> Self {
+        Self {
+            mode: MuteMode::default(),
+            duck_percent: default_duck_percent(),
+            fade_ms: default_duck_fade_ms(),
+            excluded_apps: Vec::new(),
+        }
+    }
+}
+
+// ============================================================================
+// 提示音配置
+// ============================================================================
+
+/// 单个提示音配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CueSetting {
+    /// 自定义音效文件路径（wav/ogg/mp3/flac），为空时使用内置音效
+    #[serde(default)]
+    pub file: Option<String>,
+    /// 音量（0.0-1.0）
+    #[serde(default = "default_cue_volume")]
+    pub volume: f32,
+}
+
+fn default_cue_volume() -> f32 {
+    0.2
+}
+
+impl Default for CueSetting {
+    fn default() -> Self {
+        Self {
+            file: None,
+            volume: default_cue_volume(),
+        }
+    }
+}
+
+/// 一种触发模式下各事件的提示音
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CueSet {
+    /// 开始录音
+    #[serde(default)]
+    pub start: CueSetting,
+    /// 停止录音
+    #[serde(default)]
+    pub stop: CueSetting,
+    /// 进入松手模式
+    #[serde(default)]
+    pub lock: CueSetting,
+    /// 取消录音 / 转写
+    #[serde(default)]
+    pub cancel: CueSetting,
+    /// 处理出错
+    #[serde(default)]
+    pub error: CueSetting,
+    /// AI 助手回答就绪
+    #[serde(default)]
+    pub answer_ready: CueSetting,
+}
+
+/// 提示音配置
+///
+/// 听写模式和 AI 助手模式使用各自的提示音（内置音效中助手模式音调更高），
+/// 便于不看屏幕时区分当前状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioCueConfig {
+    /// 全局静音（不播放任何提示音）
+    #[serde(default)]
+    pub muted: bool,
+    /// 听写模式提示音
+    #[serde(default)]
+    pub dictation: CueSet,
+    /// AI 助手模式提示音
+    #[serde(default)]
+    pub assistant: CueSet,
+}
+
+// ============================================================================
+// 文本插入配置
+// ============================================================================
 
+/// 文本插入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InsertionMethod {
+    /// 写入剪贴板后模拟 Ctrl+V，完成后恢复剪贴板
+    #[default]
+    Paste,
+    /// 逐字符发送 Unicode 按键事件，不经过剪贴板
+    Type,
+}
+
+/// 直接输入模式下换行使用的按键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NewlineKey {
+    #[default]
+    Enter,
+    /// Shift+Enter（聊天类应用中 Enter 会直接发送消息）
+    ShiftEnter,
+}
+
+/// 直接输入模式的节奏控制
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypingConfig {
+    /// 每个字符之间的间隔（毫秒）
+    #[serde(default = "default_char_delay_ms")]
+    pub char_delay_ms: u64,
+    /// 每输入多少个字符暂停一次，0 表示不分块
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    /// 分块之间的暂停（毫秒），给目标应用处理输入的时间
+    #[serde(default = "default_chunk_delay_ms")]
+    pub chunk_delay_ms: u64,
+    /// 换行按键
+    #[serde(default)]
+    pub newline: NewlineKey,
+}
+
+fn default_char_delay_ms() -> u64 {
+    5
+}
+
+fn default_chunk_size() -> usize {
+    64
+}
+
+fn default_chunk_delay_ms() -> u64 {
+    40
+}
+
+impl Default for TypingConfig {
+    fn default() -> Self {
+        Self {
+            char_delay_ms: default_char_delay_ms(),
+            chunk_size: default_chunk_size(),
+            chunk_delay_ms: default_chunk_delay_ms(),
+            newline: NewlineKey::default(),
+        }
+    }
+}
+
+/// 针对特定应用的插入方式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppInsertionRule {
+    /// 进程名（不区分大小写，可省略 .exe）
+    pub app: String,
+    pub method: InsertionMethod,
+}
+
+/// 文本插入配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InsertionConfig {
+    /// 全局插入方式
+    #[serde(default)]
+    pub method: InsertionMethod,
+    /// 直接输入模式的节奏控制
+    #[serde(default)]
+    pub typing: TypingConfig,
+    /// 按目标应用覆盖插入方式（如终端中使用直接输入）
+    #[serde(default)]
+    pub app_rules: Vec<AppInsertionRule>,
+}
+
+impl InsertionConfig {
+    /// 按应用配置强制使用指定的插入方式（此时忽略 app_rules）
+    pub fn with_method(mut self, method: Option<InsertionMethod>) -> Self {
+        if let Some(method) = method {
+            self.method = method;
+            self.app_rules.clear();
+        }
+        self
+    }
+
+    /// 根据目标应用的进程名确定插入方式，未匹配时使用全局设置
+    pub fn method_for(&self, app: Option<&str>) -> InsertionMethod {
+        let Some(app) = app.filter(|a| !a.is_empty()) else {
+            return self.method;
+        };
+        let name = app.to_lowercase();
+        let name = name.strip_suffix(".exe").unwrap_or(&name);
+        self.app_rules
+            .iter()
+            .find(|rule| {
+                let rule_app = rule.app.trim().to_lowercase();
+                rule_app.strip_suffix(".exe").unwrap_or(&rule_app) == name
+            })
+            .map(|rule| rule.method)
+            .unwrap_or(self.method)
+    }
+}
+
+// ============================================================================
+// 输出目标配置
+// ============================================================================
+
+/// 结果输出目标
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputSink {
+    /// 插入到目标窗口（按 insertion_config 粘贴或直接输入）
+    Insert,
+    /// 仅复制到剪贴板
+    Clipboard,
+    /// 追加到文件，每条记录带时间戳
+    File {
+        /// 文件路径，支持 ~/ 开头
+        path: String,
+        #[serde(default)]
+        format: FileSinkFormat,
+    },
+    /// 以 JSON POST 到 webhook
+    Webhook { url: String },
+}
+
+/// 文件输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileSinkFormat {
+    /// 每条记录一个以时间为标题的段落
+    #[default]
+    Markdown,
+    /// 每条记录一行，前缀为时间
+    Text,
+}
+
+fn default_output_sinks() -> Vec<OutputSink> {
+    vec![OutputSink::Insert]
+}
+
+/// 输出目标配置（按触发模式分别配置，可同时输出到多个目标）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputConfig {
+    /// 听写模式输出目标
+    #[serde(default = "default_output_sinks")]
+    pub dictation: Vec<OutputSink>,
+    /// AI 助手模式输出目标
+    #[serde(default = "default_output_sinks")]
+    pub assistant: Vec<OutputSink>,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            dictation: default_output_sinks(),
+            assistant: default_output_sinks(),
+        }
+    }
+}
+
+impl OutputConfig {
+    /// 指定触发模式的输出目标
+    pub fn sinks_for(&self, mode: TriggerMode) -> &[OutputSink] {
+        match mode {
+            TriggerMode::Dictation => &self.dictation,
+            TriggerMode::AiAssistant => &self.assistant,
+        }
+    }
+}
+
+// ============================================================================
+// 按应用配置（根据热键按下时的前台窗口自动选择）
+// ============================================================================
+
+/// 应用匹配条件，已填写的条件需全部满足（不区分大小写）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileMatch {
+    /// 进程名（可省略 .exe）
+    #[serde(default)]
+    pub process: Option<String>,
+    /// 窗口类名
+    #[serde(default)]
+    pub window_class: Option<String>,
+    /// 窗口标题包含的文字
+    #[serde(default)]
+    pub title_contains: Option<String>,
+}
+
+/// 转写文本的标点处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PunctuationPolicy {
+    /// 保持 ASR 输出不变
+    Keep,
+    /// 去除末尾标点
+    StripTrailing,
+    /// 去除全部标点
+    StripAll,
+}
+
+/// 插入完成后追加的按键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostInsertAction {
+    /// 回车（如聊天应用中直接发送）
+    Enter,
+    ShiftEnter,
+    Tab,
+    Space,
+}
+
+fn default_profile_enabled() -> bool {
+    true
+}
+
+/// 应用配置：匹配前台窗口后覆盖对应设置，未填写的项沿用全局设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppProfile {
+    pub name: String,
+    #[serde(default = "default_profile_enabled")]
+    pub enabled: bool,
+    #[serde(rename = "match")]
+    pub matcher: ProfileMatch,
+    /// 听写润色使用的 LLM 预设 ID
+    #[serde(default)]
+    pub llm_preset_id: Option<String>,
+    /// ASR 服务商（实时与 HTTP 转写均生效）
+    #[serde(default)]
+    pub asr_provider: Option<AsrProvider>,
+    /// 识别语言（如 zh、en，目前仅千问支持）
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub punctuation: Option<PunctuationPolicy>,
+    #[serde(default)]
+    pub insertion_method: Option<InsertionMethod>,
+    /// 插入完成后依次发送的按键
+    #[serde(default)]
+    pub post_insert_actions: Vec<PostInsertAction>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -441,9 +999,36 @@ pub struct AppConfig {
     /// 录音时自动静音其他应用
     #[serde(default)]
     pub enable_mute_other_apps: bool,
+    /// 静音 / 闪避方式及排除的应用
+    #[serde(default)]
+    pub mute_other_apps_config: MuteOtherAppsConfig,
     /// 个人词典（热词列表）
     #[serde(default)]
     pub dictionary: Vec<String>,
+    /// 预录缓冲配置
+    #[serde(default)]
+    pub pre_roll_config: PreRollConfig,
+    /// 录音归档配置
+    #[serde(default)]
+    pub audio_archive_config: AudioArchiveConfig,
+    /// 静音自动停止配置
+    #[serde(default)]
+    pub auto_stop_config: AutoStopConfig,
+    /// 流式录音限制配置
+    #[serde(default)]
+    pub recording_limits_config: RecordingLimitsConfig,
+    /// 提示音配置
+    #[serde(default)]
+    pub audio_cue_config: AudioCueConfig,
+    /// 文本插入方式配置
+    #[serde(default)]
+    pub insertion_config: InsertionConfig,
+    /// 结果输出目标配置
+    #[serde(default)]
+    pub output_config: OutputConfig,
+    /// 按应用配置（按顺序匹配，第一个匹配的生效）
+    #[serde(default)]
+    pub app_profiles: Vec<AppProfile>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -451,10 +1036,38 @@ pub struct LlmPreset {
     pub id: String,
     pub name: String,
     pub system_prompt: String,
+    /// 不使用响应缓存（结果需要每次重新生成的预设）
+    #[serde(default)]
+    pub bypass_cache: bool,
+}
+
+/// LLM 聊天协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatProtocol {
+    /// OpenAI 兼容格式（/chat/completions，Bearer 鉴权）
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// Anthropic Messages API（endpoint 如 https://api.anthropic.com/v1/messages）
+    Anthropic,
+    /// Google Gemini（endpoint 为 API 根地址，如 https://generativelanguage.googleapis.com/v1beta）
+    Gemini,
+    /// Ollama 原生接口（endpoint 如 http://localhost:11434/api/chat）
+    Ollama,
+}
+
+impl ChatProtocol {
+    /// 本地 Ollama 不需要 API Key
+    pub fn requires_api_key(&self) -> bool {
+        !matches!(self, ChatProtocol::Ollama)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlmConfig {
+    #[serde(default)]
+    pub protocol: ChatProtocol,
     #[serde(default = "default_llm_endpoint")]
     pub endpoint: String,
     #[serde(default = "default_llm_model")]
@@ -467,6 +1080,121 @@ pub struct LlmConfig {
     pub presets: Vec<LlmPreset>,
     #[serde(default = "default_active_preset_id")]
     pub active_preset_id: String,
+    /// 备用端点（主端点失败时依次尝试）
+    #[serde(default)]
+    pub fallback: LlmFallbackConfig,
+    /// 响应缓存
+    #[serde(default)]
+    pub cache: LlmCacheConfig,
+}
+
+/// LLM 响应缓存配置
+///
+/// 相同的短句（问候语、落款、常用语等）直接返回缓存结果，不再请求 LLM。
+/// 缓存按（提示词、主端点模型、去除首尾空白的输入）区分；备用端点的回答不缓存，保存在应用数据目录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmCacheConfig {
+    /// 是否启用缓存
+    #[serde(default = "default_cache_enabled")]
+    pub enabled: bool,
+    /// 缓存有效期（小时），0 表示不过期
+    #[serde(default = "default_cache_ttl_hours")]
+    pub ttl_hours: u64,
+    /// 最多缓存的条数，超出后删除最旧的，0 表示不限制
+    #[serde(default = "default_cache_max_entries")]
+    pub max_entries: usize,
+    /// 只缓存不超过该字数的输入
+    #[serde(default = "default_cache_max_input_chars")]
+    pub max_input_chars: usize,
+}
+
+fn default_cache_enabled() -> bool {
+    true
+}
+
+fn default_cache_ttl_hours() -> u64 {
+    24 * 7
+}
+
+fn default_cache_max_entries() -> usize {
+    1000
+}
+
+fn default_cache_max_input_chars() -> usize {
+    200
+}
+
+impl Default for LlmCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_cache_enabled(),
+            ttl_hours: default_cache_ttl_hours(),
+            max_entries: default_cache_max_entries(),
+            max_input_chars: default_cache_max_input_chars(),
+        }
+    }
+}
+
+/// 单个 LLM 端点（协议、地址、模型和 API Key）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmEndpoint {
+    /// 显示名称（为空时使用模型名）
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub protocol: ChatProtocol,
+    #[serde(default)]
+    pub endpoint: String,
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    pub api_key: String,
+}
+
+impl LlmEndpoint {
+    /// 检查端点是否有效（API Key 已填写，Ollama 可以不填）
+    pub fn is_valid(&self) -> bool {
+        (!self.api_key.trim().is_empty() || !self.protocol.requires_api_key())
+            && !self.endpoint.is_empty()
+            && !self.model.is_empty()
+    }
+
+    /// 结果中记录的端点名称
+    pub fn label(&self) -> &str {
+        if self.name.trim().is_empty() { &self.model } else { &self.name }
+    }
+}
+
+/// LLM 备用端点链
+///
+/// 主端点请求失败或超时后按顺序尝试备用端点，每个端点有独立的 Key 和模型。
+/// 开启快模型优先时，主端点填快速模型、备用端点填强模型：快速模型短时间内没有响应就切换
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmFallbackConfig {
+    /// 备用端点，按顺序尝试（无效的端点会被跳过）
+    #[serde(default)]
+    pub endpoints: Vec<LlmEndpoint>,
+    /// 每次尝试的超时（秒），收到首个文本片段前超时则切换到下一个端点，0 表示不限制。
+    /// 最后一个端点不设超时
+    #[serde(default = "default_fallback_attempt_timeout_secs")]
+    pub attempt_timeout_secs: u64,
+    /// 快模型优先：主端点使用更短的超时，尽快切换到备用的强模型
+    #[serde(default)]
+    pub fast_first: bool,
+}
+
+fn default_fallback_attempt_timeout_secs() -> u64 {
+    15
+}
+
+impl Default for LlmFallbackConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            attempt_timeout_secs: default_fallback_attempt_timeout_secs(),
+            fast_first: false,
+        }
+    }
 }
 
 fn default_llm_endpoint() -> String {
@@ -484,11 +1212,13 @@ fn default_presets() -> Vec<LlmPreset> {
             id: "polishing".to_string(),
             name: "文本润色".to_string(),
             system_prompt: "你是一个语音转写润色助手。请在不改变原意的前提下：1）删除重复或意义相近的句子；2）合并同一主题的内容；3）去除「嗯」「啊」等口头禅；4）保留数字与关键信息；5）相关数字和时间不要使用中文；6）整理成自然的段落。输出纯文本即可。".to_string(),
+            bypass_cache: false,
         },
         LlmPreset {
             id: "translation".to_string(),
             name: "中译英".to_string(),
             system_prompt: "你是一个专业的翻译助手。请将用户的中文语音转写内容翻译成地道、流畅的英文。不要输出任何解释性文字，只输出翻译结果。".to_string(),
+            bypass_cache: false,
         }
     ]
 }
@@ -568,6 +1298,9 @@ pub struct AssistantConfig {
     /// 是否启用 AI 助手模式
     #[serde(default)]
     pub enabled: bool,
+    /// 聊天协议
+    #[serde(default)]
+    pub protocol: ChatProtocol,
     /// API 端点
     #[serde(default = "default_assistant_endpoint")]
     pub endpoint: String,
@@ -583,6 +1316,76 @@ pub struct AssistantConfig {
     /// 文本处理模式系统提示词（有选中文本时使用）
     #[serde(default = "default_assistant_text_processing_prompt")]
     pub text_processing_system_prompt: String,
+    /// 多轮对话记忆
+    #[serde(default)]
+    pub conversation: ConversationConfig,
+    /// 工具调用
+    #[serde(default)]
+    pub tools: AssistantToolsConfig,
+    /// 备用端点（主端点失败时依次尝试）
+    #[serde(default)]
+    pub fallback: LlmFallbackConfig,
+    /// 响应缓存（默认关闭：问答结果可能随时间变化，只在没有对话历史和工具调用时生效）
+    #[serde(default = "default_assistant_cache")]
+    pub cache: LlmCacheConfig,
+}
+
+fn default_assistant_cache() -> LlmCacheConfig {
+    LlmCacheConfig { enabled: false, ..Default::default() }
+}
+
+/// AI 助手工具调用配置
+///
+/// 启用后模型可以执行内置工具（打开网址、搜索历史、切换润色预设等），
+/// 例如“把润色预设切换到邮件模式”会直接切换预设而不是插入文本。
+/// 需要服务支持 OpenAI 格式的 tools 参数
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssistantToolsConfig {
+    /// 是否启用工具调用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 允许执行的命令白名单（完整命令行），执行前需要在悬浮窗确认
+    #[serde(default)]
+    pub allowed_commands: Vec<String>,
+}
+
+/// AI 助手多轮对话配置
+///
+/// 保留最近几轮问答（含选中文本上下文），可以接着说“再短一点”；
+/// 空闲超时或按重置快捷键后开始新对话
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversationConfig {
+    /// 是否启用多轮对话
+    #[serde(default = "default_conversation_enabled")]
+    pub enabled: bool,
+    /// 空闲超时（秒），超过后清空历史
+    #[serde(default = "default_conversation_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+    /// 历史消息的 token 预算（估算值），每次请求前从最早的一轮开始丢弃
+    #[serde(default = "default_conversation_max_history_tokens")]
+    pub max_history_tokens: usize,
+}
+
+fn default_conversation_enabled() -> bool {
+    true
+}
+
+fn default_conversation_idle_timeout_secs() -> u64 {
+    300
+}
+
+fn default_conversation_max_history_tokens() -> usize {
+    2000
+}
+
+impl Default for ConversationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_timeout_secs: default_conversation_idle_timeout_secs(),
+            max_history_tokens: default_conversation_max_history_tokens(),
+        }
+    }
 }
 
 fn default_smart_command_endpoint() -> String {
@@ -629,11 +1432,16 @@ impl Default for AssistantConfig {
     fn default() -> Self {
         Self {
             enabled: false,
+            protocol: ChatProtocol::default(),
             endpoint: default_assistant_endpoint(),
             model: default_assistant_model(),
             api_key: String::new(),
             qa_system_prompt: default_assistant_qa_prompt(),
             text_processing_system_prompt: default_assistant_text_processing_prompt(),
+            conversation: ConversationConfig::default(),
+            tools: AssistantToolsConfig::default(),
+            fallback: LlmFallbackConfig::default(),
+            cache: default_assistant_cache(),
         }
     }
 }
@@ -646,9 +1454,38 @@ impl SmartCommandConfig {
 }
 
 impl AssistantConfig {
-    /// 检查配置是否有效（API Key 已填写）
+    /// 检查配置是否有效（API Key 已填写，Ollama 可以不填）
     pub fn is_valid(&self) -> bool {
-        !self.api_key.is_empty() && !self.endpoint.is_empty() && !self.model.is_empty()
+        self.primary_endpoint().is_valid()
+    }
+
+    /// 主端点
+    pub fn primary_endpoint(&self) -> LlmEndpoint {
+        LlmEndpoint {
+            name: String::new(),
+            protocol: self.protocol,
+            endpoint: self.endpoint.clone(),
+            model: self.model.clone(),
+            api_key: self.api_key.clone(),
+        }
+    }
+}
+
+impl LlmConfig {
+    /// 检查配置是否有效（API Key 已填写，Ollama 可以不填）
+    pub fn is_valid(&self) -> bool {
+        self.primary_endpoint().is_valid()
+    }
+
+    /// 主端点
+    pub fn primary_endpoint(&self) -> LlmEndpoint {
+        LlmEndpoint {
+            name: String::new(),
+            protocol: self.protocol,
+            endpoint: self.endpoint.clone(),
+            model: self.model.clone(),
+            api_key: self.api_key.clone(),
+        }
     }
 }
 
@@ -656,11 +1493,14 @@ impl AssistantConfig {
 impl Default for LlmConfig {
     fn default() -> Self {
         Self {
+            protocol: ChatProtocol::default(),
             endpoint: default_llm_endpoint(),
             model: default_llm_model(),
             api_key: String::new(),
             presets: default_presets(),
             active_preset_id: default_active_preset_id(),
+            fallback: LlmFallbackConfig::default(),
+            cache: LlmCacheConfig::default(),
         }
     }
 }
@@ -685,7 +1525,16 @@ impl AppConfig {
             dual_hotkey_config: DualHotkeyConfig::default(),
             transcription_mode: TranscriptionMode::default(),
             enable_mute_other_apps: false,
+            mute_other_apps_config: MuteOtherAppsConfig::default(),
             dictionary: Vec::new(),
+            pre_roll_config: PreRollConfig::default(),
+            audio_archive_config: AudioArchiveConfig::default(),
+            auto_stop_config: AutoStopConfig::default(),
+            recording_limits_config: RecordingLimitsConfig::default(),
+            audio_cue_config: AudioCueConfig::default(),
+            insertion_config: InsertionConfig::default(),
+            output_config: OutputConfig::default(),
+            app_profiles: Vec::new(),
         }
     }
 
@@ -766,11 +1615,16 @@ impl AppConfig {
                     tracing::info!("迁移 Smart Command 配置到 AI 助手配置");
                     config.assistant_config = AssistantConfig {
                         enabled: config.smart_command_config.enabled,
+                        protocol: ChatProtocol::OpenAi,
                         endpoint: config.smart_command_config.endpoint.clone(),
                         model: config.smart_command_config.model.clone(),
                         api_key: config.smart_command_config.api_key.clone(),
                         qa_system_prompt: config.smart_command_config.system_prompt.clone(),
                         text_processing_system_prompt: default_assistant_text_processing_prompt(),
+                        conversation: ConversationConfig::default(),
+                        tools: AssistantToolsConfig::default(),
+                        fallback: LlmFallbackConfig::default(),
+                        cache: default_assistant_cache(),
                     };
                     config.smart_command_config.enabled = false;
                 }
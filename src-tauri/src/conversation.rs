@@ -0,0 +1,152 @@
+// AI 助手多轮对话
+//
+// 保存最近的问答轮次（用户消息中包含选中文本上下文），下一次请求时放在 system 与本次用户消息之间；
+// 空闲超时后自动清空，每次请求前按 token 预算从最早的一轮开始丢弃
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::config::ConversationConfig;
+use crate::openai_client::Message;
+
+/// 一轮问答
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Turn {
+    user: String,
+    assistant: String,
+    tokens: usize,
+}
+
+/// 对话会话
+#[derive(Debug)]
+pub struct ConversationSession {
+    config: ConversationConfig,
+    turns: VecDeque<Turn>,
+    last_active: Option<Instant>,
+}
+
+impl ConversationSession {
+    pub fn new(config: ConversationConfig) -> Self {
+        Self { config, turns: VecDeque::new(), last_active: None }
+    }
+
+    /// 本次请求可用的历史消息（user / assistant 交替）
+    pub fn history(&mut self) -> Vec<Message> {
+        self.history_at(Instant::now())
+    }
+
+    /// 记录一轮完成的问答
+    pub fn record(&mut self, user: &str, assistant: &str) {
+        self.record_at(user, assistant, Instant::now());
+    }
+
+    /// 清空历史，开始新对话
+    pub fn reset(&mut self) {
+        if !self.turns.is_empty() {
+            tracing::info!("AI 助手对话已重置 (丢弃 {} 轮)", self.turns.len());
+        }
+        self.turns.clear();
+        self.last_active = None;
+    }
+
+    fn history_at(&mut self, now: Instant) -> Vec<Message> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+        let idle_timeout = Duration::from_secs(self.config.idle_timeout_secs);
+        if self.last_active.is_some_and(|last| now.duration_since(last) > idle_timeout) {
+            tracing::info!("AI 助手对话空闲超时，开始新对话");
+            self.reset();
+        }
+
+        // 按 token 预算丢弃最早的轮次
+        let mut total: usize = self.turns.iter().map(|t| t.tokens).sum();
+        while total > self.config.max_history_tokens {
+            let Some(oldest) = self.turns.pop_front() else { break };
+            total -= oldest.tokens;
+        }
+
+        self.turns
+            .iter()
+            .flat_map(|t| [Message::user(t.user.clone()), Message::assistant(t.assistant.clone())])
+            .collect()
+    }
+
+    fn record_at(&mut self, user: &str, assistant: &str, now: Instant) {
+        if !self.config.enabled || assistant.is_empty() {
+            return;
+        }
+        self.turns.push_back(Turn {
+            user: user.to_string(),
+            assistant: assistant.to_string(),
+            tokens: estimate_tokens(user) + estimate_tokens(assistant),
+        });
+        self.last_active = Some(now);
+    }
+}
+
+/// 粗略估算 token 数：中日韩字符按 1 个 token，其余按 4 个字符 1 个 token
+fn estimate_tokens(text: &str) -> usize {
+    let (cjk, other) = text.chars().fold((0, 0), |(cjk, other), c| {
+        if is_cjk(c) {
+            (cjk + 1, other)
+        } else {
+            (cjk, other + 1)
+        }
+    });
+    cjk + other.div_ceil(4)
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3000}'..='\u{30FF}' // 标点、假名
+        | '\u{3400}'..='\u{9FFF}' // 汉字
+        | '\u{AC00}'..='\u{D7AF}' // 韩文
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF00}'..='\u{FFEF}' // 全角符号
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(max_history_tokens: usize) -> ConversationSession {
+        ConversationSession::new(ConversationConfig { enabled: true, idle_timeout_secs: 60, max_history_tokens })
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens("你好，世界"), 5);
+        assert_eq!(estimate_tokens("make it shorter"), 4);
+        assert_eq!(estimate_tokens(""), 0);
+    }
+
+    #[test]
+    fn test_history_trimmed_to_budget() {
+        let mut session = session(8);
+        let now = Instant::now();
+        session.record_at("第一问", "第一答", now);
+        session.record_at("第二问", "第二答", now);
+        let history = session.history_at(now);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].content, "第二问");
+        assert_eq!(history[1].content, "第二答");
+    }
+
+    #[test]
+    fn test_history_expires_after_idle() {
+        let mut session = session(2000);
+        let now = Instant::now();
+        session.record_at("问", "答", now);
+        assert_eq!(session.history_at(now + Duration::from_secs(30)).len(), 2);
+        assert!(session.history_at(now + Duration::from_secs(61)).is_empty());
+    }
+
+    #[test]
+    fn test_disabled_session_keeps_nothing() {
+        let mut session = ConversationSession::new(ConversationConfig { enabled: false, ..Default::default() });
+        session.record("问", "答");
+        assert!(session.history().is_empty());
+    }
+}
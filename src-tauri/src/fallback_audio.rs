@@ -0,0 +1,131 @@
+// 备用音频存储模块
+// 流式录音期间保留一份完整音频用于备用方案（实时 ASR 失败时走 HTTP 转写）
+// 以 16kHz 单声道 i16 保存，超过阈值后溢写到临时文件，避免长录音占用大量内存
+
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::PathBuf;
+
+/// 溢写到临时文件的 PCM 数据（i16 小端）
+struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    samples: usize,
+}
+
+/// 备用音频缓冲
+///
+/// 内存中的样本超过 spill_threshold 时整体追加到临时文件并清空内存，
+/// 临时文件在 clear 或 Drop 时删除
+pub struct FallbackAudio {
+    memory: Vec<i16>,
+    spill: Option<SpillFile>,
+    spill_threshold: usize,
+}
+
+fn f32_to_i16(sample: f32) -> i16 {
+    (sample * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+impl FallbackAudio {
+    /// spill_threshold 为内存中最多保留的样本数，0 表示从不溢写
+    pub fn new(spill_threshold: usize) -> Self {
+        Self {
+            memory: Vec::new(),
+            spill: None,
+            spill_threshold,
+        }
+    }
+
+    /// 追加 16kHz 单声道样本
+    pub fn push(&mut self, samples: &[f32]) {
+        self.memory.extend(samples.iter().map(|&s| f32_to_i16(s)));
+        if self.spill_threshold > 0 && self.memory.len() >= self.spill_threshold {
+            if let Err(e) = self.spill_memory() {
+                // 溢写失败时继续保留在内存中，不影响录音
+                tracing::warn!("备用音频溢写临时文件失败，继续使用内存: {}", e);
+                self.spill_threshold = 0;
+            }
+        }
+    }
+
+    /// 总样本数（内存 + 临时文件）
+    fn len(&self) -> usize {
+        self.memory.len() + self.spill.as_ref().map_or(0, |s| s.samples)
+    }
+
+    fn spill_memory(&mut self) -> Result<()> {
+        if self.spill.is_none() {
+            let path = std::env::temp_dir().join(format!("ptt_stream_{}.pcm", uuid::Uuid::new_v4()));
+            let writer = BufWriter::new(File::create(&path)?);
+            tracing::info!("备用音频超过内存阈值，溢写到临时文件: {:?}", path);
+            self.spill = Some(SpillFile { path, writer, samples: 0 });
+        }
+        let spill = self.spill.as_mut().unwrap();
+        let bytes: Vec<u8> = self.memory.iter().flat_map(|s| s.to_le_bytes()).collect();
+        spill.writer.write_all(&bytes)?;
+        spill.samples += self.memory.len();
+        self.memory.clear();
+        Ok(())
+    }
+
+    /// 读取全部样本（临时文件在前，内存在后）
+    pub fn read_all(&mut self) -> Result<Vec<i16>> {
+        let mut samples = Vec::with_capacity(self.len());
+        if let Some(ref mut spill) = self.spill {
+            spill.writer.flush()?;
+            let mut bytes = Vec::with_capacity(spill.samples * 2);
+            File::open(&spill.path)?.read_to_end(&mut bytes)?;
+            samples.extend(bytes.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])));
+        }
+        samples.extend_from_slice(&self.memory);
+        Ok(samples)
+    }
+
+    /// 清空数据并删除临时文件
+    pub fn clear(&mut self) {
+        self.memory.clear();
+        self.memory.shrink_to_fit();
+        if let Some(spill) = self.spill.take() {
+            drop(spill.writer);
+            let _ = std::fs::remove_file(&spill.path);
+        }
+    }
+}
+
+impl Drop for FallbackAudio {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_and_spill() {
+        let mut audio = FallbackAudio::new(4);
+        audio.push(&[0.5; 3]);
+        assert!(audio.spill.is_none());
+
+        audio.push(&[-0.5; 3]);
+        assert!(audio.spill.is_some());
+        assert!(audio.memory.is_empty());
+
+        audio.push(&[1.0]);
+        assert_eq!(audio.len(), 7);
+
+        let samples = audio.read_all().unwrap();
+        assert_eq!(samples.len(), 7);
+        assert_eq!(samples[0], f32_to_i16(0.5));
+        assert_eq!(samples[3], f32_to_i16(-0.5));
+        assert_eq!(samples[6], i16::MAX);
+
+        let path = audio.spill.as_ref().unwrap().path.clone();
+        audio.clear();
+        assert!(!path.exists());
+        assert_eq!(audio.len(), 0);
+    }
+}
@@ -0,0 +1,445 @@
+// 音频文件转写模块
+// 支持 WAV / MP3 / FLAC / OGG / M4A，解码后统一转换为 16kHz 单声道 WAV，
+// 复用录音流程的 ASR 竞速策略和 LLM 预设
+
+use anyhow::Result;
+use hound::{WavSpec, WavWriter};
+use serde::Serialize;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::asr::{DoubaoASRClient, QwenASRClient, SenseVoiceClient};
+use crate::config::{AppConfig, AsrConfig};
+use crate::llm_post_processor::LlmPostProcessor;
+
+// API 要求的目标采样率
+const TARGET_SAMPLE_RATE: u32 = 16000;
+
+/// 支持的音频文件扩展名
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["wav", "mp3", "flac", "ogg", "m4a"];
+
+/// 文件转写结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FileTranscriptionResult {
+    /// 最终文本（经过 LLM 处理时为处理后的文本）
+    pub text: String,
+    /// ASR 原始文本（仅在经过 LLM 处理时存在）
+    pub original_text: Option<String>,
+    /// 音频时长（毫秒）
+    pub audio_duration_ms: u64,
+    pub asr_time_ms: u64,
+    pub llm_time_ms: Option<u64>,
+    /// 结果保存路径（选择保存到文件时存在）
+    pub output_path: Option<String>,
+}
+
+/// 检查文件扩展名是否受支持
+fn check_extension(path: &Path) -> Result<()> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    if SUPPORTED_EXTENSIONS.contains(&ext.as_str()) {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "不支持的音频格式: {:?}（支持: {}）",
+            path.extension().unwrap_or_default(),
+            SUPPORTED_EXTENSIONS.join(", ")
+        ))
+    }
+}
+
+/// 解码音频文件为交错的 f32 样本
+///
+/// 返回 (样本, 采样率, 声道数)
+fn decode_file(path: &Path) -> Result<(Vec<f32>, u32, usize)> {
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+    use symphonia::core::errors::Error as SymphoniaError;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    let file = std::fs::File::open(path)
+        .map_err(|e| anyhow::anyhow!("无法打开音频文件 {:?}: {}", path, e))?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| anyhow::anyhow!("无法识别音频格式: {}", e))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| anyhow::anyhow!("文件中没有可解码的音轨"))?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(0);
+    let mut channels = track.codec_params.channels.map(|c| c.count()).unwrap_or(0);
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| anyhow::anyhow!("不支持的音频编码: {}", e))?;
+
+    let mut samples: Vec<f32> = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            // 读到文件末尾
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(e) => return Err(anyhow::anyhow!("读取音频数据失败: {}", e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        match decoder.decode(&packet) {
+            Ok(decoded) => {
+                let spec = *decoded.spec();
+                sample_rate = spec.rate;
+                channels = spec.channels.count();
+                let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                buffer.copy_interleaved_ref(decoded);
+                samples.extend_from_slice(buffer.samples());
+            }
+            // 个别损坏的数据包跳过即可
+            Err(SymphoniaError::DecodeError(e)) => {
+                tracing::warn!("跳过损坏的音频包: {}", e);
+            }
+            Err(e) => return Err(anyhow::anyhow!("音频解码失败: {}", e)),
+        }
+    }
+
+    if samples.is_empty() || sample_rate == 0 || channels == 0 {
+        return Err(anyhow::anyhow!("音频文件为空或无法解码"));
+    }
+
+    Ok((samples, sample_rate, channels))
+}
+
+/// 将多声道音频转换为单声道
+fn to_mono(input: &[f32], channels: usize) -> Vec<f32> {
+    if channels == 1 {
+        return input.to_vec();
+    }
+
+    input
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// 线性插值重采样到目标采样率（与录音器一致）
+fn resample(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || input.is_empty() {
+        return input.to_vec();
+    }
+
+    let ratio = from_rate as f64 / to_rate as f64;
+    let output_len = (input.len() as f64 / ratio) as usize;
+    let mut output = Vec::with_capacity(output_len);
+
+    for i in 0..output_len {
+        let src_idx = i as f64 * ratio;
+        let idx_floor = src_idx.floor() as usize;
+        let idx_ceil = (idx_floor + 1).min(input.len() - 1);
+        let frac = src_idx - idx_floor as f64;
+
+        let sample = input[idx_floor] as f64 * (1.0 - frac) + input[idx_ceil] as f64 * frac;
+        output.push(sample as f32);
+    }
+
+    output
+}
+
+/// 将 16kHz 单声道样本写入内存 WAV
+fn encode_wav(samples: &[f32]) -> Result<Vec<u8>> {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: TARGET_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut cursor = Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new(&mut cursor, spec)?;
+        for &sample in samples {
+            let amplitude = (sample * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+            writer.write_sample(amplitude)?;
+        }
+        writer.finalize()?;
+    }
+    Ok(cursor.into_inner())
+}
+
+/// 读取音频文件并转换为 ASR 所需的 16kHz 单声道 WAV
+///
+/// 返回 (WAV 字节, 音频时长毫秒)
+pub fn load_as_asr_wav(path: &Path) -> Result<(Vec<u8>, u64)> {
+    check_extension(path)?;
+
+    let (samples, sample_rate, channels) = decode_file(path)?;
+    tracing::info!("音频文件解码完成: {:?}, 采样率={}Hz, 声道={}, 样本={}",
+        path, sample_rate, channels, samples.len());
+
+    let mono = to_mono(&samples, channels);
+    let resampled = resample(&mono, sample_rate, TARGET_SAMPLE_RATE);
+    let duration_ms = resampled.len() as u64 * 1000 / TARGET_SAMPLE_RATE as u64;
+
+    Ok((encode_wav(&resampled)?, duration_ms))
+}
+
+/// 根据 ASR 配置创建所有有凭证的 HTTP 客户端
+pub fn build_asr_clients(
+    cfg: &AsrConfig,
+    dictionary: &[String],
+) -> (Option<QwenASRClient>, Option<DoubaoASRClient>, Option<SenseVoiceClient>) {
+    let credentials = &cfg.credentials;
+    let qwen = (!credentials.qwen_api_key.is_empty())
+        .then(|| QwenASRClient::new(credentials.qwen_api_key.clone(), dictionary.to_vec()));
+    let doubao = (!credentials.doubao_app_id.is_empty() && !credentials.doubao_access_token.is_empty())
+        .then(|| DoubaoASRClient::new(
+            credentials.doubao_app_id.clone(),
+            credentials.doubao_access_token.clone(),
+            dictionary.to_vec(),
+        ));
+    let sensevoice = (!credentials.sensevoice_api_key.is_empty())
+        .then(|| SenseVoiceClient::new(credentials.sensevoice_api_key.clone()));
+    (qwen, doubao, sensevoice)
+}
+
+/// 按预设 ID 创建 LLM 处理器
+pub fn build_preset_processor(config: &AppConfig, preset_id: &str) -> Result<LlmPostProcessor> {
+    if !config.llm_config.presets.iter().any(|p| p.id == preset_id) {
+        return Err(anyhow::anyhow!("LLM 预设不存在: {}", preset_id));
+    }
+    if !config.llm_config.is_valid() {
+        return Err(anyhow::anyhow!("LLM 配置不完整（API Key、端点或模型）"));
+    }
+    let mut llm_config = config.llm_config.clone();
+    llm_config.active_preset_id = preset_id.to_string();
+    Ok(LlmPostProcessor::new(llm_config))
+}
+
+/// 转写音频文件
+///
+/// # Arguments
+/// * `config` - 应用配置（ASR 凭证、竞速策略、LLM 预设）
+/// * `path` - 音频文件路径
+/// * `preset_id` - 可选的 LLM 预设 ID，为 None 时只做 ASR
+/// * `save_to_file` - 是否将结果保存到音频文件旁的同名 .txt
+pub async fn transcribe_file(
+    config: &AppConfig,
+    path: &Path,
+    preset_id: Option<&str>,
+    save_to_file: bool,
+) -> Result<FileTranscriptionResult> {
+    // 先校验预设，避免 ASR 完成后才发现配置错误
+    let processor = preset_id
+        .map(|id| build_preset_processor(config, id))
+        .transpose()?;
+
+    let path_buf = path.to_path_buf();
+    let (wav_data, audio_duration_ms) =
+        tokio::task::spawn_blocking(move || load_as_asr_wav(&path_buf)).await??;
+
+    let (qwen, doubao, sensevoice) = build_asr_clients(&config.asr_config, &config.dictionary);
+    let selection = &config.asr_config.selection;
+
+    let asr_start = Instant::now();
+    let asr_text = crate::transcribe_with_available_clients(
+        qwen,
+        doubao,
+        sensevoice,
+        &wav_data,
+        selection.enable_fallback,
+        Some(selection.active_provider.clone()),
+        selection.fallback_provider.clone(),
+        "[文件转写] ",
+    )
+    .await?;
+    let asr_time_ms = asr_start.elapsed().as_millis() as u64;
+    tracing::info!("[文件转写] ASR 完成: {} 字, 耗时 {}ms", asr_text.chars().count(), asr_time_ms);
+
+    let (text, original_text, llm_time_ms) = match processor {
+        Some(processor) => {
+            let llm_start = Instant::now();
+            let polished = processor.polish_transcript(&asr_text).await?;
+            (polished, Some(asr_text), Some(llm_start.elapsed().as_millis() as u64))
+        }
+        None => (asr_text, None, None),
+    };
+
+    let output_path = if save_to_file {
+        let output = output_path_for(path);
+        tokio::fs::write(&output, &text).await
+            .map_err(|e| anyhow::anyhow!("保存转写结果失败 {:?}: {}", output, e))?;
+        tracing::info!("[文件转写] 结果已保存到: {:?}", output);
+        Some(output.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    Ok(FileTranscriptionResult {
+        text,
+        original_text,
+        audio_duration_ms,
+        asr_time_ms,
+        llm_time_ms,
+        output_path,
+    })
+}
+
+/// 结果保存路径：音频文件旁的同名 .txt
+fn output_path_for(path: &Path) -> PathBuf {
+    path.with_extension("txt")
+}
+
+const CLI_USAGE: &str = "用法: push-to-talk transcribe <音频文件>... [--preset <预设ID>] [--save]";
+
+/// 命令行入口：`push-to-talk transcribe <音频文件>... [--preset <预设ID>] [--save]`
+///
+/// 使用已保存的配置，结果输出到 stdout（--save 时同时写入同名 .txt），返回进程退出码
+pub fn run_cli(args: &[String]) -> i32 {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut preset_id: Option<String> = None;
+    let mut save_to_file = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--preset" => match iter.next() {
+                Some(id) => preset_id = Some(id.clone()),
+                None => {
+                    eprintln!("--preset 需要预设 ID\n{}", CLI_USAGE);
+                    return 2;
+                }
+            },
+            "--save" => save_to_file = true,
+            "-h" | "--help" => {
+                println!("{}", CLI_USAGE);
+                return 0;
+            }
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+
+    if files.is_empty() {
+        eprintln!("{}", CLI_USAGE);
+        return 2;
+    }
+
+    let config = match AppConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("加载配置失败: {}", e);
+            return 1;
+        }
+    };
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("创建异步运行时失败: {}", e);
+            return 1;
+        }
+    };
+
+    let mut exit_code = 0;
+    for file in &files {
+        match runtime.block_on(transcribe_file(&config, file, preset_id.as_deref(), save_to_file)) {
+            Ok(result) => {
+                if files.len() > 1 {
+                    println!("==> {}", file.display());
+                }
+                println!("{}", result.text);
+                if let Some(output) = result.output_path {
+                    eprintln!("已保存到: {}", output);
+                }
+            }
+            Err(e) => {
+                eprintln!("转写失败 {}: {}", file.display(), e);
+                exit_code = 1;
+            }
+        }
+    }
+    exit_code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_extension() {
+        assert!(check_extension(Path::new("a.wav")).is_ok());
+        assert!(check_extension(Path::new("b.M4A")).is_ok());
+        assert!(check_extension(Path::new("c.txt")).is_err());
+        assert!(check_extension(Path::new("noext")).is_err());
+    }
+
+    #[test]
+    fn test_to_mono_and_resample() {
+        let stereo = vec![1.0, 0.0, 0.5, 0.5];
+        assert_eq!(to_mono(&stereo, 2), vec![0.5, 0.5]);
+
+        let input: Vec<f32> = (0..48000).map(|i| i as f32).collect();
+        assert_eq!(resample(&input, 48000, 16000).len(), 16000);
+    }
+
+    #[test]
+    fn test_wav_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("ptt_file_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("stereo.wav");
+
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: 48000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(&path, spec).unwrap();
+        for i in 0..48000 {
+            let s = ((i as f32 * 0.05).sin() * 10000.0) as i16;
+            writer.write_sample(s).unwrap();
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let (wav, duration_ms) = load_as_asr_wav(&path).unwrap();
+        let reader = hound::WavReader::new(Cursor::new(wav)).unwrap();
+        assert_eq!(reader.spec().channels, 1);
+        assert_eq!(reader.spec().sample_rate, TARGET_SAMPLE_RATE);
+        assert_eq!(duration_ms, 1000);
+        assert_eq!(output_path_for(&path), dir.join("stereo.txt"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_build_preset_processor_allows_keyless_ollama() {
+        let mut config = AppConfig::new();
+        let preset_id = config.llm_config.presets[0].id.clone();
+        assert!(build_preset_processor(&config, &preset_id).is_err());
+        assert!(build_preset_processor(&config, "missing").is_err());
+
+        config.llm_config.protocol = crate::config::ChatProtocol::Ollama;
+        config.llm_config.endpoint = "http://localhost:11434/api/chat".to_string();
+        config.llm_config.model = "qwen2.5".to_string();
+        assert!(config.llm_config.api_key.is_empty());
+        assert!(build_preset_processor(&config, &preset_id).is_ok());
+    }
+}
@@ -153,6 +153,9 @@ struct HotkeyState {
 /// 第二个参数：bool - 是否为松手模式（true=松手模式，false=普通模式）
 type Callback = Arc<dyn Fn(TriggerMode, bool) + Send + Sync>;
 
+/// 不触发录音的快捷键回调（重置对话、撤销插入）
+type ActionCallback = Arc<dyn Fn() + Send + Sync>;
+
 /// 单例热键服务（支持双模式）
 pub struct HotkeyService {
     /// 服务是否激活（控制是否响应热键事件）
@@ -161,6 +164,10 @@ pub struct HotkeyService {
     dictation_config: Arc<RwLock<HotkeyConfig>>,
     /// AI助手模式快捷键配置
     assistant_config: Arc<RwLock<HotkeyConfig>>,
+    /// 重置 AI 助手对话的快捷键（可选）
+    conversation_reset_keys: Arc<RwLock<Option<Vec<HotkeyKey>>>>,
+    /// 撤销上次插入的快捷键（可选）
+    undo_insertion_keys: Arc<RwLock<Option<Vec<HotkeyKey>>>>,
     /// 内部状态
     state: Arc<Mutex<HotkeyState>>,
     /// 监听线程是否已启动
@@ -168,6 +175,8 @@ pub struct HotkeyService {
     /// 回调函数（现在接收 TriggerMode 参数）
     on_start: Arc<RwLock<Option<Callback>>>,
     on_stop: Arc<RwLock<Option<Callback>>>,
+    on_conversation_reset: Arc<RwLock<Option<ActionCallback>>>,
+    on_undo_insertion: Arc<RwLock<Option<ActionCallback>>>,
 }
 
 impl HotkeyService {
@@ -181,10 +190,14 @@ impl HotkeyService {
                 enable_release_lock: false,
                 release_mode_keys: None,  // AI助手模式不支持松手模式
             })),
+            conversation_reset_keys: Arc::new(RwLock::new(None)),
+            undo_insertion_keys: Arc::new(RwLock::new(None)),
             state: Arc::new(Mutex::new(HotkeyState::default())),
             listener_started: Arc::new(AtomicBool::new(false)),
             on_start: Arc::new(RwLock::new(None)),
             on_stop: Arc::new(RwLock::new(None)),
+            on_conversation_reset: Arc::new(RwLock::new(None)),
+            on_undo_insertion: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -290,9 +303,13 @@ impl HotkeyService {
         let is_active = Arc::clone(&self.is_active);
         let dictation_config = Arc::clone(&self.dictation_config);
         let assistant_config = Arc::clone(&self.assistant_config);
+        let conversation_reset_keys = Arc::clone(&self.conversation_reset_keys);
+        let undo_insertion_keys = Arc::clone(&self.undo_insertion_keys);
         let state = Arc::clone(&self.state);
         let on_start = Arc::clone(&self.on_start);
         let on_stop = Arc::clone(&self.on_stop);
+        let on_conversation_reset = Arc::clone(&self.on_conversation_reset);
+        let on_undo_insertion = Arc::clone(&self.on_undo_insertion);
 
         thread::spawn(move || {
             tracing::info!("快捷键监听线程已启动");
@@ -310,6 +327,8 @@ impl HotkeyService {
                 let mut prev_dictation_down = false;
                 let mut prev_assistant_down = false;
                 let mut prev_release_down = false;
+                let mut prev_reset_down = false;
+                let mut prev_undo_down = false;
 
                 loop {
                     thread::sleep(Duration::from_millis(HOTKEY_POLL_INTERVAL_MS));
@@ -324,12 +343,26 @@ impl HotkeyService {
                         .as_deref()
                         .map(is_hotkey_pressed_strict)
                         .unwrap_or(false);
+                    let reset_down = conversation_reset_keys
+                        .read()
+                        .unwrap()
+                        .as_deref()
+                        .map(is_hotkey_pressed_strict)
+                        .unwrap_or(false);
+                    let undo_down = undo_insertion_keys
+                        .read()
+                        .unwrap()
+                        .as_deref()
+                        .map(is_hotkey_pressed_strict)
+                        .unwrap_or(false);
 
                     // 未激活时：同步边沿状态，避免激活瞬间误触发
                     if !is_active.load(Ordering::Relaxed) {
                         prev_dictation_down = dictation_down;
                         prev_assistant_down = assistant_down;
                         prev_release_down = release_down;
+                        prev_reset_down = reset_down;
+                        prev_undo_down = undo_down;
                         continue;
                     }
 
@@ -338,6 +371,8 @@ impl HotkeyService {
                     let assistant_rise = assistant_down && !prev_assistant_down;
                     let assistant_fall = !assistant_down && prev_assistant_down;
                     let release_rise = release_down && !prev_release_down;
+                    let reset_rise = reset_down && !prev_reset_down;
+                    let undo_rise = undo_down && !prev_undo_down;
 
                     // 更新 pressed_keys（仅用于调试信息）
                     {
@@ -367,6 +402,8 @@ impl HotkeyService {
 
                     let mut start_action: Option<(TriggerMode, bool)> = None;
                     let mut stop_action: Option<(TriggerMode, bool)> = None;
+                    let mut reset_action = false;
+                    let mut undo_action = false;
 
                     {
                         let mut s = state.lock().unwrap();
@@ -410,6 +447,12 @@ impl HotkeyService {
                                 s.is_release_mode_triggered = false;
                                 s.watchdog_running = false;
                                 start_action = Some((TriggerMode::AiAssistant, false));
+                            } else if reset_rise {
+                                tracing::info!("检测到快捷键按下: 重置 AI 助手对话");
+                                reset_action = true;
+                            } else if undo_rise {
+                                tracing::info!("检测到快捷键按下: 撤销上次插入");
+                                undo_action = true;
                             }
                         } else if !s.is_release_mode_triggered {
                             // 录音中：根据当前触发模式处理停止逻辑（Press=松手停止；Toggle=再次按下停止）
@@ -469,10 +512,22 @@ impl HotkeyService {
                             cb(mode, is_release_mode);
                         }
                     }
+                    if reset_action {
+                        if let Some(cb) = on_conversation_reset.read().unwrap().as_ref() {
+                            cb();
+                        }
+                    }
+                    if undo_action {
+                        if let Some(cb) = on_undo_insertion.read().unwrap().as_ref() {
+                            cb();
+                        }
+                    }
 
                     prev_dictation_down = dictation_down;
                     prev_assistant_down = assistant_down;
                     prev_release_down = release_down;
+                    prev_reset_down = reset_down;
+                    prev_undo_down = undo_down;
                 }
             }
 
@@ -485,9 +540,13 @@ impl HotkeyService {
                 let is_active_inner = Arc::clone(&is_active);
                 let dictation_config_inner = Arc::clone(&dictation_config);
                 let assistant_config_inner = Arc::clone(&assistant_config);
+                let conversation_reset_keys_inner = Arc::clone(&conversation_reset_keys);
+                let undo_insertion_keys_inner = Arc::clone(&undo_insertion_keys);
                 let state_inner = Arc::clone(&state);
                 let on_start_inner = Arc::clone(&on_start);
                 let on_stop_inner = Arc::clone(&on_stop);
+                let on_conversation_reset_inner = Arc::clone(&on_conversation_reset);
+                let on_undo_insertion_inner = Arc::clone(&on_undo_insertion);
 
                 let callback = move |event: Event| {
                     // 检查服务是否激活
@@ -570,6 +629,32 @@ impl HotkeyService {
                                         (None, false)
                                     };
 
+                                    // 重置对话快捷键（不触发录音）
+                                    let matches_reset = conversation_reset_keys_inner.read().unwrap().as_ref().is_some_and(|keys| {
+                                        keys.iter().all(|k| s.pressed_keys.contains(k)) && s.pressed_keys.len() == keys.len()
+                                    });
+                                    if trigger_mode.is_none() && matches_reset {
+                                        drop(s);
+                                        tracing::info!("检测到快捷键按下: 重置 AI 助手对话");
+                                        if let Some(cb) = on_conversation_reset_inner.read().unwrap().as_ref() {
+                                            cb();
+                                        }
+                                        return;
+                                    }
+
+                                    // 撤销插入快捷键（不触发录音）
+                                    let matches_undo = undo_insertion_keys_inner.read().unwrap().as_ref().is_some_and(|keys| {
+                                        keys.iter().all(|k| s.pressed_keys.contains(k)) && s.pressed_keys.len() == keys.len()
+                                    });
+                                    if trigger_mode.is_none() && matches_undo {
+                                        drop(s);
+                                        tracing::info!("检测到快捷键按下: 撤销上次插入");
+                                        if let Some(cb) = on_undo_insertion_inner.read().unwrap().as_ref() {
+                                            cb();
+                                        }
+                                        return;
+                                    }
+
                                     if let Some(mode) = trigger_mode {
                                         s.is_recording = true;
                                         s.current_trigger_mode = Some(mode);
@@ -819,6 +904,8 @@ impl HotkeyService {
         // 更新配置
         *self.dictation_config.write().unwrap() = config.dictation;
         *self.assistant_config.write().unwrap() = config.assistant;
+        *self.conversation_reset_keys.write().unwrap() = config.conversation_reset;
+        *self.undo_insertion_keys.write().unwrap() = config.undo_insertion;
 
         // 更新回调
         *self.on_start.write().unwrap() = Some(Arc::new(on_start));
@@ -842,6 +929,22 @@ impl HotkeyService {
         Ok(())
     }
 
+    /// 设置重置 AI 助手对话快捷键的回调
+    pub fn set_conversation_reset_handler<F>(&self, handler: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        *self.on_conversation_reset.write().unwrap() = Some(Arc::new(handler));
+    }
+
+    /// 设置撤销上次插入快捷键的回调
+    pub fn set_undo_insertion_handler<F>(&self, handler: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        *self.on_undo_insertion.write().unwrap() = Some(Arc::new(handler));
+    }
+
     /// 停用服务（不终止线程）
     pub fn deactivate(&self) {
         tracing::info!("停用快捷键服务");
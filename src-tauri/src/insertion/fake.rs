@@ -0,0 +1,89 @@
+// 测试用插入后端：不发送任何按键，只记录调用；剪贴板为内存剪贴板，不影响系统剪贴板
+
+use anyhow::Result;
+use std::sync::Mutex;
+
+use super::{InsertionBackend, TypedKey};
+use crate::clipboard_manager::{ClipboardAccess, MemoryClipboard};
+
+/// 记录的按键动作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FakeKey {
+    /// 粘贴，附带粘贴时剪贴板中的文本（剪贴板为空为 None）
+    Paste(Option<String>),
+    Copy,
+    ReleaseModifiers,
+    /// 直接输入的按键
+    Typed(TypedKey),
+    /// 退格（次数）
+    Backspace(usize),
+}
+
+pub struct FakeBackend {
+    can_send_keys: bool,
+    events: Mutex<Vec<FakeKey>>,
+    clipboard: MemoryClipboard,
+}
+
+impl FakeBackend {
+    pub fn new() -> Self {
+        Self { can_send_keys: true, events: Mutex::new(Vec::new()), clipboard: MemoryClipboard::default() }
+    }
+
+    /// 模拟无法发送按键的环境
+    pub fn clipboard_only() -> Self {
+        Self { can_send_keys: false, ..Self::new() }
+    }
+
+    pub fn events(&self) -> Vec<FakeKey> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// 后端使用的内存剪贴板
+    pub fn memory_clipboard(&self) -> &MemoryClipboard {
+        &self.clipboard
+    }
+}
+
+impl InsertionBackend for FakeBackend {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn clipboard(&self) -> Result<Box<dyn ClipboardAccess>> {
+        Ok(Box::new(self.clipboard.clone()))
+    }
+
+    fn can_send_keys(&self) -> bool {
+        self.can_send_keys
+    }
+
+    fn paste(&self) -> Result<()> {
+        self.events.lock().unwrap().push(FakeKey::Paste(self.clipboard.text()));
+        Ok(())
+    }
+
+    fn copy(&self) -> Result<()> {
+        self.events.lock().unwrap().push(FakeKey::Copy);
+        Ok(())
+    }
+
+    fn release_modifiers(&self) -> Result<()> {
+        self.events.lock().unwrap().push(FakeKey::ReleaseModifiers);
+        Ok(())
+    }
+
+    fn can_type_unicode(&self) -> bool {
+        self.can_send_keys
+    }
+
+    fn type_key(&self, key: TypedKey) -> Result<()> {
+        self.events.lock().unwrap().push(FakeKey::Typed(key));
+        Ok(())
+    }
+
+    fn delete_backward(&self, count: usize) -> Result<()> {
+        self.events.lock().unwrap().push(FakeKey::Backspace(count));
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+// 文本插入后端
+//
+// 将“模拟粘贴 / 复制快捷键”抽象为 InsertionBackend，运行时按平台与会话类型自动选择：
+// - Windows: SendInput
+// - Linux X11: XTest 扩展
+// - Linux Wayland: /dev/uinput 虚拟键盘（需要对 /dev/uinput 的写权限，通常加入 input 组）
+// - 以上都不可用: 仅剪贴板（文本留在剪贴板中，由用户手动粘贴）
+//
+// 除粘贴外，支持 Unicode 直接输入的后端（SendInput / XTest）还可以逐字符输入文本，完全不经过剪贴板
+
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::clipboard_manager::{ClipboardAccess, SystemClipboard};
+use crate::config::{InsertionConfig, InsertionMethod};
+
+pub mod progressive;
+pub mod typing;
+pub mod undo;
+#[cfg(target_os = "linux")]
+mod uinput;
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "linux")]
+mod x11;
+
+#[cfg(test)]
+pub mod fake;
+
+pub use progressive::{ProgressiveInserter, SentenceSplitter};
+pub use typing::{type_text, TypedKey};
+pub use undo::InsertionRecord;
+
+/// 文本插入后端
+///
+/// 只负责模拟按键，剪贴板读写由调用方（TextInserter / clipboard_manager）通过 `clipboard()` 完成
+pub trait InsertionBackend: Send + Sync {
+    /// 后端名称（用于日志）
+    fn name(&self) -> &'static str;
+
+    /// 打开粘贴 / 复制所使用的剪贴板
+    fn clipboard(&self) -> Result<Box<dyn ClipboardAccess>> {
+        Ok(Box::new(SystemClipboard::new()?))
+    }
+
+    /// 是否能模拟按键；为 false 时调用方只写入剪贴板，不尝试粘贴
+    fn can_send_keys(&self) -> bool {
+        true
+    }
+
+    /// 模拟粘贴快捷键（Ctrl+V）
+    fn paste(&self) -> Result<()>;
+
+    /// 模拟复制快捷键（Ctrl+C）
+    fn copy(&self) -> Result<()>;
+
+    /// 释放所有修饰键，避免与仍被按住的物理热键冲突
+    fn release_modifiers(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// 是否支持直接输入任意 Unicode 字符
+    fn can_type_unicode(&self) -> bool {
+        false
+    }
+
+    /// 直接输入一个按键（仅 can_type_unicode 为 true 时调用）
+    fn type_key(&self, _key: TypedKey) -> Result<()> {
+        anyhow::bail!("插入后端 {} 不支持直接输入", self.name())
+    }
+
+    /// 当前前台应用的进程名（用于按应用选择插入方式），无法获取时为 None
+    fn foreground_app(&self) -> Option<String> {
+        None
+    }
+
+    /// 发送 count 次退格（用于撤销上次插入）
+    fn delete_backward(&self, _count: usize) -> Result<()> {
+        anyhow::bail!("插入后端 {} 不支持退格", self.name())
+    }
+}
+
+/// 确定本次插入实际使用的方式
+///
+/// 按前台应用匹配配置；后端不支持直接输入时回退为粘贴
+pub fn resolve_method(backend: &dyn InsertionBackend, config: &InsertionConfig) -> InsertionMethod {
+    let app = backend.foreground_app();
+    let method = config.method_for(app.as_deref());
+    if method == InsertionMethod::Type && !backend.can_type_unicode() {
+        tracing::warn!("插入后端 {} 不支持直接输入，回退为粘贴", backend.name());
+        return InsertionMethod::Paste;
+    }
+    tracing::debug!("插入方式: {:?} (前台应用: {:?})", method, app);
+    method
+}
+
+/// 仅剪贴板后端：无法模拟按键的环境下的回退
+pub struct ClipboardOnlyBackend;
+
+impl InsertionBackend for ClipboardOnlyBackend {
+    fn name(&self) -> &'static str {
+        "clipboard-only"
+    }
+
+    fn can_send_keys(&self) -> bool {
+        false
+    }
+
+    fn paste(&self) -> Result<()> {
+        anyhow::bail!("当前环境无法模拟按键")
+    }
+
+    fn copy(&self) -> Result<()> {
+        anyhow::bail!("当前环境无法模拟按键")
+    }
+}
+
+/// 桌面会话类型
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionKind {
+    X11,
+    Wayland,
+    /// 无图形会话（如 TTY）或无法判断
+    Unknown,
+}
+
+/// Linux 下可尝试的按键模拟方式
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Candidate {
+    XTest,
+    Uinput,
+}
+
+/// 根据环境变量判断会话类型
+///
+/// XDG_SESSION_TYPE 优先；缺失时按 WAYLAND_DISPLAY / DISPLAY 推断
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn session_kind(env: impl Fn(&str) -> Option<String>) -> SessionKind {
+    let is_set = |key: &str| env(key).is_some_and(|v| !v.is_empty());
+    match env("XDG_SESSION_TYPE").as_deref() {
+        Some("wayland") => SessionKind::Wayland,
+        Some("x11") => SessionKind::X11,
+        _ if is_set("WAYLAND_DISPLAY") => SessionKind::Wayland,
+        _ if is_set("DISPLAY") => SessionKind::X11,
+        _ => SessionKind::Unknown,
+    }
+}
+
+/// 按会话类型给出尝试顺序
+///
+/// Wayland 下 XTest 只能作用于 XWayland 窗口，因此排在 uinput 之后
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn candidates(kind: SessionKind) -> &'static [Candidate] {
+    match kind {
+        SessionKind::X11 => &[Candidate::XTest, Candidate::Uinput],
+        SessionKind::Wayland => &[Candidate::Uinput, Candidate::XTest],
+        SessionKind::Unknown => &[Candidate::Uinput],
+    }
+}
+
+/// 运行时自动选择插入后端
+#[cfg(target_os = "windows")]
+pub fn detect_backend() -> Arc<dyn InsertionBackend> {
+    tracing::info!("文本插入后端: SendInput");
+    Arc::new(windows::SendInputBackend)
+}
+
+/// 运行时自动选择插入后端
+#[cfg(target_os = "linux")]
+pub fn detect_backend() -> Arc<dyn InsertionBackend> {
+    let kind = session_kind(|key| std::env::var(key).ok());
+    for candidate in candidates(kind) {
+        let backend: Result<Arc<dyn InsertionBackend>> = match candidate {
+            Candidate::XTest => x11::XTestBackend::connect().map(|b| Arc::new(b) as _),
+            Candidate::Uinput => uinput::UinputBackend::new().map(|b| Arc::new(b) as _),
+        };
+        match backend {
+            Ok(backend) => {
+                tracing::info!("文本插入后端: {} (会话类型: {:?})", backend.name(), kind);
+                return backend;
+            }
+            Err(e) => tracing::warn!("文本插入后端 {:?} 不可用: {}", candidate, e),
+        }
+    }
+    tracing::warn!("无法模拟按键，文本插入回退为仅剪贴板 (会话类型: {:?})", kind);
+    Arc::new(ClipboardOnlyBackend)
+}
+
+/// 运行时自动选择插入后端
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn detect_backend() -> Arc<dyn InsertionBackend> {
+    tracing::warn!("当前平台不支持模拟按键，文本插入回退为仅剪贴板");
+    Arc::new(ClipboardOnlyBackend)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env_of(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |key| map.get(key).cloned()
+    }
+
+    #[test]
+    fn test_session_kind() {
+        assert_eq!(session_kind(env_of(&[("XDG_SESSION_TYPE", "wayland"), ("DISPLAY", ":0")])), SessionKind::Wayland);
+        assert_eq!(session_kind(env_of(&[("XDG_SESSION_TYPE", "x11"), ("WAYLAND_DISPLAY", "wayland-0")])), SessionKind::X11);
+        assert_eq!(session_kind(env_of(&[("WAYLAND_DISPLAY", "wayland-0"), ("DISPLAY", ":0")])), SessionKind::Wayland);
+        assert_eq!(session_kind(env_of(&[("XDG_SESSION_TYPE", "tty"), ("DISPLAY", ":1")])), SessionKind::X11);
+        assert_eq!(session_kind(env_of(&[("DISPLAY", "")])), SessionKind::Unknown);
+        assert_eq!(session_kind(env_of(&[])), SessionKind::Unknown);
+    }
+
+    #[test]
+    fn test_candidates_order() {
+        assert_eq!(candidates(SessionKind::X11)[0], Candidate::XTest);
+        assert_eq!(candidates(SessionKind::Wayland)[0], Candidate::Uinput);
+        assert!(!candidates(SessionKind::Unknown).contains(&Candidate::XTest));
+    }
+
+    #[test]
+    fn test_method_for_app_rules() {
+        use crate::config::AppInsertionRule;
+        let config = InsertionConfig {
+            app_rules: vec![AppInsertionRule { app: "WindowsTerminal.exe".to_string(), method: InsertionMethod::Type }],
+            ..Default::default()
+        };
+        assert_eq!(config.method_for(Some("windowsterminal.exe")), InsertionMethod::Type);
+        assert_eq!(config.method_for(Some("WindowsTerminal")), InsertionMethod::Type);
+        assert_eq!(config.method_for(Some("notepad.exe")), InsertionMethod::Paste);
+        assert_eq!(config.method_for(None), InsertionMethod::Paste);
+    }
+
+    #[test]
+    fn test_resolve_method_falls_back_without_unicode_support() {
+        let config = InsertionConfig { method: InsertionMethod::Type, ..Default::default() };
+        assert_eq!(resolve_method(&fake::FakeBackend::new(), &config), InsertionMethod::Type);
+        assert_eq!(resolve_method(&ClipboardOnlyBackend, &config), InsertionMethod::Paste);
+    }
+
+    #[test]
+    fn test_clipboard_only_backend() {
+        let backend = ClipboardOnlyBackend;
+        assert!(!backend.can_send_keys());
+        assert!(backend.paste().is_err());
+        assert!(backend.copy().is_err());
+        assert!(backend.release_modifiers().is_ok());
+    }
+}
@@ -0,0 +1,204 @@
+// 流式插入：LLM 回答边生成边插入
+//
+// SentenceSplitter 将流式文本片段切分为完整的句子，ProgressiveInserter 逐句输入或粘贴到目标窗口；
+// 第一句会替换选中内容，取消标志置位后立即停止（直接输入模式下在按键之间停止）
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::{resolve_method, typing, InsertionBackend};
+use crate::clipboard_manager::{insert_text_with_context, ClipboardGuard};
+use crate::config::{InsertionConfig, InsertionMethod};
+
+/// 句末标点（英文句号需后跟空白才算句末，避免切开小数和缩写）
+const SENTENCE_TERMINATORS: &[char] = &['。', '！', '？', '；', '…', '!', '?'];
+
+/// 按句子切分流式文本
+///
+/// 切出的句子依次拼接等于去除首尾空白后的完整回答（句间空白归入下一句）
+#[derive(Debug, Default)]
+pub struct SentenceSplitter {
+    pending: String,
+    started: bool,
+}
+
+impl SentenceSplitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加一个流式片段，有完整句子时返回（可能包含多句）
+    pub fn push(&mut self, delta: &str) -> Option<String> {
+        self.pending.push_str(delta);
+        let end = self.boundary()?;
+        let segment: String = self.pending.drain(..end).collect();
+        Some(self.start(segment))
+    }
+
+    /// 流结束时返回剩余的文本
+    pub fn finish(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.pending);
+        let rest = rest.trim_end();
+        if rest.trim().is_empty() {
+            return None;
+        }
+        Some(self.start(rest.to_string()))
+    }
+
+    /// 最后一个句子边界（字节位置）；换行前、句末标点后都是边界，边界前的空白留给下一句
+    fn boundary(&self) -> Option<usize> {
+        let mut end = None;
+        let mut chars = self.pending.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let next = chars.peek().map(|&(_, n)| n);
+            match c {
+                '\n' => end = Some(i),
+                '.' if next.is_some_and(char::is_whitespace) => end = Some(i + 1),
+                c if SENTENCE_TERMINATORS.contains(&c) => end = Some(i + c.len_utf8()),
+                _ => {}
+            }
+        }
+        end.map(|end| self.pending[..end].trim_end().len()).filter(|&end| end > 0)
+    }
+
+    /// 回答开头的空白不插入
+    fn start(&mut self, segment: String) -> String {
+        if self.started {
+            return segment;
+        }
+        self.started = true;
+        segment.trim_start().to_string()
+    }
+}
+
+/// 逐段插入器
+pub struct ProgressiveInserter<'a> {
+    backend: &'a dyn InsertionBackend,
+    config: &'a InsertionConfig,
+    method: InsertionMethod,
+    has_selection: bool,
+    /// 粘贴模式下全部插入完成后才恢复剪贴板
+    guard: Option<ClipboardGuard>,
+    cancelled: &'a AtomicBool,
+    /// 已插入的文本（取消时为实际输入的部分）
+    inserted: String,
+}
+
+impl<'a> ProgressiveInserter<'a> {
+    pub fn new(
+        backend: &'a dyn InsertionBackend,
+        config: &'a InsertionConfig,
+        has_selection: bool,
+        guard: Option<ClipboardGuard>,
+        cancelled: &'a AtomicBool,
+    ) -> Self {
+        Self {
+            backend,
+            config,
+            method: resolve_method(backend, config),
+            has_selection,
+            guard,
+            cancelled,
+            inserted: String::new(),
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// 插入一段文本；第一段会替换选中内容（直接输入和粘贴都会覆盖选区）
+    pub fn insert(&mut self, segment: &str) -> Result<()> {
+        if self.is_cancelled() {
+            return Ok(());
+        }
+        let replaces_selection = self.has_selection && self.inserted.is_empty();
+        tracing::debug!("流式插入: {} 字符 (替换选中: {})", segment.chars().count(), replaces_selection);
+
+        match self.method {
+            InsertionMethod::Type => {
+                let keys = typing::plan(segment, self.config.typing.newline);
+                let sent = typing::type_keys(self.backend, &keys, &self.config.typing, self.cancelled)?;
+                self.inserted.extend(keys[..sent].iter().map(|k| k.to_char()));
+            }
+            InsertionMethod::Paste => {
+                if let Some(guard) = self.guard.as_mut() {
+                    guard.save();
+                }
+                insert_text_with_context(self.backend, segment, replaces_selection, None)?;
+                self.inserted.push_str(segment);
+            }
+        }
+        Ok(())
+    }
+
+    /// 结束插入并恢复剪贴板，返回已插入的文本
+    pub fn finish(mut self) -> String {
+        if let Some(guard) = self.guard.take() {
+            if let Err(e) = guard.restore() {
+                tracing::warn!("流式插入: 恢复剪贴板失败: {}", e);
+            }
+        }
+        self.inserted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::insertion::fake::{FakeBackend, FakeKey};
+    use crate::insertion::TypedKey;
+
+    fn split(deltas: &[&str]) -> Vec<String> {
+        let mut splitter = SentenceSplitter::new();
+        let mut segments: Vec<String> = deltas.iter().filter_map(|d| splitter.push(d)).collect();
+        segments.extend(splitter.finish());
+        segments
+    }
+
+    #[test]
+    fn test_splitter_sentences() {
+        assert_eq!(split(&["\n你好", "。今天", "天气不错！明", "天呢"]), vec!["你好。", "今天天气不错！", "明天呢"]);
+        assert_eq!(split(&["Pi is 3.14. It", " is", " irrational.\n\n"]), vec!["Pi is 3.14.", " It is irrational."]);
+        assert_eq!(split(&["第一行\n", "\n第二行\n"]), vec!["第一行", "\n\n第二行"]);
+        assert!(split(&["  \n"]).is_empty());
+    }
+
+    #[test]
+    fn test_insert_segments_by_typing() {
+        let backend = FakeBackend::new();
+        let mut config = InsertionConfig { method: InsertionMethod::Type, ..Default::default() };
+        config.typing.char_delay_ms = 0;
+        let cancelled = AtomicBool::new(false);
+
+        let mut inserter = ProgressiveInserter::new(&backend, &config, true, None, &cancelled);
+        inserter.insert("好。").unwrap();
+        cancelled.store(true, Ordering::SeqCst);
+        inserter.insert("再见").unwrap();
+        assert!(inserter.is_cancelled());
+        assert_eq!(inserter.finish(), "好。");
+        assert_eq!(
+            backend.events(),
+            vec![FakeKey::Typed(TypedKey::Char('好')), FakeKey::Typed(TypedKey::Char('。'))]
+        );
+    }
+
+    #[test]
+    fn test_insert_segments_by_paste_restores_clipboard() {
+        let backend = FakeBackend::new();
+        backend.memory_clipboard().set("原内容");
+        let config = InsertionConfig { method: InsertionMethod::Paste, ..Default::default() };
+        let cancelled = AtomicBool::new(false);
+
+        let guard = ClipboardGuard::new(&backend).ok();
+        let mut inserter = ProgressiveInserter::new(&backend, &config, false, guard, &cancelled);
+        inserter.insert("你好。").unwrap();
+        inserter.insert("再见").unwrap();
+        assert_eq!(inserter.finish(), "你好。再见");
+        assert_eq!(
+            backend.events(),
+            vec![FakeKey::Paste(Some("你好。".to_string())), FakeKey::Paste(Some("再见".to_string()))]
+        );
+        assert_eq!(backend.memory_clipboard().text().as_deref(), Some("原内容"));
+    }
+}
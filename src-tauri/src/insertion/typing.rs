@@ -0,0 +1,156 @@
+// 直接输入模式：把文本拆成按键序列逐个发送，不经过剪贴板
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use super::InsertionBackend;
+use crate::config::{NewlineKey, TypingConfig};
+
+/// 直接输入模式下发送的单个按键
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypedKey {
+    /// 任意 Unicode 字符
+    Char(char),
+    /// 回车，shift 为 true 时发送 Shift+Enter
+    Enter { shift: bool },
+    Tab,
+}
+
+impl TypedKey {
+    /// 按键对应的字符（换行为 '\n'）
+    pub fn to_char(self) -> char {
+        match self {
+            TypedKey::Char(c) => c,
+            TypedKey::Enter { .. } => '\n',
+            TypedKey::Tab => '\t',
+        }
+    }
+}
+
+/// 将文本转换为按键序列
+///
+/// CRLF / CR 统一视为换行，其余控制字符丢弃
+pub fn plan(text: &str, newline: NewlineKey) -> Vec<TypedKey> {
+    let shift = newline == NewlineKey::ShiftEnter;
+    let mut keys = Vec::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                keys.push(TypedKey::Enter { shift });
+            }
+            '\n' => keys.push(TypedKey::Enter { shift }),
+            '\t' => keys.push(TypedKey::Tab),
+            c if c.is_control() => {}
+            c => keys.push(TypedKey::Char(c)),
+        }
+    }
+    keys
+}
+
+/// 按配置的节奏逐键输入文本
+pub fn type_text(backend: &dyn InsertionBackend, text: &str, config: &TypingConfig) -> Result<()> {
+    type_keys(backend, &plan(text, config.newline), config, &AtomicBool::new(false)).map(|_| ())
+}
+
+/// 按配置的节奏逐键输入，每个按键前检查 `cancelled`，置位后立即停止
+///
+/// 返回实际发送的按键数
+pub fn type_keys(
+    backend: &dyn InsertionBackend,
+    keys: &[TypedKey],
+    config: &TypingConfig,
+    cancelled: &AtomicBool,
+) -> Result<usize> {
+    tracing::info!(
+        "直接输入: {} 个按键 (后端: {}, 间隔: {}ms, 分块: {})",
+        keys.len(),
+        backend.name(),
+        config.char_delay_ms,
+        config.chunk_size
+    );
+
+    let chunk_size = if config.chunk_size == 0 { keys.len().max(1) } else { config.chunk_size };
+    let mut sent = 0;
+    for (i, chunk) in keys.chunks(chunk_size).enumerate() {
+        if i > 0 && config.chunk_delay_ms > 0 {
+            thread::sleep(Duration::from_millis(config.chunk_delay_ms));
+        }
+        for &key in chunk {
+            if cancelled.load(Ordering::SeqCst) {
+                tracing::info!("直接输入已取消 (已发送 {}/{} 个按键)", sent, keys.len());
+                return Ok(sent);
+            }
+            backend.type_key(key)?;
+            sent += 1;
+            if config.char_delay_ms > 0 {
+                thread::sleep(Duration::from_millis(config.char_delay_ms));
+            }
+        }
+    }
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::insertion::fake::{FakeBackend, FakeKey};
+
+    #[test]
+    fn test_plan_newlines_and_tabs() {
+        let keys = plan("a\r\nb\rc\n\td\u{7}", NewlineKey::Enter);
+        assert_eq!(
+            keys,
+            vec![
+                TypedKey::Char('a'),
+                TypedKey::Enter { shift: false },
+                TypedKey::Char('b'),
+                TypedKey::Enter { shift: false },
+                TypedKey::Char('c'),
+                TypedKey::Enter { shift: false },
+                TypedKey::Tab,
+                TypedKey::Char('d'),
+            ]
+        );
+        assert_eq!(plan("\n", NewlineKey::ShiftEnter), vec![TypedKey::Enter { shift: true }]);
+    }
+
+    #[test]
+    fn test_plan_unicode() {
+        assert_eq!(
+            plan("你好😀", NewlineKey::Enter),
+            vec![TypedKey::Char('你'), TypedKey::Char('好'), TypedKey::Char('😀')]
+        );
+    }
+
+    #[test]
+    fn test_type_text_sends_all_keys_in_chunks() {
+        let backend = FakeBackend::new();
+        let config = TypingConfig { char_delay_ms: 0, chunk_size: 2, chunk_delay_ms: 0, newline: NewlineKey::Enter };
+        type_text(&backend, "ab\nc", &config).unwrap();
+        assert_eq!(
+            backend.events(),
+            vec![
+                FakeKey::Typed(TypedKey::Char('a')),
+                FakeKey::Typed(TypedKey::Char('b')),
+                FakeKey::Typed(TypedKey::Enter { shift: false }),
+                FakeKey::Typed(TypedKey::Char('c')),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_keys_stops_when_cancelled() {
+        let backend = FakeBackend::new();
+        let config = TypingConfig { char_delay_ms: 0, chunk_size: 0, chunk_delay_ms: 0, newline: NewlineKey::Enter };
+        let keys = plan("abc", NewlineKey::Enter);
+        assert_eq!(type_keys(&backend, &keys, &config, &AtomicBool::new(true)).unwrap(), 0);
+        assert!(backend.events().is_empty());
+        assert_eq!(type_keys(&backend, &keys, &config, &AtomicBool::new(false)).unwrap(), 3);
+    }
+}
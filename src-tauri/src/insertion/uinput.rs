@@ -0,0 +1,151 @@
+// Linux uinput 后端：内核级虚拟键盘
+// 不依赖显示服务器，Wayland 合成器会把它当作普通键盘处理
+// 需要对 /dev/uinput 的写权限（通常加入 input 组或配置 udev 规则）
+
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use super::InsertionBackend;
+
+/// 按键间延迟（毫秒），与 SendInput 后端保持一致
+const KEY_DELAY_MS: u64 = 15;
+
+/// 设备创建后等待合成器识别的时间
+const DEVICE_SETTLE_MS: u64 = 200;
+
+// linux/uinput.h
+const UI_SET_EVBIT: u64 = 0x4004_5564;
+const UI_SET_KEYBIT: u64 = 0x4004_5565;
+const UI_DEV_CREATE: u64 = 0x5501;
+const UI_DEV_DESTROY: u64 = 0x5502;
+
+// linux/input-event-codes.h
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const SYN_REPORT: u16 = 0;
+const KEY_BACKSPACE: u16 = 14;
+const KEY_LEFTCTRL: u16 = 29;
+const KEY_C: u16 = 46;
+const KEY_V: u16 = 47;
+const BUS_VIRTUAL: u16 = 0x06;
+
+const DEVICE_NAME: &[u8] = b"push-to-talk virtual keyboard";
+
+pub struct UinputBackend {
+    device: Mutex<File>,
+}
+
+impl UinputBackend {
+    /// 打开 /dev/uinput 并创建只包含所需按键的虚拟键盘
+    pub fn new() -> Result<Self> {
+        let device = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/uinput")
+            .context("无法打开 /dev/uinput（需要 input 组权限）")?;
+        let fd = device.as_raw_fd();
+
+        ioctl(fd, UI_SET_EVBIT, EV_KEY as libc::c_ulong)?;
+        for key in [KEY_BACKSPACE, KEY_LEFTCTRL, KEY_C, KEY_V] {
+            ioctl(fd, UI_SET_KEYBIT, key as libc::c_ulong)?;
+        }
+
+        // 旧式 uinput_user_dev 接口，兼容所有支持 uinput 的内核
+        let mut setup: libc::uinput_user_dev = unsafe { std::mem::zeroed() };
+        for (dst, &src) in setup.name.iter_mut().zip(DEVICE_NAME) {
+            *dst = src as libc::c_char;
+        }
+        setup.id.bustype = BUS_VIRTUAL;
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                &setup as *const libc::uinput_user_dev as *const u8,
+                std::mem::size_of::<libc::uinput_user_dev>(),
+            )
+        };
+        (&device).write_all(bytes).context("写入 uinput 设备信息失败")?;
+        ioctl(fd, UI_DEV_CREATE, 0)?;
+
+        thread::sleep(Duration::from_millis(DEVICE_SETTLE_MS));
+        Ok(Self { device: Mutex::new(device) })
+    }
+
+    fn emit(device: &mut File, type_: u16, code: u16, value: i32) -> Result<()> {
+        // 时间戳留空，由内核填写
+        let mut event: libc::input_event = unsafe { std::mem::zeroed() };
+        event.type_ = type_;
+        event.code = code;
+        event.value = value;
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                &event as *const libc::input_event as *const u8,
+                std::mem::size_of::<libc::input_event>(),
+            )
+        };
+        device.write_all(bytes).context("写入 uinput 事件失败")
+    }
+
+    fn send(device: &mut File, key: u16, press: bool) -> Result<()> {
+        Self::emit(device, EV_KEY, key, press as i32)?;
+        Self::emit(device, EV_SYN, SYN_REPORT, 0)
+    }
+
+    fn send_combo(&self, key: u16) -> Result<()> {
+        let mut device = self.device.lock().unwrap();
+        Self::send(&mut device, KEY_LEFTCTRL, true)?;
+        thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+        Self::send(&mut device, key, true)?;
+        thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+        Self::send(&mut device, key, false)?;
+        thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+        Self::send(&mut device, KEY_LEFTCTRL, false)
+    }
+}
+
+impl InsertionBackend for UinputBackend {
+    fn name(&self) -> &'static str {
+        "uinput"
+    }
+
+    fn paste(&self) -> Result<()> {
+        tracing::debug!("uinput: 发送 Ctrl+V");
+        self.send_combo(KEY_V)
+    }
+
+    fn copy(&self) -> Result<()> {
+        tracing::debug!("uinput: 发送 Ctrl+C");
+        self.send_combo(KEY_C)
+    }
+
+    fn delete_backward(&self, count: usize) -> Result<()> {
+        let mut device = self.device.lock().unwrap();
+        for _ in 0..count {
+            Self::send(&mut device, KEY_BACKSPACE, true)?;
+            Self::send(&mut device, KEY_BACKSPACE, false)?;
+            thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+        }
+        Ok(())
+    }
+
+    // 虚拟键盘无法释放物理键盘上按住的键，使用默认的 release_modifiers
+}
+
+impl Drop for UinputBackend {
+    fn drop(&mut self) {
+        if let Ok(device) = self.device.lock() {
+            let _ = ioctl(device.as_raw_fd(), UI_DEV_DESTROY, 0);
+        }
+    }
+}
+
+fn ioctl(fd: libc::c_int, request: u64, arg: libc::c_ulong) -> Result<()> {
+    if unsafe { libc::ioctl(fd, request as _, arg) } < 0 {
+        return Err(std::io::Error::last_os_error()).context(format!("uinput ioctl 0x{:x} 失败", request));
+    }
+    Ok(())
+}
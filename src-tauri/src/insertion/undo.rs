@@ -0,0 +1,153 @@
+// 撤销上次插入：记录插入的位置与长度，撤销时退格删除，并恢复被替换的选中文本
+
+use anyhow::Result;
+
+use super::{resolve_method, type_text, typing, InsertionBackend, TypedKey};
+use crate::clipboard_manager::{insert_text_with_context, ClipboardGuard};
+use crate::config::{InsertionConfig, InsertionMethod, NewlineKey};
+
+/// 零宽连接符：前后的 emoji 组成一个字素簇
+const ZWJ: char = '\u{200D}';
+
+/// 一次成功插入的记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InsertionRecord {
+    /// 插入的文本
+    pub text: String,
+    /// 目标窗口句柄（插入时的前台窗口）
+    pub target_hwnd: Option<isize>,
+    /// 删除插入文本所需的退格次数
+    pub char_count: usize,
+    /// 被替换的选中文本（AI 助手模式替换选中内容时存在）
+    pub replaced_selection: Option<String>,
+}
+
+impl InsertionRecord {
+    pub fn new(text: &str, target_hwnd: Option<isize>, replaced_selection: Option<String>) -> Self {
+        Self {
+            text: text.to_string(),
+            target_hwnd,
+            // 退格按字素簇删除：CRLF 算一个字符，控制字符不计，组合符/emoji 序列算一个字符
+            char_count: grapheme_count(&typing::plan(text, NewlineKey::Enter)),
+            replaced_selection,
+        }
+    }
+}
+
+/// 统计按键序列中的字素簇数量（即退格次数）
+///
+/// 未引入完整的 Unicode 分段表，只合并常见的扩展字符：组合符、变体选择符、肤色修饰、
+/// 零宽连接符连起的 emoji 序列、国旗（成对的区域指示符）
+fn grapheme_count(keys: &[TypedKey]) -> usize {
+    let mut count = 0;
+    let mut prev: Option<char> = None;
+    let mut regional_pending = false;
+    for key in keys {
+        let TypedKey::Char(c) = *key else {
+            count += 1;
+            prev = None;
+            regional_pending = false;
+            continue;
+        };
+        let joins_prev = prev.is_some()
+            && (is_grapheme_extend(c) || prev == Some(ZWJ) || (is_regional_indicator(c) && regional_pending));
+        if !joins_prev {
+            count += 1;
+        }
+        regional_pending = is_regional_indicator(c) && !(joins_prev && regional_pending);
+        prev = Some(c);
+    }
+    count
+}
+
+fn is_grapheme_extend(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036F         // 组合变音符
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200D            // 零宽连接符
+            | 0x20D0..=0x20FF   // 组合符号（含键帽）
+            | 0x3099..=0x309A   // 日文组合浊点
+            | 0xFE00..=0xFE0F   // 变体选择符
+            | 0xFE20..=0xFE2F
+            | 0x1F3FB..=0x1F3FF // emoji 肤色修饰
+            | 0xE0020..=0xE007F // 标签字符（地区旗帜）
+            | 0xE0100..=0xE01EF
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c as u32, 0x1F1E6..=0x1F1FF)
+}
+
+/// 撤销插入：退格删除插入的文本，有被替换的选中文本时重新插入
+///
+/// 调用方负责先把焦点恢复到 record.target_hwnd
+pub fn undo(backend: &dyn InsertionBackend, config: &InsertionConfig, record: &InsertionRecord) -> Result<()> {
+    if !backend.can_send_keys() {
+        anyhow::bail!("当前环境无法模拟按键，无法撤销");
+    }
+
+    tracing::info!(
+        "撤销插入: 删除 {} 个字符, 恢复选中文本: {}",
+        record.char_count,
+        record.replaced_selection.is_some()
+    );
+    backend.release_modifiers()?;
+    backend.delete_backward(record.char_count)?;
+
+    if let Some(ref selection) = record.replaced_selection {
+        match resolve_method(backend, config) {
+            InsertionMethod::Type => type_text(backend, selection, &config.typing)?,
+            InsertionMethod::Paste => {
+                insert_text_with_context(backend, selection, false, ClipboardGuard::new(backend).ok())?
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::insertion::fake::{FakeBackend, FakeKey};
+    use crate::insertion::TypedKey;
+
+    #[test]
+    fn test_record_counts_characters() {
+        assert_eq!(InsertionRecord::new("你好😀", None, None).char_count, 3);
+        assert_eq!(InsertionRecord::new("a\r\nb\t", None, None).char_count, 4);
+    }
+
+    #[test]
+    fn test_record_counts_grapheme_clusters() {
+        assert_eq!(InsertionRecord::new("e\u{301}", None, None).char_count, 1);
+        assert_eq!(InsertionRecord::new("👍🏽好", None, None).char_count, 2);
+        assert_eq!(InsertionRecord::new("👨\u{200D}👩\u{200D}👧", None, None).char_count, 1);
+        assert_eq!(InsertionRecord::new("❤\u{FE0F}", None, None).char_count, 1);
+        assert_eq!(InsertionRecord::new("🇨🇳🇺🇸🇯", None, None).char_count, 3);
+    }
+
+    #[test]
+    fn test_undo_deletes_and_restores_selection() {
+        let backend = FakeBackend::new();
+        let mut config = InsertionConfig { method: InsertionMethod::Type, ..Default::default() };
+        config.typing.char_delay_ms = 0;
+
+        let record = InsertionRecord::new("替换后", Some(1), Some("原".to_string()));
+        undo(&backend, &config, &record).unwrap();
+        assert_eq!(
+            backend.events(),
+            vec![FakeKey::ReleaseModifiers, FakeKey::Backspace(3), FakeKey::Typed(TypedKey::Char('原'))]
+        );
+    }
+
+    #[test]
+    fn test_undo_requires_keys() {
+        let backend = FakeBackend::clipboard_only();
+        let record = InsertionRecord::new("文本", None, None);
+        assert!(undo(&backend, &InsertionConfig::default(), &record).is_err());
+        assert!(backend.events().is_empty());
+    }
+}
@@ -0,0 +1,46 @@
+// Windows 后端：Win32 SendInput
+
+use anyhow::Result;
+
+use super::{InsertionBackend, TypedKey};
+use crate::win32_input;
+
+pub struct SendInputBackend;
+
+impl InsertionBackend for SendInputBackend {
+    fn name(&self) -> &'static str {
+        "sendinput"
+    }
+
+    fn paste(&self) -> Result<()> {
+        win32_input::send_ctrl_v()
+    }
+
+    fn copy(&self) -> Result<()> {
+        win32_input::send_ctrl_c()
+    }
+
+    fn release_modifiers(&self) -> Result<()> {
+        win32_input::release_all_modifiers()
+    }
+
+    fn can_type_unicode(&self) -> bool {
+        true
+    }
+
+    fn type_key(&self, key: TypedKey) -> Result<()> {
+        match key {
+            TypedKey::Char(ch) => win32_input::send_unicode_char(ch),
+            TypedKey::Enter { shift } => win32_input::send_enter(shift),
+            TypedKey::Tab => win32_input::send_tab(),
+        }
+    }
+
+    fn delete_backward(&self, count: usize) -> Result<()> {
+        win32_input::send_backspace(count)
+    }
+
+    fn foreground_app(&self) -> Option<String> {
+        win32_input::get_foreground_window().and_then(win32_input::get_window_process_name)
+    }
+}
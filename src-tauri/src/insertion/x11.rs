@@ -0,0 +1,324 @@
+// Linux X11 后端：XTest 扩展
+// 通过 XTestFakeInput 向 X 服务器注入按键事件，作用于当前焦点窗口
+// 直接输入时，键盘映射中没有的字符临时映射到一个空闲 keycode 上再按下（与 xdotool 相同的做法），按下后立即恢复
+
+use anyhow::{anyhow, Result};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::xproto::{self, AtomEnum, ConnectionExt as _, Keycode, Keysym, Mapping, MappingNotifyEvent, Window};
+use x11rb::protocol::Event;
+use x11rb::protocol::xtest::{self, ConnectionExt as _};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+
+use super::{InsertionBackend, TypedKey};
+
+/// 按键间延迟（毫秒），与 SendInput 后端保持一致
+const KEY_DELAY_MS: u64 = 15;
+
+const XK_CONTROL_L: Keysym = 0xffe3;
+const XK_SHIFT_L: Keysym = 0xffe1;
+const XK_RETURN: Keysym = 0xff0d;
+const XK_TAB: Keysym = 0xff09;
+const XK_BACKSPACE: Keysym = 0xff08;
+const XK_C: Keysym = 0x0063;
+const XK_V: Keysym = 0x0076;
+
+/// 需要防御性释放的修饰键：Shift / Control / Alt / Super 左右各一
+const MODIFIER_KEYSYMS: [Keysym; 8] = [0xffe1, 0xffe2, 0xffe3, 0xffe4, 0xffe9, 0xffea, 0xffeb, 0xffec];
+
+pub struct XTestBackend {
+    conn: RustConnection,
+    root: Window,
+    /// 当前键盘映射下的 keycode，收到 MappingNotify 时重新读取
+    layout: Mutex<Layout>,
+}
+
+/// 由键盘映射得到的 keycode
+struct Layout {
+    control: Keycode,
+    shift: Keycode,
+    key_c: Keycode,
+    key_v: Keycode,
+    key_return: Keycode,
+    key_tab: Keycode,
+    key_backspace: Keycode,
+    modifiers: Vec<Keycode>,
+    keymap: KeyMap,
+    /// 没有绑定任何 keysym 的 keycode，用于临时映射任意字符
+    scratch: Option<Keycode>,
+}
+
+impl Layout {
+    /// 读取键盘映射，将 keysym 转为当前布局下的 keycode
+    fn load(conn: &RustConnection) -> Result<Self> {
+        let min = conn.setup().min_keycode;
+        let max = conn.setup().max_keycode;
+        let mapping = conn.get_keyboard_mapping(min, max - min + 1)?.reply()?;
+        let keymap = KeyMap { min_keycode: min, per_keycode: mapping.keysyms_per_keycode, keysyms: mapping.keysyms };
+        let lookup = |keysym: Keysym| {
+            keymap
+                .find(keysym)
+                .map(|(code, _)| code)
+                .ok_or_else(|| anyhow!("键盘映射中找不到 keysym 0x{:x}", keysym))
+        };
+
+        Ok(Self {
+            control: lookup(XK_CONTROL_L)?,
+            shift: lookup(XK_SHIFT_L)?,
+            key_c: lookup(XK_C)?,
+            key_v: lookup(XK_V)?,
+            key_return: lookup(XK_RETURN)?,
+            key_tab: lookup(XK_TAB)?,
+            key_backspace: lookup(XK_BACKSPACE)?,
+            modifiers: MODIFIER_KEYSYMS.iter().filter_map(|&k| lookup(k).ok()).collect(),
+            scratch: keymap.unused_keycode(),
+            keymap,
+        })
+    }
+}
+
+/// GetKeyboardMapping 的结果
+struct KeyMap {
+    min_keycode: Keycode,
+    per_keycode: u8,
+    keysyms: Vec<Keysym>,
+}
+
+impl KeyMap {
+    /// 查找 keysym 所在的 keycode 及列（0 = 无修饰，1 = Shift）
+    fn find(&self, keysym: Keysym) -> Option<(Keycode, usize)> {
+        if self.per_keycode == 0 {
+            return None;
+        }
+        self.keysyms.chunks(self.per_keycode as usize).enumerate().find_map(|(index, syms)| {
+            let column = syms.iter().position(|&s| s == keysym)?;
+            Keycode::try_from(self.min_keycode as usize + index).ok().map(|code| (code, column))
+        })
+    }
+
+    /// 第一个完全空闲的 keycode
+    fn unused_keycode(&self) -> Option<Keycode> {
+        if self.per_keycode == 0 {
+            return None;
+        }
+        self.keysyms
+            .chunks(self.per_keycode as usize)
+            .position(|syms| syms.iter().all(|&s| s == 0))
+            .and_then(|index| Keycode::try_from(self.min_keycode as usize + index).ok())
+    }
+}
+
+/// MappingNotify 是否意味着需要重新读取键盘映射
+///
+/// 临时映射空闲 keycode 时自身也会触发通知，这类通知忽略
+fn affects_layout(notify: &MappingNotifyEvent, scratch: Option<Keycode>) -> bool {
+    notify.request == Mapping::KEYBOARD && !(notify.count == 1 && Some(notify.first_keycode) == scratch)
+}
+
+impl XTestBackend {
+    /// 连接 $DISPLAY 并检查 XTEST 扩展
+    pub fn connect() -> Result<Self> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        if conn.extension_information(xtest::X11_EXTENSION_NAME)?.is_none() {
+            anyhow::bail!("X 服务器不支持 XTEST 扩展");
+        }
+        conn.xtest_get_version(2, 2)?.reply()?;
+        let root = conn.setup().roots[screen_num].root;
+        let layout = Layout::load(&conn)?;
+
+        Ok(Self { conn, root, layout: Mutex::new(layout) })
+    }
+
+    /// 当前键盘映射；切换布局等导致映射变化后重新读取（X 服务器向所有客户端发送 MappingNotify）
+    fn layout(&self) -> Result<MutexGuard<'_, Layout>> {
+        let mut layout = self.layout.lock().unwrap();
+        let mut changed = false;
+        while let Some(event) = self.conn.poll_for_event()? {
+            if let Event::MappingNotify(notify) = event {
+                changed |= affects_layout(&notify, layout.scratch);
+            }
+        }
+        if changed {
+            tracing::info!("xtest: 键盘映射已变化，重新读取");
+            *layout = Layout::load(&self.conn)?;
+        }
+        Ok(layout)
+    }
+
+    fn send(&self, keycode: Keycode, press: bool) -> Result<()> {
+        let event = if press { xproto::KEY_PRESS_EVENT } else { xproto::KEY_RELEASE_EVENT };
+        self.conn.xtest_fake_input(event, keycode, x11rb::CURRENT_TIME, self.root, 0, 0, 0)?;
+        // 等待服务器处理完毕，保证事件顺序和按键间隔
+        self.conn.sync()?;
+        Ok(())
+    }
+
+    fn send_with_modifier(&self, modifier: Keycode, key: Keycode) -> Result<()> {
+        self.send(modifier, true)?;
+        thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+        self.tap(key)?;
+        thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+        self.send(modifier, false)
+    }
+
+    fn tap(&self, key: Keycode) -> Result<()> {
+        self.send(key, true)?;
+        self.send(key, false)
+    }
+
+    fn remap(&self, keycode: Keycode, keysyms: &[Keysym]) -> Result<()> {
+        self.conn.change_keyboard_mapping(1, keycode, keysyms.len() as u8, keysyms)?;
+        self.conn.sync()?;
+        Ok(())
+    }
+
+    fn type_char(&self, layout: &Layout, ch: char) -> Result<()> {
+        let keysym = char_keysym(ch);
+        match layout.keymap.find(keysym) {
+            Some((code, 0)) => self.tap(code),
+            Some((code, 1)) => self.send_with_modifier(layout.shift, code),
+            _ => {
+                let scratch = layout.scratch.ok_or_else(|| anyhow!("键盘映射中没有空闲 keycode，无法输入 {:?}", ch))?;
+                let width = layout.keymap.per_keycode.max(1) as usize;
+                // 所有列都设为同一 keysym，不受当前修饰键状态影响
+                self.remap(scratch, &vec![keysym; width])?;
+                let result = self.tap(scratch);
+                // 等目标应用处理完按键后恢复为空闲，临时映射不残留在用户的键盘映射中
+                thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+                self.remap(scratch, &vec![0; width])?;
+                result
+            }
+        }
+    }
+
+    /// 读取窗口上的单个 32 位属性
+    fn property32(&self, window: Window, name: &[u8], type_: AtomEnum) -> Option<u32> {
+        let atom = self.conn.intern_atom(true, name).ok()?.reply().ok()?.atom;
+        let reply = self.conn.get_property(false, window, atom, type_, 0, 1).ok()?.reply().ok()?;
+        let mut values = reply.value32()?;
+        values.next()
+    }
+}
+
+impl InsertionBackend for XTestBackend {
+    fn name(&self) -> &'static str {
+        "xtest"
+    }
+
+    fn paste(&self) -> Result<()> {
+        tracing::debug!("xtest: 发送 Ctrl+V");
+        let layout = self.layout()?;
+        self.send_with_modifier(layout.control, layout.key_v)
+    }
+
+    fn copy(&self) -> Result<()> {
+        tracing::debug!("xtest: 发送 Ctrl+C");
+        let layout = self.layout()?;
+        self.send_with_modifier(layout.control, layout.key_c)
+    }
+
+    fn can_type_unicode(&self) -> bool {
+        true
+    }
+
+    fn type_key(&self, key: TypedKey) -> Result<()> {
+        let layout = self.layout()?;
+        match key {
+            TypedKey::Char(ch) => self.type_char(&layout, ch),
+            TypedKey::Enter { shift: false } => self.tap(layout.key_return),
+            TypedKey::Enter { shift: true } => self.send_with_modifier(layout.shift, layout.key_return),
+            TypedKey::Tab => self.tap(layout.key_tab),
+        }
+    }
+
+    fn delete_backward(&self, count: usize) -> Result<()> {
+        let layout = self.layout()?;
+        for _ in 0..count {
+            self.tap(layout.key_backspace)?;
+            thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+        }
+        Ok(())
+    }
+
+    fn foreground_app(&self) -> Option<String> {
+        // _NET_ACTIVE_WINDOW → _NET_WM_PID → /proc/<pid>/comm
+        let window = self.property32(self.root, b"_NET_ACTIVE_WINDOW", AtomEnum::WINDOW)?;
+        let pid = self.property32(window, b"_NET_WM_PID", AtomEnum::CARDINAL)?;
+        let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+        Some(comm.trim().to_string())
+    }
+
+    fn release_modifiers(&self) -> Result<()> {
+        // 只释放真正被按下的修饰键，与 SendInput 后端行为一致
+        let layout = self.layout()?;
+        let pressed = self.conn.query_keymap()?.reply()?.keys;
+        for &keycode in &layout.modifiers {
+            if pressed[keycode as usize / 8] & (1 << (keycode % 8)) != 0 {
+                let _ = self.send(keycode, false);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 字符对应的 keysym：Latin-1 直接对应，其余使用 Unicode keysym（0x01000000 + 码点）
+fn char_keysym(ch: char) -> Keysym {
+    let code = ch as u32;
+    if (0x20..=0x7e).contains(&code) || (0xa0..=0xff).contains(&code) {
+        code
+    } else {
+        0x0100_0000 | code
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keymap_find() {
+        // 每个 keycode 两个 keysym，从 keycode 8 开始
+        let keymap = KeyMap {
+            min_keycode: 8,
+            per_keycode: 2,
+            keysyms: vec![0, 0, XK_C, 0x0043, XK_V, 0x0056, XK_CONTROL_L, 0],
+        };
+        assert_eq!(keymap.find(XK_C), Some((9, 0)));
+        assert_eq!(keymap.find(0x0056), Some((10, 1)));
+        assert_eq!(keymap.find(XK_CONTROL_L), Some((11, 0)));
+        assert_eq!(keymap.find(XK_SHIFT_L), None);
+        assert_eq!(keymap.unused_keycode(), Some(8));
+
+        let empty = KeyMap { min_keycode: 8, per_keycode: 0, keysyms: vec![] };
+        assert_eq!(empty.find(XK_C), None);
+        assert_eq!(empty.unused_keycode(), None);
+    }
+
+    #[test]
+    fn test_char_keysym() {
+        assert_eq!(char_keysym('a'), 0x61);
+        assert_eq!(char_keysym('é'), 0xe9);
+        assert_eq!(char_keysym('你'), 0x0100_4f60);
+        assert_eq!(char_keysym('😀'), 0x0101_f600);
+    }
+
+    #[test]
+    fn test_affects_layout() {
+        let notify = |request, first_keycode, count| MappingNotifyEvent {
+            response_type: xproto::MAPPING_NOTIFY_EVENT,
+            sequence: 0,
+            request,
+            first_keycode,
+            count,
+        };
+        assert!(affects_layout(&notify(Mapping::KEYBOARD, 8, 248), Some(200)));
+        assert!(affects_layout(&notify(Mapping::KEYBOARD, 8, 1), Some(200)));
+        assert!(affects_layout(&notify(Mapping::KEYBOARD, 200, 1), None));
+        // 自身对空闲 keycode 的临时映射
+        assert!(!affects_layout(&notify(Mapping::KEYBOARD, 200, 1), Some(200)));
+        assert!(!affects_layout(&notify(Mapping::MODIFIER, 0, 0), Some(200)));
+    }
+}
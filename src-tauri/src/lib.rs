@@ -1,37 +1,56 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod app_data;
+mod app_profiles;
 mod assistant_processor;
+mod assistant_tools;
+mod audio_archive;
+mod audio_capture;
 mod audio_mute_manager;
 mod audio_recorder;
 mod audio_utils;
 mod asr;
 mod beep_player;
+mod chat_backend;
 mod clipboard_manager;
 mod config;
+mod conversation;
+mod fallback_audio;
+mod file_transcriber;
 mod hotkey_service;
+mod insertion;
+mod llm_cache;
+mod llm_fallback;
 mod llm_post_processor;
+mod mic_diagnostics;
 mod openai_client;
+mod output_sink;
 mod pipeline;
+mod pre_roll_buffer;
 mod streaming_recorder;
 mod text_inserter;
 mod usage_stats;
 mod win32_input;
 
+use audio_archive::{ArchiveMetadata, AudioArchive};
 use audio_mute_manager::AudioMuteManager;
 use audio_recorder::AudioRecorder;
 use asr::{QwenASRClient, SenseVoiceClient, DoubaoASRClient, QwenRealtimeClient, DoubaoRealtimeClient, DoubaoRealtimeSession, RealtimeSession};
 use assistant_processor::AssistantProcessor;
 use config::AppConfig;
 use hotkey_service::HotkeyService;
+use llm_cache::LlmCache;
 use llm_post_processor::LlmPostProcessor;
-use pipeline::{AssistantPipeline, NormalPipeline, TranscriptionContext};
+use pipeline::{AssistantPipeline, NormalPipeline, PipelineResult, TranscriptionContext};
+use pre_roll_buffer::PreRollBuffer;
 use streaming_recorder::StreamingRecorder;
 use text_inserter::TextInserter;
 use usage_stats::UsageStats;
 
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use tauri::{
     AppHandle, Emitter, Manager,
     tray::{TrayIconBuilder, TrayIconEvent, MouseButton, MouseButtonState},
@@ -85,6 +104,8 @@ struct AppState {
     audio_recorder: Arc<Mutex<Option<AudioRecorder>>>,
     streaming_recorder: Arc<Mutex<Option<StreamingRecorder>>>,
     text_inserter: Arc<Mutex<Option<TextInserter>>>,
+    /// 文本插入后端（启动时自动选择，全程复用）
+    insertion_backend: Arc<dyn insertion::InsertionBackend>,
     post_processor: Arc<Mutex<Option<LlmPostProcessor>>>,
     /// AI 助手处理器（支持双系统提示词）
     assistant_processor: Arc<Mutex<Option<AssistantProcessor>>>,
@@ -124,6 +145,32 @@ struct AppState {
     usage_stats: Arc<Mutex<UsageStats>>,
     /// 录音开始时间（用于计算录音时长）
     recording_start_instant: Arc<Mutex<Option<std::time::Instant>>>,
+    /// 预录缓冲（启用时常驻采集，仅保存在内存中）
+    pre_roll_buffer: Arc<Mutex<Option<PreRollBuffer>>>,
+    /// 录音归档（配置 + 待归档的录音）
+    audio_archive: Arc<Mutex<AudioArchive>>,
+    /// 静音自动停止配置（松手模式）
+    auto_stop_config: Arc<Mutex<config::AutoStopConfig>>,
+    /// 静音监控代数（每次进入锁定录音递增，旧的监控任务据此退出）
+    auto_stop_generation: Arc<AtomicU64>,
+    /// 提示音配置
+    audio_cue_config: Arc<Mutex<config::AudioCueConfig>>,
+    /// 文本插入方式配置（TextInserter 与 AI 助手共用）
+    insertion_config: Arc<Mutex<config::InsertionConfig>>,
+    /// 结果输出目标配置
+    output_config: Arc<Mutex<config::OutputConfig>>,
+    /// 上次插入记录（用于撤销）
+    last_insertion: Arc<Mutex<Option<insertion::InsertionRecord>>>,
+    /// 按应用配置
+    app_profiles: Arc<Mutex<Vec<config::AppProfile>>>,
+    /// 本次录音匹配到的应用配置（热键按下时按前台窗口选择）
+    active_profile: Arc<Mutex<Option<config::AppProfile>>>,
+    /// AI 助手回答是否已被取消（取消转录时置位，热键按下时复位；停止流式生成和插入）
+    llm_cancelled: Arc<AtomicBool>,
+    /// 等待用户确认的 AI 助手命令（确认 ID → 结果通道）
+    tool_confirmations: Arc<Mutex<HashMap<String, tokio::sync::oneshot::Sender<bool>>>>,
+    /// 上次捕获选中文本时的 PRIMARY 选区（Linux，用于判断选区是否过期）
+    last_primary_selection: Arc<Mutex<Option<String>>>,
 }
 
 // Tauri Commands
@@ -142,7 +189,16 @@ async fn save_config(
     dual_hotkey_config: Option<config::DualHotkeyConfig>,
     assistant_config: Option<config::AssistantConfig>,
     enable_mute_other_apps: Option<bool>,
+    mute_other_apps_config: Option<config::MuteOtherAppsConfig>,
     dictionary: Option<Vec<String>>,
+    pre_roll_config: Option<config::PreRollConfig>,
+    audio_archive_config: Option<config::AudioArchiveConfig>,
+    auto_stop_config: Option<config::AutoStopConfig>,
+    recording_limits_config: Option<config::RecordingLimitsConfig>,
+    audio_cue_config: Option<config::AudioCueConfig>,
+    insertion_config: Option<config::InsertionConfig>,
+    output_config: Option<config::OutputConfig>,
+    app_profiles: Option<Vec<config::AppProfile>>,
 ) -> Result<String, String> {
     tracing::info!("保存配置...");
 
@@ -222,7 +278,16 @@ async fn save_config(
         dual_hotkey_config: final_dual_hotkey_config,
         transcription_mode: existing.transcription_mode,
         enable_mute_other_apps: enable_mute_other_apps.unwrap_or(existing.enable_mute_other_apps),
+        mute_other_apps_config: mute_other_apps_config.unwrap_or(existing.mute_other_apps_config),
         dictionary: final_dictionary,
+        pre_roll_config: pre_roll_config.unwrap_or(existing.pre_roll_config),
+        audio_archive_config: audio_archive_config.unwrap_or(existing.audio_archive_config),
+        auto_stop_config: auto_stop_config.unwrap_or(existing.auto_stop_config),
+        recording_limits_config: recording_limits_config.unwrap_or(existing.recording_limits_config),
+        audio_cue_config: audio_cue_config.unwrap_or(existing.audio_cue_config),
+        insertion_config: insertion_config.unwrap_or(existing.insertion_config),
+        output_config: output_config.unwrap_or(existing.output_config),
+        app_profiles: app_profiles.unwrap_or(existing.app_profiles),
     };
 
     config
@@ -244,6 +309,27 @@ async fn load_usage_stats() -> Result<UsageStats, String> {
     UsageStats::load().map_err(|e| format!("加载统计数据失败: {}", e))
 }
 
+/// 转写已有的音频文件（WAV/MP3/FLAC/OGG/M4A）
+///
+/// 使用已保存的 ASR 配置和竞速策略，preset_id 不为空时再经过对应的 LLM 预设处理
+#[tauri::command]
+async fn transcribe_audio_file(
+    path: String,
+    preset_id: Option<String>,
+    save_to_file: Option<bool>,
+) -> Result<file_transcriber::FileTranscriptionResult, String> {
+    tracing::info!("转写音频文件: {}", path);
+    let config = AppConfig::load().map_err(|e| format!("加载配置失败: {}", e))?;
+    file_transcriber::transcribe_file(
+        &config,
+        std::path::Path::new(&path),
+        preset_id.as_deref(),
+        save_to_file.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| format!("文件转写失败: {}", e))
+}
+
 /// 处理录音开始的核心逻辑
 async fn handle_recording_start(
     app: AppHandle,
@@ -259,6 +345,7 @@ async fn handle_recording_start(
     doubao_access_token: Option<String>,
     audio_mute_manager: Arc<Mutex<Option<AudioMuteManager>>>,
     dictionary: Vec<String>,
+    pre_roll_buffer: Arc<Mutex<Option<PreRollBuffer>>>,
 ) {
     tracing::info!("检测到快捷键按下");
 
@@ -290,13 +377,13 @@ async fn handle_recording_start(
     }
 
     if use_realtime {
-        let provider = realtime_provider.lock().unwrap().clone();
+        let provider = profile_realtime_provider(&app, &realtime_provider);
         match provider {
             Some(config::AsrProvider::Doubao) => {
-                handle_doubao_realtime_start(app, streaming_recorder, doubao_session, audio_sender_handle, doubao_app_id, doubao_access_token, dictionary).await;
+                handle_doubao_realtime_start(app, streaming_recorder, doubao_session, audio_sender_handle, doubao_app_id, doubao_access_token, dictionary, pre_roll_buffer).await;
             }
             _ => {
-                handle_qwen_realtime_start(app, streaming_recorder, active_session, audio_sender_handle, api_key, dictionary).await;
+                handle_qwen_realtime_start(app, streaming_recorder, active_session, audio_sender_handle, api_key, dictionary, pre_roll_buffer).await;
             }
         }
     } else {
@@ -307,7 +394,8 @@ async fn handle_recording_start(
                 tracing::warn!("发现正在进行的录音，先停止它");
                 let _ = rec.stop_recording_to_memory();
             }
-            if let Err(e) = rec.start_recording(Some(app.clone())) {
+            let pre_roll_guard = pre_roll_buffer.lock().unwrap();
+            if let Err(e) = rec.start_recording(Some(app.clone()), pre_roll_guard.as_ref()) {
                 emit_error_and_hide_overlay(&app, format!("录音失败: {}", e));
             }
         } else {
@@ -325,6 +413,7 @@ async fn handle_doubao_realtime_start(
     doubao_app_id: Option<String>,
     doubao_access_token: Option<String>,
     dictionary: Vec<String>,
+    pre_roll_buffer: Arc<Mutex<Option<PreRollBuffer>>>,
 ) {
     tracing::info!("启动豆包实时流式转录...");
 
@@ -336,7 +425,8 @@ async fn handle_doubao_realtime_start(
                 tracing::warn!("发现正在进行的流式录音，先停止它");
                 let _ = rec.stop_streaming();
             }
-            match rec.start_streaming(Some(app.clone())) {
+            let pre_roll_guard = pre_roll_buffer.lock().unwrap();
+            match rec.start_streaming(Some(app.clone()), pre_roll_guard.as_ref()) {
                 Ok(rx) => Some(rx),
                 Err(e) => {
                     emit_error_and_hide_overlay(&app, format!("录音失败: {}", e));
@@ -417,6 +507,7 @@ async fn handle_qwen_realtime_start(
     audio_sender_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
     api_key: String,
     dictionary: Vec<String>,
+    pre_roll_buffer: Arc<Mutex<Option<PreRollBuffer>>>,
 ) {
     tracing::info!("启动千问实时流式转录...");
 
@@ -435,7 +526,10 @@ async fn handle_qwen_realtime_start(
         }
     }
 
-    let realtime_client = QwenRealtimeClient::new(api_key, dictionary);
+    let mut realtime_client = QwenRealtimeClient::new(api_key, dictionary);
+    if let Some(language) = active_profile(&app).and_then(|p| p.language) {
+        realtime_client = realtime_client.with_language(language);
+    }
     match realtime_client.start_session().await {
         Ok(session) => {
             tracing::info!("千问 WebSocket 连接已建立");
@@ -448,7 +542,8 @@ async fn handle_qwen_realtime_start(
                         tracing::warn!("发现正在进行的流式录音，先停止它");
                         let _ = rec.stop_streaming();
                     }
-                    match rec.start_streaming(Some(app.clone())) {
+                    let pre_roll_guard = pre_roll_buffer.lock().unwrap();
+                    match rec.start_streaming(Some(app.clone()), pre_roll_guard.as_ref()) {
                         Ok(rx) => Some(rx),
                         Err(e) => {
                             emit_error_and_hide_overlay(&app, format!("录音失败: {}", e));
@@ -502,7 +597,8 @@ async fn handle_qwen_realtime_start(
                     tracing::warn!("发现正在进行的流式录音，先停止它");
                     let _ = rec.stop_streaming();
                 }
-                if let Err(e) = rec.start_streaming(Some(app.clone())) {
+                let pre_roll_guard = pre_roll_buffer.lock().unwrap();
+                if let Err(e) = rec.start_streaming(Some(app.clone()), pre_roll_guard.as_ref()) {
                     emit_error_and_hide_overlay(&app, format!("录音失败: {}", e));
                 }
             } else {
@@ -512,6 +608,119 @@ async fn handle_qwen_realtime_start(
     }
 }
 
+/// 按配置启动或关闭预录缓冲
+///
+/// 每次都会先释放旧缓冲（停止采集并清零），避免时长变化后残留旧数据
+fn apply_pre_roll_config(pre_roll_buffer: &Arc<Mutex<Option<PreRollBuffer>>>, cfg: &config::PreRollConfig) {
+    let mut guard = pre_roll_buffer.lock().unwrap();
+    *guard = None;
+    if !cfg.enabled {
+        return;
+    }
+    match PreRollBuffer::start(cfg.duration_ms) {
+        Ok(buffer) => {
+            *guard = Some(buffer);
+            tracing::info!("预录缓冲已启用: {}ms", cfg.duration_ms);
+        }
+        Err(e) => tracing::warn!("启动预录缓冲失败，将不使用预录: {}", e),
+    }
+}
+
+/// 按当前配置播放提示音
+fn play_cue(app: &AppHandle, mode: config::TriggerMode, cue: beep_player::AudioCue) {
+    let state = app.state::<AppState>();
+    let cfg = state.audio_cue_config.lock().unwrap();
+    beep_player::play_cue(&cfg, mode, cue);
+}
+
+/// 获取当前正在录音的录音器的语音活动跟踪器
+fn active_voice_activity(state: &AppState) -> Option<audio_utils::VoiceActivityTracker> {
+    if let Some(ref rec) = *state.streaming_recorder.lock().unwrap() {
+        if rec.is_recording() {
+            return Some(rec.voice_activity());
+        }
+    }
+    if let Some(ref rec) = *state.audio_recorder.lock().unwrap() {
+        if rec.is_recording() {
+            return Some(rec.voice_activity());
+        }
+    }
+    None
+}
+
+/// 当前流式录音是否已达到最大录音时长
+fn recording_limit_reached(state: &AppState) -> bool {
+    state.streaming_recorder.lock().unwrap()
+        .as_ref()
+        .is_some_and(|rec| rec.is_recording() && rec.limit_reached())
+}
+
+/// 松手模式自动完成监控：持续静音达到设定时长或录音达到最大时长后自动完成录音
+///
+/// 最后 countdown_secs 秒向悬浮窗发送 auto_stop_countdown（剩余秒数），
+/// 重新说话时发送 null 取消倒计时。用户手动完成/取消录音后任务自动退出
+fn spawn_auto_stop_monitor(app: AppHandle) {
+    let state = app.state::<AppState>();
+    let cfg = state.auto_stop_config.lock().unwrap().clone();
+    let silence_enabled = cfg.enabled && cfg.silence_secs > 0;
+    let generation = state.auto_stop_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let silence_limit = std::time::Duration::from_secs(cfg.silence_secs as u64);
+    let countdown_secs = cfg.countdown_secs.min(cfg.silence_secs);
+
+    if silence_enabled {
+        tracing::info!("静音自动停止监控已启动: 静音 {}s 后结束录音", cfg.silence_secs);
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut last_countdown: Option<u32> = None;
+        loop {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+            let state = app.state::<AppState>();
+            if state.auto_stop_generation.load(Ordering::SeqCst) != generation
+                || !state.is_recording_locked.load(Ordering::SeqCst)
+            {
+                break;
+            }
+
+            // 达到最大时长后录音器已停止采集，走与静音自动停止相同的完成流程
+            if recording_limit_reached(&state) {
+                tracing::info!("录音已达到最大时长，自动完成松手模式录音");
+                let _ = app.emit("auto_stop_countdown", None::<u32>);
+                if let Err(e) = finish_locked_recording(app.clone()).await {
+                    tracing::warn!("达到最大时长后自动完成录音失败: {}", e);
+                }
+                break;
+            }
+
+            if !silence_enabled {
+                continue;
+            }
+            let Some(tracker) = active_voice_activity(&state) else {
+                continue;
+            };
+
+            let silence = tracker.silence_duration();
+            if silence >= silence_limit {
+                tracing::info!("检测到持续静音 {:.1}s，自动完成松手模式录音", silence.as_secs_f32());
+                let _ = app.emit("auto_stop_countdown", None::<u32>);
+                if let Err(e) = finish_locked_recording(app.clone()).await {
+                    tracing::warn!("静音自动停止失败: {}", e);
+                }
+                break;
+            }
+
+            // 剩余秒数（向上取整），进入倒计时窗口后才发送
+            let remaining = (silence_limit - silence).as_millis().div_ceil(1000) as u32;
+            let countdown = (remaining <= countdown_secs).then_some(remaining);
+            if countdown != last_countdown {
+                let _ = app.emit("auto_stop_countdown", countdown);
+                last_countdown = countdown;
+            }
+        }
+    });
+}
+
 #[tauri::command]
 async fn start_app(
     app_handle: AppHandle,
@@ -526,7 +735,16 @@ async fn start_app(
     dual_hotkey_config: Option<config::DualHotkeyConfig>,
     assistant_config: Option<config::AssistantConfig>,
     enable_mute_other_apps: Option<bool>,
+    mute_other_apps_config: Option<config::MuteOtherAppsConfig>,
     dictionary: Option<Vec<String>>,
+    pre_roll_config: Option<config::PreRollConfig>,
+    audio_archive_config: Option<config::AudioArchiveConfig>,
+    auto_stop_config: Option<config::AutoStopConfig>,
+    recording_limits_config: Option<config::RecordingLimitsConfig>,
+    audio_cue_config: Option<config::AudioCueConfig>,
+    insertion_config: Option<config::InsertionConfig>,
+    output_config: Option<config::OutputConfig>,
+    app_profiles: Option<Vec<config::AppProfile>>,
 ) -> Result<String, String> {
     tracing::info!("启动应用...");
 
@@ -627,7 +845,7 @@ async fn start_app(
         let mut processor_guard = state.post_processor.lock().unwrap();
         let llm_cfg = llm_config.unwrap_or_default();
         tracing::info!("[DEBUG] LLM 后处理配置: enabled={}, api_key_len={}", enable_post_process_mode, llm_cfg.api_key.len());
-        if enable_post_process_mode && !llm_cfg.api_key.trim().is_empty() {
+        if enable_post_process_mode && llm_cfg.is_valid() {
             tracing::info!("LLM 后处理器配置: endpoint={}, model={}", llm_cfg.endpoint, llm_cfg.model);
             *processor_guard = Some(LlmPostProcessor::new(llm_cfg));
             tracing::info!("LLM 后处理器已初始化");
@@ -658,7 +876,7 @@ async fn start_app(
 
     // 初始化文本插入器
     tracing::info!("[DEBUG] 初始化文本插入器...");
-    let text_inserter = TextInserter::new()
+    let text_inserter = TextInserter::new(Arc::clone(&state.insertion_backend), Arc::clone(&state.insertion_config))
         .map_err(|e| format!("初始化文本插入器失败: {}", e))?;
     *state.text_inserter.lock().unwrap() = Some(text_inserter);
     tracing::info!("[DEBUG] 文本插入器初始化完成");
@@ -676,6 +894,12 @@ async fn start_app(
             *manager_lock = Some(AudioMuteManager::new(should_mute));
             tracing::info!("AudioMuteManager 已创建: enabled={}", should_mute);
         }
+        // 静音 / 闪避方式（未传入时沿用已保存的配置）
+        let mute_cfg = mute_other_apps_config
+            .unwrap_or_else(|| AppConfig::load().map(|c| c.mute_other_apps_config).unwrap_or_default());
+        if let Some(ref manager) = *manager_lock {
+            manager.set_config(mute_cfg);
+        }
     }
 
     // 根据模式初始化录音器
@@ -683,8 +907,12 @@ async fn start_app(
     *state.streaming_recorder.lock().unwrap() = None;
 
     if use_realtime_mode {
-        let streaming_recorder = StreamingRecorder::new()
+        let mut streaming_recorder = StreamingRecorder::new()
             .map_err(|e| format!("初始化流式录音器失败: {}", e))?;
+        // 录音限制（未传入时沿用已保存的配置）
+        let limits = recording_limits_config
+            .unwrap_or_else(|| AppConfig::load().map(|c| c.recording_limits_config).unwrap_or_default());
+        streaming_recorder.set_limits(limits);
         *state.streaming_recorder.lock().unwrap() = Some(streaming_recorder);
     } else {
         let audio_recorder = AudioRecorder::new()
@@ -692,6 +920,43 @@ async fn start_app(
         *state.audio_recorder.lock().unwrap() = Some(audio_recorder);
     }
 
+    // 初始化预录缓冲（未传入时沿用已保存的配置）
+    {
+        let pre_roll_cfg = pre_roll_config
+            .unwrap_or_else(|| AppConfig::load().map(|c| c.pre_roll_config).unwrap_or_default());
+        apply_pre_roll_config(&state.pre_roll_buffer, &pre_roll_cfg);
+    }
+
+    // 更新录音归档配置（未传入时保持当前配置）
+    if let Some(cfg) = audio_archive_config {
+        tracing::info!("录音归档: enabled={}", cfg.enabled);
+        state.audio_archive.lock().unwrap().set_config(cfg);
+    }
+
+    // 更新静音自动停止配置（未传入时沿用已保存的配置）
+    {
+        let auto_stop_cfg = auto_stop_config
+            .unwrap_or_else(|| AppConfig::load().map(|c| c.auto_stop_config).unwrap_or_default());
+        tracing::info!("静音自动停止: enabled={}, 静音={}s", auto_stop_cfg.enabled, auto_stop_cfg.silence_secs);
+        *state.auto_stop_config.lock().unwrap() = auto_stop_cfg;
+    }
+
+    // 更新提示音配置（未传入时沿用已保存的配置）
+    *state.audio_cue_config.lock().unwrap() = audio_cue_config
+        .unwrap_or_else(|| AppConfig::load().map(|c| c.audio_cue_config).unwrap_or_default());
+
+    // 更新文本插入方式配置（未传入时沿用已保存的配置）
+    *state.insertion_config.lock().unwrap() = insertion_config
+        .unwrap_or_else(|| AppConfig::load().map(|c| c.insertion_config).unwrap_or_default());
+
+    // 更新输出目标配置（未传入时沿用已保存的配置）
+    *state.output_config.lock().unwrap() = output_config
+        .unwrap_or_else(|| AppConfig::load().map(|c| c.output_config).unwrap_or_default());
+
+    // 更新按应用配置（未传入时沿用已保存的配置）
+    *state.app_profiles.lock().unwrap() = app_profiles
+        .unwrap_or_else(|| AppConfig::load().map(|c| c.app_profiles).unwrap_or_default());
+
     // 启动全局快捷键监听（双模式支持）
     tracing::info!("[DEBUG] 准备热键配置...");
     let mut dual_hotkey_cfg = dual_hotkey_config.unwrap_or_default();
@@ -725,6 +990,8 @@ async fn start_app(
     let current_trigger_mode_start = Arc::clone(&state.current_trigger_mode);
     // 统计数据相关
     let recording_start_instant_start = Arc::clone(&state.recording_start_instant);
+    // 预录缓冲
+    let pre_roll_buffer_start = Arc::clone(&state.pre_roll_buffer);
 
     // 保存当前的 provider 配置和凭证
     // 从 asr_config 中提取正确的 API Key（用于实时ASR）
@@ -758,6 +1025,9 @@ async fn start_app(
     let post_processor_stop = Arc::clone(&state.post_processor);
     let assistant_processor_stop = Arc::clone(&state.assistant_processor);
     let text_inserter_stop = Arc::clone(&state.text_inserter);
+    let insertion_backend_stop = Arc::clone(&state.insertion_backend);
+    let last_primary_selection_stop = Arc::clone(&state.last_primary_selection);
+    let insertion_config_stop = Arc::clone(&state.insertion_config);
     let qwen_client_stop = Arc::clone(&state.qwen_client);
     let sensevoice_client_stop = Arc::clone(&state.sensevoice_client);
     let doubao_client_stop = Arc::clone(&state.doubao_client);
@@ -814,6 +1084,8 @@ async fn start_app(
         } else {
             tracing::warn!("未能获取目标窗口句柄");
         }
+        select_active_profile(&app_handle_start, target_hwnd);
+        app_handle_start.state::<AppState>().llm_cancelled.store(false, Ordering::SeqCst);
 
         // 保存当前触发模式
         *current_trigger_mode_start.lock().unwrap() = Some(trigger_mode);
@@ -823,7 +1095,7 @@ async fn start_app(
         // 注意：剪贴板捕获已移至 on_stop 回调
         // 原因：在 on_start 时物理按键仍被按住，模拟 Ctrl+C 会与 Alt/Meta 等修饰键冲突
 
-        beep_player::play_start_beep();
+        play_cue(&app_handle_start, trigger_mode, beep_player::AudioCue::Start);
 
         let app = app_handle_start.clone();
         let recorder = Arc::clone(&audio_recorder_start);
@@ -840,6 +1112,7 @@ async fn start_app(
         let audio_mute_manager = Arc::clone(&audio_mute_manager_start);
         let dictionary_state = Arc::clone(&dictionary_state_start);
         let recording_start_instant_spawn = Arc::clone(&recording_start_instant_start);
+        let pre_roll_buffer = Arc::clone(&pre_roll_buffer_start);
 
         tauri::async_runtime::spawn(async move {
             // 记录录音开始时间（包含录音准备时间：静音、显示窗口等）
@@ -863,6 +1136,7 @@ async fn start_app(
                 doubao_access_token,
                 audio_mute_manager,
                 dictionary,
+                pre_roll_buffer,
             ).await;
 
             // 2. 录音初始化完成后，再发送锁定事件
@@ -870,7 +1144,9 @@ async fn start_app(
             if is_release_mode && trigger_mode == config::TriggerMode::Dictation {
                 is_recording_locked_spawn.store(true, Ordering::SeqCst);
                 let _ = app.emit("recording_locked", ());
+                play_cue(&app, trigger_mode, beep_player::AudioCue::Lock);
                 tracing::info!("通过松手模式快捷键启动，直接进入锁定状态");
+                spawn_auto_stop_monitor(app.clone());
             }
         });
     };
@@ -942,6 +1218,9 @@ async fn start_app(
         let post_processor = Arc::clone(&post_processor_stop);
         let assistant_processor = Arc::clone(&assistant_processor_stop);
         let text_inserter = Arc::clone(&text_inserter_stop);
+        let insertion_backend = Arc::clone(&insertion_backend_stop);
+        let last_primary_selection = Arc::clone(&last_primary_selection_stop);
+        let insertion_config = insertion_config_stop.lock().unwrap().clone();
 
         // 获取目标窗口句柄（用于焦点恢复）
         let target_hwnd = *target_window_stop.lock().unwrap();
@@ -951,7 +1230,7 @@ async fn start_app(
         let recording_start_instant = Arc::clone(&recording_start_instant_stop);
 
         // 播放停止录音提示音
-        beep_player::play_stop_beep();
+        play_cue(&app, trigger_mode, beep_player::AudioCue::Stop);
 
         tauri::async_runtime::spawn(async move {
             let _ = app.emit("recording_stopped", ());
@@ -1004,7 +1283,11 @@ async fn start_app(
 
                     // 捕获选中文本（此时用户已松开热键，Ctrl+C 模拟安全）
                     tracing::info!("AI 助手模式：开始捕获选中文本...");
-                    let (clipboard_guard, selected_text) = match clipboard_manager::get_selected_text() {
+                    let selection = clipboard_manager::get_selected_text(
+                        insertion_backend.as_ref(),
+                        &mut last_primary_selection.lock().unwrap(),
+                    );
+                    let (clipboard_guard, selected_text) = match selection {
                         Ok((guard, text)) => {
                             if let Some(ref t) = text {
                                 tracing::info!("已捕获选中文本: {} 字符", t.len());
@@ -1028,6 +1311,8 @@ async fn start_app(
                         realtime_provider_state,
                         audio_sender_handle,
                         assistant_processor,
+                        insertion_backend,
+                        insertion_config,
                         clipboard_guard,
                         selected_text,
                         qwen_client_state,
@@ -1044,6 +1329,19 @@ async fn start_app(
         });
     };
 
+    let app_handle_reset = app_handle.clone();
+    hotkey_service.set_conversation_reset_handler(move || reset_conversation(&app_handle_reset));
+
+    let app_handle_undo = app_handle.clone();
+    hotkey_service.set_undo_insertion_handler(move || {
+        let app = app_handle_undo.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = undo_last_insertion(app).await {
+                tracing::warn!("快捷键撤销插入失败: {}", e);
+            }
+        });
+    });
+
     tracing::info!("[DEBUG] 准备激活热键服务...");
     hotkey_service
         .activate_dual(dual_hotkey_cfg.clone(), on_start, on_stop)
@@ -1074,6 +1372,8 @@ async fn handle_assistant_mode(
     realtime_provider: Arc<Mutex<Option<config::AsrProvider>>>,
     audio_sender_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
     assistant_processor: Arc<Mutex<Option<AssistantProcessor>>>,
+    insertion_backend: Arc<dyn insertion::InsertionBackend>,
+    insertion_config: config::InsertionConfig,
     clipboard_guard: Option<clipboard_manager::ClipboardGuard>,
     selected_text: Option<String>,
     qwen_client_state: Arc<Mutex<Option<QwenASRClient>>>,
@@ -1116,8 +1416,8 @@ async fn handle_assistant_mode(
         }
 
         // 获取实时转录结果
-        let provider = realtime_provider.lock().unwrap().clone();
-        let result = match provider {
+        let provider = profile_realtime_provider(&app, &realtime_provider);
+        let result = match provider.clone() {
             Some(config::AsrProvider::Doubao) => {
                 let mut session_guard = doubao_session.lock().await;
                 if let Some(ref mut session) = *session_guard {
@@ -1144,6 +1444,9 @@ async fn handle_assistant_mode(
                 }
             }
         };
+        if result.is_ok() {
+            note_asr_provider(&app, provider.as_ref());
+        }
 
         (result, audio_data)
     } else {
@@ -1175,9 +1478,15 @@ async fn handle_assistant_mode(
             let doubao = { doubao_client_state.lock().unwrap().clone() };
             let sensevoice = { sensevoice_client_state.lock().unwrap().clone() };
             let active_prov = realtime_provider.lock().unwrap().clone();
+            let (qwen, active_prov) = with_profile_asr(&app, qwen, active_prov);
             let fallback_prov = app.state::<AppState>().fallback_provider.lock().unwrap().clone();
 
-            transcribe_with_available_clients(qwen, doubao, sensevoice, data, enable_fb, active_prov, fallback_prov, "(AI助手HTTP) ").await
+            transcribe_with_available_clients(qwen, doubao, sensevoice, data, enable_fb, active_prov, fallback_prov, "(AI助手HTTP) ")
+                .await
+                .map(|(text, provider)| {
+                    note_asr_provider(&app, Some(&provider));
+                    text
+                })
         } else {
             Err(anyhow::anyhow!("未获取到音频数据"))
         };
@@ -1187,6 +1496,10 @@ async fn handle_assistant_mode(
 
     let asr_time_ms = asr_start.elapsed().as_millis() as u64;
 
+    if let Some(ref data) = audio_data {
+        stash_recording_for_archive(&app, data);
+    }
+
     // 2. 如果实时模式失败且有音频数据，尝试 HTTP 备用
     let final_result = if asr_result.is_err() && audio_data.is_some() {
         tracing::warn!("实时 ASR 失败，尝试 HTTP 备用");
@@ -1196,9 +1509,15 @@ async fn handle_assistant_mode(
         let doubao = { doubao_client_state.lock().unwrap().clone() };
         let sensevoice = { sensevoice_client_state.lock().unwrap().clone() };
         let active_prov = realtime_provider.lock().unwrap().clone();
+        let (qwen, active_prov) = with_profile_asr(&app, qwen, active_prov);
         let fallback_prov = app.state::<AppState>().fallback_provider.lock().unwrap().clone();
 
-        transcribe_with_available_clients(qwen, doubao, sensevoice, &data, enable_fb, active_prov, fallback_prov, "(AI助手备用) ").await
+        transcribe_with_available_clients(qwen, doubao, sensevoice, &data, enable_fb, active_prov, fallback_prov, "(AI助手备用) ")
+            .await
+            .map(|(text, provider)| {
+                note_asr_provider(&app, Some(&provider));
+                text
+            })
     } else {
         asr_result
     };
@@ -1209,15 +1528,37 @@ async fn handle_assistant_mode(
 
     let context = TranscriptionContext {
         selected_text,
-        ..Default::default()
+        profile: active_profile(&app),
     };
 
+    let sinks = output_sinks(&app, config::TriggerMode::AiAssistant);
+    let llm_cancelled = app.state::<AppState>().llm_cancelled.clone();
     let pipeline_result = pipeline
-        .process(&app, processor, clipboard_guard, final_result, asr_time_ms, context, target_hwnd)
+        .process(
+            &app,
+            processor,
+            insertion_backend,
+            insertion_config,
+            clipboard_guard,
+            final_result,
+            asr_time_ms,
+            context,
+            target_hwnd,
+            output_sink::wants_insert(&sinks),
+            llm_cancelled.clone(),
+        )
         .await;
 
     // 4. 处理结果
     match pipeline_result {
+        Ok(result) if llm_cancelled.load(Ordering::SeqCst) => {
+            // 取消事件已由 cancel_transcription 发送；已插入的部分仍可撤销
+            tracing::info!("AI 助手回答已取消 (已插入: {})", result.inserted);
+            let _ = recording_start_instant.lock().unwrap().take();
+            if let Some(record) = result.insertion {
+                *app.state::<AppState>().last_insertion.lock().unwrap() = Some(record);
+            }
+        }
         Ok(result) => {
             hide_overlay_window(&app).await;
 
@@ -1233,31 +1574,46 @@ async fn handle_assistant_mode(
                 }
             }
 
+            let transcription_id = uuid::Uuid::new_v4().to_string();
+            archive_pending_recording(&app, &transcription_id, &result);
+            // 只执行了工具动作时没有要输出的文本
+            if !result.text.is_empty() {
+                deliver_to_sinks(&app, sinks, &transcription_id, &result);
+            }
+            if let Some(ref record) = result.insertion {
+                *app.state::<AppState>().last_insertion.lock().unwrap() = Some(record.clone());
+            }
+
             let transcription_result = TranscriptionResult {
-                text: result.text,
+                id: transcription_id,
+                text: result.display_text(),
                 original_text: result.original_text,
                 asr_time_ms: result.asr_time_ms,
                 llm_time_ms: result.llm_time_ms,
                 total_time_ms: result.total_time_ms,
                 mode: Some(format!("{:?}", result.mode).to_lowercase()),
                 inserted: Some(result.inserted),
+                llm_endpoint: result.llm_endpoint,
             };
 
             let _ = app.emit("transcription_complete", transcription_result);
+            play_cue(&app, config::TriggerMode::AiAssistant, beep_player::AudioCue::AnswerReady);
         }
         Err(e) => {
             hide_overlay_window(&app).await;
             // 清理录音开始时间（防止下次录音时使用错误的时间）
             let _ = recording_start_instant.lock().unwrap().take();
+            let _ = app.state::<AppState>().audio_archive.lock().unwrap().take_pending();
             tracing::error!("AI 助手处理失败: {}", e);
             let _ = app.emit("error", format!("AI 助手处理失败: {}", e));
+            play_cue(&app, config::TriggerMode::AiAssistant, beep_player::AudioCue::Error);
         }
     }
 }
 
 /// 统一的 HTTP ASR 转录逻辑
 ///
-/// 根据配置的 active_provider 和 fallback_provider 选择合适的转录方式
+/// 根据配置的 active_provider 和 fallback_provider 选择合适的转录方式，返回（文本, 实际完成转写的服务商）
 async fn transcribe_with_available_clients(
     qwen: Option<QwenASRClient>,
     doubao: Option<DoubaoASRClient>,
@@ -1267,14 +1623,16 @@ async fn transcribe_with_available_clients(
     active_provider: Option<config::AsrProvider>,
     fallback_provider: Option<config::AsrProvider>,
     log_prefix: &str,
-) -> anyhow::Result<String> {
+) -> anyhow::Result<(String, config::AsrProvider)> {
     if enable_fallback {
         // 根据配置的 active_provider 和 fallback_provider 选择客户端组合
         match (active_provider.as_ref(), fallback_provider.as_ref()) {
             (Some(config::AsrProvider::Qwen), Some(config::AsrProvider::SiliconFlow)) => {
                 if let (Some(q), Some(s)) = (&qwen, &sensevoice) {
                     tracing::info!("{}使用千问+SenseVoice并行竞速", log_prefix);
-                    asr::transcribe_with_fallback_clients(q.clone(), s.clone(), audio_data.to_vec()).await
+                    asr::transcribe_with_fallback_clients(q.clone(), s.clone(), audio_data.to_vec())
+                        .await
+                        .map(|(text, fallback)| (text, race_winner(config::AsrProvider::Qwen, fallback)))
                 } else {
                     Err(anyhow::anyhow!("千问或 SenseVoice 客户端未初始化"))
                 }
@@ -1282,7 +1640,9 @@ async fn transcribe_with_available_clients(
             (Some(config::AsrProvider::Doubao), Some(config::AsrProvider::SiliconFlow)) => {
                 if let (Some(d), Some(s)) = (&doubao, &sensevoice) {
                     tracing::info!("{}使用豆包+SenseVoice并行竞速", log_prefix);
-                    asr::transcribe_doubao_sensevoice_race(d.clone(), s.clone(), audio_data.to_vec()).await
+                    asr::transcribe_doubao_sensevoice_race(d.clone(), s.clone(), audio_data.to_vec())
+                        .await
+                        .map(|(text, fallback)| (text, race_winner(config::AsrProvider::Doubao, fallback)))
                 } else {
                     Err(anyhow::anyhow!("豆包或 SenseVoice 客户端未初始化"))
                 }
@@ -1293,7 +1653,7 @@ async fn transcribe_with_available_clients(
                     Some(config::AsrProvider::Qwen) => {
                         if let Some(q) = qwen {
                             tracing::info!("{}使用千问 ASR", log_prefix);
-                            q.transcribe_bytes(audio_data).await
+                            q.transcribe_bytes(audio_data).await.map(|text| (text, config::AsrProvider::Qwen))
                         } else {
                             Err(anyhow::anyhow!("千问客户端未初始化"))
                         }
@@ -1301,7 +1661,7 @@ async fn transcribe_with_available_clients(
                     Some(config::AsrProvider::Doubao) => {
                         if let Some(d) = doubao {
                             tracing::info!("{}使用豆包 ASR", log_prefix);
-                            d.transcribe_bytes(audio_data).await
+                            d.transcribe_bytes(audio_data).await.map(|text| (text, config::AsrProvider::Doubao))
                         } else {
                             Err(anyhow::anyhow!("豆包客户端未初始化"))
                         }
@@ -1309,7 +1669,7 @@ async fn transcribe_with_available_clients(
                     Some(config::AsrProvider::SiliconFlow) => {
                         if let Some(s) = sensevoice {
                             tracing::info!("{}使用 SenseVoice ASR", log_prefix);
-                            s.transcribe_bytes(audio_data).await
+                            s.transcribe_bytes(audio_data).await.map(|text| (text, config::AsrProvider::SiliconFlow))
                         } else {
                             Err(anyhow::anyhow!("SenseVoice 客户端未初始化"))
                         }
@@ -1327,7 +1687,7 @@ async fn transcribe_with_available_clients(
             Some(config::AsrProvider::Qwen) => {
                 if let Some(q) = qwen {
                     tracing::info!("{}使用千问 ASR", log_prefix);
-                    q.transcribe_bytes(audio_data).await
+                    q.transcribe_bytes(audio_data).await.map(|text| (text, config::AsrProvider::Qwen))
                 } else {
                     Err(anyhow::anyhow!("千问客户端未初始化"))
                 }
@@ -1335,7 +1695,7 @@ async fn transcribe_with_available_clients(
             Some(config::AsrProvider::Doubao) => {
                 if let Some(d) = doubao {
                     tracing::info!("{}使用豆包 ASR", log_prefix);
-                    d.transcribe_bytes(audio_data).await
+                    d.transcribe_bytes(audio_data).await.map(|text| (text, config::AsrProvider::Doubao))
                 } else {
                     Err(anyhow::anyhow!("豆包客户端未初始化"))
                 }
@@ -1343,7 +1703,7 @@ async fn transcribe_with_available_clients(
             Some(config::AsrProvider::SiliconFlow) => {
                 if let Some(s) = sensevoice {
                     tracing::info!("{}使用 SenseVoice ASR", log_prefix);
-                    s.transcribe_bytes(audio_data).await
+                    s.transcribe_bytes(audio_data).await.map(|text| (text, config::AsrProvider::SiliconFlow))
                 } else {
                     Err(anyhow::anyhow!("SenseVoice 客户端未初始化"))
                 }
@@ -1356,6 +1716,15 @@ async fn transcribe_with_available_clients(
     }
 }
 
+/// 主备竞速的实际服务商（备用总是 SenseVoice）
+fn race_winner(primary: config::AsrProvider, fallback: bool) -> config::AsrProvider {
+    if fallback {
+        config::AsrProvider::SiliconFlow
+    } else {
+        primary
+    }
+}
+
 /// HTTP 模式转录处理（听写模式专用）
 async fn handle_http_transcription(
     app: AppHandle,
@@ -1393,18 +1762,25 @@ async fn handle_http_transcription(
 
     if let Some(audio_data) = audio_data {
         let _ = app.emit("transcribing", ());
+        stash_recording_for_archive(&app, &audio_data);
 
         let enable_fallback = *enable_fallback_state.lock().unwrap();
         let qwen = { qwen_client_state.lock().unwrap().clone() };
         let doubao = { doubao_client_state.lock().unwrap().clone() };
         let sensevoice = { sensevoice_client_state.lock().unwrap().clone() };
         let active_prov = app.state::<AppState>().realtime_provider.lock().unwrap().clone();
+        let (qwen, active_prov) = with_profile_asr(&app, qwen, active_prov);
         let fallback_prov = app.state::<AppState>().fallback_provider.lock().unwrap().clone();
 
         let asr_start = std::time::Instant::now();
         let result = transcribe_with_available_clients(
             qwen, doubao, sensevoice, &audio_data, enable_fallback, active_prov, fallback_prov, "(HTTP) "
-        ).await;
+        )
+        .await
+        .map(|(text, provider)| {
+            note_asr_provider(&app, Some(&provider));
+            text
+        });
         let asr_time_ms = asr_start.elapsed().as_millis() as u64;
 
         handle_transcription_result(app, post_processor, text_inserter, result, asr_time_ms, target_hwnd, usage_stats, recording_start_instant).await;
@@ -1449,6 +1825,10 @@ async fn handle_realtime_stop(
         }
     };
 
+    if let Some(ref data) = audio_data {
+        stash_recording_for_archive(&app, data);
+    }
+
     // 2. 等待音频发送任务完成
     {
         let handle = audio_sender_handle.lock().unwrap().take();
@@ -1458,8 +1838,9 @@ async fn handle_realtime_stop(
         }
     }
 
-    // 3. 检查使用的是哪个 provider
-    let provider = realtime_provider.lock().unwrap().clone();
+    // 3. 检查使用的是哪个 provider（实时转写失败时由备用方案覆盖归档记录的服务商）
+    let provider = profile_realtime_provider(&app, &realtime_provider);
+    note_asr_provider(&app, provider.as_ref());
 
     match provider {
         Some(config::AsrProvider::Doubao) => {
@@ -1665,12 +2046,18 @@ async fn fallback_transcription(
     let sensevoice = { sensevoice_client_state.lock().unwrap().clone() };
     let doubao = { doubao_client_state.lock().unwrap().clone() };
     let active_prov = app.state::<AppState>().realtime_provider.lock().unwrap().clone();
+    let (qwen, active_prov) = with_profile_asr(&app, qwen, active_prov);
     let fallback_prov = app.state::<AppState>().fallback_provider.lock().unwrap().clone();
 
     let asr_start = std::time::Instant::now();
     let result = transcribe_with_available_clients(
         qwen, doubao, sensevoice, &audio_data, enable_fallback, active_prov, fallback_prov, "(备用) "
-    ).await;
+    )
+    .await
+    .map(|(text, provider)| {
+        note_asr_provider(&app, Some(&provider));
+        text
+    });
     let asr_time_ms = asr_start.elapsed().as_millis() as u64;
 
     handle_transcription_result(app, post_processor, text_inserter, result, asr_time_ms, target_hwnd, usage_stats, recording_start_instant).await;
@@ -1680,6 +2067,9 @@ async fn fallback_transcription(
 fn emit_error_and_hide_overlay(app: &AppHandle, error_msg: String) {
     tracing::error!("发送错误并隐藏悬浮窗: {}", error_msg);
     let _ = app.emit("error", error_msg);
+    let mode = app.state::<AppState>().current_trigger_mode.lock().unwrap()
+        .unwrap_or(config::TriggerMode::Dictation);
+    play_cue(app, mode, beep_player::AudioCue::Error);
 
     // 隐藏悬浮窗，带重试机制
     hide_overlay_silently(app);
@@ -1708,6 +2098,7 @@ fn is_audio_skip_error(error: &anyhow::Error) -> bool {
 /// 转录完成事件的 payload
 #[derive(Clone, serde::Serialize)]
 struct TranscriptionResult {
+    id: String,  // 转写 ID（历史记录与录音归档共用）
     text: String,
     original_text: Option<String>, // 原始 ASR 文本（仅开启 LLM 润色时有值）
     asr_time_ms: u64,
@@ -1717,6 +2108,8 @@ struct TranscriptionResult {
     mode: Option<String>,  // 新增：处理模式
     #[serde(skip_serializing_if = "Option::is_none")]
     inserted: Option<bool>, // 新增：是否已自动插入
+    #[serde(skip_serializing_if = "Option::is_none")]
+    llm_endpoint: Option<String>, // 产出 LLM 结果的端点（配置了备用端点时区分主备）
 }
 
 /// 处理转录结果（听写模式专用，使用 NormalPipeline）
@@ -1738,9 +2131,19 @@ async fn handle_transcription_result(
 
     // 听写模式：只使用 NormalPipeline
     let pipeline = NormalPipeline::new();
+    let sinks = output_sinks(&app, config::TriggerMode::Dictation);
     let mut inserter = { text_inserter.lock().unwrap().take() };
     let pipeline_result = pipeline
-        .process(&app, post_proc, &mut inserter, result, asr_time_ms, TranscriptionContext::empty(), target_hwnd)
+        .process(
+            &app,
+            post_proc,
+            &mut inserter,
+            result,
+            asr_time_ms,
+            TranscriptionContext::empty().with_profile(active_profile(&app)),
+            target_hwnd,
+            output_sink::wants_insert(&sinks),
+        )
         .await;
     // 归还 text_inserter
     *text_inserter.lock().unwrap() = inserter;
@@ -1764,7 +2167,15 @@ async fn handle_transcription_result(
             }
 
             // 构建兼容的 TranscriptionResult
+            let transcription_id = uuid::Uuid::new_v4().to_string();
+            archive_pending_recording(&app, &transcription_id, &result);
+            deliver_to_sinks(&app, sinks, &transcription_id, &result);
+            if let Some(ref record) = result.insertion {
+                *app.state::<AppState>().last_insertion.lock().unwrap() = Some(record.clone());
+            }
+
             let transcription_result = TranscriptionResult {
+                id: transcription_id,
                 text: result.text,
                 original_text: result.original_text,
                 asr_time_ms: result.asr_time_ms,
@@ -1772,6 +2183,7 @@ async fn handle_transcription_result(
                 total_time_ms: result.total_time_ms,
                 mode: Some(format!("{:?}", result.mode).to_lowercase()),
                 inserted: Some(result.inserted),
+                llm_endpoint: result.llm_endpoint,
             };
 
             // 发送完成事件
@@ -1783,14 +2195,320 @@ async fn handle_transcription_result(
 
             // 清理录音开始时间（防止下次录音时使用错误的时间）
             let _ = recording_start_instant.lock().unwrap().take();
+            let _ = app.state::<AppState>().audio_archive.lock().unwrap().take_pending();
 
             // 发送错误事件
             tracing::error!("转录处理失败: {}", e);
             let _ = app.emit("error", format!("转录失败: {}", e));
+            play_cue(&app, config::TriggerMode::Dictation, beep_player::AudioCue::Error);
         }
     }
 }
 
+/// 按目标窗口选择本次录音的应用配置
+#[cfg(target_os = "windows")]
+fn select_active_profile(app: &AppHandle, target_hwnd: Option<isize>) {
+    let state = app.state::<AppState>();
+    let profile = target_hwnd.and_then(|hwnd| {
+        let window = app_profiles::WindowInfo::from_hwnd(hwnd);
+        let profiles = state.app_profiles.lock().unwrap();
+        let profile = app_profiles::select(&profiles, &window).cloned();
+        tracing::info!(
+            "前台窗口: {:?}, 应用配置: {:?}",
+            window,
+            profile.as_ref().map(|p| p.name.as_str())
+        );
+        profile
+    });
+    *state.active_profile.lock().unwrap() = profile;
+}
+
+/// 本次录音匹配到的应用配置
+fn active_profile(app: &AppHandle) -> Option<config::AppProfile> {
+    app.state::<AppState>().active_profile.lock().unwrap().clone()
+}
+
+/// 本次录音的实时转写服务商：应用配置指定时覆盖全局设置
+///
+/// 录音开始与结束都经由此处选择会话，两者使用同一个应用配置，保证结束时取到对应的会话
+fn profile_realtime_provider(
+    app: &AppHandle,
+    realtime_provider: &Mutex<Option<config::AsrProvider>>,
+) -> Option<config::AsrProvider> {
+    active_profile(app)
+        .and_then(|p| p.asr_provider)
+        .or_else(|| realtime_provider.lock().unwrap().clone())
+}
+
+/// 按应用配置覆盖 HTTP 转写的服务商和识别语言
+fn with_profile_asr(
+    app: &AppHandle,
+    qwen: Option<QwenASRClient>,
+    active_provider: Option<config::AsrProvider>,
+) -> (Option<QwenASRClient>, Option<config::AsrProvider>) {
+    let Some(profile) = active_profile(app) else {
+        return (qwen, active_provider);
+    };
+    let qwen = match profile.language {
+        Some(language) => qwen.map(|client| client.with_language(language)),
+        None => qwen,
+    };
+    (qwen, profile.asr_provider.or(active_provider))
+}
+
+/// 指定触发模式当前配置的输出目标
+fn output_sinks(app: &AppHandle, mode: config::TriggerMode) -> Vec<config::OutputSink> {
+    app.state::<AppState>().output_config.lock().unwrap().sinks_for(mode).to_vec()
+}
+
+/// 将结果输出到插入以外的目标（后台执行，失败时通知前端）
+fn deliver_to_sinks(app: &AppHandle, sinks: Vec<config::OutputSink>, id: &str, result: &PipelineResult) {
+    if sinks.iter().all(|sink| *sink == config::OutputSink::Insert) {
+        return;
+    }
+    let app = app.clone();
+    let payload = output_sink::SinkPayload::new(id, result.clone());
+    tauri::async_runtime::spawn(async move {
+        let failures = output_sink::deliver(&sinks, &payload).await;
+        if !failures.is_empty() {
+            let _ = app.emit("error", format!("结果输出失败: {}", failures.join("; ")));
+        }
+    });
+}
+
+/// 暂存本次录音，等待转写成功后归档（未启用归档时不做任何事）
+fn stash_recording_for_archive(app: &AppHandle, audio_data: &[u8]) {
+    app.state::<AppState>().audio_archive.lock().unwrap().stash(audio_data);
+}
+
+/// 记录实际完成转写的 ASR 服务商（写入归档元数据）
+fn note_asr_provider(app: &AppHandle, provider: Option<&config::AsrProvider>) {
+    let name = provider.map(|p| format!("{:?}", p).to_lowercase());
+    app.state::<AppState>().audio_archive.lock().unwrap().set_asr_provider(name);
+}
+
+/// 将暂存的录音连同转写结果写入归档（后台执行，不阻塞结果返回）
+fn archive_pending_recording(app: &AppHandle, id: &str, result: &PipelineResult) {
+    let state = app.state::<AppState>();
+    let (audio_data, archive_cfg) = {
+        let mut archive = state.audio_archive.lock().unwrap();
+        (archive.take_pending(), archive.config().clone())
+    };
+    let Some((audio_data, asr_provider)) = audio_data else { return };
+
+    let metadata = ArchiveMetadata {
+        id: id.to_string(),
+        created_at: app_data::now_millis(),
+        mode: format!("{:?}", result.mode).to_lowercase(),
+        asr_provider,
+        // 16kHz 单声道 16bit：去掉 44 字节头后每毫秒 32 字节
+        duration_ms: audio_data.len().saturating_sub(44) as u64 / 32,
+        text: result.text.clone(),
+        original_text: result.original_text.clone(),
+        asr_time_ms: result.asr_time_ms,
+        llm_time_ms: result.llm_time_ms,
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = AudioArchive::save(&audio_data, &metadata, &archive_cfg) {
+            tracing::error!("录音归档失败: {}", e);
+        }
+    });
+}
+
+/// 撤销上次插入：恢复目标窗口焦点后退格删除插入的文本，替换过选中内容时重新插入原文
+#[tauri::command]
+async fn undo_last_insertion(app_handle: AppHandle) -> Result<String, String> {
+    let state = app_handle.state::<AppState>();
+    let Some(record) = state.last_insertion.lock().unwrap().take() else {
+        return Err("没有可撤销的插入".to_string());
+    };
+    tracing::info!("撤销上次插入 (目标窗口: {:?})", record.target_hwnd);
+
+    pipeline::focus::hide_overlay_and_restore_focus(&app_handle, record.target_hwnd).await;
+    if record.target_hwnd.is_some() && !pipeline::focus::verify_focus(record.target_hwnd) {
+        // 保留记录，用户切回目标窗口后可再次撤销
+        *state.last_insertion.lock().unwrap() = Some(record);
+        return Err("目标窗口已关闭或无法获得焦点，未撤销".to_string());
+    }
+
+    let backend = Arc::clone(&state.insertion_backend);
+    let insertion_config = state.insertion_config.lock().unwrap().clone();
+    tauri::async_runtime::spawn_blocking(move || insertion::undo::undo(backend.as_ref(), &insertion_config, &record))
+        .await
+        .map_err(|e| format!("撤销任务失败: {}", e))?
+        .map_err(|e| format!("撤销失败: {}", e))?;
+
+    Ok("已撤销上次插入".to_string())
+}
+
+/// 清空 AI 助手的多轮对话历史
+#[tauri::command]
+async fn reset_assistant_conversation(app_handle: AppHandle) -> Result<String, String> {
+    reset_conversation(&app_handle);
+    Ok("已开始新对话".to_string())
+}
+
+/// 回应 AI 助手的命令执行确认（悬浮窗确认/拒绝按钮）
+#[tauri::command]
+async fn respond_tool_confirmation(app_handle: AppHandle, id: String, approved: bool) -> Result<(), String> {
+    let sender = app_handle.state::<AppState>().tool_confirmations.lock().unwrap().remove(&id);
+    let Some(sender) = sender else {
+        return Err("确认请求已过期".to_string());
+    };
+    tracing::info!("AI 助手命令确认: {} ({})", id, if approved { "允许" } else { "拒绝" });
+    let _ = sender.send(approved);
+    Ok(())
+}
+
+fn reset_conversation(app: &AppHandle) {
+    if let Some(ref processor) = *app.state::<AppState>().assistant_processor.lock().unwrap() {
+        processor.reset_conversation();
+    }
+    let _ = app.emit("assistant_conversation_reset", ());
+}
+
+#[tauri::command]
+async fn list_archived_recordings() -> Result<Vec<ArchiveMetadata>, String> {
+    AudioArchive::list().map_err(|e| format!("读取录音归档失败: {}", e))
+}
+
+/// 重新转写结果（新旧文本并列，便于对比）
+#[derive(Clone, serde::Serialize)]
+struct RetranscriptionResult {
+    id: String,
+    provider: config::AsrProvider,
+    preset_id: Option<String>,
+    /// 归档时的最终文本
+    original_text: String,
+    /// 本次重新转写的 ASR 文本
+    asr_text: String,
+    /// 本次最终文本（指定预设时为 LLM 处理后的文本）
+    text: String,
+    asr_time_ms: u64,
+    llm_time_ms: Option<u64>,
+}
+
+/// 使用指定的 ASR 服务商（及可选的 LLM 预设）重新转写一条归档录音
+///
+/// 优先使用运行中的客户端，服务未启动时按已保存的配置临时创建
+#[tauri::command]
+async fn retranscribe_history_entry(
+    app_handle: AppHandle,
+    id: String,
+    provider: config::AsrProvider,
+    preset_id: Option<String>,
+) -> Result<RetranscriptionResult, String> {
+    tracing::info!("重新转写历史记录: {}, provider={:?}, preset={:?}", id, provider, preset_id);
+
+    let (audio_data, metadata) = AudioArchive::load(&id)
+        .map_err(|e| format!("读取归档录音失败: {}", e))?;
+
+    let config = AppConfig::load().map_err(|e| format!("加载配置失败: {}", e))?;
+    let processor = preset_id
+        .as_deref()
+        .map(|preset| file_transcriber::build_preset_processor(&config, preset))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+
+    let (qwen, doubao, sensevoice) = {
+        let state = app_handle.state::<AppState>();
+        let (saved_qwen, saved_doubao, saved_sensevoice) =
+            file_transcriber::build_asr_clients(&config.asr_config, &config.dictionary);
+        let qwen = state.qwen_client.lock().unwrap().clone().or(saved_qwen);
+        let doubao = state.doubao_client.lock().unwrap().clone().or(saved_doubao);
+        let sensevoice = state.sensevoice_client.lock().unwrap().clone().or(saved_sensevoice);
+        (qwen, doubao, sensevoice)
+    };
+
+    let asr_start = std::time::Instant::now();
+    let asr_text = transcribe_with_available_clients(
+        qwen, doubao, sensevoice, &audio_data, false, Some(provider.clone()), None, "(重新转写) "
+    )
+    .await
+    .map(|(text, _)| text)
+    .map_err(|e| format!("重新转写失败: {}", e))?;
+    let asr_time_ms = asr_start.elapsed().as_millis() as u64;
+
+    let (text, llm_time_ms) = match processor {
+        Some(processor) => {
+            let llm_start = std::time::Instant::now();
+            let polished = processor
+                .polish_transcript(&asr_text)
+                .await
+                .map_err(|e| format!("LLM 处理失败: {}", e))?;
+            (polished, Some(llm_start.elapsed().as_millis() as u64))
+        }
+        None => (asr_text.clone(), None),
+    };
+
+    Ok(RetranscriptionResult {
+        id,
+        provider,
+        preset_id,
+        original_text: metadata.text,
+        asr_text,
+        text,
+        asr_time_ms,
+        llm_time_ms,
+    })
+}
+
+/// 删除全部归档录音，返回删除的条数
+#[tauri::command]
+async fn purge_audio_archive() -> Result<usize, String> {
+    tracing::info!("清空录音归档...");
+    AudioArchive::purge_all().map_err(|e| format!("清空录音归档失败: {}", e))
+}
+
+/// 清空 LLM 响应缓存，返回删除的条数
+#[tauri::command]
+async fn clear_llm_cache() -> Result<usize, String> {
+    tracing::info!("清空 LLM 响应缓存...");
+    LlmCache::clear().map_err(|e| format!("清空 LLM 缓存失败: {}", e))
+}
+
+/// 引导式麦克风测试：先静音测底噪，再朗读校准语句，返回电平分析和调整建议
+///
+/// 测试期间通过 mic_diagnostics_stage 事件通知前端当前阶段
+#[tauri::command]
+async fn run_mic_diagnostics(
+    app_handle: AppHandle,
+    speech_secs: Option<u32>,
+) -> Result<mic_diagnostics::MicDiagnosticsReport, String> {
+    {
+        let state = app_handle.state::<AppState>();
+        if active_voice_activity(&state).is_some() {
+            return Err("正在录音，请结束录音后再进行麦克风测试".to_string());
+        }
+    }
+
+    tracing::info!("开始麦克风诊断...");
+    tauri::async_runtime::spawn_blocking(move || mic_diagnostics::run(&app_handle, speech_secs))
+        .await
+        .map_err(|e| format!("麦克风诊断任务异常: {}", e))?
+        .map_err(|e| format!("麦克风诊断失败: {}", e))
+}
+
+/// 试听提示音（设置界面使用，忽略全局静音）
+///
+/// 传入 setting 时试听尚未保存的设置，否则使用当前配置
+#[tauri::command]
+async fn preview_audio_cue(
+    app_handle: AppHandle,
+    mode: config::TriggerMode,
+    cue: beep_player::AudioCue,
+    setting: Option<config::CueSetting>,
+) -> Result<(), String> {
+    let setting = setting.unwrap_or_else(|| {
+        let state = app_handle.state::<AppState>();
+        let cfg = state.audio_cue_config.lock().unwrap();
+        beep_player::cue_setting(&cfg, mode, cue).clone()
+    });
+    beep_player::play_setting(setting, mode, cue);
+    Ok(())
+}
+
 /// 隐藏悬浮窗的辅助函数
 async fn hide_overlay_window(app: &AppHandle) {
     if let Some(overlay) = app.get_webview_window("overlay") {
@@ -1845,6 +2563,7 @@ async fn stop_app(app_handle: AppHandle) -> Result<String, String> {
     *state.qwen_client.lock().unwrap() = None;
     *state.sensevoice_client.lock().unwrap() = None;
     *state.doubao_client.lock().unwrap() = None;
+    *state.pre_roll_buffer.lock().unwrap() = None;
     *state.is_running.lock().unwrap() = false;
 
     Ok("应用已停止".to_string())
@@ -1874,6 +2593,7 @@ async fn quit_app(app_handle: AppHandle) -> Result<(), String> {
             *state.qwen_client.lock().unwrap() = None;
             *state.sensevoice_client.lock().unwrap() = None;
             *state.doubao_client.lock().unwrap() = None;
+            *state.pre_roll_buffer.lock().unwrap() = None;
             *is_running = false;
         }
     }
@@ -1903,6 +2623,12 @@ async fn cancel_transcription(app_handle: AppHandle) -> Result<String, String> {
         }
     }
 
+    // 丢弃待归档的录音
+    let _ = state.audio_archive.lock().unwrap().take_pending();
+
+    // 停止正在生成 / 插入的 AI 助手回答
+    state.llm_cancelled.store(true, Ordering::SeqCst);
+
     // 3. 取消音频发送任务
     {
         let handle = state.audio_sender_handle.lock().unwrap().take();
@@ -1933,8 +2659,11 @@ async fn cancel_transcription(app_handle: AppHandle) -> Result<String, String> {
         }
     }
 
-    // 6. 发送取消事件
+    // 6. 发送取消事件（松手模式取消时触发模式已清空，松手模式只用于听写）
     let _ = app_handle.emit("transcription_cancelled", ());
+    let mode = state.current_trigger_mode.lock().unwrap()
+        .unwrap_or(config::TriggerMode::Dictation);
+    play_cue(&app_handle, mode, beep_player::AudioCue::Cancel);
 
     Ok("已取消转录".to_string())
 }
@@ -1973,7 +2702,7 @@ async fn finish_locked_recording(app_handle: AppHandle) -> Result<String, String
         .unwrap_or(config::TriggerMode::Dictation);
 
     // 播放停止提示音
-    beep_player::play_stop_beep();
+    play_cue(&app_handle, trigger_mode, beep_player::AudioCue::Stop);
 
     // 结束会话并恢复其他应用的音量
     if let Some(ref manager) = *state.audio_mute_manager.lock().unwrap() {
@@ -2203,7 +2932,16 @@ async fn update_runtime_config(
     llm_config: Option<config::LlmConfig>,
     assistant_config: Option<config::AssistantConfig>,
     enable_mute_other_apps: Option<bool>,
+    mute_other_apps_config: Option<config::MuteOtherAppsConfig>,
     dictionary: Option<Vec<String>>,
+    pre_roll_config: Option<config::PreRollConfig>,
+    audio_archive_config: Option<config::AudioArchiveConfig>,
+    auto_stop_config: Option<config::AutoStopConfig>,
+    recording_limits_config: Option<config::RecordingLimitsConfig>,
+    audio_cue_config: Option<config::AudioCueConfig>,
+    insertion_config: Option<config::InsertionConfig>,
+    output_config: Option<config::OutputConfig>,
+    app_profiles: Option<Vec<config::AppProfile>>,
 ) -> Result<String, String> {
     let state = app_handle.state::<AppState>();
 
@@ -2226,7 +2964,7 @@ async fn update_runtime_config(
     if let Some(cfg) = llm_config {
         let enable_pp = *state.enable_post_process.lock().unwrap();
         let mut processor_guard = state.post_processor.lock().unwrap();
-        if enable_pp && !cfg.api_key.trim().is_empty() {
+        if enable_pp && cfg.is_valid() {
             *processor_guard = Some(LlmPostProcessor::new(cfg));
             tracing::info!("热更新: LLM 处理器已重新初始化");
         } else {
@@ -2275,6 +3013,72 @@ async fn update_runtime_config(
         updated.push("词库");
     }
 
+    // 6. 更新预录缓冲（关闭时立即释放麦克风并清空缓冲）
+    if let Some(cfg) = pre_roll_config {
+        apply_pre_roll_config(&state.pre_roll_buffer, &cfg);
+        updated.push("预录缓冲");
+    }
+
+    // 7. 更新录音归档配置
+    if let Some(cfg) = audio_archive_config {
+        state.audio_archive.lock().unwrap().set_config(cfg);
+        tracing::info!("热更新: 录音归档配置已更新");
+        updated.push("录音归档");
+    }
+
+    // 8. 更新静音自动停止配置（下次进入锁定录音时生效）
+    if let Some(cfg) = auto_stop_config {
+        *state.auto_stop_config.lock().unwrap() = cfg;
+        tracing::info!("热更新: 静音自动停止配置已更新");
+        updated.push("静音自动停止");
+    }
+
+    // 9. 更新流式录音限制（下次开始录音时生效）
+    if let Some(cfg) = recording_limits_config {
+        if let Some(ref mut rec) = *state.streaming_recorder.lock().unwrap() {
+            rec.set_limits(cfg);
+            tracing::info!("热更新: 流式录音限制已更新");
+        }
+        updated.push("录音限制");
+    }
+
+    // 10. 更新提示音配置
+    if let Some(cfg) = audio_cue_config {
+        *state.audio_cue_config.lock().unwrap() = cfg;
+        tracing::info!("热更新: 提示音配置已更新");
+        updated.push("提示音");
+    }
+
+    // 11. 更新静音 / 闪避方式
+    if let Some(cfg) = mute_other_apps_config {
+        if let Some(ref manager) = *state.audio_mute_manager.lock().unwrap() {
+            manager.set_config(cfg);
+            tracing::info!("热更新: 静音其他应用方式已更新");
+            updated.push("静音方式");
+        }
+    }
+
+    // 12. 更新文本插入方式（下次插入时生效）
+    if let Some(cfg) = insertion_config {
+        *state.insertion_config.lock().unwrap() = cfg;
+        tracing::info!("热更新: 文本插入方式已更新");
+        updated.push("插入方式");
+    }
+
+    // 13. 更新输出目标（下次转写完成时生效）
+    if let Some(cfg) = output_config {
+        *state.output_config.lock().unwrap() = cfg;
+        tracing::info!("热更新: 输出目标已更新");
+        updated.push("输出目标");
+    }
+
+    // 14. 更新按应用配置（下次按下热键时生效）
+    if let Some(profiles) = app_profiles {
+        *state.app_profiles.lock().unwrap() = profiles;
+        tracing::info!("热更新: 按应用配置已更新");
+        updated.push("应用配置");
+    }
+
     if updated.is_empty() {
         Ok("无配置需要更新".to_string())
     } else {
@@ -2282,6 +3086,34 @@ async fn update_runtime_config(
     }
 }
 
+/// 命令行子命令入口
+///
+/// 目前支持 `transcribe`，返回 None 表示不是子命令，由调用方继续启动 GUI
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    match args.get(1).map(String::as_str) {
+        Some("transcribe") => {
+            attach_parent_console();
+            tracing_subscriber::fmt().with_writer(std::io::stderr).init();
+            Some(file_transcriber::run_cli(&args[2..]))
+        }
+        _ => None,
+    }
+}
+
+/// 附加到启动本进程的终端的控制台
+///
+/// Windows 发布版是 GUI 子系统程序，没有自己的控制台，不附加时子命令的 stdout/stderr 输出会丢失。
+/// 注意 cmd 不会等待 GUI 子系统程序退出，输出可能出现在下一个提示符之后
+#[cfg(target_os = "windows")]
+fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // 不是从终端启动（没有父控制台）或已有控制台时失败，忽略即可
+    let _ = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_parent_console() {}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // 初始化日志
@@ -2322,10 +3154,19 @@ pub fn run() {
                 UsageStats::default()
             });
 
+            let audio_archive_config = AppConfig::load()
+                .map(|c| c.audio_archive_config)
+                .unwrap_or_default();
+
+            // 纯 Wayland 会话下通过 AT-SPI 跟踪获得焦点的文本控件
+            #[cfg(all(target_os = "linux", feature = "atspi"))]
+            clipboard_manager::start_atspi_watcher();
+
             let app_state = AppState {
                 audio_recorder: Arc::new(Mutex::new(None)),
                 streaming_recorder: Arc::new(Mutex::new(None)),
                 text_inserter: Arc::new(Mutex::new(None)),
+                insertion_backend: insertion::detect_backend(),
                 post_processor: Arc::new(Mutex::new(None)),
                 assistant_processor: Arc::new(Mutex::new(None)),
                 is_running: Arc::new(Mutex::new(false)),
@@ -2351,6 +3192,19 @@ pub fn run() {
                 dictionary: Arc::new(Mutex::new(Vec::new())),
                 usage_stats: Arc::new(Mutex::new(usage_stats)),
                 recording_start_instant: Arc::new(Mutex::new(None)),
+                pre_roll_buffer: Arc::new(Mutex::new(None)),
+                audio_archive: Arc::new(Mutex::new(AudioArchive::new(audio_archive_config))),
+                auto_stop_config: Arc::new(Mutex::new(config::AutoStopConfig::default())),
+                audio_cue_config: Arc::new(Mutex::new(config::AudioCueConfig::default())),
+                insertion_config: Arc::new(Mutex::new(config::InsertionConfig::default())),
+                output_config: Arc::new(Mutex::new(config::OutputConfig::default())),
+                last_insertion: Arc::new(Mutex::new(None)),
+                app_profiles: Arc::new(Mutex::new(Vec::new())),
+                active_profile: Arc::new(Mutex::new(None)),
+                llm_cancelled: Arc::new(AtomicBool::new(false)),
+                tool_confirmations: Arc::new(Mutex::new(HashMap::new())),
+                last_primary_selection: Arc::new(Mutex::new(None)),
+                auto_stop_generation: Arc::new(AtomicU64::new(0)),
             };
 
             // 创建托盘菜单
@@ -2417,6 +3271,16 @@ pub fn run() {
             set_hotkey_service_active,
             get_hotkey_debug_info,
             update_runtime_config,
+            transcribe_audio_file,
+            list_archived_recordings,
+            purge_audio_archive,
+            clear_llm_cache,
+            retranscribe_history_entry,
+            run_mic_diagnostics,
+            preview_audio_cue,
+            undo_last_insertion,
+            reset_assistant_conversation,
+            respond_tool_confirmation,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
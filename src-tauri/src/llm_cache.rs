@@ -0,0 +1,240 @@
+// LLM 响应缓存模块
+// 相同的短句（问候语、落款、常用语等）直接返回上次的 LLM 结果，节省延迟和 token。
+// 润色和 AI 助手共用，按（提示词哈希、主端点模型、去除首尾空白的输入）区分
+// 输入只去除首尾空白：大小写和内部空白可能影响结果（代码、专有名词等）
+//
+// 存储布局：<数据目录>/PushToTalk/llm_cache/<键哈希>.json
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::app_data::{self, now_millis, Retention};
+use crate::config::LlmCacheConfig;
+
+/// 缓存键
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheKey {
+    /// 系统提示词的哈希
+    pub prompt_hash: String,
+    pub model: String,
+    /// 去除首尾空白后的输入
+    pub input: String,
+}
+
+impl CacheKey {
+    /// 创建缓存键，输入为空或超过字数上限时返回 None（不缓存）
+    pub fn new(system_prompt: &str, model: &str, input: &str, config: &LlmCacheConfig) -> Option<Self> {
+        let input = input.trim();
+        if input.is_empty() || input.chars().count() > config.max_input_chars {
+            return None;
+        }
+        Some(Self {
+            prompt_hash: format!("{:016x}", fnv1a(system_prompt.as_bytes())),
+            model: model.to_string(),
+            input: input.to_string(),
+        })
+    }
+
+    fn file_name(&self) -> String {
+        let key = format!("{}\0{}\0{}", self.prompt_hash, self.model, self.input);
+        format!("{:016x}.json", fnv1a(key.as_bytes()))
+    }
+}
+
+/// 缓存条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    #[serde(flatten)]
+    key: CacheKey,
+    response: String,
+    /// 产出结果的端点
+    endpoint: String,
+    /// 创建时间（Unix 毫秒）
+    created_at: u64,
+}
+
+/// 缓存命中的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedResponse {
+    pub response: String,
+    /// 产出结果的端点（标注来自缓存）
+    pub endpoint: String,
+}
+
+/// LLM 响应缓存（无状态，直接读写缓存目录）
+pub struct LlmCache;
+
+impl LlmCache {
+    /// 缓存目录
+    pub fn cache_dir() -> Result<PathBuf> {
+        app_data::data_subdir("llm_cache")
+    }
+
+    /// 查询缓存（过期或读取失败视为未命中）
+    pub fn get(key: &CacheKey, config: &LlmCacheConfig) -> Option<CachedResponse> {
+        let dir = Self::cache_dir().ok()?;
+        let hit = get_in(&dir, key, config);
+        if hit.is_some() {
+            tracing::info!("LLM 缓存命中: {}", key.input);
+        }
+        hit
+    }
+
+    /// 写入缓存并执行条数上限（失败只记录日志）
+    pub fn put(key: &CacheKey, response: &str, endpoint: &str, config: &LlmCacheConfig) {
+        let result = Self::cache_dir().and_then(|dir| {
+            put_in(&dir, key, response, endpoint)?;
+            enforce_limits_in(&dir, config)
+        });
+        match result {
+            Ok(removed) if removed > 0 => tracing::info!("LLM 缓存已清理 {} 条旧记录", removed),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("写入 LLM 缓存失败: {}", e),
+        }
+    }
+
+    /// 清空缓存，返回删除的条数
+    pub fn clear() -> Result<usize> {
+        clear_in(&Self::cache_dir()?)
+    }
+}
+
+/// FNV-1a 64 位哈希（跨版本稳定，用于文件名）
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn get_in(dir: &Path, key: &CacheKey, config: &LlmCacheConfig) -> Option<CachedResponse> {
+    let content = std::fs::read_to_string(dir.join(key.file_name())).ok()?;
+    let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+    // 文件名哈希冲突时键不一致
+    if entry.key != *key {
+        return None;
+    }
+    if config.ttl_hours > 0 {
+        let age_ms = now_millis().saturating_sub(entry.created_at);
+        if age_ms > config.ttl_hours * 3600 * 1000 {
+            return None;
+        }
+    }
+    Some(CachedResponse {
+        response: entry.response,
+        endpoint: format!("{}（缓存）", entry.endpoint),
+    })
+}
+
+fn put_in(dir: &Path, key: &CacheKey, response: &str, endpoint: &str) -> Result<()> {
+    let entry = CacheEntry {
+        key: key.clone(),
+        response: response.to_string(),
+        endpoint: endpoint.to_string(),
+        created_at: now_millis(),
+    };
+    std::fs::write(dir.join(key.file_name()), serde_json::to_string_pretty(&entry)?)?;
+    Ok(())
+}
+
+/// 执行缓存限制：先删除过期的条目，再从最旧的开始删除直到条数不超过上限
+fn enforce_limits_in(dir: &Path, config: &LlmCacheConfig) -> Result<usize> {
+    let retention = Retention {
+        max_age: (config.ttl_hours > 0).then(|| Duration::from_secs(config.ttl_hours * 3600)),
+        max_files: (config.max_entries > 0).then_some(config.max_entries),
+        max_bytes: None,
+    };
+    app_data::prune_oldest_first(dir, "json", &retention, |path| std::fs::remove_file(path).is_ok())
+}
+
+fn clear_in(dir: &Path) -> Result<usize> {
+    let mut removed = 0;
+    for entry in std::fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            std::fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    tracing::info!("已清空 LLM 缓存: {} 条", removed);
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_data::test_support::{set_age, temp_dir};
+
+    #[test]
+    fn test_key_trims_input() {
+        let config = LlmCacheConfig::default();
+        let a = CacheKey::new("润色", "glm", "  Hello World\n", &config).unwrap();
+        let b = CacheKey::new("润色", "glm", "Hello World", &config).unwrap();
+        assert_eq!(a, b);
+        // 大小写和内部空白保留
+        assert_ne!(a, CacheKey::new("润色", "glm", "hello world", &config).unwrap());
+        assert_ne!(a, CacheKey::new("润色", "glm", "Hello  World", &config).unwrap());
+        assert_ne!(a, CacheKey::new("翻译", "glm", "Hello World", &config).unwrap());
+        assert_ne!(a.file_name(), CacheKey::new("润色", "glm-plus", "Hello World", &config).unwrap().file_name());
+
+        assert!(CacheKey::new("润色", "glm", "   ", &config).is_none());
+        let short = LlmCacheConfig { max_input_chars: 4, ..Default::default() };
+        assert!(CacheKey::new("润色", "glm", "谢谢大家", &short).is_some());
+        assert!(CacheKey::new("润色", "glm", "谢谢大家了", &short).is_none());
+    }
+
+    #[test]
+    fn test_put_get_and_clear() {
+        let dir = temp_dir("ptt_llm_cache_test");
+        let config = LlmCacheConfig::default();
+        let key = CacheKey::new("润色", "glm", "你好", &config).unwrap();
+        assert!(get_in(&dir, &key, &config).is_none());
+
+        put_in(&dir, &key, "你好！", "glm").unwrap();
+        assert_eq!(
+            get_in(&dir, &key, &config),
+            Some(CachedResponse { response: "你好！".to_string(), endpoint: "glm（缓存）".to_string() })
+        );
+
+        assert_eq!(clear_in(&dir).unwrap(), 1);
+        assert!(get_in(&dir, &key, &config).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_expired_entry_is_miss() {
+        let dir = temp_dir("ptt_llm_cache_test");
+        let config = LlmCacheConfig { ttl_hours: 1, ..Default::default() };
+        let key = CacheKey::new("润色", "glm", "你好", &config).unwrap();
+        let entry = CacheEntry {
+            key: key.clone(),
+            response: "你好！".to_string(),
+            endpoint: "glm".to_string(),
+            created_at: now_millis() - 2 * 3600 * 1000,
+        };
+        std::fs::write(dir.join(key.file_name()), serde_json::to_string(&entry).unwrap()).unwrap();
+        assert!(get_in(&dir, &key, &config).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_max_entries() {
+        let dir = temp_dir("ptt_llm_cache_test");
+        let config = LlmCacheConfig { max_entries: 2, ..Default::default() };
+        let keys: Vec<CacheKey> = ["一", "二", "三"]
+            .iter()
+            .map(|input| CacheKey::new("润色", "glm", input, &config).unwrap())
+            .collect();
+        for (age_secs, key) in [30, 20, 10].into_iter().zip(&keys) {
+            put_in(&dir, key, "结果", "glm").unwrap();
+            set_age(&dir.join(key.file_name()), age_secs);
+        }
+
+        assert_eq!(enforce_limits_in(&dir, &config).unwrap(), 1);
+        // 最旧的条目被删除
+        assert!(get_in(&dir, &keys[0], &config).is_none());
+        assert!(get_in(&dir, &keys[2], &config).is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
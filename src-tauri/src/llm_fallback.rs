@@ -0,0 +1,297 @@
+// src-tauri/src/llm_fallback.rs
+//
+// LLM 备用端点链
+//
+// 按顺序尝试多个 LLM 端点（各自的协议、Key 和模型），失败或超时后切换到下一个，
+// 返回结果时记录实际产出结果的端点。润色和 AI 助手共用
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::config::{LlmEndpoint, LlmFallbackConfig};
+use crate::openai_client::{collect_stream, ChatCompletion, ChatOptions, Message, OpenAiClient, OpenAiClientConfig};
+
+/// 快模型优先时主端点的超时
+const FAST_FIRST_TIMEOUT: Duration = Duration::from_secs(4);
+
+/// 带端点名称的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Routed<T> {
+    pub value: T,
+    /// 产出结果的端点（端点名称或模型名）
+    pub endpoint: String,
+    /// 结果是否来自备用端点（备用端点的回答不写入按主端点模型区分的缓存）
+    pub from_fallback: bool,
+}
+
+/// 端点链中的一个端点
+#[derive(Clone)]
+struct Attempt {
+    label: String,
+    client: OpenAiClient,
+    /// 收到首个文本片段前的超时（None 表示只受客户端自身超时限制）
+    timeout: Option<Duration>,
+}
+
+/// LLM 备用端点链
+///
+/// 只配置主端点时行为与单个 `OpenAiClient` 相同
+#[derive(Clone)]
+pub struct FallbackClient {
+    attempts: Vec<Attempt>,
+}
+
+impl FallbackClient {
+    /// 由主端点和备用端点配置创建，无效的备用端点会被跳过
+    pub fn new(primary: LlmEndpoint, fallback: &LlmFallbackConfig) -> Self {
+        let mut endpoints = vec![primary];
+        for endpoint in &fallback.endpoints {
+            if endpoint.is_valid() {
+                endpoints.push(endpoint.clone());
+            } else {
+                tracing::warn!("LLM 备用端点 {} 配置不完整，已跳过", endpoint.label());
+            }
+        }
+
+        let attempt_timeout = (fallback.attempt_timeout_secs > 0)
+            .then(|| Duration::from_secs(fallback.attempt_timeout_secs));
+        let last = endpoints.len() - 1;
+        let attempts = endpoints
+            .into_iter()
+            .enumerate()
+            .map(|(i, endpoint)| {
+                // 最后一个端点没有可切换的目标，不设超时
+                let timeout = match i {
+                    i if i == last => None,
+                    0 if fallback.fast_first => Some(FAST_FIRST_TIMEOUT),
+                    _ => attempt_timeout,
+                };
+                let config = OpenAiClientConfig::new(&endpoint.endpoint, &endpoint.api_key, &endpoint.model)
+                    .with_protocol(endpoint.protocol);
+                Attempt {
+                    label: endpoint.label().to_string(),
+                    client: OpenAiClient::new(config),
+                    timeout,
+                }
+            })
+            .collect();
+
+        Self { attempts }
+    }
+
+    /// 主端点是否支持工具调用（备用端点不支持时忽略工具）
+    pub fn supports_tools(&self) -> bool {
+        self.attempts[0].client.supports_tools()
+    }
+
+    /// 非流式聊天，依次尝试各端点
+    pub async fn chat(&self, messages: &[Message], options: ChatOptions) -> Result<Routed<String>> {
+        let mut last_error = None;
+        for (i, attempt) in self.attempts.iter().enumerate() {
+            let request = attempt.client.chat(messages, options.clone());
+            let result = match attempt.timeout {
+                Some(timeout) => tokio::time::timeout(timeout, request)
+                    .await
+                    .unwrap_or_else(|_| Err(timeout_error(timeout))),
+                None => request.await,
+            };
+            match result {
+                Ok(value) => return Ok(self.routed(i, value)),
+                Err(e) => last_error = Some(self.log_failure(i, e)),
+            }
+        }
+        Err(last_error.expect("端点链至少包含主端点"))
+    }
+
+    /// 简化的单轮对话
+    pub async fn chat_simple(&self, system_prompt: &str, user_message: &str, options: ChatOptions) -> Result<Routed<String>> {
+        let messages = vec![Message::system(system_prompt), Message::user(user_message)];
+        self.chat(&messages, options).await
+    }
+
+    /// 流式聊天并收集完整结果，每收到一个文本片段调用一次 `on_delta`
+    ///
+    /// 在收到首个文本片段前失败或超时才会切换端点；已经输出了片段的端点中途失败时直接返回错误，
+    /// 避免同一段回答被重复输出
+    pub async fn chat_stream(
+        &self,
+        messages: &[Message],
+        options: ChatOptions,
+        mut on_delta: impl FnMut(&str),
+    ) -> Result<Routed<ChatCompletion>> {
+        let mut last_error = None;
+        for (i, attempt) in self.attempts.iter().enumerate() {
+            let started = AtomicBool::new(false);
+            let request = async {
+                let stream = attempt.client.chat_stream(messages, options.clone()).await?;
+                collect_stream(stream, |delta: &str| {
+                    started.store(true, Ordering::Relaxed);
+                    on_delta(delta);
+                })
+                .await
+            };
+            tokio::pin!(request);
+
+            let result = match attempt.timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, &mut request).await {
+                    Ok(result) => result,
+                    // 已经开始输出：不再切换，等待当前端点完成
+                    Err(_) if started.load(Ordering::Relaxed) => request.await,
+                    Err(_) => Err(timeout_error(timeout)),
+                },
+                None => request.await,
+            };
+            match result {
+                Ok(completion) => return Ok(self.routed(i, completion)),
+                Err(e) if started.load(Ordering::Relaxed) => return Err(e),
+                Err(e) => last_error = Some(self.log_failure(i, e)),
+            }
+        }
+        Err(last_error.expect("端点链至少包含主端点"))
+    }
+
+    /// 简化的单轮流式对话
+    pub async fn chat_simple_stream(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        options: ChatOptions,
+        on_delta: impl FnMut(&str),
+    ) -> Result<Routed<ChatCompletion>> {
+        let messages = vec![Message::system(system_prompt), Message::user(user_message)];
+        self.chat_stream(&messages, options, on_delta).await
+    }
+
+    fn routed<T>(&self, index: usize, value: T) -> Routed<T> {
+        let endpoint = self.attempts[index].label.clone();
+        if index > 0 {
+            tracing::info!("LLM 备用端点 {} 返回结果", endpoint);
+        }
+        Routed { value, endpoint, from_fallback: index > 0 }
+    }
+
+    fn log_failure(&self, index: usize, error: anyhow::Error) -> anyhow::Error {
+        let label = &self.attempts[index].label;
+        if index + 1 < self.attempts.len() {
+            tracing::warn!("LLM 端点 {} 请求失败，切换到下一个端点: {}", label, error);
+        }
+        error.context(format!("LLM 端点 {} 请求失败", label))
+    }
+}
+
+fn timeout_error(timeout: Duration) -> anyhow::Error {
+    anyhow::anyhow!("{} 秒内未响应", timeout.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn endpoint(url: &str, model: &str) -> LlmEndpoint {
+        LlmEndpoint {
+            name: String::new(),
+            protocol: Default::default(),
+            endpoint: url.to_string(),
+            model: model.to_string(),
+            api_key: "test-key".to_string(),
+        }
+    }
+
+    fn fallback(endpoints: Vec<LlmEndpoint>) -> LlmFallbackConfig {
+        LlmFallbackConfig { endpoints, attempt_timeout_secs: 1, fast_first: false }
+    }
+
+    /// 已关闭的端口，连接会被拒绝
+    async fn dead_url() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    /// 接受连接但从不响应
+    async fn hanging_url() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (_socket, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+        url
+    }
+
+    /// 返回 OpenAI 格式的完整 JSON 响应
+    async fn answering_url(content: &str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let body = serde_json::json!({
+            "choices": [{ "message": { "role": "assistant", "content": content }, "finish_reason": "stop" }]
+        })
+        .to_string();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 8192];
+            let _ = socket.read(&mut buf).await.unwrap();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn test_timeouts() {
+        let mut config = fallback(vec![endpoint("http://b", "strong"), endpoint("http://c", "")]);
+        let client = FallbackClient::new(endpoint("http://a", "fast"), &config);
+        // 无效的备用端点被跳过，最后一个端点不设超时
+        assert_eq!(client.attempts.len(), 2);
+        assert_eq!(client.attempts[0].timeout, Some(Duration::from_secs(1)));
+        assert_eq!(client.attempts[1].timeout, None);
+
+        config.fast_first = true;
+        let client = FallbackClient::new(endpoint("http://a", "fast"), &config);
+        assert_eq!(client.attempts[0].timeout, Some(FAST_FIRST_TIMEOUT));
+
+        let client = FallbackClient::new(endpoint("http://a", "fast"), &fallback(Vec::new()));
+        assert_eq!(client.attempts[0].timeout, None);
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_when_primary_fails() {
+        let mut strong = endpoint(&answering_url("你好。").await, "strong");
+        strong.name = "备用".to_string();
+        let client = FallbackClient::new(endpoint(&dead_url().await, "fast"), &fallback(vec![strong]));
+
+        let result = client.chat_simple("system", "user", ChatOptions::default()).await.unwrap();
+        assert_eq!(
+            result,
+            Routed { value: "你好。".to_string(), endpoint: "备用".to_string(), from_fallback: true }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_falls_back_on_timeout() {
+        let strong = endpoint(&answering_url("你好。").await, "strong");
+        let client = FallbackClient::new(endpoint(&hanging_url().await, "fast"), &fallback(vec![strong]));
+
+        let mut deltas = Vec::new();
+        let result = client
+            .chat_simple_stream("system", "user", ChatOptions::default(), |d| deltas.push(d.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(result.endpoint, "strong");
+        assert!(result.from_fallback);
+        assert_eq!(result.value.content, "你好。");
+        assert_eq!(deltas, vec!["你好。"]);
+    }
+
+    #[tokio::test]
+    async fn test_all_endpoints_fail() {
+        let client = FallbackClient::new(endpoint(&dead_url().await, "a"), &fallback(vec![endpoint(&dead_url().await, "b")]));
+        let error = client.chat_simple("system", "user", ChatOptions::default()).await.unwrap_err();
+        assert!(error.to_string().contains("LLM 端点 b 请求失败"));
+    }
+}
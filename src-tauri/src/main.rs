@@ -2,5 +2,11 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // 命令行子命令（如 `push-to-talk transcribe <文件>`）不启动 GUI
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(code) = push_to_talk_lib::run_cli(&args) {
+        std::process::exit(code);
+    }
+
     push_to_talk_lib::run()
 }
@@ -0,0 +1,265 @@
+// 麦克风诊断模块
+// 引导式音量测试：先保持安静测量底噪，再朗读校准语句测量语音电平，
+// 报告峰值、削波比例、底噪和信噪比，并给出系统麦克风增益 / AGC 建议
+
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::audio_capture::capture_ring;
+use crate::audio_utils::{calculate_rms, ClippingMonitor, AGC_MAX_GAIN, AGC_TARGET_RMS, CLIP_THRESHOLD, VAD_THRESHOLD};
+
+/// 校准语句（覆盖常见声母韵母，语速正常朗读约 4 秒）
+pub const CALIBRATION_PHRASE: &str = "今天天气很好，我正在测试语音输入的麦克风音量。";
+
+/// 安静阶段时长（测量底噪）
+const SILENCE_SECS: u32 = 2;
+/// 朗读阶段默认时长
+const DEFAULT_SPEECH_SECS: u32 = 5;
+/// 分析帧长（毫秒）
+const FRAME_MS: u32 = 20;
+/// 信噪比低于此值视为噪音过大
+const MIN_SNR_DB: f32 = 15.0;
+
+/// 诊断阶段事件（mic_diagnostics_stage）
+#[derive(Clone, Serialize)]
+struct StagePayload {
+    /// silence / speech / analyzing
+    stage: &'static str,
+    duration_secs: u32,
+    phrase: Option<&'static str>,
+}
+
+/// 麦克风诊断报告
+#[derive(Debug, Clone, Serialize)]
+pub struct MicDiagnosticsReport {
+    /// 峰值（0.0-1.0）
+    pub peak: f32,
+    pub peak_dbfs: f32,
+    /// 削波样本比例（0.0-1.0）
+    pub clipping_ratio: f32,
+    /// 底噪电平（安静阶段帧 RMS 中位数）
+    pub noise_floor_dbfs: f32,
+    /// 语音电平（朗读阶段帧 RMS 的 90 分位）
+    pub speech_level_dbfs: f32,
+    /// 估算信噪比
+    pub snr_db: f32,
+    /// 建议的系统麦克风增益调整（dB，正数为调高）
+    pub suggested_gain_db: f32,
+    /// 是否无需调整
+    pub ok: bool,
+    pub recommendations: Vec<String>,
+}
+
+fn to_dbfs(value: f32) -> f32 {
+    20.0 * value.max(1e-6).log10()
+}
+
+/// 按帧计算 RMS
+fn frame_rms(samples: &[f32], frame_len: usize) -> Vec<f32> {
+    samples.chunks(frame_len.max(1)).map(calculate_rms).collect()
+}
+
+/// 分位数（p 取 0.0-1.0）
+fn percentile(values: &[f32], p: f32) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let idx = ((sorted.len() - 1) as f32 * p).round() as usize;
+    sorted[idx]
+}
+
+/// 分析安静段和朗读段（单声道）
+pub fn analyze(silence: &[f32], speech: &[f32], sample_rate: u32) -> MicDiagnosticsReport {
+    let frame_len = (sample_rate * FRAME_MS / 1000) as usize;
+
+    let noise_floor = percentile(&frame_rms(silence, frame_len), 0.5);
+    let speech_level = percentile(&frame_rms(speech, frame_len), 0.9);
+    let peak = speech.iter().chain(silence).fold(0.0f32, |m, &s| m.max(s.abs()));
+    let clipped = speech.iter().filter(|s| s.abs() >= CLIP_THRESHOLD).count();
+    let clipping_ratio = if speech.is_empty() { 0.0 } else { clipped as f32 / speech.len() as f32 };
+    let snr_db = to_dbfs(speech_level) - to_dbfs(noise_floor);
+
+    let mut recommendations = Vec::new();
+    let mut suggested_gain_db = 0.0;
+    let mut ok = true;
+
+    if speech_level < VAD_THRESHOLD {
+        ok = false;
+        recommendations.push("几乎没有检测到声音：请检查麦克风是否被静音，或系统是否选错了输入设备".to_string());
+    } else if clipping_ratio > ClippingMonitor::RATIO_LIMIT {
+        // 削波后无法还原，只能降低输入增益
+        ok = false;
+        suggested_gain_db = (to_dbfs(AGC_TARGET_RMS) - to_dbfs(speech_level)).clamp(-20.0, -3.0);
+        recommendations.push(format!(
+            "检测到削波（{:.2}% 样本），请将系统麦克风增益降低约 {:.0} dB，并关闭声卡或驱动自带的自动增益",
+            clipping_ratio * 100.0,
+            -suggested_gain_db,
+        ));
+    } else if speech_level * AGC_MAX_GAIN < AGC_TARGET_RMS {
+        ok = false;
+        suggested_gain_db = (to_dbfs(AGC_TARGET_RMS / AGC_MAX_GAIN) - to_dbfs(speech_level)).clamp(0.0, 20.0).ceil();
+        recommendations.push(format!(
+            "音量过低（{:.0} dBFS），超出内置 AGC 的补偿范围（最多 {:.0} dB），请将系统麦克风增益调高约 {:.0} dB 或靠近麦克风",
+            to_dbfs(speech_level),
+            to_dbfs(AGC_MAX_GAIN),
+            suggested_gain_db,
+        ));
+    } else if speech_level < AGC_TARGET_RMS {
+        recommendations.push("音量略低，内置 AGC 会自动补偿，无需调整".to_string());
+    }
+
+    if speech_level >= VAD_THRESHOLD && snr_db < MIN_SNR_DB {
+        ok = false;
+        recommendations.push(format!(
+            "背景噪音较大（信噪比 {:.0} dB），建议靠近麦克风、使用指向性麦克风或降低环境噪音",
+            snr_db,
+        ));
+    }
+    if noise_floor >= VAD_THRESHOLD {
+        ok = false;
+        recommendations.push("底噪高于语音检测阈值，停顿时的噪音也会被当作语音发送".to_string());
+    }
+
+    if recommendations.is_empty() {
+        recommendations.push("麦克风状态良好".to_string());
+    }
+
+    MicDiagnosticsReport {
+        peak,
+        peak_dbfs: to_dbfs(peak),
+        clipping_ratio,
+        noise_floor_dbfs: to_dbfs(noise_floor),
+        speech_level_dbfs: to_dbfs(speech_level),
+        snr_db,
+        suggested_gain_db,
+        ok,
+        recommendations,
+    }
+}
+
+/// 执行引导式麦克风测试（阻塞，需在后台线程调用）
+///
+/// 通过 mic_diagnostics_stage 事件通知前端当前阶段（安静 → 朗读 → 分析）
+pub fn run(app: &AppHandle, speech_secs: Option<u32>) -> Result<MicDiagnosticsReport> {
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+    let speech_secs = speech_secs.unwrap_or(DEFAULT_SPEECH_SECS).clamp(2, 15);
+
+    let host = cpal::default_host();
+    let device = host
+        .default_input_device()
+        .ok_or_else(|| anyhow::anyhow!("没有找到默认音频输入设备"))?;
+    let supported_config = device
+        .default_input_config()
+        .map_err(|e| anyhow::anyhow!("无法获取默认音频配置: {}", e))?;
+    let config = supported_config.config();
+    let sample_rate = config.sample_rate.0;
+    let channels = config.channels as usize;
+
+    tracing::info!("麦克风诊断开始: 设备={:?}, 采样率={}Hz, 声道={}",
+        device.name().ok(), sample_rate, channels);
+
+    // 只保留单声道（多声道取平均），不做 AGC，测量原始电平
+    let samples: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
+    let (mut producer, consumer) = capture_ring(sample_rate, config.channels, Arc::new(AtomicBool::new(true)));
+    let err_fn = |err| tracing::error!("麦克风诊断录音流错误: {}", err);
+    let stream = match supported_config.sample_format() {
+        cpal::SampleFormat::F32 => device.build_input_stream(
+            &config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| producer.push_f32(data),
+            err_fn,
+            None,
+        )?,
+        cpal::SampleFormat::I16 => device.build_input_stream(
+            &config,
+            move |data: &[i16], _: &cpal::InputCallbackInfo| producer.push_i16(data),
+            err_fn,
+            None,
+        )?,
+        cpal::SampleFormat::U16 => device.build_input_stream(
+            &config,
+            move |data: &[u16], _: &cpal::InputCallbackInfo| producer.push_u16(data),
+            err_fn,
+            None,
+        )?,
+        _ => return Err(anyhow::anyhow!("不支持的采样格式")),
+    };
+    let samples_worker = Arc::clone(&samples);
+    let worker = consumer.spawn_worker(move |data| {
+        let mut samples = samples_worker.lock().unwrap();
+        samples.extend(data.chunks_exact(channels).map(|f| f.iter().sum::<f32>() / channels as f32));
+    });
+
+    stream.play()?;
+
+    let _ = app.emit("mic_diagnostics_stage", StagePayload { stage: "silence", duration_secs: SILENCE_SECS, phrase: None });
+    std::thread::sleep(Duration::from_secs(SILENCE_SECS as u64));
+    let split = samples.lock().unwrap().len();
+
+    let _ = app.emit("mic_diagnostics_stage", StagePayload { stage: "speech", duration_secs: speech_secs, phrase: Some(CALIBRATION_PHRASE) });
+    std::thread::sleep(Duration::from_secs(speech_secs as u64));
+
+    drop(stream);
+    worker.finish();
+    let _ = app.emit("mic_diagnostics_stage", StagePayload { stage: "analyzing", duration_secs: 0, phrase: None });
+
+    let samples = std::mem::take(&mut *samples.lock().unwrap());
+    if samples.len() <= split {
+        return Err(anyhow::anyhow!("没有采集到音频数据"));
+    }
+    let (silence, speech) = samples.split_at(split);
+    let report = analyze(silence, speech, sample_rate);
+    tracing::info!("麦克风诊断完成: 峰值={:.1}dBFS, 削波={:.3}%, 底噪={:.1}dBFS, 信噪比={:.1}dB",
+        report.peak_dbfs, report.clipping_ratio * 100.0, report.noise_floor_dbfs, report.snr_db);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn tone(amplitude: f32, secs: f32) -> Vec<f32> {
+        (0..(RATE as f32 * secs) as usize)
+            .map(|i| amplitude * (i as f32 * 2.0 * std::f32::consts::PI * 220.0 / RATE as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_good_microphone() {
+        let report = analyze(&tone(0.001, 1.0), &tone(0.2, 2.0), RATE);
+        assert!(report.ok, "{:?}", report.recommendations);
+        assert_eq!(report.clipping_ratio, 0.0);
+        assert!(report.snr_db > 40.0);
+    }
+
+    #[test]
+    fn test_clipping_microphone() {
+        let clipped: Vec<f32> = tone(2.0, 2.0).into_iter().map(|s| s.clamp(-1.0, 1.0)).collect();
+        let report = analyze(&tone(0.001, 1.0), &clipped, RATE);
+        assert!(!report.ok);
+        assert!(report.clipping_ratio > 0.1);
+        assert!(report.suggested_gain_db < 0.0);
+    }
+
+    #[test]
+    fn test_quiet_microphone() {
+        let report = analyze(&tone(0.0001, 1.0), &tone(0.01, 2.0), RATE);
+        assert!(!report.ok);
+        assert!(report.suggested_gain_db > 0.0);
+    }
+
+    #[test]
+    fn test_noisy_environment() {
+        let report = analyze(&tone(0.05, 1.0), &tone(0.1, 2.0), RATE);
+        assert!(report.snr_db < MIN_SNR_DB);
+        assert!(!report.ok);
+    }
+}
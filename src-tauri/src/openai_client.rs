@@ -1,16 +1,27 @@
 // src-tauri/src/openai_client.rs
 //
-// 通用 OpenAI 兼容 API 客户端
+// 通用 LLM API 客户端
 //
 // 提供统一的 LLM 调用接口，支持所有 OpenAI 兼容的 API 服务
-// （如 OpenAI、智谱 GLM、DeepSeek、通义千问等）
+// （如 OpenAI、智谱 GLM、DeepSeek、通义千问等），
+// 以及 Anthropic、Gemini、Ollama 原生协议（请求与响应格式见 chat_backend.rs）
 
 use anyhow::Result;
+use futures_util::{Stream, StreamExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::Arc;
 use std::time::Duration;
 
+use crate::chat_backend::{backend_for, ChatBackend, Framing};
+use crate::config::ChatProtocol;
+
+/// 流式请求的总超时（长回答可能持续较久，覆盖客户端默认的 30 秒）
+const STREAM_TIMEOUT: Duration = Duration::from_secs(120);
+
 // ============================================================================
 // 消息类型定义
 // ============================================================================
@@ -21,6 +32,8 @@ pub enum Role {
     System,
     User,
     Assistant,
+    /// 工具执行结果
+    Tool,
 }
 
 impl Role {
@@ -29,6 +42,7 @@ impl Role {
             Role::System => "system",
             Role::User => "user",
             Role::Assistant => "assistant",
+            Role::Tool => "tool",
         }
     }
 }
@@ -38,6 +52,10 @@ impl Role {
 pub struct Message {
     pub role: Role,
     pub content: String,
+    /// 模型请求的工具调用（assistant 消息）
+    pub tool_calls: Vec<ToolCall>,
+    /// 对应的工具调用 ID（tool 消息）
+    pub tool_call_id: Option<String>,
 }
 
 impl Message {
@@ -45,6 +63,8 @@ impl Message {
         Self {
             role: Role::System,
             content: content.into(),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
         }
     }
 
@@ -52,16 +72,61 @@ impl Message {
         Self {
             role: Role::User,
             content: content.into(),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
         }
     }
 
-    #[allow(dead_code)]
     pub fn assistant(content: impl Into<String>) -> Self {
         Self {
             role: Role::Assistant,
             content: content.into(),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }
+    }
+
+    /// 带工具调用的 assistant 消息（下一轮请求时原样发回）
+    pub fn assistant_with_tool_calls(content: impl Into<String>, tool_calls: Vec<ToolCall>) -> Self {
+        Self {
+            role: Role::Assistant,
+            content: content.into(),
+            tool_calls,
+            tool_call_id: None,
         }
     }
+
+    /// 工具执行结果
+    pub fn tool(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            role: Role::Tool,
+            content: content.into(),
+            tool_calls: Vec::new(),
+            tool_call_id: Some(tool_call_id.into()),
+        }
+    }
+}
+
+// ============================================================================
+// 工具调用
+// ============================================================================
+
+/// 提供给模型的工具（OpenAI function calling 格式）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    /// 参数的 JSON Schema
+    pub parameters: Value,
+}
+
+/// 模型请求的一次工具调用
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    /// JSON 格式的参数（原样保留，由调用方解析）
+    pub arguments: String,
 }
 
 // ============================================================================
@@ -76,6 +141,8 @@ pub struct ChatOptions {
     /// 温度参数（0.0-1.0，越低越确定）
     /// 使用 f64 避免浮点精度问题（f32 的 0.3 会变成 0.30000001192092896）
     pub temperature: f64,
+    /// 可供调用的工具（为空时不发送，协议不支持时忽略）
+    pub tools: Vec<ToolDefinition>,
 }
 
 impl Default for ChatOptions {
@@ -83,6 +150,7 @@ impl Default for ChatOptions {
         Self {
             max_tokens: 1024,
             temperature: 0.3,
+            tools: Vec::new(),
         }
     }
 }
@@ -93,6 +161,7 @@ impl ChatOptions {
         Self {
             max_tokens: 2048,  // 使用与 Smart Command 相同的值，避免 API 兼容性问题
             temperature: 0.3,
+            tools: Vec::new(),
         }
     }
 
@@ -101,17 +170,290 @@ impl ChatOptions {
         Self {
             max_tokens: 2048,
             temperature: 0.5,
+            tools: Vec::new(),
         }
     }
+
+    /// 附加可调用的工具
+    pub fn with_tools(mut self, tools: Vec<ToolDefinition>) -> Self {
+        self.tools = tools;
+        self
+    }
+}
+
+// ============================================================================
+// 流式响应
+// ============================================================================
+
+/// token 用量（流式响应结束时由服务端返回，部分服务不返回）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: u32,
+    #[serde(default)]
+    pub completion_tokens: u32,
+    #[serde(default)]
+    pub total_tokens: u32,
+}
+
+impl Usage {
+    pub fn new(prompt_tokens: u32, completion_tokens: u32) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+
+    /// 合并分多次返回的用量（Anthropic 在开始时返回输入 token，结束时返回输出 token）
+    fn merge(&mut self, other: &Usage) {
+        if other.prompt_tokens > 0 {
+            self.prompt_tokens = other.prompt_tokens;
+        }
+        if other.completion_tokens > 0 {
+            self.completion_tokens = other.completion_tokens;
+        }
+        self.total_tokens = other.total_tokens.max(self.prompt_tokens + self.completion_tokens);
+    }
+}
+
+/// 流式响应事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    /// 新生成的文本片段
+    Delta(String),
+    /// 模型请求的工具调用（流结束时一次性给出，紧接着是 Done）
+    ToolCalls(Vec<ToolCall>),
+    /// 生成结束（流中最后一个事件）
+    Done {
+        /// 结束原因（stop / length / content_filter 等）
+        finish_reason: Option<String>,
+        usage: Option<Usage>,
+    },
+}
+
+/// 流式响应
+pub type ChatStream = Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send>>;
+
+/// 流式响应收集完成后的结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChatCompletion {
+    pub content: String,
+    pub finish_reason: Option<String>,
+    pub usage: Option<Usage>,
+    /// 模型请求的工具调用（finish_reason 为 tool_calls 时）
+    pub tool_calls: Vec<ToolCall>,
+}
+
+/// 读取整个流，每收到一个文本片段调用一次 `on_delta`
+pub async fn collect_stream(mut stream: ChatStream, mut on_delta: impl FnMut(&str)) -> Result<ChatCompletion> {
+    let mut completion = ChatCompletion::default();
+    while let Some(event) = stream.next().await {
+        match event? {
+            StreamEvent::Delta(delta) => {
+                on_delta(&delta);
+                completion.content.push_str(&delta);
+            }
+            StreamEvent::ToolCalls(tool_calls) => completion.tool_calls = tool_calls,
+            StreamEvent::Done { finish_reason, usage } => {
+                completion.finish_reason = finish_reason;
+                completion.usage = usage;
+                break;
+            }
+        }
+    }
+
+    if completion.finish_reason.as_deref() == Some("length") {
+        tracing::warn!("LLM 流式响应因 max_tokens 限制被截断");
+    }
+    tracing::info!(
+        "LLM 流式响应结束: finish_reason={:?}, usage={:?}",
+        completion.finish_reason,
+        completion.usage
+    );
+    completion.content = completion.content.trim().to_string();
+    Ok(completion)
+}
+
+/// 流式响应分帧解析器
+///
+/// 按行缓冲（一个网络分块可能截断在行中间或多字节字符中间）。
+/// SSE 以空行结束一个事件，返回事件中所有 data 字段拼接后的内容；NDJSON 每个非空行就是一个分块
+#[derive(Debug)]
+struct StreamParser {
+    framing: Framing,
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl StreamParser {
+    fn new(framing: Framing) -> Self {
+        Self {
+            framing,
+            buffer: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    /// 输入一个网络分块，返回其中已完整的分块数据
+    fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            if let Some(event) = self.line(line.trim_end_matches(['\n', '\r'])) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// 连接关闭时处理缓冲区中剩余的内容
+    fn flush(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.buffer);
+        let rest = String::from_utf8_lossy(&rest);
+        self.line(rest.trim_end_matches('\r')).or_else(|| self.line(""))
+    }
+
+    fn line(&mut self, line: &str) -> Option<String> {
+        if self.framing == Framing::Ndjson {
+            return (!line.trim().is_empty()).then(|| line.to_string());
+        }
+        if line.is_empty() {
+            return (!self.data.is_empty()).then(|| std::mem::take(&mut self.data).join("\n"));
+        }
+        // 注释行（": keep-alive"）以及 event / id / retry 字段不需要处理
+        if let Some(value) = line.strip_prefix("data:") {
+            self.data.push(value.strip_prefix(' ').unwrap_or(value).to_string());
+        }
+        None
+    }
+}
+
+/// 将流式响应字节流转换为 StreamEvent
+struct StreamState<S> {
+    body: S,
+    backend: Arc<dyn ChatBackend>,
+    parser: StreamParser,
+    pending: VecDeque<String>,
+    finish_reason: Option<String>,
+    usage: Option<Usage>,
+    /// 按 index 拼接的工具调用（名称和参数分多个分块返回）
+    tool_calls: Vec<ToolCall>,
+    eof: bool,
+    finished: bool,
+}
+
+impl<S, B, E> StreamState<S>
+where
+    S: Stream<Item = std::result::Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: Into<anyhow::Error>,
+{
+    fn new(body: S, backend: Arc<dyn ChatBackend>) -> Self {
+        Self {
+            body,
+            parser: StreamParser::new(backend.framing()),
+            backend,
+            pending: VecDeque::new(),
+            finish_reason: None,
+            usage: None,
+            tool_calls: Vec::new(),
+            eof: false,
+            finished: false,
+        }
+    }
+
+    async fn next_event(&mut self) -> Option<Result<StreamEvent>> {
+        loop {
+            if self.finished {
+                return None;
+            }
+            while let Some(data) = self.pending.pop_front() {
+                match self.backend.parse_stream_chunk(&data) {
+                    Ok(chunk) => {
+                        if chunk.finish_reason.is_some() {
+                            self.finish_reason = chunk.finish_reason;
+                        }
+                        if let Some(usage) = chunk.usage {
+                            self.usage.get_or_insert_with(Usage::default).merge(&usage);
+                        }
+                        for delta in chunk.tool_calls {
+                            if self.tool_calls.len() <= delta.index {
+                                self.tool_calls.resize_with(delta.index + 1, ToolCall::default);
+                            }
+                            let call = &mut self.tool_calls[delta.index];
+                            if let Some(id) = delta.id {
+                                call.id = id;
+                            }
+                            if let Some(name) = delta.name {
+                                call.name = name;
+                            }
+                            call.arguments.push_str(&delta.arguments);
+                        }
+                        // 收到结束标记后不再读取，剩余内容忽略
+                        if chunk.done {
+                            self.pending.clear();
+                            self.eof = true;
+                        }
+                        if !chunk.delta.is_empty() {
+                            return Some(Ok(StreamEvent::Delta(chunk.delta)));
+                        }
+                    }
+                    Err(e) => {
+                        self.finished = true;
+                        return Some(Err(e));
+                    }
+                }
+            }
+            // 部分服务不发送结束标记，连接关闭即视为结束
+            if self.eof {
+                return Some(Ok(self.finish()));
+            }
+            match self.body.next().await {
+                Some(Ok(bytes)) => self.pending.extend(self.parser.feed(bytes.as_ref())),
+                Some(Err(e)) => {
+                    self.finished = true;
+                    return Some(Err(e.into()));
+                }
+                None => {
+                    self.eof = true;
+                    self.pending.extend(self.parser.flush());
+                }
+            }
+        }
+    }
+
+    /// 先给出拼接好的工具调用，再给出 Done
+    fn finish(&mut self) -> StreamEvent {
+        if !self.tool_calls.is_empty() {
+            return StreamEvent::ToolCalls(std::mem::take(&mut self.tool_calls));
+        }
+        self.finished = true;
+        StreamEvent::Done {
+            finish_reason: self.finish_reason.take(),
+            usage: self.usage.take(),
+        }
+    }
+
+    fn into_stream(self) -> impl Stream<Item = Result<StreamEvent>> {
+        futures_util::stream::unfold(self, |mut state| async move {
+            state.next_event().await.map(|event| (event, state))
+        })
+    }
 }
 
 // ============================================================================
 // 客户端配置
 // ============================================================================
 
-/// OpenAI 兼容 API 客户端配置
+/// LLM API 客户端配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenAiClientConfig {
+    /// 聊天协议
+    #[serde(default)]
+    pub protocol: ChatProtocol,
     /// API 端点 (如 https://api.openai.com/v1/chat/completions)
     pub endpoint: String,
     /// API Key
@@ -123,23 +465,31 @@ pub struct OpenAiClientConfig {
 impl OpenAiClientConfig {
     pub fn new(endpoint: impl Into<String>, api_key: impl Into<String>, model: impl Into<String>) -> Self {
         Self {
+            protocol: ChatProtocol::default(),
             endpoint: endpoint.into(),
             api_key: api_key.into(),
             model: model.into(),
         }
     }
+
+    /// 使用指定的聊天协议（默认 OpenAI 兼容格式）
+    pub fn with_protocol(mut self, protocol: ChatProtocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
 }
 
 // ============================================================================
 // OpenAI 客户端
 // ============================================================================
 
-/// 通用 OpenAI 兼容 API 客户端
+/// 通用 LLM API 客户端
 ///
-/// 支持所有 OpenAI 兼容的 API 服务，提供统一的聊天接口
+/// 支持所有 OpenAI 兼容的 API 服务以及 Anthropic、Gemini、Ollama 原生协议，提供统一的聊天接口
 #[derive(Clone)]
 pub struct OpenAiClient {
     config: OpenAiClientConfig,
+    backend: Arc<dyn ChatBackend>,
     client: Client,
 }
 
@@ -155,7 +505,13 @@ impl OpenAiClient {
             .build()
             .unwrap_or_else(|_| Client::new());
 
-        Self { config, client }
+        let backend = backend_for(config.protocol);
+        Self { config, backend, client }
+    }
+
+    /// 当前协议是否支持工具调用
+    pub fn supports_tools(&self) -> bool {
+        self.backend.supports_tools()
     }
 
     /// 通用聊天方法
@@ -179,60 +535,118 @@ impl OpenAiClient {
             return Ok(String::new());
         }
 
-        // 构建 OpenAI 兼容格式的消息
-        let messages_json: Vec<Value> = messages
-            .iter()
-            .map(|m| {
-                serde_json::json!({
-                    "role": m.role.as_str(),
-                    "content": m.content
-                })
-            })
-            .collect();
-
-        let request_body = serde_json::json!({
-            "model": self.config.model,
-            "messages": messages_json,
-            "max_tokens": options.max_tokens,
-            "temperature": options.temperature
-        });
+        let request = self.backend.request(&self.config, messages, &options, false);
 
         // 打印完整请求信息用于调试
         tracing::info!(
-            "[DEBUG] OpenAI 请求: endpoint={}, model={}, api_key_len={}, max_tokens={}, temperature={}",
-            self.config.endpoint,
+            "[DEBUG] {} 请求: url={}, model={}, api_key_len={}, max_tokens={}, temperature={}",
+            self.backend.name(),
+            request.url,
             self.config.model,
             self.config.api_key.len(),
             options.max_tokens,
             options.temperature
         );
-        tracing::info!("[DEBUG] 请求体: {}", serde_json::to_string_pretty(&request_body).unwrap_or_default());
+        tracing::info!("[DEBUG] 请求体: {}", serde_json::to_string_pretty(&request.body).unwrap_or_default());
 
-        let response = self
+        let mut builder = self
             .client
-            .post(&self.config.endpoint)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await?;
+            .post(&request.url)
+            .header("Content-Type", "application/json");
+        for (name, value) in &request.headers {
+            builder = builder.header(*name, value);
+        }
+        let response = builder.json(&request.body).send().await?;
 
         let status = response.status();
         if !status.is_success() {
             let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("OpenAI API 请求失败 ({}): {}", status, text);
+            anyhow::bail!("{} API 请求失败 ({}): {}", self.backend.name(), status, text);
         }
 
         let payload: Value = response.json().await?;
+        Ok(self.backend.parse_response(&payload)?.content.trim().to_string())
+    }
 
-        // 解析 OpenAI 格式的响应
-        let content = payload["choices"]
-            .as_array()
-            .and_then(|arr| arr.first())
-            .and_then(|choice| choice["message"]["content"].as_str())
-            .ok_or_else(|| anyhow::anyhow!("OpenAI API 返回格式不可解析: {:?}", payload))?;
+    /// 流式聊天方法
+    ///
+    /// 以流式请求，按 text/event-stream（Ollama 为逐行 JSON）逐个返回生成的文本片段，
+    /// 最后一个事件为 `StreamEvent::Done`（包含结束原因和 token 用量）。
+    /// 服务端忽略 stream 参数直接返回完整 JSON 时，整段内容作为一个片段返回
+    ///
+    /// # Example
+    /// ```ignore
+    /// let stream = client.chat_stream(&messages, ChatOptions::default()).await?;
+    /// let completion = collect_stream(stream, |delta| print!("{}", delta)).await?;
+    /// ```
+    pub async fn chat_stream(&self, messages: &[Message], options: ChatOptions) -> Result<ChatStream> {
+        let request = self.backend.request(&self.config, messages, &options, true);
+        tracing::info!(
+            "[DEBUG] {} 流式请求: url={}, model={}, max_tokens={}, temperature={}",
+            self.backend.name(),
+            request.url,
+            self.config.model,
+            options.max_tokens,
+            options.temperature
+        );
+
+        let mut builder = self
+            .client
+            .post(&request.url)
+            .timeout(STREAM_TIMEOUT)
+            .header("Content-Type", "application/json");
+        if self.backend.framing() == Framing::Sse {
+            builder = builder.header("Accept", "text/event-stream");
+        }
+        for (name, value) in &request.headers {
+            builder = builder.header(*name, value);
+        }
+        let response = builder.json(&request.body).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("{} API 请求失败 ({}): {}", self.backend.name(), status, text);
+        }
+
+        // Ollama 的非流式响应就是单行 JSON，按逐行解析同样适用
+        let is_event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        if self.backend.framing() == Framing::Sse && !is_event_stream {
+            tracing::warn!("{} 服务端未返回流式响应，按完整响应处理", self.backend.name());
+            let payload: Value = response.json().await?;
+            let completion = self.backend.parse_response(&payload)?;
+            let mut events = vec![Ok(StreamEvent::Delta(completion.content))];
+            if !completion.tool_calls.is_empty() {
+                events.push(Ok(StreamEvent::ToolCalls(completion.tool_calls)));
+            }
+            events.push(Ok(StreamEvent::Done {
+                finish_reason: completion.finish_reason,
+                usage: completion.usage,
+            }));
+            return Ok(Box::pin(futures_util::stream::iter(events)));
+        }
+
+        Ok(Box::pin(StreamState::new(response.bytes_stream(), self.backend.clone()).into_stream()))
+    }
 
-        Ok(content.trim().to_string())
+    /// 简化的单轮流式对话方法，返回完整结果
+    pub async fn chat_simple_stream(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        options: ChatOptions,
+        on_delta: impl FnMut(&str),
+    ) -> Result<ChatCompletion> {
+        let messages = vec![
+            Message::system(system_prompt),
+            Message::user(user_message),
+        ];
+        let stream = self.chat_stream(&messages, options).await?;
+        collect_stream(stream, on_delta).await
     }
 
     /// 简化的单轮对话方法
@@ -298,4 +712,196 @@ mod tests {
         let invalid = OpenAiClientConfig::new("", "sk-xxx", "gpt-4");
         assert!(!invalid.is_valid());
     }
+
+    #[test]
+    fn test_sse_parser_handles_split_chunks() {
+        let mut parser = StreamParser::new(Framing::Sse);
+        let text = "data: {\"a\":\"你好\"}\r\n\r\n: keep-alive\n\ndata: [DONE]\n\n".as_bytes();
+        // 从多字节字符中间切开
+        let (head, tail) = text.split_at(12);
+        assert!(parser.feed(head).is_empty());
+        assert_eq!(parser.feed(tail), vec!["{\"a\":\"你好\"}".to_string(), "[DONE]".to_string()]);
+        assert_eq!(parser.flush(), None);
+    }
+
+    #[test]
+    fn test_ndjson_parser() {
+        let mut parser = StreamParser::new(Framing::Ndjson);
+        assert_eq!(parser.feed(b"{\"a\":1}\n\n{\"b\""), vec!["{\"a\":1}".to_string()]);
+        assert_eq!(parser.flush(), Some("{\"b\"".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_stream_events() {
+        let body = [
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\" 你\"}}]}\n\ndata: {\"choices\":[{\"delta\":",
+            "{\"content\":\"好 \"},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":3,\"completion_tokens\":2,\"total_tokens\":5}}\n\n",
+            "data: [DONE]\n\n",
+        ];
+        let chunks = body.iter().map(|c| Ok::<_, std::io::Error>(c.as_bytes())).collect::<Vec<_>>();
+        let stream: ChatStream = Box::pin(StreamState::new(futures_util::stream::iter(chunks), backend_for(ChatProtocol::OpenAi)).into_stream());
+
+        let mut deltas = Vec::new();
+        let completion = collect_stream(stream, |d| deltas.push(d.to_string())).await.unwrap();
+        assert_eq!(deltas, vec![" 你", "好 "]);
+        assert_eq!(completion.content, "你好");
+        assert_eq!(completion.finish_reason.as_deref(), Some("stop"));
+        assert_eq!(completion.usage.map(|u| u.total_tokens), Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_stream_tool_calls() {
+        let body = [
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"function\":{\"name\":\"open_url\",\"arguments\":\"\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{\\\"url\\\":\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"\\\"https://a.b\\\"}\"}}]},\"finish_reason\":\"tool_calls\"}]}\n\n",
+            "data: [DONE]\n\n",
+        ];
+        let chunks = body.iter().map(|c| Ok::<_, std::io::Error>(c.as_bytes())).collect::<Vec<_>>();
+        let stream: ChatStream = Box::pin(StreamState::new(futures_util::stream::iter(chunks), backend_for(ChatProtocol::OpenAi)).into_stream());
+
+        let completion = collect_stream(stream, |_| {}).await.unwrap();
+        assert_eq!(completion.content, "");
+        assert_eq!(completion.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(
+            completion.tool_calls,
+            vec![ToolCall {
+                id: "call_1".to_string(),
+                name: "open_url".to_string(),
+                arguments: r#"{"url":"https://a.b"}"#.to_string(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_without_done_marker() {
+        let chunks = vec![Ok::<_, std::io::Error>("data: {\"choices\":[{\"delta\":{\"content\":\"ok\"}}]}".as_bytes())];
+        let mut stream = StreamState::new(futures_util::stream::iter(chunks), backend_for(ChatProtocol::OpenAi));
+        assert_eq!(stream.next_event().await.unwrap().unwrap(), StreamEvent::Delta("ok".to_string()));
+        assert_eq!(
+            stream.next_event().await.unwrap().unwrap(),
+            StreamEvent::Done { finish_reason: None, usage: None }
+        );
+        assert!(stream.next_event().await.is_none());
+    }
+
+    /// 本地模拟服务：接受一个连接，返回固定响应，并返回收到的原始请求
+    async fn mock_server(content_type: &'static str, body: String) -> (String, tokio::task::JoinHandle<String>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(head_end) = text.find("\r\n\r\n") {
+                    let content_length = text[..head_end]
+                        .lines()
+                        .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                        .and_then(|v| v.parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if request.len() >= head_end + 4 + content_length {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                content_type,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+        (url, handle)
+    }
+
+    fn test_messages() -> Vec<Message> {
+        vec![Message::system("简洁回答"), Message::user("你好")]
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_stream_against_mock_server() {
+        let body = [
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":12,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"ping"}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"你"}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"好"}}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":2}}"#,
+            r#"{"type":"message_stop"}"#,
+        ]
+        .iter()
+        .map(|data| format!("event: message\ndata: {}\n\n", data))
+        .collect();
+        let (url, server) = mock_server("text/event-stream", body).await;
+
+        let config = OpenAiClientConfig::new(format!("{}/v1/messages", url), "sk-ant", "claude-test")
+            .with_protocol(ChatProtocol::Anthropic);
+        let stream = OpenAiClient::new(config).chat_stream(&test_messages(), ChatOptions::default()).await.unwrap();
+        let completion = collect_stream(stream, |_| {}).await.unwrap();
+        assert_eq!(completion.content, "你好");
+        assert_eq!(completion.finish_reason.as_deref(), Some("stop"));
+        assert_eq!(completion.usage, Some(Usage::new(12, 2)));
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /v1/messages"));
+        assert!(request.contains("x-api-key: sk-ant"));
+        assert!(request.contains("anthropic-version: "));
+        assert!(request.contains(r#""system":"简洁回答""#));
+    }
+
+    #[tokio::test]
+    async fn test_gemini_chat_against_mock_server() {
+        let body = r#"{"candidates":[{"content":{"role":"model","parts":[{"text":" 你好 "}]},"finishReason":"STOP"}]}"#;
+        let (url, server) = mock_server("application/json", body.to_string()).await;
+
+        let config = OpenAiClientConfig::new(format!("{}/v1beta", url), "g-key", "gemini-test")
+            .with_protocol(ChatProtocol::Gemini);
+        let content = OpenAiClient::new(config).chat(&test_messages(), ChatOptions::default()).await.unwrap();
+        assert_eq!(content, "你好");
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /v1beta/models/gemini-test:generateContent"));
+        assert!(request.contains("x-goog-api-key: g-key"));
+        assert!(request.contains(r#""systemInstruction""#));
+    }
+
+    #[tokio::test]
+    async fn test_ollama_stream_against_mock_server() {
+        let body = [
+            r#"{"message":{"role":"assistant","content":"Hel"},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":"lo"},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","prompt_eval_count":8,"eval_count":2}"#,
+        ]
+        .join("\n");
+        let (url, server) = mock_server("application/x-ndjson", body).await;
+
+        let config = OpenAiClientConfig::new(format!("{}/api/chat", url), "", "llama-test")
+            .with_protocol(ChatProtocol::Ollama);
+        let mut deltas = Vec::new();
+        let completion = OpenAiClient::new(config)
+            .chat_simple_stream("简洁回答", "hi", ChatOptions::default(), |d| deltas.push(d.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(deltas, vec!["Hel", "lo"]);
+        assert_eq!(completion.content, "Hello");
+        assert_eq!(completion.usage.map(|u| u.total_tokens), Some(10));
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /api/chat"));
+        assert!(!request.to_ascii_lowercase().contains("authorization"));
+        assert!(request.contains(r#""stream":true"#));
+    }
 }
@@ -0,0 +1,167 @@
+// 结果输出模块
+// 将管道结果分发到配置的输出目标：剪贴板、文件、webhook
+//
+// 插入目标窗口（OutputSink::Insert）由 Pipeline 自身完成，这里只处理其余目标
+
+use anyhow::{Context, Result};
+use arboard::Clipboard;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::{FileSinkFormat, OutputSink};
+use crate::pipeline::PipelineResult;
+
+/// webhook 请求超时
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 文件和 webhook 收到的数据
+#[derive(Debug, Clone, Serialize)]
+pub struct SinkPayload {
+    /// 转写 ID（与前端历史记录 ID 一致）
+    pub id: String,
+    /// 本地时间（RFC 3339）
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub result: PipelineResult,
+}
+
+impl SinkPayload {
+    pub fn new(id: &str, result: PipelineResult) -> Self {
+        Self::at(id, result, Local::now())
+    }
+
+    fn at(id: &str, result: PipelineResult, time: DateTime<Local>) -> Self {
+        Self {
+            id: id.to_string(),
+            timestamp: time.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            result,
+        }
+    }
+}
+
+/// 是否需要插入到目标窗口
+pub fn wants_insert(sinks: &[OutputSink]) -> bool {
+    sinks.contains(&OutputSink::Insert)
+}
+
+/// 将结果依次输出到各个目标（跳过 Insert）
+///
+/// 单个目标失败不影响其他目标，返回失败目标的错误描述
+pub async fn deliver(sinks: &[OutputSink], payload: &SinkPayload) -> Vec<String> {
+    let mut failures = Vec::new();
+    for sink in sinks {
+        let result = match sink {
+            OutputSink::Insert => continue,
+            OutputSink::Clipboard => copy_to_clipboard(&payload.result.text),
+            OutputSink::File { path, format } => append_to_file(path, *format, payload),
+            OutputSink::Webhook { url } => post_webhook(url, payload).await,
+        };
+        match result {
+            Ok(()) => tracing::info!("输出目标 {:?} 完成", sink),
+            Err(e) => {
+                tracing::error!("输出目标 {:?} 失败: {:#}", sink, e);
+                failures.push(format!("{:#}", e));
+            }
+        }
+    }
+    failures
+}
+
+fn copy_to_clipboard(text: &str) -> Result<()> {
+    Clipboard::new()?.set_text(text)?;
+    Ok(())
+}
+
+fn append_to_file(path: &str, format: FileSinkFormat, payload: &SinkPayload) -> Result<()> {
+    let path = expand_home(path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("无法打开输出文件 {}", path.display()))?;
+    file.write_all(format_entry(format, payload).as_bytes())?;
+    Ok(())
+}
+
+/// 单条记录的文件内容
+fn format_entry(format: FileSinkFormat, payload: &SinkPayload) -> String {
+    // 2026-01-02T09:30:00+08:00 → 2026-01-02 09:30:00
+    let time = payload.timestamp.get(..19).unwrap_or(&payload.timestamp).replacen('T', " ", 1);
+    let text = payload.result.text.trim();
+    match format {
+        FileSinkFormat::Markdown => format!("## {}\n\n{}\n\n", time, text),
+        FileSinkFormat::Text => format!("[{}] {}\n", time, text),
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+async fn post_webhook(url: &str, payload: &SinkPayload) -> Result<()> {
+    reqwest::Client::new()
+        .post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .json(payload)
+        .send()
+        .await
+        .with_context(|| format!("webhook 请求失败: {}", url))?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::TranscriptionMode;
+    use chrono::TimeZone;
+
+    fn payload(text: &str) -> SinkPayload {
+        let result = PipelineResult::success(text.to_string(), None, 120, None, TranscriptionMode::Normal, false);
+        SinkPayload::at("id-1", result, Local.with_ymd_and_hms(2026, 1, 2, 9, 30, 0).unwrap())
+    }
+
+    #[test]
+    fn test_format_entry() {
+        let payload = payload("站会：完成登录页 \n");
+        assert_eq!(
+            format_entry(FileSinkFormat::Markdown, &payload),
+            "## 2026-01-02 09:30:00\n\n站会：完成登录页\n\n"
+        );
+        assert_eq!(format_entry(FileSinkFormat::Text, &payload), "[2026-01-02 09:30:00] 站会：完成登录页\n");
+    }
+
+    #[test]
+    fn test_payload_serializes_pipeline_result() {
+        let json = serde_json::to_value(payload("你好")).unwrap();
+        assert_eq!(json["id"], "id-1");
+        assert_eq!(json["text"], "你好");
+        assert_eq!(json["mode"], "normal");
+        assert_eq!(json["asr_time_ms"], 120);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_appends_to_file() {
+        let path = std::env::temp_dir().join(format!("ptt-sink-{}.md", uuid::Uuid::new_v4()));
+        let sinks = vec![
+            OutputSink::Insert,
+            OutputSink::File { path: path.to_string_lossy().into_owned(), format: FileSinkFormat::Text },
+        ];
+        assert!(deliver(&sinks, &payload("第一条")).await.is_empty());
+        assert!(deliver(&sinks, &payload("第二条")).await.is_empty());
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(content, "[2026-01-02 09:30:00] 第一条\n[2026-01-02 09:30:00] 第二条\n");
+        assert!(wants_insert(&sinks));
+    }
+}
@@ -5,14 +5,24 @@
 // 2. 如果无选中文本：语音指令 → ASR → AssistantProcessor (问答模式) → 自动插入
 //
 // 使用独立的 AssistantProcessor，支持双系统提示词
+// 回答流式生成，每完成一句就插入目标窗口（第一句替换选中内容），用户取消后立即停止
+// 启用工具调用时模型可以直接执行动作（切换预设等），动作的结果只显示不插入
 
 use anyhow::Result;
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::JoinHandle;
 use tauri::{AppHandle, Emitter};
 
-use crate::assistant_processor::AssistantProcessor;
+use crate::app_profiles;
+use crate::assistant_processor::{AssistantProcessor, AssistantReply};
+use crate::assistant_tools::ToolRegistry;
 use crate::clipboard_manager::{ClipboardGuard, insert_text_with_context};
-use super::types::{PipelineResult, TranscriptionContext, TranscriptionMode};
+use crate::config::{InsertionConfig, InsertionMethod};
+use crate::insertion::{self, InsertionBackend, InsertionRecord, ProgressiveInserter, SentenceSplitter};
+use super::types::{LlmStreamDelta, PipelineResult, TranscriptionContext, TranscriptionMode};
 
 /// AI 助手模式处理管道
 ///
@@ -35,11 +45,15 @@ impl AssistantPipeline {
     /// # Arguments
     /// * `app` - Tauri 应用句柄（用于发送事件）
     /// * `processor` - AI 助手处理器（调用方负责从锁中获取）
+    /// * `backend` - 文本插入后端（模拟粘贴）
+    /// * `insertion_config` - 文本插入方式配置（粘贴或直接输入）
     /// * `clipboard_guard` - 剪贴板守卫（用于恢复）
     /// * `asr_result` - ASR 转录结果（用户的语音指令）
     /// * `asr_time_ms` - ASR 耗时（毫秒）
     /// * `context` - 上下文信息（包含选中文本）
     /// * `target_hwnd` - 目标窗口句柄（用于焦点恢复）
+    /// * `insert` - 是否插入到目标窗口（输出目标未包含 Insert 时为 false）
+    /// * `cancelled` - 取消标志（用户取消时置位，停止生成和插入）
     ///
     /// # Returns
     /// * `Ok(PipelineResult)` - 处理成功
@@ -48,11 +62,15 @@ impl AssistantPipeline {
         &self,
         app: &AppHandle,
         processor: Option<AssistantProcessor>,
+        backend: Arc<dyn InsertionBackend>,
+        insertion_config: InsertionConfig,
         clipboard_guard: Option<ClipboardGuard>,
         asr_result: Result<String>,
         asr_time_ms: u64,
         context: TranscriptionContext,
         target_hwnd: Option<isize>,  // 目标窗口句柄（用于焦点恢复）
+        insert: bool,
+        cancelled: Arc<AtomicBool>,
     ) -> Result<PipelineResult> {
         // 1. 解包 ASR 结果（用户指令）
         let user_instruction = asr_result?;
@@ -71,36 +89,125 @@ impl AssistantPipeline {
         let _ = app.emit("post_processing", "assistant");
         let llm_start = Instant::now();
 
-        // 4. 根据是否有选中文本选择处理方式
-        let result = if let Some(ref selected_text) = context.selected_text {
-            // 有选中文本：使用文本处理模式
-            tracing::info!(
-                "AssistantPipeline: 文本处理模式 (选中文本: {} 字符)",
-                selected_text.len()
-            );
-            processor.process_with_context(&user_instruction, selected_text).await?
-        } else {
-            // 无选中文本：使用问答模式
-            tracing::info!("AssistantPipeline: 问答模式");
-            processor.process(&user_instruction).await?
+        // 应用配置可覆盖插入方式并追加按键
+        let has_selection = context.selected_text.is_some();
+        let profile = context.profile.as_ref();
+        let insertion_config = insertion_config.with_method(profile.and_then(|p| p.insertion_method));
+
+        // 能模拟按键时边生成边插入，否则等完整回答后一次性插入（剪贴板）
+        let mut clipboard_guard = clipboard_guard;
+        let streaming_insert = insert && backend.can_send_keys();
+        let (segment_tx, segment_rx) = mpsc::channel::<String>();
+        let worker = streaming_insert.then(|| {
+            Self::spawn_insert_worker(
+                app.clone(),
+                backend.clone(),
+                insertion_config.clone(),
+                has_selection,
+                clipboard_guard.take(),
+                cancelled.clone(),
+                target_hwnd,
+                segment_rx,
+            )
+        });
+
+        // 4. 根据是否有选中文本选择处理方式（流式生成，逐段发送进度事件，完整的句子交给插入线程）
+        let tools = processor
+            .tools_config()
+            .map(|config| ToolRegistry::new(app.clone(), config.clone()));
+        let mut splitter = SentenceSplitter::new();
+        let on_delta = |delta: &str| {
+            let _ = app.emit("llm_stream_delta", LlmStreamDelta::new("assistant", delta));
+            if streaming_insert {
+                if let Some(segment) = splitter.push(delta) {
+                    let _ = segment_tx.send(segment);
+                }
+            }
+        };
+        let generate = async {
+            if let Some(ref selected_text) = context.selected_text {
+                // 有选中文本：使用文本处理模式
+                tracing::info!(
+                    "AssistantPipeline: 文本处理模式 (选中文本: {} 字符)",
+                    selected_text.len()
+                );
+                processor.process_with_context(&user_instruction, selected_text, tools.as_ref(), on_delta).await
+            } else {
+                // 无选中文本：使用问答模式
+                tracing::info!("AssistantPipeline: 问答模式");
+                processor.process(&user_instruction, tools.as_ref(), on_delta).await
+            }
+        };
+        let answer = tokio::select! {
+            answer = generate => Some(answer),
+            _ = wait_for_cancel(&cancelled) => None,
         };
 
         let llm_time_ms = llm_start.elapsed().as_millis() as u64;
+        if let Some(Ok(_)) = answer {
+            if let Some(rest) = splitter.finish() {
+                let _ = segment_tx.send(rest);
+            }
+        }
+        drop(segment_tx);
+
+        // 等待插入线程插完剩余的句子（出错时也要等待，确保剪贴板已恢复）
+        let streamed = match worker {
+            Some(worker) => Some(worker.await?),
+            None => None,
+        };
+        let mut stream_failed = false;
+        let AssistantReply { text: result, actions, endpoint } = match answer {
+            Some(Ok(reply)) => reply,
+            // 已经插入了部分回答：与取消一样作为部分结果返回，保留插入记录以便撤销
+            Some(Err(e)) if streamed.as_deref().is_some_and(|text| !text.is_empty()) => {
+                tracing::error!("AssistantPipeline: 回答生成中途失败，返回已插入的部分: {}", e);
+                stream_failed = true;
+                AssistantReply { text: streamed.clone().unwrap_or_default(), ..Default::default() }
+            }
+            Some(Err(e)) => return Err(e),
+            None => {
+                tracing::info!("AssistantPipeline: 回答生成已取消");
+                AssistantReply { text: streamed.clone().unwrap_or_default(), ..Default::default() }
+            }
+        };
+        let was_cancelled = cancelled.load(Ordering::SeqCst);
         tracing::info!(
             "AssistantPipeline: LLM 回答: {} (LLM耗时: {}ms)",
             result,
             llm_time_ms
         );
+        if !actions.is_empty() {
+            tracing::info!("AssistantPipeline: 已执行工具动作: {:?}", actions);
+        }
 
-        // 5. 插入前隐藏窗口并主动恢复焦点到目标应用
-        // 使用新的焦点恢复机制，确保文本插入到正确的窗口
-        super::focus::hide_overlay_and_restore_focus(app, target_hwnd).await;
-
-        // 6. 插入结果（替换选中或插入at 光标）
-        let has_selection = context.selected_text.is_some();
-        let inserted = Self::insert_result(&result, has_selection, clipboard_guard);
+        // 5. 插入结果（流式插入已完成时只记录插入的文本）
+        let inserted_text = match streamed {
+            Some(text) => (!text.is_empty()).then_some(text),
+            None if insert && !was_cancelled && !result.is_empty() => {
+                // 插入前隐藏窗口并主动恢复焦点到目标应用
+                // 使用新的焦点恢复机制，确保文本插入到正确的窗口
+                super::focus::hide_overlay_and_restore_focus(app, target_hwnd).await;
+                Self::insert_result(backend.as_ref(), &insertion_config, &result, has_selection, clipboard_guard)
+                    .then(|| result.clone())
+            }
+            None => {
+                if !insert {
+                    tracing::info!("AssistantPipeline: 输出目标不含插入，跳过插入");
+                }
+                None
+            }
+        };
+        // 回答不完整（取消或中途失败）时不执行插入后按键
+        if let (Some(_), Some(profile), false) = (&inserted_text, profile, was_cancelled || stream_failed) {
+            if let Err(e) = app_profiles::run_post_insert_actions(backend.as_ref(), &profile.post_insert_actions) {
+                tracing::warn!("AssistantPipeline: 插入后按键失败: {}", e);
+            }
+        }
 
-        // 7. 返回结果
+        // 6. 返回结果（插入成功时附带插入记录，替换了选中内容时一并保存原文；取消或中途失败时记录已插入的部分）
+        let inserted = inserted_text.is_some();
+        let record = inserted_text.map(|text| InsertionRecord::new(&text, target_hwnd, context.selected_text.clone()));
         Ok(PipelineResult::success(
             result,
             Some(user_instruction),
@@ -108,12 +215,66 @@ impl AssistantPipeline {
             Some(llm_time_ms),
             TranscriptionMode::Assistant,
             inserted,
-        ))
+        )
+        .with_insertion(record)
+        .with_tool_actions(actions)
+        .with_llm_endpoint(endpoint))
+    }
+
+    /// 在阻塞线程中逐句插入，返回已插入的文本
+    ///
+    /// 第一句到达时才隐藏悬浮窗并恢复焦点；之后焦点离开目标窗口则停止插入，避免输入到其他窗口
+    fn spawn_insert_worker(
+        app: AppHandle,
+        backend: Arc<dyn InsertionBackend>,
+        config: InsertionConfig,
+        has_selection: bool,
+        guard: Option<ClipboardGuard>,
+        cancelled: Arc<AtomicBool>,
+        target_hwnd: Option<isize>,
+        segments: Receiver<String>,
+    ) -> JoinHandle<String> {
+        tauri::async_runtime::spawn_blocking(move || {
+            let mut guard = guard;
+            let mut inserter: Option<ProgressiveInserter> = None;
+            for segment in segments.iter() {
+                if cancelled.load(Ordering::SeqCst) {
+                    break;
+                }
+                if inserter.is_some() && target_hwnd.is_some() && !super::focus::verify_focus(target_hwnd) {
+                    tracing::warn!("AssistantPipeline: 焦点已离开目标窗口，停止流式插入");
+                    break;
+                }
+                let current = inserter.get_or_insert_with(|| {
+                    tauri::async_runtime::block_on(super::focus::hide_overlay_and_restore_focus(&app, target_hwnd));
+                    ProgressiveInserter::new(backend.as_ref(), &config, has_selection, guard.take(), &cancelled)
+                });
+                if let Err(e) = current.insert(&segment) {
+                    tracing::error!("AssistantPipeline: 流式插入失败: {}", e);
+                    break;
+                }
+            }
+            let inserted = inserter.map(ProgressiveInserter::finish).unwrap_or_default();
+            tracing::info!("AssistantPipeline: 流式插入结束 ({} 字符)", inserted.chars().count());
+            inserted
+        })
     }
 
     /// 插入文本到当前光标位置
-    fn insert_result(text: &str, has_selection: bool, guard: Option<ClipboardGuard>) -> bool {
-        match insert_text_with_context(text, has_selection, guard) {
+    ///
+    /// 直接输入模式下同样会替换选中内容；剪贴板守卫随后被丢弃并恢复原内容
+    fn insert_result(
+        backend: &dyn InsertionBackend,
+        config: &InsertionConfig,
+        text: &str,
+        has_selection: bool,
+        guard: Option<ClipboardGuard>,
+    ) -> bool {
+        let result = match insertion::resolve_method(backend, config) {
+            InsertionMethod::Type => insertion::type_text(backend, text, &config.typing),
+            InsertionMethod::Paste => insert_text_with_context(backend, text, has_selection, guard),
+        };
+        match result {
             Ok(()) => {
                 tracing::info!("AssistantPipeline: 结果已插入");
                 true
@@ -127,6 +288,13 @@ impl AssistantPipeline {
 
 }
 
+/// 等待取消标志置位
+async fn wait_for_cancel(cancelled: &AtomicBool) {
+    while !cancelled.load(Ordering::SeqCst) {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
 impl Default for AssistantPipeline {
     fn default() -> Self {
         Self::new()
@@ -136,10 +304,36 @@ impl Default for AssistantPipeline {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::insertion::fake::{FakeBackend, FakeKey};
 
     #[test]
     fn test_pipeline_creation() {
         let _pipeline = AssistantPipeline::new();
         // Pipeline 是无状态的，只需要能创建即可
     }
+
+    #[test]
+    fn test_insert_result_uses_backend() {
+        let backend = FakeBackend::new();
+        backend.memory_clipboard().set("原内容");
+        let guard = ClipboardGuard::new(&backend).ok();
+        let config = InsertionConfig::default();
+        assert!(AssistantPipeline::insert_result(&backend, &config, "结果", false, guard));
+        assert_eq!(backend.events(), vec![FakeKey::Paste(Some("结果".to_string()))]);
+        assert_eq!(backend.memory_clipboard().text().as_deref(), Some("原内容"));
+
+        let clipboard_only = FakeBackend::clipboard_only();
+        assert!(!AssistantPipeline::insert_result(&clipboard_only, &config, "结果", false, None));
+        assert!(clipboard_only.events().is_empty());
+        assert_eq!(clipboard_only.memory_clipboard().text().as_deref(), Some("结果"));
+    }
+
+    #[test]
+    fn test_insert_result_typing_mode() {
+        let backend = FakeBackend::new();
+        let mut config = InsertionConfig { method: InsertionMethod::Type, ..Default::default() };
+        config.typing.char_delay_ms = 0;
+        assert!(AssistantPipeline::insert_result(&backend, &config, "是", true, None));
+        assert_eq!(backend.events(), vec![FakeKey::Typed(insertion::TypedKey::Char('是'))]);
+    }
 }
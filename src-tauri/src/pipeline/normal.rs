@@ -10,9 +10,12 @@ use anyhow::Result;
 use std::time::Instant;
 use tauri::{AppHandle, Emitter};
 
+use crate::app_profiles;
+use crate::config::AppProfile;
+use crate::insertion::InsertionRecord;
 use crate::llm_post_processor::LlmPostProcessor;
 use crate::text_inserter::TextInserter;
-use super::types::{PipelineResult, TranscriptionContext, TranscriptionMode};
+use super::types::{LlmStreamDelta, PipelineResult, TranscriptionContext, TranscriptionMode};
 
 /// 普通模式处理管道
 ///
@@ -37,8 +40,9 @@ impl NormalPipeline {
     /// * `text_inserter` - 文本插入器（调用方负责从锁中获取）
     /// * `asr_result` - ASR 转录结果
     /// * `asr_time_ms` - ASR 耗时（毫秒）
-    /// * `_context` - 上下文（普通模式不使用）
+    /// * `context` - 上下文（使用其中的应用配置覆盖润色预设、标点、插入方式和插入后按键）
     /// * `target_hwnd` - 目标窗口句柄（用于焦点恢复）
+    /// * `insert` - 是否插入到目标窗口（输出目标未包含 Insert 时为 false）
     ///
     /// # Returns
     /// * `Ok(PipelineResult)` - 处理成功
@@ -50,24 +54,41 @@ impl NormalPipeline {
         text_inserter: &mut Option<TextInserter>,
         asr_result: Result<String>,
         asr_time_ms: u64,
-        _context: TranscriptionContext,  // 普通模式不使用上下文
+        context: TranscriptionContext,
         target_hwnd: Option<isize>,      // 目标窗口句柄（用于焦点恢复）
+        insert: bool,
     ) -> Result<PipelineResult> {
         // 1. 解包 ASR 结果
-        let text = asr_result?;
+        let mut text = asr_result?;
         tracing::info!("NormalPipeline: 收到 ASR 结果: {} (耗时: {}ms)", text, asr_time_ms);
 
-        // 2. 可选 LLM 后处理
-        let (final_text, original_text, llm_time_ms) = Self::maybe_polish(app, post_processor, &text).await;
-
-        // 3. 插入前隐藏窗口并主动恢复焦点到目标应用
-        // 使用新的焦点恢复机制，确保文本插入到正确的窗口
-        super::focus::hide_overlay_and_restore_focus(app, target_hwnd).await;
+        let profile = context.profile.as_ref();
+        if let Some(policy) = profile.and_then(|p| p.punctuation) {
+            text = app_profiles::apply_punctuation(&text, policy);
+        }
 
-        // 4. 插入文本
-        let inserted = Self::insert_text(text_inserter, &final_text);
+        // 2. 可选 LLM 后处理（应用配置可指定润色预设）
+        let post_processor = match profile.and_then(|p| p.llm_preset_id.as_deref()) {
+            Some(preset_id) => post_processor.map(|p| p.with_preset(preset_id)),
+            None => post_processor,
+        };
+        let (final_text, original_text, llm_time_ms, llm_endpoint) = Self::maybe_polish(app, post_processor, &text).await;
+
+        // 3. 输出目标不含插入时跳过
+        let inserted = if insert {
+            // 插入前隐藏窗口并主动恢复焦点到目标应用
+            // 使用新的焦点恢复机制，确保文本插入到正确的窗口
+            super::focus::hide_overlay_and_restore_focus(app, target_hwnd).await;
+
+            // 4. 插入文本
+            Self::insert_text(text_inserter, &final_text, profile)
+        } else {
+            tracing::info!("NormalPipeline: 输出目标不含插入，跳过插入");
+            false
+        };
 
-        // 5. 返回结果
+        // 5. 返回结果（插入成功时附带插入记录，用于撤销）
+        let record = inserted.then(|| InsertionRecord::new(&final_text, target_hwnd, None));
         Ok(PipelineResult::success(
             final_text,
             original_text,
@@ -75,51 +96,63 @@ impl NormalPipeline {
             llm_time_ms,
             TranscriptionMode::Normal,
             inserted,
-        ))
+        )
+        .with_insertion(record)
+        .with_llm_endpoint(llm_endpoint))
     }
 
     /// 可选的 LLM 后处理
     ///
-    /// 如果配置了 LLM 后处理器，则调用它进行润色
+    /// 如果配置了 LLM 后处理器，则调用它进行润色（返回润色结果、原文、耗时和产出结果的端点）
     /// 失败时返回原文
     async fn maybe_polish(
         app: &AppHandle,
         processor: Option<LlmPostProcessor>,
         text: &str,
-    ) -> (String, Option<String>, Option<u64>) {
+    ) -> (String, Option<String>, Option<u64>, Option<String>) {
         if let Some(processor) = processor {
             tracing::info!("NormalPipeline: 开始 LLM 后处理...");
             let _ = app.emit("post_processing", "polishing");
 
             let llm_start = Instant::now();
-            match processor.polish_transcript(text).await {
-                Ok(polished) => {
+            let on_delta = |delta: &str| {
+                let _ = app.emit("llm_stream_delta", LlmStreamDelta::new("polishing", delta));
+            };
+            match processor.polish_transcript_streaming(text, on_delta).await {
+                Ok(Some(polished)) => {
                     let llm_elapsed = llm_start.elapsed().as_millis() as u64;
                     tracing::info!(
-                        "NormalPipeline: LLM 后处理完成: {} (耗时: {}ms)",
-                        polished,
+                        "NormalPipeline: LLM 后处理完成: {} (端点: {}, 耗时: {}ms)",
+                        polished.value,
+                        polished.endpoint,
                         llm_elapsed
                     );
-                    (polished, Some(text.to_string()), Some(llm_elapsed))
+                    (polished.value, Some(text.to_string()), Some(llm_elapsed), Some(polished.endpoint))
                 }
+                Ok(None) => (text.to_string(), None, None, None),
                 Err(e) => {
-                    tracing::warn!("NormalPipeline: LLM 后处理失败，使用原文: {}", e);
-                    (text.to_string(), None, None)
+                    tracing::warn!("NormalPipeline: LLM 后处理失败，使用原文: {:#}", e);
+                    (text.to_string(), None, None, None)
                 }
             }
         } else {
-            (text.to_string(), None, None)
+            (text.to_string(), None, None, None)
         }
     }
 
-    /// 插入文本到当前活动窗口
+    /// 插入文本到当前活动窗口，成功后发送应用配置的插入后按键
     ///
     /// 返回是否成功插入
-    fn insert_text(text_inserter: &mut Option<TextInserter>, text: &str) -> bool {
+    fn insert_text(text_inserter: &mut Option<TextInserter>, text: &str, profile: Option<&AppProfile>) -> bool {
         if let Some(ref mut inserter) = text_inserter {
-            match inserter.insert_text(text) {
+            match inserter.insert_text_with(text, profile.and_then(|p| p.insertion_method)) {
                 Ok(()) => {
                     tracing::info!("NormalPipeline: 文本插入成功");
+                    if let Some(profile) = profile {
+                        if let Err(e) = app_profiles::run_post_insert_actions(inserter.backend(), &profile.post_insert_actions) {
+                            tracing::warn!("NormalPipeline: 插入后按键失败: {}", e);
+                        }
+                    }
                     true
                 }
                 Err(e) => {
@@ -144,10 +177,22 @@ impl Default for NormalPipeline {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::insertion::fake::{FakeBackend, FakeKey};
+    use std::sync::Arc;
 
     #[test]
     fn test_pipeline_creation() {
         let _pipeline = NormalPipeline::new();
         // Pipeline 现在是无状态的，只需要能创建即可
     }
+
+    #[test]
+    fn test_insert_text_uses_backend() {
+        let backend = Arc::new(FakeBackend::new());
+        let mut inserter = Some(TextInserter::new(backend.clone(), Default::default()).unwrap());
+        assert!(NormalPipeline::insert_text(&mut inserter, "听写结果", None));
+        assert_eq!(backend.events(), vec![FakeKey::Paste(Some("听写结果".to_string()))]);
+
+        assert!(!NormalPipeline::insert_text(&mut None, "听写结果", None));
+    }
 }
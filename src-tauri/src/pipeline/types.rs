@@ -7,6 +7,9 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::config::AppProfile;
+use crate::insertion::InsertionRecord;
+
 /// 转录处理模式
 ///
 /// 决定 ASR 结果如何被后续处理
@@ -27,6 +30,8 @@ pub enum TranscriptionMode {
 pub struct TranscriptionContext {
     /// 用户选中的文本
     pub selected_text: Option<String>,
+    /// 按前台窗口匹配到的应用配置
+    pub profile: Option<AppProfile>,
 }
 
 impl TranscriptionContext {
@@ -34,6 +39,12 @@ impl TranscriptionContext {
     pub fn empty() -> Self {
         Self::default()
     }
+
+    /// 附加应用配置
+    pub fn with_profile(mut self, profile: Option<AppProfile>) -> Self {
+        self.profile = profile;
+        self
+    }
 }
 
 /// Pipeline 处理结果
@@ -55,6 +66,13 @@ pub struct PipelineResult {
     pub mode: TranscriptionMode,
     /// 是否已自动插入文本
     pub inserted: bool,
+    /// 插入记录（用于撤销上次插入，未插入时为 None）
+    #[serde(skip)]
+    pub insertion: Option<InsertionRecord>,
+    /// AI 助手执行的工具动作摘要（不插入）
+    pub tool_actions: Vec<String>,
+    /// 产出 LLM 结果的端点（配置了备用端点时用于区分）
+    pub llm_endpoint: Option<String>,
 }
 
 impl PipelineResult {
@@ -75,6 +93,51 @@ impl PipelineResult {
             total_time_ms: asr_time_ms + llm_time_ms.unwrap_or(0),
             mode,
             inserted,
+            insertion: None,
+            tool_actions: Vec::new(),
+            llm_endpoint: None,
+        }
+    }
+
+    /// 附加插入记录
+    pub fn with_insertion(mut self, record: Option<InsertionRecord>) -> Self {
+        self.insertion = record;
+        self
+    }
+
+    /// 附加工具动作摘要
+    pub fn with_tool_actions(mut self, actions: Vec<String>) -> Self {
+        self.tool_actions = actions;
+        self
+    }
+
+    /// 附加产出 LLM 结果的端点
+    pub fn with_llm_endpoint(mut self, endpoint: Option<String>) -> Self {
+        self.llm_endpoint = endpoint;
+        self
+    }
+
+    /// 展示给用户的文本：没有回答文本时为工具动作摘要
+    pub fn display_text(&self) -> String {
+        if self.text.is_empty() && !self.tool_actions.is_empty() {
+            self.tool_actions.join("\n")
+        } else {
+            self.text.clone()
         }
     }
 }
+
+/// LLM 流式生成进度（llm_stream_delta 事件）
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmStreamDelta {
+    /// 与 post_processing 事件一致："polishing" 或 "assistant"
+    pub stage: &'static str,
+    /// 新生成的文本片段
+    pub delta: String,
+}
+
+impl LlmStreamDelta {
+    pub fn new(stage: &'static str, delta: &str) -> Self {
+        Self { stage, delta: delta.to_string() }
+    }
+}
@@ -0,0 +1,177 @@
+// 预录环形缓冲模块
+// 麦克风常驻采集，仅在内存中保留最近 N 毫秒音频，热键按下时拼接到录音开头
+// 隐私约束：数据永不落盘，停用或释放时立即清零
+
+use anyhow::Result;
+use cpal::Stream;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+// 预录时长上下限（毫秒）
+const MIN_PRE_ROLL_MS: u32 = 50;
+const MAX_PRE_ROLL_MS: u32 = 2000;
+
+/// 预录快照（设备原始格式，交错多声道 f32）
+pub struct PreRollSnapshot {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+/// 预录环形缓冲
+///
+/// 持有一个常驻的输入流，持续覆盖写入固定容量的环形缓冲区
+pub struct PreRollBuffer {
+    sample_rate: u32,
+    channels: u16,
+    ring: Arc<Mutex<VecDeque<f32>>>,
+    stream: Option<Stream>,
+}
+
+/// 写入环形缓冲，超出容量时丢弃最旧的样本
+fn push_samples(ring: &Mutex<VecDeque<f32>>, capacity: usize, data: impl ExactSizeIterator<Item = f32>) {
+    let mut ring = ring.lock().unwrap();
+    let incoming = data.len();
+    if incoming >= capacity {
+        ring.clear();
+        ring.extend(data.skip(incoming - capacity));
+        return;
+    }
+    let overflow = (ring.len() + incoming).saturating_sub(capacity);
+    ring.drain(..overflow);
+    ring.extend(data);
+}
+
+impl PreRollBuffer {
+    /// 打开默认输入设备并开始预录
+    pub fn start(duration_ms: u32) -> Result<Self> {
+        use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+        let duration_ms = duration_ms.clamp(MIN_PRE_ROLL_MS, MAX_PRE_ROLL_MS);
+
+        let host = cpal::default_host();
+        let device = host
+            .default_input_device()
+            .ok_or_else(|| anyhow::anyhow!("没有找到默认音频输入设备"))?;
+
+        let supported_config = device
+            .default_input_config()
+            .map_err(|e| anyhow::anyhow!("无法获取默认音频配置: {}", e))?;
+
+        let config = supported_config.config();
+        let sample_rate = config.sample_rate.0;
+        let channels = config.channels;
+
+        // 按帧计算容量，保证始终是声道数的整数倍
+        let frames = (sample_rate as u64 * duration_ms as u64 / 1000) as usize;
+        let capacity = frames * channels as usize;
+        let ring: Arc<Mutex<VecDeque<f32>>> = Arc::new(Mutex::new(VecDeque::with_capacity(capacity)));
+
+        tracing::info!("预录缓冲启动: 采样率={}Hz, 声道={}, 时长={}ms, 容量={}样本",
+            sample_rate, channels, duration_ms, capacity);
+
+        let err_fn = |err| tracing::error!("预录流错误: {}", err);
+
+        let stream = match supported_config.sample_format() {
+            cpal::SampleFormat::F32 => {
+                let ring_f32 = Arc::clone(&ring);
+                device.build_input_stream(
+                    &config,
+                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                        push_samples(&ring_f32, capacity, data.iter().copied());
+                    },
+                    err_fn,
+                    None,
+                )?
+            }
+            cpal::SampleFormat::I16 => {
+                let ring_i16 = Arc::clone(&ring);
+                device.build_input_stream(
+                    &config,
+                    move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                        push_samples(&ring_i16, capacity, data.iter().map(|&s| s as f32 / i16::MAX as f32));
+                    },
+                    err_fn,
+                    None,
+                )?
+            }
+            cpal::SampleFormat::U16 => {
+                let ring_u16 = Arc::clone(&ring);
+                device.build_input_stream(
+                    &config,
+                    move |data: &[u16], _: &cpal::InputCallbackInfo| {
+                        push_samples(&ring_u16, capacity, data.iter().map(|&s| (s as f32 - 32768.0) / 32768.0));
+                    },
+                    err_fn,
+                    None,
+                )?
+            }
+            _ => return Err(anyhow::anyhow!("不支持的采样格式")),
+        };
+
+        stream.play()?;
+
+        Ok(Self {
+            sample_rate,
+            channels,
+            ring,
+            stream: Some(stream),
+        })
+    }
+
+    /// 复制当前缓冲内容（不清空，预录继续进行）
+    pub fn snapshot(&self) -> PreRollSnapshot {
+        let samples: Vec<f32> = self.ring.lock().unwrap().iter().copied().collect();
+        PreRollSnapshot {
+            samples,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+        }
+    }
+}
+
+impl PreRollSnapshot {
+    /// 快照格式是否与录音设备一致（不一致时无法直接拼接）
+    pub fn matches(&self, sample_rate: u32, channels: u16) -> bool {
+        self.sample_rate == sample_rate && self.channels == channels && !self.samples.is_empty()
+    }
+}
+
+impl Drop for PreRollBuffer {
+    fn drop(&mut self) {
+        // 先停止流，再清零缓冲，确保没有残留音频
+        self.stream = None;
+        if let Ok(mut ring) = self.ring.lock() {
+            ring.iter_mut().for_each(|s| *s = 0.0);
+            ring.clear();
+            ring.shrink_to_fit();
+        }
+        tracing::info!("预录缓冲已停止并清空");
+    }
+}
+
+// 实现 Send 和 Sync traits（与录音器一致，cpal::Stream 仅在持有线程中使用）
+unsafe impl Send for PreRollBuffer {}
+unsafe impl Sync for PreRollBuffer {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_samples_keeps_latest() {
+        let ring = Mutex::new(VecDeque::new());
+        push_samples(&ring, 4, [1.0, 2.0, 3.0].into_iter());
+        push_samples(&ring, 4, [4.0, 5.0].into_iter());
+        let data: Vec<f32> = ring.lock().unwrap().iter().copied().collect();
+        assert_eq!(data, vec![2.0, 3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn test_push_samples_larger_than_capacity() {
+        let ring = Mutex::new(VecDeque::new());
+        push_samples(&ring, 3, [1.0, 2.0, 3.0, 4.0, 5.0].into_iter());
+        let data: Vec<f32> = ring.lock().unwrap().iter().copied().collect();
+        assert_eq!(data, vec![3.0, 4.0, 5.0]);
+    }
+}
@@ -3,28 +3,125 @@
 
 use anyhow::Result;
 use cpal::Stream;
-use crossbeam_channel::{Receiver, Sender, bounded};
+use crossbeam_channel::{Receiver, Sender, unbounded};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter};
 
-use crate::audio_utils::{calculate_audio_level, emit_audio_level, apply_agc, is_voice_active, validate_audio};
+use crate::audio_capture::{capture_ring, CaptureWorker};
+use crate::audio_utils::{calculate_audio_level, check_clipping, emit_audio_level, apply_agc, is_voice_active, validate_audio, ClippingMonitor, VoiceActivityTracker};
+use crate::config::RecordingLimitsConfig;
+use crate::fallback_audio::FallbackAudio;
+use crate::pre_roll_buffer::PreRollBuffer;
 
 // API 要求的目标采样率
 const TARGET_SAMPLE_RATE: u32 = 16000;
 // 每个音频块的样本数（0.2秒 @ 16kHz = 3200 样本）
 const CHUNK_SAMPLES: usize = 3200;
+// 发送积压超过此块数时提醒（25 块 = 5 秒音频）
+const BACKLOG_WARN_CHUNKS: usize = 25;
+
+/// 录音限制提醒事件（recording_limit_warning）
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingLimitWarning {
+    /// max_duration_approaching / max_duration_reached / send_backlog
+    pub kind: &'static str,
+    /// 距时长上限的剩余秒数
+    pub remaining_secs: Option<u32>,
+    /// 尚未发送的音频时长（秒）
+    pub backlog_secs: Option<f32>,
+}
+
+/// 录音限制跟踪（时长上限 + 发送积压），在音频处理线程中使用
+struct LimitTracker {
+    /// 最大样本数（16kHz），0 表示不限制
+    max_samples: usize,
+    /// 距上限多少样本时提醒
+    warn_samples: usize,
+    captured: AtomicUsize,
+    duration_warned: AtomicBool,
+    /// 与录音器共享，自动完成监控据此结束录音
+    limit_reached: Arc<AtomicBool>,
+    backlog_warned: AtomicBool,
+}
+
+impl LimitTracker {
+    fn new(limits: &RecordingLimitsConfig, limit_reached: Arc<AtomicBool>) -> Self {
+        limit_reached.store(false, Ordering::Relaxed);
+        Self {
+            max_samples: limits.max_duration_secs as usize * TARGET_SAMPLE_RATE as usize,
+            warn_samples: limits.warn_before_secs as usize * TARGET_SAMPLE_RATE as usize,
+            captured: AtomicUsize::new(0),
+            duration_warned: AtomicBool::new(false),
+            limit_reached,
+            backlog_warned: AtomicBool::new(false),
+        }
+    }
+
+    fn emit(app: &Option<AppHandle>, warning: RecordingLimitWarning) {
+        if let Some(app) = app {
+            let _ = app.emit("recording_limit_warning", warning);
+        }
+    }
+
+    /// 记录新采集的样本，超过时长上限后返回 false（后续音频全部忽略）
+    fn accept(&self, app: &Option<AppHandle>, samples: usize) -> bool {
+        if self.limit_reached.load(Ordering::Relaxed) {
+            return false;
+        }
+        let total = self.captured.fetch_add(samples, Ordering::Relaxed) + samples;
+        if self.max_samples == 0 {
+            return true;
+        }
+
+        if total > self.max_samples {
+            self.limit_reached.store(true, Ordering::Relaxed);
+            tracing::warn!("录音已达到最大时长 {}s，停止采集并结束录音", self.max_samples / TARGET_SAMPLE_RATE as usize);
+            Self::emit(app, RecordingLimitWarning { kind: "max_duration_reached", remaining_secs: Some(0), backlog_secs: None });
+            return false;
+        }
+
+        let remaining = self.max_samples - total;
+        if self.warn_samples > 0 && remaining <= self.warn_samples && !self.duration_warned.swap(true, Ordering::Relaxed) {
+            let remaining_secs = (remaining / TARGET_SAMPLE_RATE as usize) as u32;
+            tracing::warn!("录音即将达到最大时长，剩余 {}s", remaining_secs);
+            Self::emit(app, RecordingLimitWarning { kind: "max_duration_approaching", remaining_secs: Some(remaining_secs), backlog_secs: None });
+        }
+        true
+    }
+
+    /// 检查发送积压（WebSocket 过慢时块会在通道中排队），积压清空后可再次提醒
+    fn check_backlog(&self, app: &Option<AppHandle>, queued_chunks: usize) {
+        if queued_chunks == 0 {
+            self.backlog_warned.store(false, Ordering::Relaxed);
+        } else if queued_chunks >= BACKLOG_WARN_CHUNKS && !self.backlog_warned.swap(true, Ordering::Relaxed) {
+            let backlog_secs = (queued_chunks * CHUNK_SAMPLES) as f32 / TARGET_SAMPLE_RATE as f32;
+            tracing::warn!("音频发送积压 {} 块（{:.1}s），网络可能过慢", queued_chunks, backlog_secs);
+            Self::emit(app, RecordingLimitWarning { kind: "send_backlog", remaining_secs: None, backlog_secs: Some(backlog_secs) });
+        }
+    }
+}
 
 /// 流式音频录制器
 /// 边录音边输出 PCM 数据块，同时保留完整音频用于备用方案
 pub struct StreamingRecorder {
     device_sample_rate: u32,
     channels: u16,
-    is_recording: Arc<Mutex<bool>>,
+    is_recording: Arc<AtomicBool>,
     stream: Option<Stream>,
+    // 音频处理工作线程
+    capture_worker: Option<CaptureWorker>,
     // 用于流式输出的通道
     chunk_sender: Option<Sender<Vec<i16>>>,
-    // 累积的完整音频数据（用于备用方案）
-    full_audio_data: Arc<Mutex<Vec<f32>>>,
+    // 累积的完整音频数据（16kHz i16，用于备用方案）
+    fallback_audio: Arc<Mutex<FallbackAudio>>,
+    // 时长上限和溢写阈值
+    limits: RecordingLimitsConfig,
+    // 语音活动跟踪（用于静音自动停止）
+    voice_activity: VoiceActivityTracker,
+    // 是否已达到最大录音时长（用于自动完成录音）
+    limit_reached: Arc<AtomicBool>,
 }
 
 impl StreamingRecorder {
@@ -32,13 +129,22 @@ impl StreamingRecorder {
         Ok(Self {
             device_sample_rate: 48000,
             channels: 1,
-            is_recording: Arc::new(Mutex::new(false)),
+            is_recording: Arc::new(AtomicBool::new(false)),
             stream: None,
+            capture_worker: None,
             chunk_sender: None,
-            full_audio_data: Arc::new(Mutex::new(Vec::new())),
+            fallback_audio: Arc::new(Mutex::new(FallbackAudio::new(0))),
+            limits: RecordingLimitsConfig::default(),
+            voice_activity: VoiceActivityTracker::default(),
+            limit_reached: Arc::new(AtomicBool::new(false)),
         })
     }
 
+    /// 更新录音限制（下次开始录音时生效）
+    pub fn set_limits(&mut self, limits: RecordingLimitsConfig) {
+        self.limits = limits;
+    }
+
     /// 将音频从设备采样率降采样到目标采样率 (16kHz)
     fn resample(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
         if from_rate == to_rate {
@@ -95,17 +201,22 @@ impl StreamingRecorder {
 
     /// 启动流式录音，返回音频块接收通道
     /// app_handle 用于发送音频级别事件到前端
-    pub fn start_streaming(&mut self, app_handle: Option<AppHandle>) -> Result<Receiver<Vec<i16>>> {
+    /// pre_roll 存在时，预录音频会同时拼接到完整音频和第一个发送块之前
+    pub fn start_streaming(&mut self, app_handle: Option<AppHandle>, pre_roll: Option<&PreRollBuffer>) -> Result<Receiver<Vec<i16>>> {
         use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 
         tracing::info!("开始流式录音...");
 
-        // 清空之前的数据
-        self.full_audio_data.lock().unwrap().clear();
-        *self.is_recording.lock().unwrap() = true;
+        // 清空之前的数据（同时删除上次的临时文件）
+        *self.fallback_audio.lock().unwrap() = FallbackAudio::new(
+            self.limits.spill_threshold_secs as usize * TARGET_SAMPLE_RATE as usize,
+        );
+        self.is_recording.store(true, Ordering::Release);
+        self.voice_activity.reset();
 
-        // 创建音频块通道（缓冲 50 个块，约 10 秒）
-        let (chunk_tx, chunk_rx) = bounded::<Vec<i16>>(50);
+        // 创建音频块通道（不限容量：网络慢时块在通道中排队而不是丢弃，
+        // 总量受最大录音时长约束，积压过多时提醒前端）
+        let (chunk_tx, chunk_rx) = unbounded::<Vec<i16>>();
         self.chunk_sender = Some(chunk_tx.clone());
 
         let host = cpal::default_host();
@@ -124,273 +235,140 @@ impl StreamingRecorder {
         tracing::info!("流式录音配置: 采样率={}Hz, 声道={}, 目标采样率={}Hz, 块大小={}样本",
             self.device_sample_rate, self.channels, TARGET_SAMPLE_RATE, CHUNK_SAMPLES);
 
-        let is_recording = Arc::clone(&self.is_recording);
-        let full_audio_data = Arc::clone(&self.full_audio_data);
+        let err_fn = |err| tracing::error!("录音流错误: {}", err);
+
+        // 回调只写入环形缓冲（无锁、无分配），处理在工作线程中进行
+        let (mut producer, consumer) = capture_ring(self.device_sample_rate, self.channels, Arc::clone(&self.is_recording));
+
+        let stream = match supported_config.sample_format() {
+            cpal::SampleFormat::F32 => device.build_input_stream(
+                &config,
+                move |data: &[f32], _: &cpal::InputCallbackInfo| producer.push_f32(data),
+                err_fn,
+                None,
+            )?,
+            cpal::SampleFormat::I16 => device.build_input_stream(
+                &config,
+                move |data: &[i16], _: &cpal::InputCallbackInfo| producer.push_i16(data),
+                err_fn,
+                None,
+            )?,
+            cpal::SampleFormat::U16 => device.build_input_stream(
+                &config,
+                move |data: &[u16], _: &cpal::InputCallbackInfo| producer.push_u16(data),
+                err_fn,
+                None,
+            )?,
+            _ => return Err(anyhow::anyhow!("不支持的采样格式")),
+        };
+
+        // 主录音流启动前取预录快照：之后采集的音频只进入主录音流，拼接时不会重复
+        // （工作线程尚未启动，新采集的音频暂存在环形缓冲中，拼接在预录之后）
+        let snapshot = pre_roll.map(PreRollBuffer::snapshot);
+
+        stream.play()?;
+
         let device_sample_rate = self.device_sample_rate;
         let channels = self.channels;
 
         // 用于累积样本直到达到块大小
-        let pending_samples: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
-        let pending_samples_clone = Arc::clone(&pending_samples);
+        let mut pending: Vec<f32> = Vec::with_capacity(CHUNK_SAMPLES * 2);
+        let limits = LimitTracker::new(&self.limits, Arc::clone(&self.limit_reached));
+
+        if let Some(snapshot) = snapshot {
+            if snapshot.matches(device_sample_rate, channels) {
+                let mono = Self::to_mono(&snapshot.samples, channels);
+                let resampled = Self::resample(&mono, device_sample_rate, TARGET_SAMPLE_RATE);
+                self.fallback_audio.lock().unwrap().push(&resampled);
+                // 预录音频同样计入最大时长
+                limits.accept(&app_handle, resampled.len());
+                pending.extend(resampled);
+                tracing::info!("已拼接预录音频: {} 样本 (16kHz)", pending.len());
+            } else {
+                tracing::warn!("预录音频格式与录音设备不一致，跳过拼接");
+            }
+        }
 
-        // 基于时间的音频级别发送控制（目标 30-40Hz）
-        use std::time::Instant;
-        let last_emit_time: Arc<Mutex<Instant>> = Arc::new(Mutex::new(Instant::now()));
-        let last_emit_time_clone = Arc::clone(&last_emit_time);
-        let emit_counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
-        let emit_counter_clone = Arc::clone(&emit_counter);
+        let fallback_audio = Arc::clone(&self.fallback_audio);
+        let voice_activity = self.voice_activity.clone();
+        let mut clipping_monitor = ClippingMonitor::new(device_sample_rate, channels);
 
+        // 以下状态只在工作线程中使用，无需加锁
+        use std::time::Instant;
+        // 基于时间的音频级别发送控制（目标 30-40Hz）
+        let mut last_emit_time = Instant::now();
+        let mut emit_counter: u32 = 0;
         // VAD 拖尾计数器：检测到静音后继续发送几个块，防止句尾吞字
-        let vad_hangover: Arc<Mutex<usize>> = Arc::new(Mutex::new(0));
-        let vad_hangover_clone = Arc::clone(&vad_hangover);
+        let mut vad_hangover: usize = 0;
         const HANGOVER_CHUNKS: usize = 3; // 3块 * 0.2s = 0.6秒拖尾，平衡防吞字和响应速度
-
         // AGC 增益状态，用于平滑过渡
-        let agc_gain: Arc<Mutex<f32>> = Arc::new(Mutex::new(1.0));
-        let agc_gain_clone = Arc::clone(&agc_gain);
+        let mut agc_gain: f32 = 1.0;
 
-        // 克隆 app_handle 用于闭包
-        let app_handle_f32 = app_handle.clone();
+        self.capture_worker = Some(consumer.spawn_worker(move |samples| {
+            // 削波检测（基于设备原始样本）
+            check_clipping(&app_handle, &mut clipping_monitor, samples);
 
-        let err_fn = |err| tracing::error!("录音流错误: {}", err);
+            // 处理数据：转单声道 + 降采样
+            let mono = Self::to_mono(samples, channels);
+            let resampled = Self::resample(&mono, device_sample_rate, TARGET_SAMPLE_RATE);
 
-        let stream = match supported_config.sample_format() {
-            cpal::SampleFormat::F32 => device.build_input_stream(
-                &config,
-                move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                    if !*is_recording.lock().unwrap() {
-                        return;
-                    }
+            // 超过最大时长后不再采集
+            if !limits.accept(&app_handle, resampled.len()) {
+                return;
+            }
 
-                    // 保存原始数据用于备用方案
-                    full_audio_data.lock().unwrap().extend_from_slice(data);
-
-                    // 处理数据：转单声道 + 降采样
-                    let mono = Self::to_mono(data, channels);
-                    let resampled = Self::resample(&mono, device_sample_rate, TARGET_SAMPLE_RATE);
-
-                    // 基于时间的音频级别发送（目标 ~30Hz，每 33ms 发送一次）
-                    if let Some(ref app) = app_handle_f32 {
-                        let mut last_emit = last_emit_time_clone.lock().unwrap();
-                        if last_emit.elapsed().as_millis() >= 33 {
-                            let level = calculate_audio_level(&resampled);
-                            emit_audio_level(app, level);
-                            *last_emit = Instant::now();
-
-                            // 调试日志：每30次打印一次（约每秒）
-                            let mut counter = emit_counter_clone.lock().unwrap();
-                            *counter += 1;
-                            if *counter % 30 == 0 {
-                                tracing::info!("[AudioLevel] 发送音频级别: {:.4} (30Hz)", level);
-                            }
-                        }
-                    }
+            // 保存数据用于备用方案
+            fallback_audio.lock().unwrap().push(&resampled);
+
+            voice_activity.update(&resampled);
+
+            // 基于时间的音频级别发送（目标 ~30Hz，每 33ms 发送一次）
+            if let Some(ref app) = app_handle {
+                if last_emit_time.elapsed().as_millis() >= 33 {
+                    let level = calculate_audio_level(&resampled);
+                    emit_audio_level(app, level);
+                    last_emit_time = Instant::now();
 
-                    // 累积样本
-                    let mut pending = pending_samples_clone.lock().unwrap();
-                    pending.extend(resampled);
-
-                    // 当累积足够样本时，发送块
-                    while pending.len() >= CHUNK_SAMPLES {
-                        let mut chunk: Vec<f32> = pending.drain(..CHUNK_SAMPLES).collect();
-
-                        // VAD 判断
-                        let is_active = is_voice_active(&chunk);
-                        let mut hangover = vad_hangover_clone.lock().unwrap();
-
-                        if is_active {
-                            *hangover = HANGOVER_CHUNKS;
-                        } else if *hangover > 0 {
-                            *hangover -= 1;
-                        }
-
-                        // 静音且拖尾结束，丢弃前先衰减增益
-                        if !is_active && *hangover == 0 {
-                            let mut gain = agc_gain_clone.lock().unwrap();
-                            *gain = *gain * 0.5 + 0.5;
-                            continue;
-                        }
-                        drop(hangover);
-
-                        // AGC（带平滑处理）
-                        let mut gain = agc_gain_clone.lock().unwrap();
-                        apply_agc(&mut chunk, &mut gain);
-                        drop(gain);
-
-                        let chunk_i16 = Self::f32_to_i16(&chunk);
-
-                        if chunk_tx.try_send(chunk_i16).is_err() {
-                            tracing::warn!("音频块通道已满，丢弃块");
-                        }
+                    // 调试日志：每30次打印一次（约每秒）
+                    emit_counter += 1;
+                    if emit_counter % 30 == 0 {
+                        tracing::info!("[AudioLevel] 发送音频级别: {:.4} (30Hz)", level);
                     }
-                },
-                err_fn,
-                None,
-            )?,
-            cpal::SampleFormat::I16 => {
-                let is_recording_i16 = Arc::clone(&is_recording);
-                let full_audio_data_i16 = Arc::clone(&full_audio_data);
-                let pending_samples_i16 = Arc::clone(&pending_samples);
-                let chunk_tx_i16 = chunk_tx.clone();
-                let last_emit_time_i16 = Arc::clone(&last_emit_time);
-                let app_handle_i16 = app_handle.clone();
-                let vad_hangover_i16 = Arc::clone(&vad_hangover);
-                let agc_gain_i16 = Arc::clone(&agc_gain);
-
-                device.build_input_stream(
-                    &config,
-                    move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                        if !*is_recording_i16.lock().unwrap() {
-                            return;
-                        }
-
-                        // 转换为 f32
-                        let f32_data: Vec<f32> = data.iter()
-                            .map(|&s| s as f32 / i16::MAX as f32)
-                            .collect();
-
-                        // 保存原始数据
-                        full_audio_data_i16.lock().unwrap().extend(&f32_data);
-
-                        // 处理数据
-                        let mono = Self::to_mono(&f32_data, channels);
-                        let resampled = Self::resample(&mono, device_sample_rate, TARGET_SAMPLE_RATE);
-
-                        // 基于时间的音频级别发送（目标 ~30Hz）
-                        if let Some(ref app) = app_handle_i16 {
-                            let mut last_emit = last_emit_time_i16.lock().unwrap();
-                            if last_emit.elapsed().as_millis() >= 33 {
-                                let level = calculate_audio_level(&resampled);
-                                emit_audio_level(app, level);
-                                *last_emit = Instant::now();
-                            }
-                        }
-
-                        // 累积样本
-                        let mut pending = pending_samples_i16.lock().unwrap();
-                        pending.extend(resampled);
-
-                        while pending.len() >= CHUNK_SAMPLES {
-                            let mut chunk: Vec<f32> = pending.drain(..CHUNK_SAMPLES).collect();
-
-                            // VAD 判断
-                            let is_active = is_voice_active(&chunk);
-                            let mut hangover = vad_hangover_i16.lock().unwrap();
-
-                            if is_active {
-                                *hangover = HANGOVER_CHUNKS;
-                            } else if *hangover > 0 {
-                                *hangover -= 1;
-                            }
-
-                            // 静音且拖尾结束，丢弃前先衰减增益
-                            if !is_active && *hangover == 0 {
-                                let mut gain = agc_gain_i16.lock().unwrap();
-                                *gain = *gain * 0.5 + 0.5;
-                                continue;
-                            }
-                            drop(hangover);
-
-                            // AGC（带平滑处理）
-                            let mut gain = agc_gain_i16.lock().unwrap();
-                            apply_agc(&mut chunk, &mut gain);
-                            drop(gain);
-
-                            let chunk_i16 = Self::f32_to_i16(&chunk);
-
-                            if chunk_tx_i16.try_send(chunk_i16).is_err() {
-                                tracing::warn!("音频块通道已满，丢弃块");
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )?
+                }
             }
-            cpal::SampleFormat::U16 => {
-                let is_recording_u16 = Arc::clone(&is_recording);
-                let full_audio_data_u16 = Arc::clone(&full_audio_data);
-                let pending_samples_u16 = Arc::clone(&pending_samples);
-                let chunk_tx_u16 = chunk_tx.clone();
-                let last_emit_time_u16 = Arc::clone(&last_emit_time);
-                let app_handle_u16 = app_handle;
-                let vad_hangover_u16 = Arc::clone(&vad_hangover);
-                let agc_gain_u16 = Arc::clone(&agc_gain);
-
-                device.build_input_stream(
-                    &config,
-                    move |data: &[u16], _: &cpal::InputCallbackInfo| {
-                        if !*is_recording_u16.lock().unwrap() {
-                            return;
-                        }
-
-                        // 转换为 f32
-                        let f32_data: Vec<f32> = data.iter()
-                            .map(|&s| (s as f32 - 32768.0) / 32768.0)
-                            .collect();
-
-                        // 保存原始数据
-                        full_audio_data_u16.lock().unwrap().extend(&f32_data);
-
-                        // 处理数据
-                        let mono = Self::to_mono(&f32_data, channels);
-                        let resampled = Self::resample(&mono, device_sample_rate, TARGET_SAMPLE_RATE);
-
-                        // 基于时间的音频级别发送（目标 ~30Hz）
-                        if let Some(ref app) = app_handle_u16 {
-                            let mut last_emit = last_emit_time_u16.lock().unwrap();
-                            if last_emit.elapsed().as_millis() >= 33 {
-                                let level = calculate_audio_level(&resampled);
-                                emit_audio_level(app, level);
-                                *last_emit = Instant::now();
-                            }
-                        }
-
-                        // 累积样本
-                        let mut pending = pending_samples_u16.lock().unwrap();
-                        pending.extend(resampled);
-
-                        while pending.len() >= CHUNK_SAMPLES {
-                            let mut chunk: Vec<f32> = pending.drain(..CHUNK_SAMPLES).collect();
-
-                            // VAD 判断
-                            let is_active = is_voice_active(&chunk);
-                            let mut hangover = vad_hangover_u16.lock().unwrap();
-
-                            if is_active {
-                                *hangover = HANGOVER_CHUNKS;
-                            } else if *hangover > 0 {
-                                *hangover -= 1;
-                            }
-
-                            // 静音且拖尾结束，丢弃前先衰减增益
-                            if !is_active && *hangover == 0 {
-                                let mut gain = agc_gain_u16.lock().unwrap();
-                                *gain = *gain * 0.5 + 0.5;
-                                continue;
-                            }
-                            drop(hangover);
-
-                            // AGC（带平滑处理）
-                            let mut gain = agc_gain_u16.lock().unwrap();
-                            apply_agc(&mut chunk, &mut gain);
-                            drop(gain);
-
-                            let chunk_i16 = Self::f32_to_i16(&chunk);
-
-                            if chunk_tx_u16.try_send(chunk_i16).is_err() {
-                                tracing::warn!("音频块通道已满，丢弃块");
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )?
+
+            // 累积样本
+            pending.extend(resampled);
+
+            // 当累积足够样本时，发送块
+            while pending.len() >= CHUNK_SAMPLES {
+                let mut chunk: Vec<f32> = pending.drain(..CHUNK_SAMPLES).collect();
+
+                // VAD 判断
+                let is_active = is_voice_active(&chunk);
+
+                if is_active {
+                    vad_hangover = HANGOVER_CHUNKS;
+                } else if vad_hangover > 0 {
+                    vad_hangover -= 1;
+                }
+
+                // 静音且拖尾结束，丢弃前先衰减增益
+                if !is_active && vad_hangover == 0 {
+                    agc_gain = agc_gain * 0.5 + 0.5;
+                    continue;
+                }
+
+                // AGC（带平滑处理）
+                apply_agc(&mut chunk, &mut agc_gain);
+
+                let chunk_i16 = Self::f32_to_i16(&chunk);
+
+                let _ = chunk_tx.send(chunk_i16);
+                limits.check_backlog(&app_handle, chunk_tx.len());
             }
-            _ => return Err(anyhow::anyhow!("不支持的采样格式")),
-        };
+        }));
 
-        stream.play()?;
         self.stream = Some(stream);
 
         tracing::info!("流式录音已启动");
@@ -408,28 +386,27 @@ impl StreamingRecorder {
         std::thread::sleep(std::time::Duration::from_millis(200));
 
         // 停止录音标志
-        *self.is_recording.lock().unwrap() = false;
-
-        // 再等待一小段时间确保最后的数据被写入
-        std::thread::sleep(std::time::Duration::from_millis(100));
+        self.is_recording.store(false, Ordering::Release);
 
-        // 最后 drop stream
+        // drop stream 后等待工作线程处理完环形缓冲中剩余的数据（同时释放块发送端）
         self.stream = None;
+        if let Some(worker) = self.capture_worker.take() {
+            worker.finish();
+        }
         self.chunk_sender = None;
 
-        // 获取完整音频数据
-        let raw_audio = self.full_audio_data.lock().unwrap().clone();
+        // 获取完整音频数据（已是 16kHz 单声道），读取后删除临时文件
+        let samples = {
+            let mut fallback = self.fallback_audio.lock().unwrap();
+            let samples = fallback.read_all();
+            fallback.clear();
+            samples?
+        };
 
-        if raw_audio.is_empty() {
+        if samples.is_empty() {
             return Err(anyhow::anyhow!("没有录制到音频数据"));
         }
 
-        // 转换为单声道
-        let mono_audio = Self::to_mono(&raw_audio, self.channels);
-
-        // 降采样到 16kHz
-        let resampled_audio = Self::resample(&mono_audio, self.device_sample_rate, TARGET_SAMPLE_RATE);
-
         // 写入 WAV 格式
         let spec = WavSpec {
             channels: 1,
@@ -441,9 +418,8 @@ impl StreamingRecorder {
         let mut cursor = Cursor::new(Vec::new());
         {
             let mut writer = WavWriter::new(&mut cursor, spec)?;
-            for &sample in resampled_audio.iter() {
-                let amplitude = (sample * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
-                writer.write_sample(amplitude)?;
+            for &sample in samples.iter() {
+                writer.write_sample(sample)?;
             }
             writer.finalize()?;
         }
@@ -457,10 +433,19 @@ impl StreamingRecorder {
         Ok(wav_data)
     }
 
+    /// 获取语音活动跟踪器（用于静音自动停止）
+    pub fn voice_activity(&self) -> VoiceActivityTracker {
+        self.voice_activity.clone()
+    }
+
+    /// 是否已达到最大录音时长（之后的音频不再采集）
+    pub fn limit_reached(&self) -> bool {
+        self.limit_reached.load(Ordering::Relaxed)
+    }
+
     /// 检查是否正在录音
-    #[allow(dead_code)]
     pub fn is_recording(&self) -> bool {
-        *self.is_recording.lock().unwrap()
+        self.is_recording.load(Ordering::Acquire)
     }
 }
 
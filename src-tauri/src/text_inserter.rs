@@ -1,28 +1,57 @@
 // 文本插入模块
-// 使用 Win32 SendInput API 替代 enigo 实现更低延迟的键盘模拟
-use arboard::Clipboard;
+// 通过剪贴板 + 模拟粘贴插入文本，或按配置直接输入（不经过剪贴板），按键模拟由 InsertionBackend 完成
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 use anyhow::Result;
 
-use crate::win32_input;
+use crate::clipboard_manager::ClipboardAccess;
+use crate::config::{InsertionConfig, InsertionMethod};
+use crate::insertion::{self, InsertionBackend};
 
 pub struct TextInserter {
-    clipboard: Clipboard,
+    clipboard: Box<dyn ClipboardAccess>,
+    backend: Arc<dyn InsertionBackend>,
+    config: Arc<Mutex<InsertionConfig>>,
 }
 
 impl TextInserter {
-    pub fn new() -> Result<Self> {
+    pub fn new(backend: Arc<dyn InsertionBackend>, config: Arc<Mutex<InsertionConfig>>) -> Result<Self> {
         Ok(Self {
-            clipboard: Clipboard::new()?,
+            clipboard: backend.clipboard()?,
+            backend,
+            config,
         })
     }
 
+    /// 插入使用的后端
+    pub fn backend(&self) -> &dyn InsertionBackend {
+        self.backend.as_ref()
+    }
+
     pub fn insert_text(&mut self, text: &str) -> Result<()> {
+        self.insert_text_with(text, None)
+    }
+
+    /// 插入文本，method 不为空时覆盖配置的插入方式（按应用配置）
+    pub fn insert_text_with(&mut self, text: &str, method: Option<InsertionMethod>) -> Result<()> {
         tracing::info!("准备插入文本: {}", text);
 
-        // 1. 保存当前剪贴板内容
-        let original_clipboard = self.clipboard.get_text().ok();
+        let config = self.config.lock().unwrap().clone().with_method(method);
+        if insertion::resolve_method(self.backend.as_ref(), &config) == InsertionMethod::Type {
+            insertion::type_text(self.backend.as_ref(), text, &config.typing)?;
+            tracing::info!("文本直接输入完成 (后端: {})", self.backend.name());
+            return Ok(());
+        }
+
+        // 无法模拟按键时只写入剪贴板，且不恢复原内容，由用户手动粘贴
+        if !self.backend.can_send_keys() {
+            self.clipboard.set_text(text)?;
+            anyhow::bail!("当前环境无法模拟按键，文本已复制到剪贴板，请手动粘贴");
+        }
+
+        // 1. 保存当前剪贴板内容（全部格式）
+        let original_clipboard = self.clipboard.capture();
 
         // 2. 将文本复制到剪贴板
         self.clipboard.set_text(text)?;
@@ -30,18 +59,65 @@ impl TextInserter {
         // 3. 等待剪贴板更新
         thread::sleep(Duration::from_millis(50));
 
-        // 4. 使用 Win32 SendInput 模拟 Ctrl+V 粘贴
-        win32_input::send_ctrl_v()?;
+        // 4. 模拟 Ctrl+V 粘贴
+        self.backend.paste()?;
 
         // 5. 等待粘贴完成
         thread::sleep(Duration::from_millis(150));
 
         // 6. 恢复原剪贴板内容
-        if let Some(original) = original_clipboard {
-            self.clipboard.set_text(original)?;
-        }
+        self.clipboard.restore(&original_clipboard)?;
 
-        tracing::info!("文本插入完成");
+        tracing::info!("文本插入完成 (后端: {})", self.backend.name());
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::insertion::fake::{FakeBackend, FakeKey};
+    use crate::insertion::TypedKey;
+
+    fn config_with(method: InsertionMethod) -> Arc<Mutex<InsertionConfig>> {
+        let mut config = InsertionConfig { method, ..Default::default() };
+        config.typing.char_delay_ms = 0;
+        Arc::new(Mutex::new(config))
+    }
+
+    #[test]
+    fn test_insert_text_pastes_through_backend() {
+        let backend = Arc::new(FakeBackend::new());
+        backend.memory_clipboard().set("原内容");
+        let mut inserter = TextInserter::new(backend.clone(), config_with(InsertionMethod::Paste)).unwrap();
+        inserter.insert_text("你好").unwrap();
+        assert_eq!(backend.events(), vec![FakeKey::Paste(Some("你好".to_string()))]);
+        assert_eq!(backend.memory_clipboard().text().as_deref(), Some("原内容"));
+    }
+
+    #[test]
+    fn test_insert_text_clipboard_only() {
+        let backend = Arc::new(FakeBackend::clipboard_only());
+        let mut inserter = TextInserter::new(backend.clone(), config_with(InsertionMethod::Paste)).unwrap();
+        assert!(inserter.insert_text("留在剪贴板").is_err());
+        assert!(backend.events().is_empty());
+        assert_eq!(backend.memory_clipboard().text().as_deref(), Some("留在剪贴板"));
+    }
+
+    #[test]
+    fn test_insert_text_typing_mode_skips_clipboard() {
+        let backend = Arc::new(FakeBackend::new());
+        backend.memory_clipboard().set("原内容");
+        let mut inserter = TextInserter::new(backend.clone(), config_with(InsertionMethod::Type)).unwrap();
+        inserter.insert_text("a\tb").unwrap();
+        assert_eq!(
+            backend.events(),
+            vec![
+                FakeKey::Typed(TypedKey::Char('a')),
+                FakeKey::Typed(TypedKey::Tab),
+                FakeKey::Typed(TypedKey::Char('b')),
+            ]
+        );
+        assert_eq!(backend.memory_clipboard().text().as_deref(), Some("原内容"));
+    }
+}
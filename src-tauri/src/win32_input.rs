@@ -9,12 +9,12 @@ use std::time::Duration;
 #[cfg(target_os = "windows")]
 use windows::Win32::UI::Input::KeyboardAndMouse::{
     SendInput, GetAsyncKeyState, INPUT, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS,
-    KEYEVENTF_KEYUP, VIRTUAL_KEY,
+    KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, VIRTUAL_KEY,
     VK_CONTROL, VK_LCONTROL, VK_RCONTROL,
     VK_SHIFT, VK_LSHIFT, VK_RSHIFT,
     VK_MENU, VK_LMENU, VK_RMENU,
     VK_LWIN, VK_RWIN,
-    VK_C, VK_V,
+    VK_C, VK_V, VK_RETURN, VK_TAB, VK_BACK,
 };
 
 /// 按键间延迟（毫秒）
@@ -142,18 +142,92 @@ pub fn release_all_modifiers() -> Result<()> {
     Ok(())
 }
 
+// ==================== Unicode 直接输入 ====================
+// 不经过剪贴板，直接向前台窗口发送字符
+
+/// 发送一个 Unicode 字符（KEYEVENTF_UNICODE）
+///
+/// BMP 之外的字符（如 emoji）以 UTF-16 代理对发送
+#[cfg(target_os = "windows")]
+pub fn send_unicode_char(ch: char) -> Result<()> {
+    let mut units = [0u16; 2];
+    let mut inputs = Vec::with_capacity(4);
+    for &unit in ch.encode_utf16(&mut units).iter() {
+        for flags in [KEYEVENTF_UNICODE, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP] {
+            inputs.push(INPUT {
+                r#type: INPUT_KEYBOARD,
+                Anonymous: windows::Win32::UI::Input::KeyboardAndMouse::INPUT_0 {
+                    ki: KEYBDINPUT {
+                        wVk: VIRTUAL_KEY(0),
+                        wScan: unit,
+                        dwFlags: flags,
+                        time: 0,
+                        dwExtraInfo: 0,
+                    },
+                },
+            });
+        }
+    }
+
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        anyhow::bail!("SendInput failed for unicode char: U+{:04X}", ch as u32);
+    }
+
+    Ok(())
+}
+
+/// 发送回车键（可选 Shift+Enter）
+#[cfg(target_os = "windows")]
+pub fn send_enter(with_shift: bool) -> Result<()> {
+    if with_shift {
+        send_key_down(VK_SHIFT)?;
+        thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+    }
+    send_key_down(VK_RETURN)?;
+    send_key_up(VK_RETURN)?;
+    if with_shift {
+        thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+        send_key_up(VK_SHIFT)?;
+    }
+    Ok(())
+}
+
+/// 发送 Tab 键
+#[cfg(target_os = "windows")]
+pub fn send_tab() -> Result<()> {
+    send_key_down(VK_TAB)?;
+    send_key_up(VK_TAB)
+}
+
+/// 连续发送 count 次退格键
+#[cfg(target_os = "windows")]
+pub fn send_backspace(count: usize) -> Result<()> {
+    for _ in 0..count {
+        send_key_down(VK_BACK)?;
+        send_key_up(VK_BACK)?;
+        thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+    }
+    Ok(())
+}
+
 // ==================== 焦点管理 API ====================
 // 用于在文本插入前确保目标窗口获得焦点
 
 #[cfg(target_os = "windows")]
 use windows::Win32::UI::WindowsAndMessaging::{
     GetForegroundWindow, SetForegroundWindow, IsWindow,
-    GetWindowThreadProcessId,
+    GetWindowThreadProcessId, GetClassNameW, GetWindowTextW,
+};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Threading::{
+    GetCurrentThreadId, AttachThreadInput,
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
 };
 #[cfg(target_os = "windows")]
-use windows::Win32::System::Threading::{GetCurrentThreadId, AttachThreadInput};
+use windows::Win32::Foundation::{CloseHandle, HWND};
 #[cfg(target_os = "windows")]
-use windows::Win32::Foundation::HWND;
+use windows::core::PWSTR;
 
 /// 获取当前前台窗口句柄
 ///
@@ -172,6 +246,48 @@ pub fn get_foreground_window() -> Option<isize> {
     }
 }
 
+/// 获取窗口所属进程的可执行文件名（如 WindowsTerminal.exe）
+#[cfg(target_os = "windows")]
+pub fn get_window_process_name(hwnd: isize) -> Option<String> {
+    let mut pid = 0u32;
+    unsafe { GetWindowThreadProcessId(HWND(hwnd as *mut _), Some(&mut pid)) };
+    get_process_name(pid)
+}
+
+/// 获取进程的可执行文件名（如 chrome.exe）
+#[cfg(target_os = "windows")]
+pub fn get_process_name(pid: u32) -> Option<String> {
+    if pid == 0 {
+        return None;
+    }
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buffer = [0u16; 260];
+        let mut len = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, PWSTR(buffer.as_mut_ptr()), &mut len);
+        let _ = CloseHandle(handle);
+        result.ok()?;
+        let path = String::from_utf16_lossy(&buffer[..len as usize]);
+        path.rsplit('\\').next().map(|name| name.to_string())
+    }
+}
+
+/// 获取窗口类名（如 Chrome_WidgetWin_1）
+#[cfg(target_os = "windows")]
+pub fn get_window_class(hwnd: isize) -> Option<String> {
+    let mut buffer = [0u16; 256];
+    let len = unsafe { GetClassNameW(HWND(hwnd as *mut _), &mut buffer) };
+    (len > 0).then(|| String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+/// 获取窗口标题
+#[cfg(target_os = "windows")]
+pub fn get_window_title(hwnd: isize) -> Option<String> {
+    let mut buffer = [0u16; 512];
+    let len = unsafe { GetWindowTextW(HWND(hwnd as *mut _), &mut buffer) };
+    (len > 0).then(|| String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
 /// 检查窗口句柄是否有效
 #[cfg(target_os = "windows")]
 pub fn is_window_valid(hwnd: isize) -> bool {
@@ -9,15 +9,19 @@ import type {
   AppStatus,
   AsrConfig,
   AssistantConfig,
+  AudioArchiveConfig,
   DualHotkeyConfig,
   LlmConfig,
+  PreRollConfig,
   UsageStats,
 } from "./types";
 import type { AppPage } from "./pages/types";
 import {
   DEFAULT_ASSISTANT_CONFIG,
+  DEFAULT_AUDIO_ARCHIVE_CONFIG,
   DEFAULT_DUAL_HOTKEY_CONFIG,
   DEFAULT_LLM_CONFIG,
+  DEFAULT_PRE_ROLL_CONFIG,
 } from "./constants";
 import { loadUsageStats } from "./utils";
 import { TopStatusBar } from "./components/layout/TopStatusBar";
@@ -32,12 +36,15 @@ import { useTauriEventListeners } from "./hooks/useTauriEventListeners";
 import { useAppServiceController } from "./hooks/useAppServiceController";
 import { useLlmPresets } from "./hooks/useLlmPresets";
 import { useUpdater } from "./hooks/useUpdater";
+import { useMicDiagnostics } from "./hooks/useMicDiagnostics";
+import { useFileTranscription } from "./hooks/useFileTranscription";
 import { DashboardPage } from "./pages/DashboardPage";
 import { AsrPage } from "./pages/AsrPage";
 import { LlmPage } from "./pages/LlmPage";
 import { AssistantPage } from "./pages/AssistantPage";
 import { DictionaryPage } from "./pages/DictionaryPage";
 import { HistoryPage } from "./pages/HistoryPage";
+import { TranscribePage } from "./pages/TranscribePage";
 import { HotkeysPage } from "./pages/HotkeysPage";
 import { PreferencesPage } from "./pages/PreferencesPage";
 import { HelpPage } from "./pages/HelpPage";
@@ -105,6 +112,8 @@ function App() {
   const [rememberChoice, setRememberChoice] = useState(false);
   const [enableAutostart, setEnableAutostart] = useState(false);
   const [enableMuteOtherApps, setEnableMuteOtherApps] = useState(false);
+  const [preRollConfig, setPreRollConfig] = useState<PreRollConfig>(DEFAULT_PRE_ROLL_CONFIG);
+  const [audioArchiveConfig, setAudioArchiveConfig] = useState<AudioArchiveConfig>(DEFAULT_AUDIO_ARCHIVE_CONFIG);
   const [closeAction, setCloseAction] = useState<"close" | "minimize" | null>(null);
   const [sidebarCollapsed, setSidebarCollapsed] = useState(false);
   const {
@@ -119,6 +128,12 @@ function App() {
     onToast: showToast,
     onError: (message) => setError(message),
   });
+  const micDiagnostics = useMicDiagnostics({
+    onError: (message) => setError(message),
+  });
+  const fileTranscription = useFileTranscription({
+    onError: (message) => setError(message),
+  });
   // hotkeyConfig 已迁移到 dualHotkeyConfig，不再单独使用
   const [dualHotkeyConfig, setDualHotkeyConfig] = useState<DualHotkeyConfig>(DEFAULT_DUAL_HOTKEY_CONFIG);
   const [assistantConfig, setAssistantConfig] = useState<AssistantConfig>(DEFAULT_ASSISTANT_CONFIG);
@@ -192,6 +207,8 @@ function App() {
     setShowCloseDialog,
     setHistory,
     setUsageStats,
+    setLlmConfig,
+    setEnablePostProcess,
   });
 
   // 取消 debounce timer 的回调，供即时保存使用
@@ -220,6 +237,9 @@ function App() {
     loadConfig,
     handleSaveConfig,
     immediatelySaveConfig,
+    saveExtraConfig,
+    purgeAudioArchive,
+    clearLlmCache,
     handleAutostartToggle,
     handleCloseAction,
     applyRuntimeConfig,
@@ -249,6 +269,8 @@ function App() {
     setEnableAutostart,
     enableMuteOtherApps,
     setEnableMuteOtherApps,
+    setPreRollConfig,
+    setAudioArchiveConfig,
     closeAction,
     setCloseAction,
     rememberChoice,
@@ -460,6 +482,10 @@ function App() {
             showApiKey={showApiKey}
             setShowApiKey={setShowApiKey}
             isRunning={isConfigLocked}
+            onClearCache={async () => {
+              const removed = await clearLlmCache();
+              showToast(`已清空 ${removed} 条缓存`);
+            }}
           />
         );
       case "assistant":
@@ -495,6 +521,18 @@ function App() {
         return (
           <HistoryPage history={history} onCopyText={handleCopyText} onClear={handleClearHistory} />
         );
+      case "transcribe":
+        return (
+          <TranscribePage
+            presets={llmConfig.presets}
+            isTranscribing={fileTranscription.isTranscribing}
+            result={fileTranscription.result}
+            onTranscribe={(request) => {
+              void fileTranscription.transcribeFile(request);
+            }}
+            onCopyText={handleCopyText}
+          />
+        );
       case "hotkeys":
         return (
           <HotkeysPage
@@ -519,6 +557,21 @@ function App() {
             }}
             enableMuteOtherApps={enableMuteOtherApps}
             setEnableMuteOtherApps={setEnableMuteOtherApps}
+            preRollConfig={preRollConfig}
+            setPreRollConfig={setPreRollConfig}
+            onSaveExtraConfig={saveExtraConfig}
+            audioArchiveConfig={audioArchiveConfig}
+            setAudioArchiveConfig={setAudioArchiveConfig}
+            onPurgeAudioArchive={async () => {
+              const removed = await purgeAudioArchive();
+              showToast(`已删除 ${removed} 条录音归档`);
+            }}
+            micDiagnosticsRunning={micDiagnostics.isRunning}
+            micDiagnosticsStage={micDiagnostics.stage}
+            micDiagnosticsReport={micDiagnostics.report}
+            onRunMicDiagnostics={() => {
+              void micDiagnostics.runDiagnostics();
+            }}
             updateStatus={updateStatus}
             updateInfo={updateInfo}
             currentVersion={currentVersion}
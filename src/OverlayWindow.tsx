@@ -4,7 +4,7 @@
 // 优化：合并 RAF 循环，统一时间驱动
 
 import { useState, useEffect, useRef } from "react";
-import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { listen, Event, UnlistenFn } from "@tauri-apps/api/event";
 import { invoke } from "@tauri-apps/api/core";
 
 // 音频级别事件 payload 类型
@@ -15,6 +15,22 @@ interface AudioLevelPayload {
 // 状态类型
 type OverlayStatus = "recording" | "transcribing";
 
+// 录音限制提醒事件 payload（recording_limit_warning）
+interface RecordingLimitWarningPayload {
+  kind: "max_duration_approaching" | "max_duration_reached" | "send_backlog";
+  remaining_secs: number | null;
+  backlog_secs: number | null;
+}
+
+// AI 助手命令执行确认请求（tool_confirmation_request）
+interface ToolConfirmationPayload {
+  id: string;
+  command: string;
+}
+
+// 削波提醒显示时长（毫秒），后端每 3 秒最多提醒一次
+const CLIPPING_WARNING_MS = 2000;
+
 // 静音阈值常量（与后端 NOISE_FLOOR 对齐）
 // 低于此值视为静音，防止浮点数抖动导致的微小波动
 const SILENCE_THRESHOLD = 0.005;
@@ -189,13 +205,15 @@ function LockedControls({
   onCancel,
   level,
   time,
-  disabled
+  disabled,
+  countdown
 }: {
   onFinish: () => void;
   onCancel: () => void;
   level: number;
   time: number;
   disabled: boolean;
+  countdown: number | null;
 }) {
   const minHeight = 3;
   const maxHeight = 26;
@@ -217,31 +235,38 @@ function LockedControls({
         </svg>
       </button>
 
-      <div className="locked-wave-mini">
-        {baseScales.map((baseScale, i) => {
-          // 静音检测：level 为 0 时直接返回最小高度
-          // （Hook 已将低于 SILENCE_THRESHOLD 的值归零）
-          if (level === 0) {
+      {countdown !== null ? (
+        // 静音自动停止倒计时
+        <div className="locked-countdown" title="持续静音，即将自动发送">
+          {countdown}
+        </div>
+      ) : (
+        <div className="locked-wave-mini">
+          {baseScales.map((baseScale, i) => {
+            // 静音检测：level 为 0 时直接返回最小高度
+            // （Hook 已将低于 SILENCE_THRESHOLD 的值归零）
+            if (level === 0) {
+              return (
+                <div key={i} className="wave-bar-mini" style={{ height: `${minHeight}px` }} />
+              );
+            }
+
+            const distanceFromCenter = Math.abs(i - 3);  // 7条时中心是索引3
+            const flowWave = Math.sin(time * 1.2 - distanceFromCenter * 0.6) * 0.15;
+            const turbulence = Math.cos(time * 2.5 + i * 1.7) * 0.12 * level;
+            const dynamicScale = baseScale + flowWave * level + turbulence;
+            const height = minHeight + level * dynamicScale * (maxHeight - minHeight);
+
             return (
-              <div key={i} className="wave-bar-mini" style={{ height: `${minHeight}px` }} />
+              <div
+                key={i}
+                className="wave-bar-mini"
+                style={{ height: `${Math.max(minHeight, Math.min(maxHeight, height))}px` }}
+              />
             );
-          }
-
-          const distanceFromCenter = Math.abs(i - 3);  // 7条时中心是索引3
-          const flowWave = Math.sin(time * 1.2 - distanceFromCenter * 0.6) * 0.15;
-          const turbulence = Math.cos(time * 2.5 + i * 1.7) * 0.12 * level;
-          const dynamicScale = baseScale + flowWave * level + turbulence;
-          const height = minHeight + level * dynamicScale * (maxHeight - minHeight);
-
-          return (
-            <div
-              key={i}
-              className="wave-bar-mini"
-              style={{ height: `${Math.max(minHeight, Math.min(maxHeight, height))}px` }}
-            />
-          );
-        })}
-      </div>
+          })}
+        </div>
+      )}
 
       <button
         onClick={onFinish}
@@ -257,11 +282,57 @@ function LockedControls({
   );
 }
 
+// AI 助手命令确认组件 - 沿用松手模式的按钮布局
+function ToolConfirmation({
+  command,
+  onRespond,
+}: {
+  command: string;
+  onRespond: (approved: boolean) => void;
+}) {
+  return (
+    <div className="locked-controls">
+      <button
+        onClick={() => onRespond(false)}
+        className="locked-btn locked-btn-cancel"
+        title="拒绝执行"
+      >
+        <svg width="18" height="18" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2.5" strokeLinecap="round" strokeLinejoin="round">
+          <line x1="18" y1="6" x2="6" y2="18" />
+          <line x1="6" y1="6" x2="18" y2="18" />
+        </svg>
+      </button>
+
+      <div className="tool-confirm-command" title={command}>
+        {command}
+      </div>
+
+      <button
+        onClick={() => onRespond(true)}
+        className="locked-btn locked-btn-finish"
+        title="允许执行"
+      >
+        <svg width="18" height="18" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="3" strokeLinecap="round" strokeLinejoin="round">
+          <polyline points="20 6 9 17 4 12" />
+        </svg>
+      </button>
+    </div>
+  );
+}
+
 // 主悬浮窗组件
 export default function OverlayWindow() {
   const [status, setStatus] = useState<OverlayStatus>("recording");
   const [isLocked, setIsLocked] = useState(false);
   const [isSubmitting, setIsSubmitting] = useState(false);
+  // 静音自动停止倒计时（剩余秒数，null 表示未在倒计时）
+  const [countdown, setCountdown] = useState<number | null>(null);
+  // 录音限制提醒（接近/达到最大时长、发送积压）
+  const [limitWarning, setLimitWarning] = useState<RecordingLimitWarningPayload["kind"] | null>(null);
+  // 麦克风削波提醒（短暂显示后自动清除）
+  const [clipping, setClipping] = useState(false);
+  // AI 助手等待确认的命令
+  const [confirmation, setConfirmation] = useState<ToolConfirmationPayload | null>(null);
 
   // 使用 Hook 获取平滑的音频级别和动画时间
   const { level: audioLevel, time: animationTime } = useSmoothAudioLevel(status === "recording");
@@ -274,7 +345,7 @@ export default function OverlayWindow() {
       // 辅助函数：注册监听器并检查取消状态，解决 StrictMode 竞态条件
       const registerListener = async (
         event: string,
-        handler: () => void,
+        handler: (event: Event<unknown>) => void,
       ): Promise<boolean> => {
         const unlisten = await listen(event, handler);
         if (cancelled) {
@@ -289,6 +360,8 @@ export default function OverlayWindow() {
         setStatus("recording");
         setIsLocked(false);
         setIsSubmitting(false);
+        setCountdown(null);
+        setLimitWarning(null);
       }))) return;
 
       if (!(await registerListener("recording_locked", () => {
@@ -299,28 +372,59 @@ export default function OverlayWindow() {
 
       if (!(await registerListener("recording_stopped", () => {
         setStatus("transcribing");
+        setCountdown(null);
+        setLimitWarning(null);
+      }))) return;
+
+      if (!(await registerListener("recording_limit_warning", (event) => {
+        const payload = event.payload as RecordingLimitWarningPayload;
+        console.warn("录音限制提醒:", payload);
+        setLimitWarning(payload.kind);
+      }))) return;
+
+      if (!(await registerListener("mic_clipping", (event) => {
+        console.warn("麦克风削波:", event.payload);
+        setClipping(true);
+      }))) return;
+
+      if (!(await registerListener("auto_stop_countdown", (event) => {
+        setCountdown(event.payload as number | null);
       }))) return;
 
       if (!(await registerListener("transcribing", () => {
         setStatus("transcribing");
       }))) return;
 
+      if (!(await registerListener("tool_confirmation_request", (event) => {
+        setConfirmation(event.payload as ToolConfirmationPayload);
+      }))) return;
+
+      if (!(await registerListener("tool_confirmation_closed", (event) => {
+        const id = event.payload as string;
+        setConfirmation((prev) => (prev?.id === id ? null : prev));
+      }))) return;
+
       if (!(await registerListener("transcription_complete", () => {
+        setConfirmation(null);
         setStatus("recording");
         setIsLocked(false);
         setIsSubmitting(false);
       }))) return;
 
       if (!(await registerListener("error", () => {
+        setConfirmation(null);
         setStatus("recording");
         setIsLocked(false);
         setIsSubmitting(false);
       }))) return;
 
       if (!(await registerListener("transcription_cancelled", () => {
+        setConfirmation(null);
         setStatus("recording");
         setIsLocked(false);
         setIsSubmitting(false);
+        setCountdown(null);
+        setLimitWarning(null);
       }))) return;
     };
 
@@ -332,9 +436,9 @@ export default function OverlayWindow() {
     };
   }, []);
 
-  // 超时保护机制
+  // 超时保护机制（等待命令确认时由后端负责超时）
   useEffect(() => {
-    if (status === "transcribing") {
+    if (status === "transcribing" && !confirmation) {
       const timeout = setTimeout(async () => {
         console.warn("转写超时 15 秒，强制调用隐藏悬浮窗");
         try {
@@ -348,7 +452,7 @@ export default function OverlayWindow() {
       }, 15000);
       return () => clearTimeout(timeout);
     }
-  }, [status]);
+  }, [status, confirmation]);
 
   // 松手模式超时保护
   useEffect(() => {
@@ -377,6 +481,23 @@ export default function OverlayWindow() {
     }
   };
 
+  useEffect(() => {
+    if (!clipping) return;
+    const timer = setTimeout(() => setClipping(false), CLIPPING_WARNING_MS);
+    return () => clearTimeout(timer);
+  }, [clipping]);
+
+  const handleConfirmation = async (approved: boolean) => {
+    if (!confirmation) return;
+    const { id } = confirmation;
+    setConfirmation(null);
+    try {
+      await invoke("respond_tool_confirmation", { id, approved });
+    } catch (e) {
+      console.error("提交命令确认失败:", e);
+    }
+  };
+
   const handleCancel = async () => {
     if (isSubmitting) return;
     setIsSubmitting(true);
@@ -390,8 +511,10 @@ export default function OverlayWindow() {
 
   return (
     <div className="overlay-root">
-      <div className={`overlay-pill ${isLocked ? 'overlay-pill-locked' : ''}`}>
-        {status === "recording" ? (
+      <div className={`overlay-pill ${isLocked || confirmation ? 'overlay-pill-locked' : ''} ${limitWarning || clipping ? 'overlay-pill-warning' : ''}`}>
+        {confirmation ? (
+          <ToolConfirmation command={confirmation.command} onRespond={handleConfirmation} />
+        ) : status === "recording" ? (
           isLocked ? (
             <LockedControls
               onFinish={handleFinish}
@@ -399,6 +522,7 @@ export default function OverlayWindow() {
               level={audioLevel}
               time={animationTime}
               disabled={isSubmitting}
+              countdown={countdown}
             />
           ) : (
             <WaveformBars level={audioLevel} time={animationTime} />
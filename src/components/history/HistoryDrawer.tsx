@@ -86,7 +86,10 @@ export function HistoryDrawer({
                           {record.presetName}
                         </span>
                       )}
-                      <span className="text-[10px] bg-stone-50 text-stone-600 px-1.5 py-0.5 rounded">
+                      <span
+                        className="text-[10px] bg-stone-50 text-stone-600 px-1.5 py-0.5 rounded"
+                        title={record.llmEndpoint ? `LLM 端点：${record.llmEndpoint}` : undefined}
+                      >
                         {(record.totalTimeMs / 1000).toFixed(1)}s
                       </span>
                     </div>
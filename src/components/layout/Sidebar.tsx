@@ -1,6 +1,7 @@
 import type { AppPage } from "../../pages/types";
 import {
   BookText,
+  FileAudio,
   History,
   Keyboard,
   LayoutDashboard,
@@ -179,6 +180,15 @@ export function Sidebar({
           <History className="shrink-0 w-5 h-5" />
           <span className={navTextClass}>历史记录</span>
         </button>
+
+        <button
+          onClick={() => onNavigate("transcribe")}
+          className={navItem("transcribe")}
+          title="文件转写"
+        >
+          <FileAudio className="shrink-0 w-5 h-5" />
+          <span className={navTextClass}>文件转写</span>
+        </button>
       </nav>
 
       <div className="pt-3 border-t border-[var(--stone)]">
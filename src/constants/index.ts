@@ -1,4 +1,4 @@
-import type { HotkeyKey, LlmPreset, LlmConfig, AssistantConfig, AsrProvider, AsrProviderMeta } from '../types';
+import type { HotkeyKey, LlmPreset, LlmConfig, AssistantConfig, AsrProvider, AsrProviderMeta, PreRollConfig, AudioArchiveConfig, LlmCacheConfig } from '../types';
 
 // 按键显示名称映射
 export const KEY_DISPLAY_NAMES: Record<HotkeyKey, string> = {
@@ -112,6 +112,27 @@ export const DEFAULT_DUAL_HOTKEY_CONFIG = {
   assistant: { keys: ['alt_left', 'space'] as HotkeyKey[] }
 };
 
+// 默认 LLM 响应缓存配置（与后端 LlmCacheConfig::default 一致）
+export const DEFAULT_LLM_CACHE_CONFIG: LlmCacheConfig = {
+  enabled: true,
+  ttl_hours: 168,
+  max_entries: 1000,
+  max_input_chars: 200,
+};
+
+// 默认预录配置（隐私开关默认关闭）
+export const DEFAULT_PRE_ROLL_CONFIG: PreRollConfig = {
+  enabled: false,
+  duration_ms: 300,
+};
+
+// 默认录音归档配置（默认关闭）
+export const DEFAULT_AUDIO_ARCHIVE_CONFIG: AudioArchiveConfig = {
+  enabled: false,
+  max_total_mb: 500,
+  max_age_days: 30,
+};
+
 // 默认 ASR 缓存
 export const DEFAULT_ASR_CACHE = {
   active_provider: 'qwen' as AsrProvider,
@@ -6,19 +6,29 @@ import type {
   AppStatus,
   AsrConfig,
   AssistantConfig,
+  AudioArchiveConfig,
   DualHotkeyConfig,
   HotkeyKey,
   LlmConfig,
+  PreRollConfig,
 } from "../types";
 import {
   DEFAULT_ASSISTANT_CONFIG,
+  DEFAULT_AUDIO_ARCHIVE_CONFIG,
   DEFAULT_DUAL_HOTKEY_CONFIG,
   DEFAULT_LLM_CONFIG,
+  DEFAULT_PRE_ROLL_CONFIG,
 } from "../constants";
 import { isAsrConfigValid } from "../utils";
 
 const DICTIONARY_STORAGE_KEY = "pushtotalk_dictionary";
 
+/** 不参与自动保存的独立配置项（修改后立即保存并热更新） */
+export type ExtraConfigUpdates = {
+  preRollConfig?: PreRollConfig;
+  audioArchiveConfig?: AudioArchiveConfig;
+};
+
 export type UseAppServiceControllerParams = {
   setAsrConfig: React.Dispatch<React.SetStateAction<AsrConfig>>;
 
@@ -59,6 +69,9 @@ export type UseAppServiceControllerParams = {
   enableMuteOtherApps: boolean;
   setEnableMuteOtherApps: React.Dispatch<React.SetStateAction<boolean>>;
 
+  setPreRollConfig: React.Dispatch<React.SetStateAction<PreRollConfig>>;
+  setAudioArchiveConfig: React.Dispatch<React.SetStateAction<AudioArchiveConfig>>;
+
   closeAction: "close" | "minimize" | null;
   setCloseAction: React.Dispatch<React.SetStateAction<"close" | "minimize" | null>>;
 
@@ -98,6 +111,8 @@ export function useAppServiceController({
   setEnableAutostart,
   enableMuteOtherApps,
   setEnableMuteOtherApps,
+  setPreRollConfig,
+  setAudioArchiveConfig,
   closeAction,
   setCloseAction,
   rememberChoice,
@@ -299,6 +314,8 @@ export function useAppServiceController({
       }
 
       setEnableMuteOtherApps(config.enable_mute_other_apps ?? false);
+      setPreRollConfig(config.pre_roll_config ?? DEFAULT_PRE_ROLL_CONFIG);
+      setAudioArchiveConfig(config.audio_archive_config ?? DEFAULT_AUDIO_ARCHIVE_CONFIG);
 
       const configDictionary =
         config.dictionary && Array.isArray(config.dictionary) ? config.dictionary : [];
@@ -341,6 +358,7 @@ export function useAppServiceController({
     setApiKey,
     setAsrConfig,
     setAssistantConfig,
+    setAudioArchiveConfig,
     setCloseAction,
     setDictionary,
     setDualHotkeyConfig,
@@ -349,6 +367,7 @@ export function useAppServiceController({
     setEnablePostProcess,
     setFallbackApiKey,
     setLlmConfig,
+    setPreRollConfig,
     setStatus,
     setError,
     setUseRealtime,
@@ -505,6 +524,70 @@ export function useAppServiceController({
     stopApp,
   ]);
 
+  /**
+   * 保存独立配置项并热更新到运行中的服务（不重启服务）
+   * 服务未运行时后端直接返回，下次启动时从配置文件读取
+   */
+  const saveExtraConfig = useCallback(async (updates: ExtraConfigUpdates) => {
+    try {
+      await invoke<string>("save_config", {
+        apiKey,
+        fallbackApiKey,
+        useRealtime,
+        enablePostProcess,
+        llmConfig,
+        smartCommandConfig: null,
+        assistantConfig,
+        asrConfig,
+        dualHotkeyConfig,
+        enableMuteOtherApps,
+        dictionary,
+        ...updates,
+      });
+      await invoke<string>("update_runtime_config", { ...updates });
+      setError(null);
+    } catch (err) {
+      setError(String(err));
+      throw err; // 重新抛出，让调用方可以处理回滚
+    }
+  }, [
+    apiKey,
+    fallbackApiKey,
+    useRealtime,
+    enablePostProcess,
+    llmConfig,
+    assistantConfig,
+    asrConfig,
+    dualHotkeyConfig,
+    enableMuteOtherApps,
+    dictionary,
+    setError,
+  ]);
+
+  /** 删除全部录音归档，返回删除的文件数 */
+  const purgeAudioArchive = useCallback(async () => {
+    try {
+      const removed = await invoke<number>("purge_audio_archive");
+      setError(null);
+      return removed;
+    } catch (err) {
+      setError(String(err));
+      throw err;
+    }
+  }, [setError]);
+
+  /** 清空 LLM 响应缓存，返回删除的条目数 */
+  const clearLlmCache = useCallback(async () => {
+    try {
+      const removed = await invoke<number>("clear_llm_cache");
+      setError(null);
+      return removed;
+    } catch (err) {
+      setError(String(err));
+      throw err;
+    }
+  }, [setError]);
+
   const handleAutostartToggle = useCallback(async () => {
     try {
       const newValue = !enableAutostart;
@@ -645,6 +728,9 @@ export function useAppServiceController({
     loadConfig,
     handleSaveConfig,
     immediatelySaveConfig,
+    saveExtraConfig,
+    purgeAudioArchive,
+    clearLlmCache,
     handleAutostartToggle,
     handleStartStop,
     handleCancelTranscription,
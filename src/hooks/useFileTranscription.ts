@@ -0,0 +1,44 @@
+import { useCallback, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import type { FileTranscriptionResult } from "../types";
+
+export type UseFileTranscriptionParams = {
+  onError: (message: string) => void;
+};
+
+export type FileTranscriptionRequest = {
+  path: string;
+  presetId: string | null;
+  saveToFile: boolean;
+};
+
+export function useFileTranscription({ onError }: UseFileTranscriptionParams) {
+  const [isTranscribing, setIsTranscribing] = useState(false);
+  const [result, setResult] = useState<FileTranscriptionResult | null>(null);
+
+  const transcribeFile = useCallback(
+    async ({ path, presetId, saveToFile }: FileTranscriptionRequest) => {
+      setIsTranscribing(true);
+      setResult(null);
+      try {
+        const output = await invoke<FileTranscriptionResult>("transcribe_audio_file", {
+          path,
+          presetId,
+          saveToFile,
+        });
+        setResult(output);
+      } catch (err) {
+        onError(String(err));
+      } finally {
+        setIsTranscribing(false);
+      }
+    },
+    [onError]
+  );
+
+  return {
+    isTranscribing,
+    result,
+    transcribeFile,
+  };
+}
@@ -0,0 +1,43 @@
+import { useCallback, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import type { MicDiagnosticsReport, MicDiagnosticsStage } from "../types";
+
+export type UseMicDiagnosticsParams = {
+  onError: (message: string) => void;
+};
+
+export function useMicDiagnostics({ onError }: UseMicDiagnosticsParams) {
+  const [isRunning, setIsRunning] = useState(false);
+  const [stage, setStage] = useState<MicDiagnosticsStage | null>(null);
+  const [report, setReport] = useState<MicDiagnosticsReport | null>(null);
+
+  const runDiagnostics = useCallback(async () => {
+    setIsRunning(true);
+    setReport(null);
+    setStage(null);
+
+    // 诊断期间监听阶段事件，结束后立即取消
+    const unlisten = await listen<MicDiagnosticsStage>("mic_diagnostics_stage", (e) => {
+      setStage(e.payload);
+    });
+
+    try {
+      const result = await invoke<MicDiagnosticsReport>("run_mic_diagnostics");
+      setReport(result);
+    } catch (err) {
+      onError(String(err));
+    } finally {
+      unlisten();
+      setStage(null);
+      setIsRunning(false);
+    }
+  }, [onError]);
+
+  return {
+    isRunning,
+    stage,
+    report,
+    runDiagnostics,
+  };
+}
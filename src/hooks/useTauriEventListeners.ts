@@ -3,7 +3,7 @@ import { useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { nanoid } from "nanoid";
-import type { AppConfig, AppStatus, HistoryRecord, LlmConfig, TranscriptionResult, UsageStats } from "../types";
+import type { AppConfig, AppStatus, AssistantConfigChanged, HistoryRecord, LlmConfig, LlmStreamDelta, TranscriptionResult, UsageStats } from "../types";
 import { MAX_HISTORY } from "../constants";
 import { saveHistory, loadUsageStats } from "../utils";
 
@@ -24,6 +24,8 @@ export type UseTauriEventListenersParams = {
 
   setHistory: React.Dispatch<React.SetStateAction<HistoryRecord[]>>;
   setUsageStats?: React.Dispatch<React.SetStateAction<UsageStats>>;
+  setLlmConfig?: React.Dispatch<React.SetStateAction<LlmConfig>>;
+  setEnablePostProcess?: React.Dispatch<React.SetStateAction<boolean>>;
 };
 
 export function useTauriEventListeners({
@@ -39,6 +41,8 @@ export function useTauriEventListeners({
   setShowCloseDialog,
   setHistory,
   setUsageStats,
+  setLlmConfig,
+  setEnablePostProcess,
 }: UseTauriEventListenersParams) {
   useEffect(() => {
     let unlistenFns: UnlistenFn[] = [];
@@ -93,6 +97,8 @@ export function useTauriEventListeners({
         }))) return;
 
         if (!(await registerListener<string>("post_processing", (mode) => {
+          // 清空上次结果，随后由 llm_stream_delta 逐段填充
+          setTranscript("");
           if (mode === "polishing") {
             setStatus("polishing");
           } else if (mode === "assistant") {
@@ -100,6 +106,10 @@ export function useTauriEventListeners({
           }
         }))) return;
 
+        if (!(await registerListener<LlmStreamDelta>("llm_stream_delta", ({ delta }) => {
+          setTranscript((prev) => prev + delta);
+        }))) return;
+
         if (!(await registerListener<TranscriptionResult>("transcription_complete", (result) => {
           setTranscript(result.text);
           setOriginalTranscript(result.original_text);
@@ -119,7 +129,7 @@ export function useTauriEventListeners({
             : null;
 
           addHistoryRecord({
-            id: nanoid(8),
+            id: result.id || nanoid(8),
             timestamp: Date.now(),
             originalText: result.original_text || result.text,
             polishedText: result.original_text ? result.text : null,
@@ -127,6 +137,7 @@ export function useTauriEventListeners({
             mode,
             asrTimeMs: result.asr_time_ms,
             llmTimeMs: result.llm_time_ms,
+            llmEndpoint: result.llm_endpoint ?? null,
             totalTimeMs: result.total_time_ms,
             success: true,
             errorMessage: null,
@@ -156,6 +167,17 @@ export function useTauriEventListeners({
           });
         }))) return;
 
+        // AI 助手工具切换了润色预设或开关，同步到界面（后端已保存配置）
+        if (!(await registerListener<AssistantConfigChanged>("assistant_config_changed", (changed) => {
+          if (changed.active_preset_id !== undefined && setLlmConfig) {
+            const presetId = changed.active_preset_id;
+            setLlmConfig((prev) => ({ ...prev, active_preset_id: presetId }));
+          }
+          if (changed.enable_post_process !== undefined && setEnablePostProcess) {
+            setEnablePostProcess(changed.enable_post_process);
+          }
+        }))) return;
+
         if (!(await registerListener("transcription_cancelled", () => {
           setStatus("running");
           setError(null);
@@ -194,7 +216,9 @@ export function useTauriEventListeners({
     setAsrTime,
     setCurrentMode,
     setError,
+    setEnablePostProcess,
     setHistory,
+    setLlmConfig,
     setLlmTime,
     setOriginalTranscript,
     setShowCloseDialog,
@@ -1,7 +1,8 @@
-import type { Dispatch, SetStateAction } from "react";
-import { AlertCircle, MessageSquareQuote, Plus, Trash2 } from "lucide-react";
+import { useState, type Dispatch, type SetStateAction } from "react";
+import { AlertCircle, Eraser, MessageSquareQuote, Plus, Trash2 } from "lucide-react";
 import type { LlmConfig, LlmPreset } from "../types";
-import { ApiKeyInput } from "../components/common";
+import { DEFAULT_LLM_CACHE_CONFIG } from "../constants";
+import { ApiKeyInput, Toggle } from "../components/common";
 
 export type LlmPageProps = {
   llmConfig: LlmConfig;
@@ -14,6 +15,7 @@ export type LlmPageProps = {
   showApiKey: boolean;
   setShowApiKey: (next: boolean) => void;
   isRunning: boolean;
+  onClearCache: () => Promise<void>;
 };
 
 export function LlmPage({
@@ -26,7 +28,22 @@ export function LlmPage({
   showApiKey,
   setShowApiKey,
   isRunning,
+  onClearCache,
 }: LlmPageProps) {
+  const cacheConfig = llmConfig.cache ?? DEFAULT_LLM_CACHE_CONFIG;
+  const [isClearingCache, setIsClearingCache] = useState(false);
+
+  const handleClearCache = async () => {
+    setIsClearingCache(true);
+    try {
+      await onClearCache();
+    } catch {
+      // 错误已由上层展示
+    } finally {
+      setIsClearingCache(false);
+    }
+  };
+
   return (
     <div className="mx-auto max-w-5xl font-sans">
       <div className="bg-white border border-[var(--stone)] rounded-2xl overflow-hidden">
@@ -162,6 +179,39 @@ export function LlmPage({
                   </div>
                 </div>
               </div>
+
+              <div className="h-px bg-[var(--stone)]" />
+
+              <div className="space-y-4">
+                <h4 className="text-xs font-bold text-stone-400 uppercase tracking-widest">响应缓存</h4>
+                <div className="flex items-center justify-between p-4 bg-[var(--paper)] border border-[var(--stone)] rounded-2xl">
+                  <div>
+                    <div className="text-sm font-bold text-[var(--ink)]">缓存常用短句</div>
+                    <div className="text-[11px] text-stone-400 font-semibold">
+                      不超过 {cacheConfig.max_input_chars} 字的相同输入直接返回上次结果，不再请求模型
+                    </div>
+                  </div>
+                  <div className="flex items-center gap-3">
+                    <button
+                      onClick={() => {
+                        void handleClearCache();
+                      }}
+                      disabled={isClearingCache}
+                      className="px-3 py-2 rounded-xl bg-white border border-[var(--stone)] text-stone-700 text-xs font-bold hover:border-[rgba(176,174,165,0.75)] transition-colors disabled:opacity-50 flex items-center gap-2"
+                    >
+                      <Eraser size={14} />
+                      {isClearingCache ? "清空中..." : "清空缓存"}
+                    </button>
+                    <Toggle
+                      checked={cacheConfig.enabled}
+                      onCheckedChange={(enabled) => setLlmConfig({ ...llmConfig, cache: { ...cacheConfig, enabled } })}
+                      disabled={isRunning}
+                      size="sm"
+                      variant="orange"
+                    />
+                  </div>
+                </div>
+              </div>
             </div>
           </section>
         </div>
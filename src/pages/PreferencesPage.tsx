@@ -1,6 +1,41 @@
-import { Download, Power, RefreshCw, SlidersHorizontal, VolumeX } from "lucide-react";
-import type { AppStatus, UpdateStatus } from "../types";
-import { Toggle } from "../components/common";
+import { useState } from "react";
+import { Activity, Archive, Download, Mic, Power, RefreshCw, SlidersHorizontal, Trash2, VolumeX } from "lucide-react";
+import type {
+  AppStatus,
+  AudioArchiveConfig,
+  MicDiagnosticsReport,
+  MicDiagnosticsStage,
+  PreRollConfig,
+  UpdateStatus,
+} from "../types";
+import type { ExtraConfigUpdates } from "../hooks/useAppServiceController";
+import { ConfigSelect, ConfigToggle, Toggle } from "../components/common";
+
+const PRE_ROLL_DURATION_OPTIONS = ["200", "300", "500", "1000"] as const;
+const ARCHIVE_SIZE_OPTIONS = [
+  { value: "200", label: "200 MB" },
+  { value: "500", label: "500 MB" },
+  { value: "1000", label: "1 GB" },
+  { value: "0", label: "不限制" },
+];
+const ARCHIVE_AGE_OPTIONS = [
+  { value: "7", label: "7 天" },
+  { value: "30", label: "30 天" },
+  { value: "90", label: "90 天" },
+  { value: "0", label: "永久保留" },
+];
+
+function describeMicStage(stage: MicDiagnosticsStage | null): string {
+  if (!stage) return "准备中...";
+  switch (stage.stage) {
+    case "silence":
+      return `请保持安静 ${stage.duration_secs} 秒，正在测量底噪`;
+    case "speech":
+      return `请在 ${stage.duration_secs} 秒内以正常音量朗读：${stage.phrase ?? ""}`;
+    case "analyzing":
+      return "正在分析...";
+  }
+}
 
 export type PreferencesPageProps = {
   status: AppStatus;
@@ -11,6 +46,19 @@ export type PreferencesPageProps = {
   enableMuteOtherApps: boolean;
   setEnableMuteOtherApps: (next: boolean) => void;
 
+  preRollConfig: PreRollConfig;
+  setPreRollConfig: (next: PreRollConfig) => void;
+  onSaveExtraConfig: (updates: ExtraConfigUpdates) => Promise<void>;
+
+  audioArchiveConfig: AudioArchiveConfig;
+  setAudioArchiveConfig: (next: AudioArchiveConfig) => void;
+  onPurgeAudioArchive: () => Promise<void>;
+
+  micDiagnosticsRunning: boolean;
+  micDiagnosticsStage: MicDiagnosticsStage | null;
+  micDiagnosticsReport: MicDiagnosticsReport | null;
+  onRunMicDiagnostics: () => void;
+
   updateStatus: UpdateStatus;
   updateInfo: { version: string; notes?: string } | null;
   currentVersion: string;
@@ -24,6 +72,16 @@ export function PreferencesPage({
   onToggleAutostart,
   enableMuteOtherApps,
   setEnableMuteOtherApps,
+  preRollConfig,
+  setPreRollConfig,
+  onSaveExtraConfig,
+  audioArchiveConfig,
+  setAudioArchiveConfig,
+  onPurgeAudioArchive,
+  micDiagnosticsRunning,
+  micDiagnosticsStage,
+  micDiagnosticsReport,
+  onRunMicDiagnostics,
   updateStatus,
   updateInfo,
   currentVersion,
@@ -31,6 +89,25 @@ export function PreferencesPage({
   onDownloadAndInstall,
 }: PreferencesPageProps) {
   const canInstallUpdate = updateStatus === "available" || updateStatus === "downloading";
+  const isBusy = status === "recording" || status === "transcribing";
+  const [isPurging, setIsPurging] = useState(false);
+  const [confirmPurge, setConfirmPurge] = useState(false);
+
+  const handlePurgeArchive = async () => {
+    if (!confirmPurge) {
+      setConfirmPurge(true);
+      return;
+    }
+    setIsPurging(true);
+    try {
+      await onPurgeAudioArchive();
+    } catch {
+      // 错误已由上层展示
+    } finally {
+      setIsPurging(false);
+      setConfirmPurge(false);
+    }
+  };
 
   return (
     <div className="mx-auto max-w-3xl space-y-6 font-sans">
@@ -82,12 +159,191 @@ export function PreferencesPage({
           <Toggle
             checked={enableMuteOtherApps}
             onCheckedChange={setEnableMuteOtherApps}
-            disabled={status === "recording" || status === "transcribing"}
+            disabled={isBusy}
             size="sm"
             variant="orange"
           />
         </div>
 
+        <div className="p-4 bg-[var(--paper)] border border-[var(--stone)] rounded-2xl space-y-3">
+          <div className="flex items-center justify-between">
+            <div className="flex items-center gap-3">
+              <div
+                className={[
+                  "p-2 rounded-xl",
+                  preRollConfig.enabled
+                    ? "bg-[rgba(217,119,87,0.12)] text-[var(--crail)]"
+                    : "bg-white border border-[var(--stone)] text-stone-500",
+                ].join(" ")}
+              >
+                <Mic size={16} />
+              </div>
+              <div>
+                <div className="text-sm font-bold text-[var(--ink)]">麦克风预录</div>
+                <div className="text-[11px] text-stone-400 font-semibold">
+                  {preRollConfig.enabled ? "避免按下热键前的首字被截断" : "仅在按下热键后打开麦克风"}
+                </div>
+              </div>
+            </div>
+            <ConfigToggle
+              checked={preRollConfig.enabled}
+              onCheckedChange={(enabled) => setPreRollConfig({ ...preRollConfig, enabled })}
+              onCommit={async (enabled) => {
+                await onSaveExtraConfig({ preRollConfig: { ...preRollConfig, enabled } });
+              }}
+              disabled={isBusy}
+              size="sm"
+              variant="orange"
+            />
+          </div>
+          <div className="text-[11px] text-stone-500 leading-relaxed">
+            隐私提示：开启后服务运行期间麦克风将保持常开，系统会显示麦克风占用标识。
+            仅在内存中循环保留最近 {preRollConfig.duration_ms} 毫秒音频，按下热键时拼接到录音开头，数据不会写入磁盘或上传。
+          </div>
+          {preRollConfig.enabled && (
+            <div className="flex items-center justify-between">
+              <div className="text-xs font-bold text-stone-500">预录时长</div>
+              <ConfigSelect
+                value={String(preRollConfig.duration_ms)}
+                onChange={(value) => setPreRollConfig({ ...preRollConfig, duration_ms: Number(value) })}
+                onCommit={async (value) => {
+                  await onSaveExtraConfig({ preRollConfig: { ...preRollConfig, duration_ms: Number(value) } });
+                }}
+                options={PRE_ROLL_DURATION_OPTIONS.map((value) => ({ value, label: `${value} ms` }))}
+                disabled={isBusy}
+              />
+            </div>
+          )}
+        </div>
+
+        <div className="p-4 bg-[var(--paper)] border border-[var(--stone)] rounded-2xl space-y-3">
+          <div className="flex items-center justify-between">
+            <div className="flex items-center gap-3">
+              <div
+                className={[
+                  "p-2 rounded-xl",
+                  audioArchiveConfig.enabled
+                    ? "bg-[rgba(34,197,94,0.12)] text-green-500"
+                    : "bg-white border border-[var(--stone)] text-stone-500",
+                ].join(" ")}
+              >
+                <Archive size={16} />
+              </div>
+              <div>
+                <div className="text-sm font-bold text-[var(--ink)]">录音归档</div>
+                <div className="text-[11px] text-stone-400 font-semibold">
+                  {audioArchiveConfig.enabled ? "保存录音以便重新转写" : "录音转写后即丢弃"}
+                </div>
+              </div>
+            </div>
+            <ConfigToggle
+              checked={audioArchiveConfig.enabled}
+              onCheckedChange={(enabled) => setAudioArchiveConfig({ ...audioArchiveConfig, enabled })}
+              onCommit={async (enabled) => {
+                await onSaveExtraConfig({ audioArchiveConfig: { ...audioArchiveConfig, enabled } });
+              }}
+              size="sm"
+              variant="green"
+            />
+          </div>
+          {audioArchiveConfig.enabled && (
+            <>
+              <div className="flex items-center justify-between">
+                <div className="text-xs font-bold text-stone-500">占用空间上限</div>
+                <ConfigSelect
+                  value={String(audioArchiveConfig.max_total_mb)}
+                  onChange={(value) => setAudioArchiveConfig({ ...audioArchiveConfig, max_total_mb: Number(value) })}
+                  onCommit={async (value) => {
+                    await onSaveExtraConfig({ audioArchiveConfig: { ...audioArchiveConfig, max_total_mb: Number(value) } });
+                  }}
+                  options={ARCHIVE_SIZE_OPTIONS}
+                />
+              </div>
+              <div className="flex items-center justify-between">
+                <div className="text-xs font-bold text-stone-500">保留时长</div>
+                <ConfigSelect
+                  value={String(audioArchiveConfig.max_age_days)}
+                  onChange={(value) => setAudioArchiveConfig({ ...audioArchiveConfig, max_age_days: Number(value) })}
+                  onCommit={async (value) => {
+                    await onSaveExtraConfig({ audioArchiveConfig: { ...audioArchiveConfig, max_age_days: Number(value) } });
+                  }}
+                  options={ARCHIVE_AGE_OPTIONS}
+                />
+              </div>
+            </>
+          )}
+          <div className="flex items-center justify-between">
+            <div className="text-[11px] text-stone-400 font-semibold">超出上限时自动删除最早的录音</div>
+            <button
+              onClick={() => {
+                void handlePurgeArchive();
+              }}
+              onBlur={() => setConfirmPurge(false)}
+              disabled={isPurging}
+              className="px-3 py-2 rounded-xl bg-white border border-[var(--stone)] text-stone-700 font-bold hover:border-[rgba(176,174,165,0.75)] transition-colors disabled:opacity-50 flex items-center gap-2"
+            >
+              <Trash2 size={14} />
+              {isPurging ? "删除中..." : confirmPurge ? "确认删除？" : "清空归档"}
+            </button>
+          </div>
+        </div>
+
+        <div className="p-4 bg-[var(--paper)] border border-[var(--stone)] rounded-2xl space-y-3">
+          <div className="flex items-center justify-between">
+            <div className="flex items-center gap-3">
+              <div className="p-2 rounded-xl bg-white border border-[var(--stone)] text-stone-500">
+                <Activity size={16} />
+              </div>
+              <div>
+                <div className="text-sm font-bold text-[var(--ink)]">麦克风测试</div>
+                <div className="text-[11px] text-stone-400 font-semibold">
+                  {micDiagnosticsRunning ? describeMicStage(micDiagnosticsStage) : "检测底噪、音量与削波并给出调整建议"}
+                </div>
+              </div>
+            </div>
+            <button
+              onClick={onRunMicDiagnostics}
+              disabled={micDiagnosticsRunning || isBusy}
+              className="px-3 py-2 rounded-xl bg-white border border-[var(--stone)] text-stone-700 font-bold hover:border-[rgba(176,174,165,0.75)] transition-colors disabled:opacity-50 flex items-center gap-2"
+            >
+              {micDiagnosticsRunning ? <RefreshCw size={14} className="animate-spin" /> : <Mic size={14} />}
+              {micDiagnosticsRunning ? "测试中" : "开始测试"}
+            </button>
+          </div>
+          {!micDiagnosticsRunning && micDiagnosticsReport && (
+            <div className="space-y-2">
+              <div className="grid grid-cols-4 gap-2 text-center">
+                {[
+                  { label: "峰值", value: `${micDiagnosticsReport.peak_dbfs.toFixed(1)} dB` },
+                  { label: "底噪", value: `${micDiagnosticsReport.noise_floor_dbfs.toFixed(1)} dB` },
+                  { label: "信噪比", value: `${micDiagnosticsReport.snr_db.toFixed(1)} dB` },
+                  { label: "削波", value: `${(micDiagnosticsReport.clipping_ratio * 100).toFixed(2)}%` },
+                ].map((item) => (
+                  <div key={item.label} className="p-2 bg-white border border-[var(--stone)] rounded-xl">
+                    <div className="text-[10px] text-stone-400 font-bold">{item.label}</div>
+                    <div className="text-xs font-bold text-[var(--ink)]">{item.value}</div>
+                  </div>
+                ))}
+              </div>
+              <div
+                className={[
+                  "text-[11px] font-semibold",
+                  micDiagnosticsReport.ok ? "text-green-600" : "text-[var(--crail)]",
+                ].join(" ")}
+              >
+                {micDiagnosticsReport.ok ? "麦克风状态良好" : "麦克风需要调整"}
+              </div>
+              {micDiagnosticsReport.recommendations.length > 0 && (
+                <ul className="list-disc pl-4 text-[11px] text-stone-500 space-y-1">
+                  {micDiagnosticsReport.recommendations.map((tip) => (
+                    <li key={tip}>{tip}</li>
+                  ))}
+                </ul>
+              )}
+            </div>
+          )}
+        </div>
+
         <div className="flex items-center justify-between p-4 bg-[var(--paper)] border border-[var(--stone)] rounded-2xl">
           <div>
             <div className="text-sm font-bold text-[var(--ink)]">检查更新</div>
@@ -0,0 +1,118 @@
+import { useState, type MouseEvent } from "react";
+import { Copy, FileAudio, RefreshCw } from "lucide-react";
+import type { FileTranscriptionResult, LlmPreset } from "../types";
+import type { FileTranscriptionRequest } from "../hooks/useFileTranscription";
+
+export type TranscribePageProps = {
+  presets: LlmPreset[];
+  isTranscribing: boolean;
+  result: FileTranscriptionResult | null;
+  onTranscribe: (request: FileTranscriptionRequest) => void;
+  onCopyText: (text: string, e?: MouseEvent) => void;
+};
+
+function formatSeconds(ms: number): string {
+  return `${(ms / 1000).toFixed(1)}s`;
+}
+
+export function TranscribePage({
+  presets,
+  isTranscribing,
+  result,
+  onTranscribe,
+  onCopyText,
+}: TranscribePageProps) {
+  const [path, setPath] = useState("");
+  const [presetId, setPresetId] = useState("");
+  const [saveToFile, setSaveToFile] = useState(false);
+
+  // 去掉从资源管理器"复制为路径"带上的引号
+  const trimmedPath = path.trim().replace(/^"(.*)"$/, "$1");
+
+  return (
+    <div className="mx-auto max-w-3xl space-y-6 font-sans">
+      <div className="bg-white border border-[var(--stone)] rounded-2xl p-6 space-y-5">
+        <div className="flex items-center gap-2 text-xs font-bold text-stone-500 uppercase tracking-widest">
+          <FileAudio size={14} />
+          <span>文件转写</span>
+        </div>
+
+        <div className="space-y-2">
+          <label className="text-xs font-bold text-stone-500">音频文件路径</label>
+          <input
+            type="text"
+            value={path}
+            disabled={isTranscribing}
+            onChange={(e) => setPath(e.target.value)}
+            className="w-full px-3 py-2.5 bg-[var(--paper)] border border-[var(--stone)] rounded-xl text-sm mono focus:outline-none focus:border-[var(--steel)] disabled:opacity-60"
+            placeholder="支持 WAV / MP3 / FLAC / OGG / M4A，例如 D:\录音\会议.m4a"
+          />
+        </div>
+
+        <div className="grid grid-cols-2 gap-4">
+          <div className="space-y-2">
+            <label className="text-xs font-bold text-stone-500">润色预设</label>
+            <select
+              value={presetId}
+              disabled={isTranscribing}
+              onChange={(e) => setPresetId(e.target.value)}
+              className="w-full px-3 py-2.5 bg-[var(--paper)] border border-[var(--stone)] rounded-xl text-sm focus:outline-none focus:border-[var(--steel)] disabled:opacity-60"
+            >
+              <option value="">不润色（仅转写）</option>
+              {presets.map((preset) => (
+                <option key={preset.id} value={preset.id}>
+                  {preset.name}
+                </option>
+              ))}
+            </select>
+          </div>
+          <label className="flex items-center gap-2 self-end pb-3 text-sm text-stone-600 font-semibold cursor-pointer">
+            <input
+              type="checkbox"
+              checked={saveToFile}
+              disabled={isTranscribing}
+              onChange={(e) => setSaveToFile(e.target.checked)}
+            />
+            保存为同名 .txt 文件
+          </label>
+        </div>
+
+        <button
+          onClick={() => onTranscribe({ path: trimmedPath, presetId: presetId || null, saveToFile })}
+          disabled={isTranscribing || !trimmedPath}
+          className="w-full py-2.5 rounded-xl bg-white border border-[var(--stone)] text-sm text-stone-700 font-bold hover:border-[rgba(176,174,165,0.75)] hover:text-[var(--steel)] transition-colors disabled:opacity-50 flex items-center justify-center gap-2"
+        >
+          {isTranscribing ? <RefreshCw size={14} className="animate-spin" /> : <FileAudio size={14} />}
+          {isTranscribing ? "转写中..." : "开始转写"}
+        </button>
+      </div>
+
+      {result && (
+        <div className="bg-white border border-[var(--stone)] rounded-2xl p-6 space-y-4">
+          <div className="flex items-center justify-between">
+            <div className="text-[11px] text-stone-400 font-semibold">
+              音频 {formatSeconds(result.audio_duration_ms)} · 识别 {formatSeconds(result.asr_time_ms)}
+              {result.llm_time_ms !== null && ` · 润色 ${formatSeconds(result.llm_time_ms)}`}
+            </div>
+            <button
+              onClick={(e) => onCopyText(result.text, e)}
+              className="p-2 rounded-xl text-stone-400 hover:bg-[var(--paper)] hover:text-[var(--steel)] transition-colors"
+              title="复制"
+            >
+              <Copy size={14} />
+            </button>
+          </div>
+          <div className="p-4 bg-[var(--paper)] border border-[var(--stone)] rounded-2xl text-sm text-[var(--ink)] leading-relaxed whitespace-pre-wrap max-h-[360px] overflow-y-auto custom-scroll">
+            {result.text || "（未识别到内容）"}
+          </div>
+          {result.original_text !== null && result.original_text !== result.text && (
+            <div className="text-[11px] text-stone-400 leading-relaxed">原文：{result.original_text}</div>
+          )}
+          {result.output_path && (
+            <div className="text-[11px] text-stone-500 font-semibold mono break-all">已保存到 {result.output_path}</div>
+          )}
+        </div>
+      )}
+    </div>
+  );
+}
@@ -7,4 +7,5 @@ export type AppPage =
   | "preferences"
   | "dictionary"
   | "history"
+  | "transcribe"
   | "help";
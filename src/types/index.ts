@@ -23,6 +23,8 @@ export interface HotkeyConfig {
 export interface DualHotkeyConfig {
   dictation: HotkeyConfig;  // 听写模式（默认 Ctrl+Win）
   assistant: HotkeyConfig;  // AI助手模式（默认 Alt+Space）
+  conversation_reset?: HotkeyKey[] | null;  // 重置 AI 助手多轮对话（可选）
+  undo_insertion?: HotkeyKey[] | null;      // 撤销上次插入（可选）
 }
 
 // ASR 配置
@@ -51,24 +53,235 @@ export interface LlmPreset {
   id: string;
   name: string;
   system_prompt: string;
+  bypass_cache?: boolean;  // 不使用响应缓存
 }
 
+// LLM 聊天协议（gemini 的 endpoint 为 API 根地址，其余为完整接口地址）
+export type ChatProtocol = "openai" | "anthropic" | "gemini" | "ollama";
+
 export interface LlmConfig {
+  protocol?: ChatProtocol;  // 默认 openai
   endpoint: string;
   model: string;
   api_key: string;
   presets: LlmPreset[];
   active_preset_id: string;
+  fallback?: LlmFallbackConfig;  // 备用端点
+  cache?: LlmCacheConfig;        // 响应缓存
+}
+
+// LLM 响应缓存：相同的短句直接返回缓存结果（清空：clear_llm_cache 命令）
+export interface LlmCacheConfig {
+  enabled: boolean;
+  ttl_hours: number;        // 有效期，0 表示不过期
+  max_entries: number;      // 最多缓存条数，0 表示不限制
+  max_input_chars: number;  // 只缓存不超过该字数的输入
+}
+
+// 单个 LLM 端点
+export interface LlmEndpoint {
+  name: string;  // 显示名称，为空时使用模型名
+  protocol?: ChatProtocol;
+  endpoint: string;
+  model: string;
+  api_key: string;
+}
+
+// LLM 备用端点链：主端点失败或超时后依次尝试
+export interface LlmFallbackConfig {
+  endpoints: LlmEndpoint[];
+  attempt_timeout_secs: number;  // 收到首个文本片段前的超时，0 表示不限制
+  fast_first: boolean;           // 快模型优先：主端点（快速模型）更快切换到备用的强模型
 }
 
 // AI 助手配置（双系统提示词）
 export interface AssistantConfig {
   enabled: boolean;
+  protocol?: ChatProtocol;  // 默认 openai
   endpoint: string;
   model: string;
   api_key: string;
   qa_system_prompt: string;               // 问答模式提示词（无选中文本时）
   text_processing_system_prompt: string;  // 文本处理提示词（有选中文本时）
+  conversation?: ConversationConfig;      // 多轮对话记忆
+  tools?: AssistantToolsConfig;           // 工具调用
+  fallback?: LlmFallbackConfig;           // 备用端点
+  cache?: LlmCacheConfig;                 // 响应缓存（默认关闭，仅无对话历史和工具调用时生效）
+}
+
+// AI 助手工具调用配置（需要 OpenAI 格式的服务）
+export interface AssistantToolsConfig {
+  enabled: boolean;
+  allowed_commands: string[];  // 允许执行的命令白名单（执行前在悬浮窗确认）
+}
+
+// AI 助手多轮对话配置
+export interface ConversationConfig {
+  enabled: boolean;
+  idle_timeout_secs: number;   // 空闲超时（秒），超过后开始新对话
+  max_history_tokens: number;  // 历史消息 token 预算（估算）
+}
+
+// 预录缓冲配置（麦克风常驻，仅内存保存）
+export interface PreRollConfig {
+  enabled: boolean;      // 隐私开关，默认关闭
+  duration_ms: number;   // 预录时长（毫秒）
+}
+
+// 录音归档配置
+export interface AudioArchiveConfig {
+  enabled: boolean;
+  max_total_mb: number;   // 0 表示不限制
+  max_age_days: number;   // 0 表示不限制
+}
+
+// 流式录音限制配置
+export interface RecordingLimitsConfig {
+  max_duration_secs: number;     // 最大录音时长，0 表示不限制（默认）
+  warn_before_secs: number;      // 距上限多少秒时提醒
+  spill_threshold_secs: number;  // 备用音频超过此时长后溢写到临时文件
+}
+
+// 静音自动停止配置（松手模式）
+export interface AutoStopConfig {
+  enabled: boolean;
+  silence_secs: number;    // 持续静音多少秒后自动完成
+  countdown_secs: number;  // 结束前悬浮窗倒计时秒数
+}
+
+// 录音时其他应用的处理方式：完全静音 / 压低音量
+export type MuteMode = "mute" | "duck";
+
+// 录音时静音其他应用配置（总开关为 enable_mute_other_apps）
+export interface MuteOtherAppsConfig {
+  mode: MuteMode;
+  duck_percent: number;      // 闪避时压低到原音量的百分比
+  fade_ms: number;           // 闪避时音量渐变时长
+  excluded_apps: string[];   // 不处理的应用进程名
+}
+
+// 提示音事件
+export type AudioCue = "start" | "stop" | "lock" | "cancel" | "error" | "answer_ready";
+
+// 单个提示音配置
+export interface CueSetting {
+  file: string | null;  // 自定义音效文件路径，为空时使用内置音效
+  volume: number;       // 0.0-1.0
+}
+
+// 一种触发模式下各事件的提示音
+export type CueSet = Record<AudioCue, CueSetting>;
+
+// 提示音配置（听写 / AI 助手模式各一套）
+export interface AudioCueConfig {
+  muted: boolean;
+  dictation: CueSet;
+  assistant: CueSet;
+}
+
+// 文本插入方式：剪贴板粘贴 / 直接输入（不经过剪贴板）
+export type InsertionMethod = "paste" | "type";
+
+// 直接输入模式的节奏控制
+export interface TypingConfig {
+  char_delay_ms: number;   // 字符间隔
+  chunk_size: number;      // 每块字符数，0 表示不分块
+  chunk_delay_ms: number;  // 分块之间的暂停
+  newline: "enter" | "shift_enter";
+}
+
+// 按目标应用覆盖插入方式
+export interface AppInsertionRule {
+  app: string;  // 进程名，不区分大小写
+  method: InsertionMethod;
+}
+
+// 文本插入配置
+export interface InsertionConfig {
+  method: InsertionMethod;
+  typing: TypingConfig;
+  app_rules: AppInsertionRule[];
+}
+
+// 结果输出目标（可同时配置多个）
+export type OutputSink =
+  | { type: "insert" }                                                 // 插入到目标窗口
+  | { type: "clipboard" }                                              // 仅复制到剪贴板
+  | { type: "file"; path: string; format?: "markdown" | "text" }       // 追加到文件（带时间戳）
+  | { type: "webhook"; url: string };                                  // POST JSON
+
+// 输出目标配置（按触发模式）
+export interface OutputConfig {
+  dictation: OutputSink[];
+  assistant: OutputSink[];
+}
+
+// 按应用配置：匹配条件（已填写的需全部满足，不区分大小写）
+export interface ProfileMatch {
+  process?: string | null;         // 进程名，可省略 .exe
+  window_class?: string | null;
+  title_contains?: string | null;
+}
+
+export type PunctuationPolicy = "keep" | "strip_trailing" | "strip_all";
+export type PostInsertAction = "enter" | "shift_enter" | "tab" | "space";
+
+// 按应用配置：未填写的项沿用全局设置
+export interface AppProfile {
+  name: string;
+  enabled: boolean;
+  match: ProfileMatch;
+  llm_preset_id?: string | null;
+  asr_provider?: AsrProvider | null;  // 覆盖全局服务商（实时与 HTTP 转写）
+  language?: string | null;           // 目前仅千问支持
+  punctuation?: PunctuationPolicy | null;
+  insertion_method?: InsertionMethod | null;
+  post_insert_actions: PostInsertAction[];
+}
+
+// 归档录音元数据（list_archived_recordings）
+export interface ArchiveMetadata {
+  id: string;
+  created_at: number;
+  mode: string;
+  asr_provider: string | null;
+  duration_ms: number;
+  text: string;
+  original_text: string | null;
+  asr_time_ms: number;
+  llm_time_ms: number | null;
+}
+
+// 重新转写结果（retranscribe_history_entry）
+export interface RetranscriptionResult {
+  id: string;
+  provider: AsrProvider;
+  preset_id: string | null;
+  original_text: string;  // 归档时的最终文本
+  asr_text: string;
+  text: string;
+  asr_time_ms: number;
+  llm_time_ms: number | null;
+}
+
+// 麦克风诊断阶段事件（mic_diagnostics_stage）
+export interface MicDiagnosticsStage {
+  stage: "silence" | "speech" | "analyzing";
+  duration_secs: number;
+  phrase: string | null;
+}
+
+// 麦克风诊断报告（run_mic_diagnostics）
+export interface MicDiagnosticsReport {
+  peak: number;
+  peak_dbfs: number;
+  clipping_ratio: number;
+  noise_floor_dbfs: number;
+  speech_level_dbfs: number;
+  snr_db: number;
+  suggested_gain_db: number;  // 正数为调高
+  ok: boolean;
+  recommendations: string[];
 }
 
 // 应用配置
@@ -84,11 +297,21 @@ export interface AppConfig {
   hotkey_config: HotkeyConfig;            // 保留用于迁移
   dual_hotkey_config: DualHotkeyConfig;
   enable_mute_other_apps: boolean;
+  mute_other_apps_config?: MuteOtherAppsConfig;
   dictionary: string[];
+  pre_roll_config?: PreRollConfig;
+  audio_archive_config?: AudioArchiveConfig;
+  auto_stop_config?: AutoStopConfig;
+  recording_limits_config?: RecordingLimitsConfig;
+  audio_cue_config?: AudioCueConfig;
+  insertion_config?: InsertionConfig;
+  output_config?: OutputConfig;
+  app_profiles?: AppProfile[];
 }
 
 // 转录结果
 export interface TranscriptionResult {
+  id?: string;  // 转写 ID（与录音归档共用）
   text: string;
   original_text: string | null;
   asr_time_ms: number;
@@ -96,6 +319,35 @@ export interface TranscriptionResult {
   total_time_ms: number;
   mode?: string; // "normal" | "smartcommand"
   inserted?: boolean;
+  llm_endpoint?: string;  // 产出 LLM 结果的端点
+}
+
+// AI 助手工具修改了配置（assistant_config_changed 事件）
+export interface AssistantConfigChanged {
+  active_preset_id?: string;
+  enable_post_process?: boolean;
+}
+
+// AI 助手命令执行确认请求（tool_confirmation_request 事件）
+export interface ToolConfirmationRequest {
+  id: string;
+  command: string;
+}
+
+// LLM 流式生成进度（llm_stream_delta 事件）
+export interface LlmStreamDelta {
+  stage: "polishing" | "assistant";
+  delta: string;
+}
+
+// 音频文件转写结果（transcribe_audio_file）
+export interface FileTranscriptionResult {
+  text: string;
+  original_text: string | null;
+  audio_duration_ms: number;
+  asr_time_ms: number;
+  llm_time_ms: number | null;
+  output_path: string | null;
 }
 
 // 历史记录
@@ -108,6 +360,7 @@ export interface HistoryRecord {
   mode: "normal" | "assistant" | null;  // 处理模式
   asrTimeMs: number;
   llmTimeMs: number | null;
+  llmEndpoint?: string | null;  // 产出 LLM 结果的端点
   totalTimeMs: number;
   success: boolean;
   errorMessage: string | null;